@@ -1,5 +1,9 @@
 //! Archived bitwise containers.
 
+pub mod rank_select;
+#[cfg(feature = "validation")]
+pub mod validation;
+
 use crate::{vec::ArchivedVec, Archived};
 use bitvec::{
     order::{BitOrder, Lsb0},
@@ -9,10 +13,22 @@ use bitvec::{
 };
 use core::{marker::PhantomData, ops::Deref};
 
+pub use self::rank_select::ArchivedRankSelect;
+
 /// An archived `BitVec`.
 // We also have to store the bit length in the archived `BitVec`.
 // This is because when calling `as_raw_slice` we will get unwanted bits if the `BitVec` bit length is not a multiple of the bit size of T.
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 #[derive(Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ArchivedBitVec<T = Archived<usize>, O = Lsb0> {
@@ -25,6 +41,6 @@ impl<T: BitStore, O: BitOrder> Deref for ArchivedBitVec<T, O> {
     type Target = BitSlice<T, O>;
 
     fn deref(&self) -> &Self::Target {
-        &self.inner.view_bits::<O>()[..self.bit_len as usize]
+        &self.inner.view_bits::<O>()[..from_archived!(self.bit_len) as usize]
     }
 }
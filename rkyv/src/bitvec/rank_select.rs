@@ -0,0 +1,485 @@
+//! Archived bit vectors with rank and select support.
+
+use crate::{
+    bitvec::ArchivedBitVec,
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{ScratchSpace, Serializer},
+    Serialize,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use bitvec::vec::BitVec;
+use bitvec::{
+    order::{BitOrder, Lsb0},
+    slice::BitSlice,
+    store::BitStore,
+};
+use core::ops::Deref;
+
+/// The number of bits in each block of the rank index.
+pub const BLOCK_BITS: usize = 64;
+
+/// The number of bits in each superblock of the rank index.
+pub const SUPERBLOCK_BITS: usize = 512;
+
+/// The number of set or unset bits between the select samples.
+pub const SELECT_SAMPLE: usize = 1024;
+
+/// The number of set or unset bits between the select hints.
+pub const SELECT_HINT: usize = 32;
+
+const BLOCKS_PER_SUPERBLOCK: usize = SUPERBLOCK_BITS / BLOCK_BITS;
+
+/// An archived `BitVec` with an index for rank and select queries.
+///
+/// The index stores the number of set bits before every superblock of [`SUPERBLOCK_BITS`] bits
+/// and the number of set bits before every block of [`BLOCK_BITS`] bits relative to its
+/// superblock. For selects, the set and unset bits are split into spans of [`SELECT_SAMPLE`] bits
+/// each. Spans shorter than `u16::MAX` bits store the position of their first bit and the offset
+/// of every [`SELECT_HINT`]th bit from it, and longer spans store the position of every bit. Ranks
+/// and selects both take constant time. This can be used with
+/// [`RankSelect`](crate::with::RankSelect).
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedRankSelect<T = Archived<usize>, O = Lsb0> {
+    pub(crate) bits: ArchivedBitVec<T, O>,
+    pub(crate) ranks: ArchivedVec<Archived<usize>>,
+    pub(crate) block_ranks: ArchivedVec<Archived<u16>>,
+    pub(crate) select_ones: ArchivedVec<Archived<usize>>,
+    pub(crate) select_ones_hints: ArchivedVec<Archived<u16>>,
+    pub(crate) select_ones_spill: ArchivedVec<Archived<usize>>,
+    pub(crate) select_zeros: ArchivedVec<Archived<usize>>,
+    pub(crate) select_zeros_hints: ArchivedVec<Archived<u16>>,
+    pub(crate) select_zeros_spill: ArchivedVec<Archived<usize>>,
+}
+
+impl<T, O> ArchivedRankSelect<T, O> {
+    /// Returns the underlying archived `BitVec`.
+    #[inline]
+    pub fn bits(&self) -> &ArchivedBitVec<T, O> {
+        &self.bits
+    }
+
+    #[inline]
+    fn rank(&self, superblock: usize) -> usize {
+        from_archived!(self.ranks[superblock]) as usize
+    }
+
+    #[inline]
+    fn block_rank(&self, block: usize) -> usize {
+        from_archived!(self.block_ranks[block]) as usize
+    }
+
+    #[inline]
+    fn superblocks(&self) -> usize {
+        self.ranks.len() - 1
+    }
+
+    /// Resolves an archived rank/select bit vector from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `bit_len` must be the number of bits that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a rank/select bit vector
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        bit_len: usize,
+        pos: usize,
+        resolver: RankSelectResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.bits);
+        {
+            let (ip, io) = out_field!(fo.inner);
+            ArchivedVec::resolve_from_len(resolver.raw_len, pos + fp + ip, resolver.bits, io);
+            let (bp, bo) = out_field!(fo.bit_len);
+            bit_len.resolve(pos + fp + bp, (), bo);
+        }
+        let (fp, fo) = out_field!(out.ranks);
+        ArchivedVec::resolve_from_len(resolver.ranks_len, pos + fp, resolver.ranks, fo);
+        let (fp, fo) = out_field!(out.block_ranks);
+        ArchivedVec::resolve_from_len(resolver.block_ranks_len, pos + fp, resolver.block_ranks, fo);
+        let (fp, fo) = out_field!(out.select_ones);
+        ArchivedVec::resolve_from_len(resolver.select_ones_len, pos + fp, resolver.select_ones, fo);
+        let (fp, fo) = out_field!(out.select_ones_hints);
+        ArchivedVec::resolve_from_len(
+            resolver.select_ones_hints_len,
+            pos + fp,
+            resolver.select_ones_hints,
+            fo,
+        );
+        let (fp, fo) = out_field!(out.select_ones_spill);
+        ArchivedVec::resolve_from_len(
+            resolver.select_ones_spill_len,
+            pos + fp,
+            resolver.select_ones_spill,
+            fo,
+        );
+        let (fp, fo) = out_field!(out.select_zeros);
+        ArchivedVec::resolve_from_len(
+            resolver.select_zeros_len,
+            pos + fp,
+            resolver.select_zeros,
+            fo,
+        );
+        let (fp, fo) = out_field!(out.select_zeros_hints);
+        ArchivedVec::resolve_from_len(
+            resolver.select_zeros_hints_len,
+            pos + fp,
+            resolver.select_zeros_hints,
+            fo,
+        );
+        let (fp, fo) = out_field!(out.select_zeros_spill);
+        ArchivedVec::resolve_from_len(
+            resolver.select_zeros_spill_len,
+            pos + fp,
+            resolver.select_zeros_spill,
+            fo,
+        );
+    }
+}
+
+impl<T: BitStore, O: BitOrder> ArchivedRankSelect<T, O> {
+    /// Gets the bits as a `BitSlice`.
+    #[inline]
+    pub fn as_bitslice(&self) -> &BitSlice<T, O> {
+        &self.bits
+    }
+
+    /// Returns the number of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.rank(self.superblocks())
+    }
+
+    /// Returns the number of unset bits.
+    #[inline]
+    pub fn count_zeros(&self) -> usize {
+        self.as_bitslice().len() - self.count_ones()
+    }
+
+    /// Returns the number of set bits before the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of bits.
+    #[inline]
+    pub fn rank1(&self, index: usize) -> usize {
+        let bits = self.as_bitslice();
+        assert!(
+            index <= bits.len(),
+            "rank index {} out of range for {} bits",
+            index,
+            bits.len()
+        );
+        if index == bits.len() {
+            return self.count_ones();
+        }
+
+        let block = index / BLOCK_BITS;
+        self.rank(index / SUPERBLOCK_BITS)
+            + self.block_rank(block)
+            + bits[block * BLOCK_BITS..index].count_ones()
+    }
+
+    /// Returns the number of unset bits before the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of bits.
+    #[inline]
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Returns the number of bits equal to `bit` before the start of the given superblock.
+    #[inline]
+    fn superblock_rank(&self, superblock: usize, bit: bool) -> usize {
+        let ones = self.rank(superblock);
+        if bit {
+            ones
+        } else {
+            usize::min(superblock * SUPERBLOCK_BITS, self.as_bitslice().len()) - ones
+        }
+    }
+
+    /// Returns the index of the bit equal to `bit` with the given rank.
+    fn select(&self, rank: usize, bit: bool) -> usize {
+        let (samples, hints, spill) = if bit {
+            (
+                &self.select_ones,
+                &self.select_ones_hints,
+                &self.select_ones_spill,
+            )
+        } else {
+            (
+                &self.select_zeros,
+                &self.select_zeros_hints,
+                &self.select_zeros_spill,
+            )
+        };
+        let sample = from_archived!(samples[rank / SELECT_SAMPLE]) as usize;
+        let hint = from_archived!(hints[rank / SELECT_HINT]);
+        if hint == u16::MAX {
+            // Long spans store the position of every bit
+            return from_archived!(spill[sample + rank % SELECT_SAMPLE]) as usize;
+        }
+
+        // Short spans are less than `u16::MAX` bits long, so only a bounded number of superblocks
+        // lie between the hint and the bit
+        let mut superblock = (sample + hint as usize) / SUPERBLOCK_BITS;
+        while self.superblock_rank(superblock + 1, bit) <= rank {
+            superblock += 1;
+        }
+        let rank = rank - self.superblock_rank(superblock, bit);
+
+        // Scan the blocks of the superblock
+        let first = superblock * BLOCKS_PER_SUPERBLOCK;
+        let last = usize::min(first + BLOCKS_PER_SUPERBLOCK, self.block_ranks.len());
+        let block_rank = |block: usize| {
+            let ones = self.block_rank(block);
+            if bit {
+                ones
+            } else {
+                (block - first) * BLOCK_BITS - ones
+            }
+        };
+        let mut block = first;
+        while block + 1 < last && block_rank(block + 1) <= rank {
+            block += 1;
+        }
+        let rank = rank - block_rank(block);
+
+        // Scan the bits of the block
+        let bits = self.as_bitslice();
+        let start = block * BLOCK_BITS;
+        let block_bits = &bits[start..usize::min(start + BLOCK_BITS, bits.len())];
+        let offset = if bit {
+            block_bits.iter_ones().nth(rank)
+        } else {
+            block_bits.iter_zeros().nth(rank)
+        };
+        start + offset.unwrap()
+    }
+
+    /// Returns the index of the set bit with the given rank, or `None` if there are not enough set
+    /// bits.
+    #[inline]
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        if rank < self.count_ones() {
+            Some(self.select(rank, true))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of the unset bit with the given rank, or `None` if there are not enough
+    /// unset bits.
+    #[inline]
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        if rank < self.count_zeros() {
+            Some(self.select(rank, false))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: BitStore, O: BitOrder> Deref for ArchivedRankSelect<T, O> {
+    type Target = BitSlice<T, O>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_bitslice()
+    }
+}
+
+/// The rank and select index of a bit slice.
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq)]
+pub(crate) struct RankSelectIndex {
+    pub(crate) ranks: Vec<usize>,
+    pub(crate) block_ranks: Vec<u16>,
+    pub(crate) select_ones: SelectIndex,
+    pub(crate) select_zeros: SelectIndex,
+}
+
+#[cfg(feature = "alloc")]
+impl RankSelectIndex {
+    pub(crate) fn new<T: BitStore, O: BitOrder>(bits: &BitSlice<T, O>) -> Self {
+        let mut result = Self {
+            ranks: Vec::new(),
+            block_ranks: Vec::new(),
+            select_ones: SelectIndex::new(bits.iter_ones()),
+            select_zeros: SelectIndex::new(bits.iter_zeros()),
+        };
+
+        let mut ones = 0;
+        for superblock in bits.chunks(SUPERBLOCK_BITS) {
+            result.ranks.push(ones);
+
+            let mut superblock_ones = 0;
+            for block in superblock.chunks(BLOCK_BITS) {
+                result.block_ranks.push(superblock_ones as u16);
+                superblock_ones += block.count_ones();
+            }
+            ones += superblock_ones;
+        }
+        result.ranks.push(ones);
+
+        result
+    }
+}
+
+/// The select index of the set or unset bits of a bit slice.
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq)]
+pub(crate) struct SelectIndex {
+    /// The position of the first bit of every short span, or the index of the first spilled
+    /// position of every long span
+    pub(crate) samples: Vec<usize>,
+    /// The offset of every `SELECT_HINT`th bit from the start of its span, or `u16::MAX` if the
+    /// span is long
+    pub(crate) hints: Vec<u16>,
+    /// The position of every bit in the long spans
+    pub(crate) spill: Vec<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl SelectIndex {
+    fn new(positions: impl Iterator<Item = usize>) -> Self {
+        let mut result = Self {
+            samples: Vec::new(),
+            hints: Vec::new(),
+            spill: Vec::new(),
+        };
+
+        let mut positions = positions.peekable();
+        let mut span = Vec::with_capacity(SELECT_SAMPLE);
+        while positions.peek().is_some() {
+            span.clear();
+            span.extend(positions.by_ref().take(SELECT_SAMPLE));
+            let first = span[0];
+            let hints = span.iter().step_by(SELECT_HINT);
+            if span[span.len() - 1] - first < u16::MAX as usize {
+                result.samples.push(first);
+                result
+                    .hints
+                    .extend(hints.map(|position| (position - first) as u16));
+            } else {
+                result.samples.push(result.spill.len());
+                result.hints.extend(hints.map(|_| u16::MAX));
+                result.spill.extend_from_slice(&span);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    impl<T: BitStore, O: BitOrder> ArchivedRankSelect<T, O> {
+        /// Serializes a rank/select bit vector from a bit slice.
+        ///
+        /// The bits are repacked into elements of the archived type, so the bit slice may use any
+        /// store and order.
+        pub fn serialize_from_bitslice<U, P, S>(
+            bits: &BitSlice<U, P>,
+            serializer: &mut S,
+        ) -> Result<RankSelectResolver, S::Error>
+        where
+            U: BitStore,
+            P: BitOrder,
+            T: Serialize<S, Archived = T>,
+            S: ScratchSpace + Serializer + ?Sized,
+        {
+            let mut repacked = BitVec::<T, O>::with_capacity(bits.len());
+            repacked.extend_from_bitslice(bits);
+            let raw = repacked.as_raw_slice();
+            let index = RankSelectIndex::new(repacked.as_bitslice());
+
+            Ok(RankSelectResolver {
+                raw_len: raw.len(),
+                bits: ArchivedVec::<T>::serialize_from_slice(raw, serializer)?,
+                ranks_len: index.ranks.len(),
+                ranks: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &index.ranks,
+                    serializer,
+                )?,
+                block_ranks_len: index.block_ranks.len(),
+                block_ranks: ArchivedVec::<Archived<u16>>::serialize_from_slice(
+                    &index.block_ranks,
+                    serializer,
+                )?,
+                select_ones_len: index.select_ones.samples.len(),
+                select_ones: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &index.select_ones.samples,
+                    serializer,
+                )?,
+                select_ones_hints_len: index.select_ones.hints.len(),
+                select_ones_hints: ArchivedVec::<Archived<u16>>::serialize_from_slice(
+                    &index.select_ones.hints,
+                    serializer,
+                )?,
+                select_ones_spill_len: index.select_ones.spill.len(),
+                select_ones_spill: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &index.select_ones.spill,
+                    serializer,
+                )?,
+                select_zeros_len: index.select_zeros.samples.len(),
+                select_zeros: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &index.select_zeros.samples,
+                    serializer,
+                )?,
+                select_zeros_hints_len: index.select_zeros.hints.len(),
+                select_zeros_hints: ArchivedVec::<Archived<u16>>::serialize_from_slice(
+                    &index.select_zeros.hints,
+                    serializer,
+                )?,
+                select_zeros_spill_len: index.select_zeros.spill.len(),
+                select_zeros_spill: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &index.select_zeros.spill,
+                    serializer,
+                )?,
+            })
+        }
+    }
+};
+
+/// The resolver for archived rank/select bit vectors.
+pub struct RankSelectResolver {
+    raw_len: usize,
+    bits: VecResolver,
+    ranks_len: usize,
+    ranks: VecResolver,
+    block_ranks_len: usize,
+    block_ranks: VecResolver,
+    select_ones_len: usize,
+    select_ones: VecResolver,
+    select_ones_hints_len: usize,
+    select_ones_hints: VecResolver,
+    select_ones_spill_len: usize,
+    select_ones_spill: VecResolver,
+    select_zeros_len: usize,
+    select_zeros: VecResolver,
+    select_zeros_hints_len: usize,
+    select_zeros_hints: VecResolver,
+    select_zeros_spill_len: usize,
+    select_zeros_spill: VecResolver,
+}
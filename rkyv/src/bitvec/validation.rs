@@ -0,0 +1,266 @@
+//! Validation implementations for archived bitwise containers.
+
+use crate::{
+    bitvec::{rank_select::RankSelectIndex, ArchivedBitVec, ArchivedRankSelect},
+    validation::{owned::CheckOwnedPointerError, ArchiveContext},
+    vec::ArchivedVec,
+    Archived,
+};
+use bitvec::{order::BitOrder, store::BitStore, view::BitView};
+use bytecheck::{CheckBytes, Error};
+use core::{fmt, ptr};
+
+/// Errors that can occur while checking an archived rank/select bit vector.
+#[derive(Debug)]
+pub enum RankSelectError<B, R, S> {
+    /// An error occurred while checking the bits
+    CheckBitsError(B),
+    /// An error occurred while checking the superblock ranks
+    CheckRanksError(R),
+    /// An error occurred while checking the block ranks
+    CheckBlockRanksError(S),
+    /// An error occurred while checking the select samples of the set bits
+    CheckSelectOnesError(R),
+    /// An error occurred while checking the select hints of the set bits
+    CheckSelectOnesHintsError(S),
+    /// An error occurred while checking the spilled select positions of the set bits
+    CheckSelectOnesSpillError(R),
+    /// An error occurred while checking the select samples of the unset bits
+    CheckSelectZerosError(R),
+    /// An error occurred while checking the select hints of the unset bits
+    CheckSelectZerosHintsError(S),
+    /// An error occurred while checking the spilled select positions of the unset bits
+    CheckSelectZerosSpillError(R),
+    /// The bit length was larger than the number of raw bits
+    InvalidBitLen {
+        /// The bit length
+        bit_len: usize,
+        /// The number of raw bits
+        raw_bits: usize,
+    },
+    /// The superblock ranks did not match the bits
+    RanksMismatch,
+    /// The block ranks did not match the bits
+    BlockRanksMismatch,
+    /// The select samples of the set bits did not match the bits
+    SelectOnesMismatch,
+    /// The select hints of the set bits did not match the bits
+    SelectOnesHintsMismatch,
+    /// The spilled select positions of the set bits did not match the bits
+    SelectOnesSpillMismatch,
+    /// The select samples of the unset bits did not match the bits
+    SelectZerosMismatch,
+    /// The select hints of the unset bits did not match the bits
+    SelectZerosHintsMismatch,
+    /// The spilled select positions of the unset bits did not match the bits
+    SelectZerosSpillMismatch,
+}
+
+impl<B, R, S> fmt::Display for RankSelectError<B, R, S>
+where
+    B: fmt::Display,
+    R: fmt::Display,
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RankSelectError::CheckBitsError(e) => write!(f, "bits check error: {}", e),
+            RankSelectError::CheckRanksError(e) => {
+                write!(f, "superblock ranks check error: {}", e)
+            }
+            RankSelectError::CheckBlockRanksError(e) => {
+                write!(f, "block ranks check error: {}", e)
+            }
+            RankSelectError::CheckSelectOnesError(e) => {
+                write!(f, "set bit select samples check error: {}", e)
+            }
+            RankSelectError::CheckSelectOnesHintsError(e) => {
+                write!(f, "set bit select hints check error: {}", e)
+            }
+            RankSelectError::CheckSelectOnesSpillError(e) => {
+                write!(f, "set bit select spill check error: {}", e)
+            }
+            RankSelectError::CheckSelectZerosError(e) => {
+                write!(f, "unset bit select samples check error: {}", e)
+            }
+            RankSelectError::CheckSelectZerosHintsError(e) => {
+                write!(f, "unset bit select hints check error: {}", e)
+            }
+            RankSelectError::CheckSelectZerosSpillError(e) => {
+                write!(f, "unset bit select spill check error: {}", e)
+            }
+            RankSelectError::InvalidBitLen { bit_len, raw_bits } => write!(
+                f,
+                "invalid bit length: {} bits with only {} raw bits",
+                bit_len, raw_bits
+            ),
+            RankSelectError::RanksMismatch => write!(f, "superblock ranks do not match bits"),
+            RankSelectError::BlockRanksMismatch => write!(f, "block ranks do not match bits"),
+            RankSelectError::SelectOnesMismatch => {
+                write!(f, "set bit select samples do not match bits")
+            }
+            RankSelectError::SelectOnesHintsMismatch => {
+                write!(f, "set bit select hints do not match bits")
+            }
+            RankSelectError::SelectOnesSpillMismatch => {
+                write!(f, "set bit select spill does not match bits")
+            }
+            RankSelectError::SelectZerosMismatch => {
+                write!(f, "unset bit select samples do not match bits")
+            }
+            RankSelectError::SelectZerosHintsMismatch => {
+                write!(f, "unset bit select hints do not match bits")
+            }
+            RankSelectError::SelectZerosSpillMismatch => {
+                write!(f, "unset bit select spill does not match bits")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<B, R, S> Error for RankSelectError<B, R, S>
+    where
+        B: Error + 'static,
+        R: Error + 'static,
+        S: Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                RankSelectError::CheckBitsError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckRanksError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckBlockRanksError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectOnesError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectOnesHintsError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectOnesSpillError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectZerosError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectZerosHintsError(e) => Some(e as &dyn Error),
+                RankSelectError::CheckSelectZerosSpillError(e) => Some(e as &dyn Error),
+                RankSelectError::InvalidBitLen { .. } => None,
+                RankSelectError::RanksMismatch => None,
+                RankSelectError::BlockRanksMismatch => None,
+                RankSelectError::SelectOnesMismatch => None,
+                RankSelectError::SelectOnesHintsMismatch => None,
+                RankSelectError::SelectOnesSpillMismatch => None,
+                RankSelectError::SelectZerosMismatch => None,
+                RankSelectError::SelectZerosHintsMismatch => None,
+                RankSelectError::SelectZerosSpillMismatch => None,
+            }
+        }
+    }
+};
+
+impl<T, O, C> CheckBytes<C> for ArchivedRankSelect<T, O>
+where
+    T: BitStore,
+    O: BitOrder,
+    ArchivedBitVec<T, O>: CheckBytes<C>,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = RankSelectError<
+        <ArchivedBitVec<T, O> as CheckBytes<C>>::Error,
+        CheckOwnedPointerError<[Archived<usize>], C>,
+        CheckOwnedPointerError<[Archived<u16>], C>,
+    >;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let bits = ArchivedBitVec::<T, O>::check_bytes(ptr::addr_of!((*value).bits), context)
+            .map_err(RankSelectError::CheckBitsError)?;
+        let ranks =
+            ArchivedVec::<Archived<usize>>::check_bytes(ptr::addr_of!((*value).ranks), context)
+                .map_err(RankSelectError::CheckRanksError)?;
+        let block_ranks =
+            ArchivedVec::<Archived<u16>>::check_bytes(ptr::addr_of!((*value).block_ranks), context)
+                .map_err(RankSelectError::CheckBlockRanksError)?;
+        let select_ones = ArchivedVec::<Archived<usize>>::check_bytes(
+            ptr::addr_of!((*value).select_ones),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectOnesError)?;
+        let select_ones_hints = ArchivedVec::<Archived<u16>>::check_bytes(
+            ptr::addr_of!((*value).select_ones_hints),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectOnesHintsError)?;
+        let select_ones_spill = ArchivedVec::<Archived<usize>>::check_bytes(
+            ptr::addr_of!((*value).select_ones_spill),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectOnesSpillError)?;
+        let select_zeros = ArchivedVec::<Archived<usize>>::check_bytes(
+            ptr::addr_of!((*value).select_zeros),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectZerosError)?;
+        let select_zeros_hints = ArchivedVec::<Archived<u16>>::check_bytes(
+            ptr::addr_of!((*value).select_zeros_hints),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectZerosHintsError)?;
+        let select_zeros_spill = ArchivedVec::<Archived<usize>>::check_bytes(
+            ptr::addr_of!((*value).select_zeros_spill),
+            context,
+        )
+        .map_err(RankSelectError::CheckSelectZerosSpillError)?;
+
+        let raw_bits = bits.inner.view_bits::<O>();
+        let bit_len = from_archived!(bits.bit_len) as usize;
+        if bit_len > raw_bits.len() {
+            return Err(RankSelectError::InvalidBitLen {
+                bit_len,
+                raw_bits: raw_bits.len(),
+            });
+        }
+
+        // Rebuilding the index is the simplest way to make sure that every query stays in bounds
+        // and returns the right answer
+        let index = RankSelectIndex::new(&raw_bits[..bit_len]);
+        let matches = |archived: &[Archived<usize>], expected: &[usize]| {
+            archived.len() == expected.len()
+                && archived
+                    .iter()
+                    .zip(expected)
+                    .all(|(a, e)| from_archived!(*a) as usize == *e)
+        };
+        let matches_u16 = |archived: &[Archived<u16>], expected: &[u16]| {
+            archived.len() == expected.len()
+                && archived
+                    .iter()
+                    .zip(expected)
+                    .all(|(a, e)| from_archived!(*a) == *e)
+        };
+        if !matches(ranks, &index.ranks) {
+            return Err(RankSelectError::RanksMismatch);
+        }
+        if !matches_u16(block_ranks, &index.block_ranks) {
+            return Err(RankSelectError::BlockRanksMismatch);
+        }
+        if !matches(select_ones, &index.select_ones.samples) {
+            return Err(RankSelectError::SelectOnesMismatch);
+        }
+        if !matches_u16(select_ones_hints, &index.select_ones.hints) {
+            return Err(RankSelectError::SelectOnesHintsMismatch);
+        }
+        if !matches(select_ones_spill, &index.select_ones.spill) {
+            return Err(RankSelectError::SelectOnesSpillMismatch);
+        }
+        if !matches(select_zeros, &index.select_zeros.samples) {
+            return Err(RankSelectError::SelectZerosMismatch);
+        }
+        if !matches_u16(select_zeros_hints, &index.select_zeros.hints) {
+            return Err(RankSelectError::SelectZerosHintsMismatch);
+        }
+        if !matches(select_zeros_spill, &index.select_zeros.spill) {
+            return Err(RankSelectError::SelectZerosSpillMismatch);
+        }
+
+        Ok(&*value)
+    }
+}
@@ -295,3 +295,49 @@ const _: () = {
         }
     }
 };
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<T: ArchivePointee + Compact + ?Sized> Compact for ArchivedBox<T> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{PointeeSize, Transcode, TranscodeError, Transcoder};
+
+    impl<T> Transcode for ArchivedBox<T>
+    where
+        T: ArchivePointee + PointeeSize + Transcode + ?Sized,
+        T::ArchivedMetadata: Copy + Transcode,
+    {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            RelPtr::<T>::transcode(value.cast(), transcoder)
+        }
+    }
+};
@@ -11,6 +11,7 @@ use core::{
     hash::{Hash, Hasher},
     iter::FusedIterator,
     marker::PhantomData,
+    mem::MaybeUninit,
     ops::Index,
     ptr::NonNull,
 };
@@ -22,6 +23,16 @@ struct InnerNodeEntry<K> {
     key: K,
 }
 
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 struct LeafNodeEntry<K, V> {
     key: K,
@@ -193,7 +204,7 @@ pub struct ArchivedBTreeMap<K, V> {
 
 /// The resolver for an [`ArchivedBTreeMap`].
 pub struct BTreeMapResolver {
-    root_pos: usize,
+    pub(crate) root_pos: usize,
 }
 
 /// The minimum number of entries to place in a leaf node.
@@ -329,6 +340,14 @@ impl<K, V> ArchivedBTreeMap<K, V> {
         }
     }
 
+    /// Returns the offsets of the key and value in each entry of the map's leaf nodes.
+    #[inline]
+    pub fn entry_offsets() -> (usize, usize) {
+        let mut entry = MaybeUninit::<LeafNodeEntry<K, V>>::uninit();
+        let out = entry.as_mut_ptr();
+        (out_field!(out.key).0, out_field!(out.value).0)
+    }
+
     /// Returns the number of items in the archived B-tree map.
     #[inline]
     pub fn len(&self) -> usize {
@@ -367,11 +386,18 @@ impl<K, V> ArchivedBTreeMap<K, V> {
 
 #[cfg(feature = "alloc")]
 const _: () = {
-    use crate::{ser::Serializer, Serialize};
+    use crate::{
+        ser::{Serializer, SpillSpace},
+        Serialize, SpillVec,
+    };
     #[cfg(not(feature = "std"))]
     use alloc::vec::Vec;
     use core::mem;
 
+    // The memory span of a single node should not exceed 4kb to keep everything within the
+    // distance of a single IO page
+    const MAX_NODE_SIZE: usize = 4096;
+
     impl<K, V> ArchivedBTreeMap<K, V> {
         /// Serializes an ordered iterator of key-value pairs as a B-tree map.
         ///
@@ -392,10 +418,6 @@ const _: () = {
             if iter.len() == 0 {
                 Ok(BTreeMapResolver { root_pos: 0 })
             } else {
-                // The memory span of a single node should not exceed 4kb to keep everything within
-                // the distance of a single IO page
-                const MAX_NODE_SIZE: usize = 4096;
-
                 // The nodes that must go in the next level in reverse order (key, node_pos)
                 let mut next_level = Vec::new();
                 let mut resolvers = Vec::new();
@@ -551,6 +573,224 @@ const _: () = {
                 })
             }
         }
+
+        /// Serializes an ordered iterator of key-value pairs of unknown length as a B-tree map.
+        ///
+        /// Only the entries of a single node are kept in memory at a time. The first key of each
+        /// node is kept until the next level of the tree is written, and the node positions are
+        /// kept in spill space. If serialization fails, the keys that have not been written yet are
+        /// dropped and the spill space is truncated back to where it started. The result is the
+        /// same as [`serialize_from_reverse_iter`](ArchivedBTreeMap::serialize_from_reverse_iter).
+        ///
+        /// Returns the number of entries serialized along with the resolver.
+        ///
+        /// # Safety
+        ///
+        /// - Keys returned by the iterator must be unique
+        /// - Keys must be in reverse sorted order from last to first
+        pub unsafe fn serialize_from_reverse_stream<UK, UV, BK, BV, S, I>(
+            iter: I,
+            serializer: &mut S,
+        ) -> Result<(usize, BTreeMapResolver), S::Error>
+        where
+            UK: Serialize<S, Archived = K>,
+            UV: Serialize<S, Archived = V>,
+            BK: Borrow<UK>,
+            BV: Borrow<UV>,
+            S: Serializer + SpillSpace + ?Sized,
+            I: Iterator<Item = (BK, BV)>,
+        {
+            let spill_start = serializer.spill_pos();
+            let result = Self::serialize_reverse_stream_levels(iter, serializer);
+
+            // Free all of the levels at once, whether or not serialization succeeded
+            let truncated = serializer.truncate_spill(spill_start);
+            let result = result?;
+            truncated?;
+
+            Ok(result)
+        }
+
+        unsafe fn serialize_reverse_stream_levels<UK, UV, BK, BV, S, I>(
+            mut iter: I,
+            serializer: &mut S,
+        ) -> Result<(usize, BTreeMapResolver), S::Error>
+        where
+            UK: Serialize<S, Archived = K>,
+            UV: Serialize<S, Archived = V>,
+            BK: Borrow<UK>,
+            BV: Borrow<UV>,
+            S: Serializer + SpillSpace + ?Sized,
+            I: Iterator<Item = (BK, BV)>,
+        {
+            let mut len = 0;
+
+            // The nodes that must go in the next level in reverse order
+            let mut next_keys = Vec::new();
+            let mut next_positions = SpillVec::<u64>::new(serializer);
+            let mut last_node_pos = None;
+            let mut resolvers = Vec::new();
+
+            while let Some((key, value)) = iter.next() {
+                // Start a new block
+                let block_start_pos = serializer.pos();
+
+                // Serialize the last entry
+                let key_resolver = key.borrow().serialize(serializer)?;
+                let value_resolver = value.borrow().serialize(serializer)?;
+                resolvers.push((key, value, key_resolver, value_resolver));
+
+                loop {
+                    // This is an estimate of the block size
+                    // It's not exact because there may be padding to align the node and entries
+                    // slice
+                    let estimated_block_size = serializer.pos() - block_start_pos
+                        + mem::size_of::<NodeHeader>()
+                        + resolvers.len() * mem::size_of::<LeafNodeEntry<K, V>>();
+
+                    // If we've reached or exceeded the maximum node size and have put enough
+                    // entries in this node, then break
+                    if estimated_block_size >= MAX_NODE_SIZE
+                        && resolvers.len() >= MIN_ENTRIES_PER_LEAF_NODE
+                    {
+                        break;
+                    }
+
+                    if let Some((key, value)) = iter.next() {
+                        // Serialize the next entry
+                        let key_resolver = key.borrow().serialize(serializer)?;
+                        let value_resolver = value.borrow().serialize(serializer)?;
+                        resolvers.push((key, value, key_resolver, value_resolver));
+                    } else {
+                        break;
+                    }
+                }
+
+                // Finish the current node
+                serializer.align(usize::max(
+                    mem::align_of::<NodeHeader>(),
+                    mem::align_of::<LeafNodeEntry<K, V>>(),
+                ))?;
+                let raw_node = NodeHeaderData {
+                    meta: combine_meta(false, resolvers.len()),
+                    size: serializer.pos() - block_start_pos,
+                    // The last node in the next level is the next block we're linked to
+                    pos: last_node_pos,
+                };
+                let node_pos = serializer.resolve_aligned(&raw_node, ())?;
+                len += resolvers.len();
+
+                serializer.align_for::<LeafNodeEntry<K, V>>()?;
+                let mut first_key = None;
+                for (key, value, key_resolver, value_resolver) in resolvers.drain(..).rev() {
+                    serializer.resolve_aligned(
+                        &LeafNodeEntry {
+                            key: key.borrow(),
+                            value: value.borrow(),
+                        },
+                        (key_resolver, value_resolver),
+                    )?;
+                    first_key.get_or_insert(key);
+                }
+
+                // Add the first key and node position to the next level
+                next_keys.push(first_key.unwrap());
+                next_positions.push(serializer, node_pos as u64)?;
+                last_node_pos = Some(node_pos);
+            }
+
+            // Subsequent levels are populated by serializing node keys from the previous level
+            // When there's only one node left, that's our root
+            let mut resolvers = Vec::new();
+            while next_keys.len() > 1 {
+                // Our previous next level becomes our current level
+                let current_len = next_keys.len();
+                let mut current_keys = mem::take(&mut next_keys).into_iter();
+                let current_positions =
+                    mem::replace(&mut next_positions, SpillVec::new(serializer));
+
+                let mut next = 0;
+                while current_len - next > 1 {
+                    // Start a new inner block
+                    let block_start_pos = serializer.pos();
+
+                    // When we break, we're guaranteed to have at least one node left
+                    while current_len - next > 1 {
+                        let key = current_keys.next().unwrap();
+                        let pos = current_positions.get(serializer, next)? as usize;
+                        next += 1;
+
+                        // Serialize the next entry
+                        let resolver = key.borrow().serialize(serializer)?;
+                        resolvers.push((key, pos, resolver));
+
+                        // Estimate the block size
+                        let estimated_block_size = serializer.pos() - block_start_pos
+                            + mem::size_of::<NodeHeader>()
+                            + resolvers.len() * mem::size_of::<InnerNodeEntry<K>>();
+
+                        // If we've reached or exceeded the maximum node size and have put enough
+                        // keys in this node, then break
+                        if estimated_block_size >= MAX_NODE_SIZE
+                            && resolvers.len() >= MIN_ENTRIES_PER_INNER_NODE
+                        {
+                            break;
+                        }
+                    }
+
+                    // Same as serialize_from_reverse_iter: with exactly two entries left, serialize
+                    // the next one so that the final block does not get only one entry
+                    if current_len - next == 2 {
+                        let key = current_keys.next().unwrap();
+                        let pos = current_positions.get(serializer, next)? as usize;
+                        next += 1;
+
+                        // Serialize the next entry
+                        let resolver = key.borrow().serialize(serializer)?;
+                        resolvers.push((key, pos, resolver));
+                    }
+
+                    // The next item is the first node
+                    let first_key = current_keys.next().unwrap();
+                    let first_pos = current_positions.get(serializer, next)? as usize;
+                    next += 1;
+
+                    // Finish the current node
+                    serializer.align(usize::max(
+                        mem::align_of::<NodeHeaderData>(),
+                        mem::align_of::<InnerNodeEntry<K>>(),
+                    ))?;
+                    let node_header = NodeHeaderData {
+                        meta: combine_meta(true, resolvers.len()),
+                        size: serializer.pos() - block_start_pos,
+                        // The pos of the first key is used to make the pointer for inner nodes
+                        pos: Some(first_pos),
+                    };
+                    let node_pos = serializer.resolve_aligned(&node_header, ())?;
+
+                    serializer.align_for::<InnerNodeEntry<K>>()?;
+                    for (key, pos, resolver) in resolvers.drain(..).rev() {
+                        let inner_node_data = InnerNodeEntryData::<UK> { key: key.borrow() };
+                        serializer.resolve_aligned(&inner_node_data, (pos, resolver))?;
+                    }
+
+                    // Add the first key and node position to the next level
+                    next_keys.push(first_key);
+                    next_positions.push(serializer, node_pos as u64)?;
+                }
+
+                debug_assert!(current_len == next);
+            }
+
+            // The root is only node in the final level
+            let root_pos = if next_keys.is_empty() {
+                0
+            } else {
+                next_positions.get(serializer, 0)? as usize
+            };
+
+            Ok((len, BTreeMapResolver { root_pos }))
+        }
     }
 };
 
@@ -736,3 +976,225 @@ impl<'a, K, V> Iterator for Values<'a, K, V> {
 
 impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
 impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::{
+        compact::{field_pos, Compact, CompactResolvers, Compactor},
+        FixedUsize,
+    };
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+    use core::mem;
+    #[cfg(not(feature = "std"))]
+    use hashbrown::HashMap;
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    impl NodeHeader {
+        fn existing_pos<K, V>(&self, compactor: &Compactor) -> Option<usize> {
+            match self.classify::<K, V>() {
+                ClassifiedNode::Inner(node) => compactor.existing_pos(node),
+                ClassifiedNode::Leaf(node) => compactor.existing_pos(node),
+            }
+        }
+
+        /// Copies the node and the dependencies of its entries, then returns the position of the
+        /// node in the new archive.
+        ///
+        /// `nodes` maps the addresses of nodes that have already been copied to their positions in
+        /// the new archive. The nodes that this node points to must already have been copied.
+        fn compact_node<K: Compact, V: Compact>(
+            &self,
+            compactor: &mut Compactor,
+            nodes: &mut HashMap<usize, usize>,
+        ) -> usize {
+            let block_start_pos = compactor.pos();
+            let mut resolvers = CompactResolvers::new();
+
+            let out = match self.classify::<K, V>() {
+                ClassifiedNode::Inner(node) => {
+                    // Entries are serialized from the greatest key to the least
+                    for entry in node.tail.iter().rev() {
+                        entry.key.compact_dependencies(compactor, &mut resolvers);
+                    }
+                    // Inner nodes are aligned the same way that they are when serialized
+                    compactor.align(usize::max(
+                        mem::align_of::<NodeHeaderData>(),
+                        mem::align_of::<InnerNodeEntry<K>>(),
+                    ));
+                    let out = compactor.copy_value(node);
+                    for entry in node.tail.iter().rev() {
+                        let child = nodes[&(entry.ptr.as_ptr() as usize)];
+                        entry
+                            .ptr
+                            .compact_ptr(field_pos(node, out, &entry.ptr), child, compactor);
+                        entry.key.compact_pointers(
+                            field_pos(node, out, &entry.key),
+                            compactor,
+                            &mut resolvers,
+                        );
+                    }
+                    out
+                }
+                ClassifiedNode::Leaf(node) => {
+                    for entry in node.tail.iter().rev() {
+                        entry.compact_dependencies(compactor, &mut resolvers);
+                    }
+                    let out = compactor.copy_value(node);
+                    for entry in node.tail.iter().rev() {
+                        entry.compact_pointers(
+                            field_pos(node, out, entry),
+                            compactor,
+                            &mut resolvers,
+                        );
+                    }
+                    out
+                }
+            };
+
+            // The last leaf node has a null pointer instead of pointing to a next leaf node
+            let ptr_pos = field_pos(self, out, &self.ptr);
+            let target = if self.ptr.is_null() {
+                ptr_pos
+            } else {
+                nodes[&(self.ptr.as_ptr() as usize)]
+            };
+            self.ptr.compact_ptr(ptr_pos, target, compactor);
+
+            // The size of a node covers the dependencies of its entries that precede it
+            let size = to_archived!((out - block_start_pos) as FixedUsize);
+            compactor.write(field_pos(self, out, &self.size), size);
+
+            nodes.insert(self as *const Self as usize, out);
+            out
+        }
+    }
+
+    impl<K: Compact, V: Compact> Compact for ArchivedBTreeMap<K, V> {
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            // Empty maps have a dangling root pointer
+            if self.is_empty() {
+                return;
+            }
+
+            // Nodes are serialized one level at a time starting from the leaves, and each level is
+            // serialized from the greatest keys to the least. Copying them in the same order keeps
+            // the nodes in the order that validation expects, and guarantees that every node is
+            // copied after the nodes it points to.
+            let mut levels = vec![vec![unsafe { &*self.root.as_ptr() }]];
+            while levels.last().unwrap()[0].is_inner() {
+                let mut next_level = Vec::new();
+                for node in levels.last().unwrap().iter() {
+                    let node = node.classify_inner::<K>();
+                    next_level.push(unsafe { &*node.header.ptr.as_ptr() });
+                    for entry in node.tail.iter() {
+                        next_level.push(unsafe { &*entry.ptr.as_ptr() });
+                    }
+                }
+                levels.push(next_level);
+            }
+
+            // Nodes point to each other, so they are either all reused or all copied
+            if levels
+                .iter()
+                .flatten()
+                .all(|node| node.existing_pos::<K, V>(compactor).is_some())
+            {
+                resolvers.push(levels[0][0].existing_pos::<K, V>(compactor).unwrap());
+                return;
+            }
+
+            let mut nodes = HashMap::new();
+            let mut root = 0;
+            for level in levels.iter().rev() {
+                for node in level.iter().rev() {
+                    root = node.compact_node::<K, V>(compactor, &mut nodes);
+                }
+            }
+            resolvers.push(root);
+        }
+
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            let root_pos = field_pos(self, out, &self.root);
+            let target = if self.is_empty() {
+                root_pos
+            } else {
+                resolvers.take()
+            };
+            self.root.compact_ptr(root_pos, target, compactor);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::ptr;
+
+    impl NodeHeader {
+        unsafe fn transcode_node<K: Transcode, V: Transcode>(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let meta_ptr = ptr::addr_of_mut!((*value).meta);
+            let (is_inner, len) = split_meta(from_archived!(transcoder.read(meta_ptr)));
+            Archived::<u16>::transcode(meta_ptr, transcoder)?;
+            Archived::<usize>::transcode(ptr::addr_of_mut!((*value).size), transcoder)?;
+            let next = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).ptr), transcoder)?;
+
+            if is_inner {
+                Self::transcode_node::<K, V>(next, transcoder)?;
+
+                let node = ptr_meta::from_raw_parts_mut::<InnerNode<K>>(value.cast(), len);
+                let entries = ptr::addr_of_mut!((*node).tail);
+                transcoder.bounds_check_range(
+                    entries.cast(),
+                    len * core::mem::size_of::<InnerNodeEntry<K>>(),
+                )?;
+                for i in 0..len {
+                    let entry = entries.cast::<InnerNodeEntry<K>>().add(i);
+                    let child = RelPtr::transcode_ptr(ptr::addr_of_mut!((*entry).ptr), transcoder)?;
+                    K::transcode(ptr::addr_of_mut!((*entry).key), transcoder)?;
+                    Self::transcode_node::<K, V>(child, transcoder)?;
+                }
+            } else {
+                // The pointer of a leaf node points to the next leaf node, which is transcoded
+                // when it is reached through its parent instead
+                let node = ptr_meta::from_raw_parts_mut::<LeafNode<K, V>>(value.cast(), len);
+                <[LeafNodeEntry<K, V>]>::transcode(ptr::addr_of_mut!((*node).tail), transcoder)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<K: Transcode, V: Transcode> Transcode for ArchivedBTreeMap<K, V> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let len_ptr = ptr::addr_of_mut!((*value).len);
+            let len = from_archived!(transcoder.read(len_ptr)) as usize;
+            Archived::<usize>::transcode(len_ptr, transcoder)?;
+
+            // Empty maps have a dangling root pointer
+            let root = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).root), transcoder)?;
+            if len > 0 {
+                NodeHeader::transcode_node::<K, V>(root, transcoder)?;
+            }
+
+            Ok(())
+        }
+    }
+};
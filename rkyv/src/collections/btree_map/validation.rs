@@ -19,6 +19,89 @@ use core::{
 };
 use ptr_meta::Pointee;
 
+impl<K, V> ArchivedBTreeMap<K, V> {
+    /// Visits the leaf nodes of a map whose leaf entries have the given layout, in key order.
+    ///
+    /// This does not need to know the key and value types, so it can be used to read maps with a
+    /// layout that is only known at runtime. `visit` is called with a pointer to the first entry of
+    /// each leaf and the number of entries in that leaf, and returns whether to keep visiting.
+    /// Returns the number of entries in the leaves that were visited. Walking stops early if the
+    /// tree has more levels or leaf entries than the map has entries, so a malformed tree returns a
+    /// count that doesn't match the length of the map.
+    ///
+    /// # Safety
+    ///
+    /// `map` must be inside the archive that `context` was created for, and the map itself must
+    /// already have been bounds checked.
+    pub(crate) unsafe fn visit_raw_leaves<C, E, F>(
+        map: *const Self,
+        entry_layout: Layout,
+        context: &mut C,
+        mut visit: F,
+    ) -> Result<usize, E>
+    where
+        C: ArchiveContext + ?Sized,
+        E: From<C::Error>,
+        F: FnMut(*const u8, usize) -> Result<bool, E>,
+    {
+        unsafe fn check_node<'a, C: ArchiveContext + ?Sized>(
+            ptr: &crate::RelPtr<NodeHeader>,
+            context: &mut C,
+        ) -> Result<&'a NodeHeader, C::Error> {
+            let node = context.bounds_check_ptr(ptr.base(), ptr.offset())?;
+            context.bounds_check_layout(node, &Layout::new::<NodeHeader>())?;
+            Ok(&*node.cast::<NodeHeader>())
+        }
+
+        let map = &*map;
+        let len = map.len();
+        if len == 0 {
+            return Ok(0);
+        }
+
+        // Every level of the tree has at least one entry, so a well-formed tree can't be deeper
+        // than the number of entries
+        let mut node = check_node(&map.root, context)?;
+        let mut depth = 0;
+        while node.is_inner() {
+            depth += 1;
+            if depth > len {
+                return Ok(0);
+            }
+            node = check_node(&node.ptr, context)?;
+        }
+
+        let entry_layout = entry_layout.pad_to_align();
+        let entries_offset = Layout::new::<NodeHeader>()
+            .extend(entry_layout)
+            .map_err(C::wrap_layout_error)?
+            .1;
+        let mut visited = 0;
+        loop {
+            let node_ptr = (node as *const NodeHeader).cast::<u8>();
+            let node_len = node.len();
+            let node_layout = Layout::from_size_align(
+                entries_offset + entry_layout.size() * node_len,
+                usize::max(Layout::new::<NodeHeader>().align(), entry_layout.align()),
+            )
+            .map_err(C::wrap_layout_error)?;
+            context.bounds_check_layout(node_ptr, &node_layout)?;
+
+            visited += node_len;
+            if visited > len || !visit(node_ptr.add(entries_offset), node_len)? {
+                return Ok(visited);
+            }
+            if node.ptr.is_null() {
+                return Ok(visited);
+            }
+            node = check_node(&node.ptr, context)?;
+            if node.is_inner() {
+                return Ok(visited);
+            }
+        }
+    }
+}
+
 impl<K, C> CheckBytes<C> for InnerNodeEntry<K>
 where
     K: CheckBytes<C>,
@@ -116,3 +116,45 @@ impl<'a, K> IntoIterator for &'a ArchivedBTreeSet<K> {
 
 /// The resolver for archived B-tree sets.
 pub struct BTreeSetResolver(BTreeMapResolver);
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact> Compact for ArchivedBTreeSet<K> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<K: Transcode> Transcode for ArchivedBTreeSet<K> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedBTreeMap::<K, ()>::transcode(core::ptr::addr_of_mut!((*value).0), transcoder)
+        }
+    }
+};
@@ -5,44 +5,244 @@ use crate::{Archive, Archived, RelPtr};
 use core::{
     fmt,
     hash::{Hash, Hasher},
+    marker::PhantomData,
     slice,
 };
+use seahash::SeaHasher;
 
-/// The hash builder for archived hash indexes.
+/// The default hasher for archived hash indexes.
 pub use seahash::SeaHasher as HashBuilder;
 
 #[cfg(feature = "validation")]
 pub mod validation;
 
+/// A hasher that archived hash indexes can use to hash their keys.
+///
+/// Hashers are created from a seed that is stored in the archive alongside the hash index, so
+/// every hash index can use a different seed and still be read back with the same hasher.
+pub trait ArchiveHasher: Hasher + Clone {
+    /// Whether hash indexes store their seed in the archive.
+    ///
+    /// Hash indexes that don't store their seed always use a seed of zero. The seed is stored right
+    /// before the displacements of the hash index, so hash indexes that don't store it have the
+    /// same format as hash indexes from before hashers could be chosen.
+    const STORE_SEED: bool = true;
+
+    /// Creates a new hasher from the given seed.
+    fn from_seed(seed: u64) -> Self;
+
+    /// Returns the seed to use for a newly-serialized hash index.
+    ///
+    /// The default seed is zero so that serializing the same collection always produces the same
+    /// archive.
+    #[inline]
+    fn new_seed() -> u64 {
+        0
+    }
+}
+
+impl ArchiveHasher for SeaHasher {
+    const STORE_SEED: bool = false;
+
+    #[inline]
+    fn from_seed(seed: u64) -> Self {
+        SeaHasher::with_seeds(
+            0x08576fb6170b5f5f ^ seed,
+            0x587775eeb84a7e46 ^ seed.rotate_left(16),
+            0xac701115428ee569 ^ seed.rotate_left(32),
+            0x910feb91b92bb1cd ^ seed.rotate_left(48),
+        )
+    }
+}
+
+/// A fast hasher for small keys like integers.
+///
+/// This is the hasher used by the Rust compiler with an extra finalization step to mix the bits of
+/// the hash. It is much faster than the default hasher for short keys, but it is not resistant to
+/// keys that are chosen to collide.
+#[derive(Clone, Copy, Debug)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    const K: u64 = 0x517cc1b727220a95;
+
+    #[inline]
+    fn add_to_hash(&mut self, value: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ value).wrapping_mul(Self::K);
+    }
+}
+
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut value = [0; 8];
+            value.copy_from_slice(chunk);
+            self.add_to_hash(u64::from_le_bytes(value));
+        }
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut value = [0; 8];
+            value[..remainder.len()].copy_from_slice(remainder);
+            self.add_to_hash(u64::from_le_bytes(value));
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        // The default writes the native bytes, which would make the hash depend on the host
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        // Hash indexes use the low bits of hashes, which are poorly mixed by the multiplication
+        let mut hash = self.hash;
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+        hash ^ (hash >> 33)
+    }
+}
+
+impl ArchiveHasher for FxHasher {
+    #[inline]
+    fn from_seed(seed: u64) -> Self {
+        Self { hash: seed }
+    }
+}
+
+/// A hasher that uses a random seed for every hash index that it serializes.
+///
+/// Random seeds make it difficult to choose keys that collide, at the cost of serializing the same
+/// collection to different bytes every time.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct Keyed<H>(H);
+
+// Every method is forwarded because the defaults write the native bytes of integers, which can
+// hash differently than the wrapped hasher does
+#[cfg(feature = "std")]
+macro_rules! forward_writes {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            #[inline]
+            fn $method(&mut self, i: $ty) {
+                self.0.$method(i);
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
+impl<H: Hasher> Hasher for Keyed<H> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    forward_writes!(
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_u128(u128),
+        write_usize(usize),
+        write_i8(i8),
+        write_i16(i16),
+        write_i32(i32),
+        write_i64(i64),
+        write_i128(i128),
+        write_isize(isize),
+    );
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: ArchiveHasher> ArchiveHasher for Keyed<H> {
+    #[inline]
+    fn from_seed(seed: u64) -> Self {
+        Self(H::from_seed(seed))
+    }
+
+    #[inline]
+    fn new_seed() -> u64 {
+        use std::{collections::hash_map::RandomState, hash::BuildHasher};
+
+        RandomState::new().build_hasher().finish()
+    }
+}
+
 /// An archived hash index.
+///
+/// If the hasher stores its seed, the seed is located right before the displacements.
 #[cfg_attr(feature = "strict", repr(C))]
-pub struct ArchivedHashIndex {
+pub struct ArchivedHashIndex<H = HashBuilder> {
     len: Archived<usize>,
     displace: RelPtr<Archived<u32>>,
+    _hasher: PhantomData<H>,
 }
 
-impl ArchivedHashIndex {
+impl<H> ArchivedHashIndex<H> {
     /// Gets the number of items in the hash index.
     #[inline]
     pub const fn len(&self) -> usize {
         from_archived!(self.len) as usize
     }
 
+    /// Gets the seed that the hasher for this hash index is created from.
     #[inline]
-    fn make_hasher() -> HashBuilder {
-        HashBuilder::with_seeds(
-            0x08576fb6170b5f5f,
-            0x587775eeb84a7e46,
-            0xac701115428ee569,
-            0x910feb91b92bb1cd,
-        )
+    pub fn seed(&self) -> u64
+    where
+        H: ArchiveHasher,
+    {
+        if H::STORE_SEED {
+            unsafe { from_archived!(*self.displace.as_ptr().cast::<Archived<u64>>().sub(1)) }
+        } else {
+            0
+        }
     }
 
-    /// Gets the hasher for this hash index. The hasher for all archived hash indexes is the same
-    /// for reproducibility.
+    /// Gets the hasher for this hash index.
     #[inline]
-    pub fn hasher(&self) -> HashBuilder {
-        Self::make_hasher()
+    pub fn hasher(&self) -> H
+    where
+        H: ArchiveHasher,
+    {
+        H::from_seed(self.seed())
     }
 
     #[inline]
@@ -60,7 +260,10 @@ impl ArchivedHashIndex {
     /// The hash index does not have access to the keys used to build it, so the key at the returned
     /// index must be checked for equality.
     #[inline]
-    pub fn index<K: Hash + ?Sized>(&self, k: &K) -> Option<usize> {
+    pub fn index<K: Hash + ?Sized>(&self, k: &K) -> Option<usize>
+    where
+        H: ArchiveHasher,
+    {
         if self.is_empty() {
             return None;
         }
@@ -113,8 +316,8 @@ impl ArchivedHashIndex {
 #[cfg(feature = "alloc")]
 const _: () = {
     use crate::{
-        ser::{ScratchSpace, Serializer},
-        ScratchVec,
+        ser::{ScratchSpace, Serializer, SpillSpace},
+        ScratchVec, SpillVec,
     };
     #[cfg(not(feature = "std"))]
     use alloc::vec::Vec;
@@ -123,19 +326,49 @@ const _: () = {
         mem::{size_of, MaybeUninit},
     };
 
-    impl ArchivedHashIndex {
+    impl<H: ArchiveHasher> ArchivedHashIndex<H> {
         /// Builds and serializes a hash index from an iterator of key-value pairs.
         ///
+        /// The hash index uses a new seed from the hasher.
+        ///
         /// # Safety
         ///
         /// - The keys returned by the iterator must be unique.
         /// - `entries` must have a capacity of `iter.len()` entries.
         #[allow(clippy::type_complexity)]
+        #[inline]
         pub unsafe fn build_and_serialize<'a, K, V, S, I>(
             iter: I,
             serializer: &mut S,
             entries: &mut ScratchVec<MaybeUninit<(&'a K, &'a V)>>,
         ) -> Result<HashIndexResolver, S::Error>
+        where
+            K: 'a + Hash,
+            V: 'a,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a K, &'a V)>,
+        {
+            Self::build_and_serialize_with_seed(iter, H::new_seed(), serializer, entries)
+        }
+
+        /// Builds and serializes a hash index from an iterator of key-value pairs using the given
+        /// seed for the hasher.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the seed is not zero and the hasher doesn't store its seed.
+        ///
+        /// # Safety
+        ///
+        /// - The keys returned by the iterator must be unique.
+        /// - `entries` must have a capacity of `iter.len()` entries.
+        #[allow(clippy::type_complexity)]
+        pub unsafe fn build_and_serialize_with_seed<'a, K, V, S, I>(
+            iter: I,
+            seed: u64,
+            serializer: &mut S,
+            entries: &mut ScratchVec<MaybeUninit<(&'a K, &'a V)>>,
+        ) -> Result<HashIndexResolver, S::Error>
         where
             K: 'a + Hash,
             V: 'a,
@@ -152,7 +385,7 @@ const _: () = {
             let mut displaces = ScratchVec::new(serializer, len)?;
 
             for (key, value) in iter {
-                let mut hasher = Self::make_hasher();
+                let mut hasher = H::from_seed(seed);
                 key.hash(&mut hasher);
                 let displace = (hasher.finish() % len as u64) as u32;
                 displaces.push((displace, (key, value)));
@@ -184,14 +417,14 @@ const _: () = {
                 start = end;
 
                 if bucket_size > 1 {
-                    'find_seed: for seed in 0x80_00_00_00u32..=0xFF_FF_FF_FFu32 {
-                        let mut base_hasher = Self::make_hasher();
-                        seed.hash(&mut base_hasher);
+                    'find_seed: for displace_seed in 0x80_00_00_00u32..=0xFF_FF_FF_FFu32 {
+                        let mut base_hasher = H::from_seed(seed);
+                        displace_seed.hash(&mut base_hasher);
 
                         assignments.clear();
 
                         for &(_, (key, _)) in bucket.iter() {
-                            let mut hasher = base_hasher;
+                            let mut hasher = base_hasher.clone();
                             key.hash(&mut hasher);
                             let index = (hasher.finish() % len as u64) as u32;
                             if occupied[index as usize] || assignments.contains(&index) {
@@ -207,7 +440,7 @@ const _: () = {
                                 .as_mut_ptr()
                                 .write(bucket[i].1);
                         }
-                        displacements[displace as usize] = to_archived!(seed);
+                        displacements[displace as usize] = to_archived!(displace_seed);
                         break;
                     }
                 } else {
@@ -223,8 +456,7 @@ const _: () = {
                 }
             }
 
-            // Write displacements
-            let displace_pos = serializer.align_for::<Archived<u32>>()?;
+            let displace_pos = Self::serialize_seed(seed, serializer)?;
             let displacements_slice = slice::from_raw_parts(
                 displacements.as_ptr().cast::<u8>(),
                 len * size_of::<Archived<u32>>(),
@@ -239,10 +471,197 @@ const _: () = {
 
             Ok(HashIndexResolver { displace_pos })
         }
+
+        /// Builds and serializes a hash index from a slice of entries using the given seed for the
+        /// hasher.
+        ///
+        /// `hash_key` must hash the key of an entry into the given hasher. All of the data used to
+        /// build the hash index is kept in spill space, so memory usage beyond the entries only
+        /// grows with the size of the largest bucket. The result is the same as
+        /// [`build_and_serialize_with_seed`](ArchivedHashIndex::build_and_serialize_with_seed) with
+        /// the entries in the same order.
+        ///
+        /// Returns the resolver along with the index of the entry placed in each slot. The slots
+        /// are left in spill space where it ended when this was called. If serialization fails, the
+        /// spill space is truncated back to where it ended instead.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the seed is not zero and the hasher doesn't store its seed.
+        ///
+        /// # Safety
+        ///
+        /// The keys of the entries must be unique.
+        pub unsafe fn build_and_serialize_with_spill<E, F, S>(
+            entries: &[E],
+            hash_key: F,
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<(HashIndexResolver, SpillVec<u32>), S::Error>
+        where
+            F: Fn(&E, &mut H),
+            S: Serializer + SpillSpace + ?Sized,
+        {
+            let spill_start = serializer.spill_pos();
+            let result = Self::build_and_serialize_spilled(entries, hash_key, seed, serializer);
+            if result.is_err() {
+                // Leave the spill space the way we found it even if this fails too
+                let _ = serializer.truncate_spill(spill_start);
+            }
+            result
+        }
+
+        unsafe fn build_and_serialize_spilled<E, F, S>(
+            entries: &[E],
+            hash_key: F,
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<(HashIndexResolver, SpillVec<u32>), S::Error>
+        where
+            F: Fn(&E, &mut H),
+            S: Serializer + SpillSpace + ?Sized,
+        {
+            const CHUNK_LEN: usize = 64;
+
+            let len = entries.len();
+
+            let mut slots = SpillVec::from_elem(serializer, 0u32, len)?;
+
+            // The entries in each bucket are linked together in the order they were given
+            let mut bucket_size = SpillVec::from_elem(serializer, 0u32, len)?;
+            let mut bucket_head = SpillVec::from_elem(serializer, u32::MAX, len)?;
+            let mut bucket_next = SpillVec::from_elem(serializer, u32::MAX, len)?;
+            let mut max_bucket_size = 0;
+            for (i, entry) in entries.iter().enumerate().rev() {
+                let mut hasher = H::from_seed(seed);
+                hash_key(entry, &mut hasher);
+                let displace = (hasher.finish() % len as u64) as usize;
+
+                let size = bucket_size.get(serializer, displace)? + 1;
+                bucket_size.set(serializer, displace, size)?;
+                max_bucket_size = u32::max(max_bucket_size, size);
+                let next = bucket_head.get(serializer, displace)?;
+                bucket_next.set(serializer, i, next)?;
+                bucket_head.set(serializer, displace, i as u32)?;
+            }
+
+            let mut occupied = SpillVec::from_elem(serializer, 0u8, len)?;
+            let mut displacements = SpillVec::from_elem(serializer, u32::MAX, len)?;
+
+            let mut first_empty = 0;
+            let mut bucket = Vec::with_capacity(8);
+            let mut assignments = Vec::with_capacity(8);
+            let mut sizes = [0u32; CHUNK_LEN];
+
+            // Buckets are placed from largest to smallest, and in order within each size
+            for size in (1..=max_bucket_size).rev() {
+                for chunk_start in (0..len).step_by(CHUNK_LEN) {
+                    let chunk_len = usize::min(CHUNK_LEN, len - chunk_start);
+                    bucket_size.read(serializer, chunk_start, &mut sizes[..chunk_len])?;
+
+                    for (offset, _) in sizes[..chunk_len]
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, &s)| s == size)
+                    {
+                        let displace = chunk_start + offset;
+
+                        if size > 1 {
+                            bucket.clear();
+                            let mut next = bucket_head.get(serializer, displace)?;
+                            while next != u32::MAX {
+                                bucket.push((next, &entries[next as usize]));
+                                next = bucket_next.get(serializer, next as usize)?;
+                            }
+
+                            'find_seed: for displace_seed in 0x80_00_00_00u32..=0xFF_FF_FF_FFu32 {
+                                let mut base_hasher = H::from_seed(seed);
+                                displace_seed.hash(&mut base_hasher);
+
+                                assignments.clear();
+
+                                for &(_, entry) in bucket.iter() {
+                                    let mut hasher = base_hasher.clone();
+                                    hash_key(entry, &mut hasher);
+                                    let index = (hasher.finish() % len as u64) as u32;
+                                    if occupied.get(serializer, index as usize)? != 0
+                                        || assignments.contains(&index)
+                                    {
+                                        continue 'find_seed;
+                                    } else {
+                                        assignments.push(index);
+                                    }
+                                }
+
+                                for (&index, &(entry_index, _)) in
+                                    assignments.iter().zip(bucket.iter())
+                                {
+                                    occupied.set(serializer, index as usize, 1)?;
+                                    slots.set(serializer, index as usize, entry_index)?;
+                                }
+                                displacements.set(serializer, displace, displace_seed)?;
+                                break;
+                            }
+                        } else {
+                            while occupied.get(serializer, first_empty)? != 0 {
+                                first_empty += 1;
+                            }
+                            occupied.set(serializer, first_empty, 1)?;
+                            let entry_index = bucket_head.get(serializer, displace)?;
+                            slots.set(serializer, first_empty, entry_index)?;
+                            displacements.set(serializer, displace, first_empty as u32)?;
+                            first_empty += 1;
+                        }
+                    }
+                }
+            }
+
+            let displace_pos = Self::serialize_seed(seed, serializer)?;
+            let mut chunk = [0u32; CHUNK_LEN];
+            let mut archived_chunk = [to_archived!(0u32); CHUNK_LEN];
+            for chunk_start in (0..len).step_by(CHUNK_LEN) {
+                let chunk_len = usize::min(CHUNK_LEN, len - chunk_start);
+                displacements.read(serializer, chunk_start, &mut chunk[..chunk_len])?;
+                for (archived, &displacement) in archived_chunk.iter_mut().zip(&chunk[..chunk_len])
+                {
+                    *archived = to_archived!(displacement);
+                }
+                serializer.write(slice::from_raw_parts(
+                    archived_chunk.as_ptr().cast::<u8>(),
+                    chunk_len * size_of::<Archived<u32>>(),
+                ))?;
+            }
+
+            // Free spill vecs
+            displacements.free(serializer)?;
+            occupied.free(serializer)?;
+            bucket_next.free(serializer)?;
+            bucket_head.free(serializer)?;
+            bucket_size.free(serializer)?;
+
+            Ok((HashIndexResolver { displace_pos }, slots))
+        }
+
+        /// Writes the seed if the hasher stores it, then returns the position of the displacements.
+        #[inline]
+        fn serialize_seed<S: Serializer + ?Sized>(
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<usize, S::Error> {
+            if H::STORE_SEED {
+                serializer.serialize_value(&seed)?;
+            } else {
+                assert_eq!(
+                    seed, 0,
+                    "hashers that don't store their seed must use a seed of zero"
+                );
+            }
+            serializer.align_for::<Archived<u32>>()
+        }
     }
 };
 
-impl fmt::Debug for ArchivedHashIndex {
+impl<H> fmt::Debug for ArchivedHashIndex<H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.displace_slice()).finish()
     }
@@ -252,3 +671,84 @@ impl fmt::Debug for ArchivedHashIndex {
 pub struct HashIndexResolver {
     displace_pos: usize,
 }
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<H: ArchiveHasher> Compact for ArchivedHashIndex<H> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            if H::STORE_SEED {
+                // The displacements are aligned right after the seed
+                let seed = unsafe { &*self.displace.as_ptr().cast::<Archived<u64>>().sub(1) };
+                compactor.compact_out_of_line(seed);
+            }
+            resolvers.push(compactor.compact_out_of_line(self.displace_slice()));
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.displace.compact_ptr(
+                field_pos(self, out, &self.displace),
+                resolvers.take(),
+                compactor,
+            );
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::{mem::size_of, ptr};
+
+    impl<H: ArchiveHasher> ArchivedHashIndex<H> {
+        /// Transcodes the hash index and returns its length.
+        ///
+        /// # Safety
+        ///
+        /// `value` must point to a valid hash index inside the archive that `transcoder` was
+        /// created for.
+        #[inline]
+        pub(crate) unsafe fn transcode_len(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<usize, TranscodeError> {
+            let len_ptr = ptr::addr_of_mut!((*value).len);
+            let len = from_archived!(transcoder.read(len_ptr)) as usize;
+            Archived::<usize>::transcode(len_ptr, transcoder)?;
+
+            let displace = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).displace), transcoder)?;
+            if H::STORE_SEED {
+                let seed = transcoder
+                    .bounds_check_ptr(displace.cast(), -(size_of::<Archived<u64>>() as isize))?
+                    .cast::<Archived<u64>>();
+                transcoder.bounds_check_pointee(seed)?;
+                Archived::<u64>::transcode(seed, transcoder)?;
+            }
+            <[Archived<u32>]>::transcode(ptr::slice_from_raw_parts_mut(displace, len), transcoder)?;
+
+            Ok(len)
+        }
+    }
+
+    impl<H: ArchiveHasher> Transcode for ArchivedHashIndex<H> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            Self::transcode_len(value, transcoder).map(|_| ())
+        }
+    }
+};
@@ -1,11 +1,17 @@
 //! Validation implementation for ArchivedHashIndex.
 
-use crate::{collections::ArchivedHashIndex, validation::ArchiveContext, Archived, RelPtr};
+use crate::{
+    collections::{hash_index::ArchiveHasher, ArchivedHashIndex},
+    validation::ArchiveContext,
+    Archived, RelPtr,
+};
 use bytecheck::{CheckBytes, Error, SliceCheckError};
 use core::{
     alloc::{Layout, LayoutError},
     convert::Infallible,
-    fmt, ptr,
+    fmt,
+    mem::size_of,
+    ptr,
 };
 
 /// Errors that can occur while checking an archived hash index.
@@ -74,7 +80,7 @@ const _: () = {
     }
 };
 
-impl<C: ArchiveContext + ?Sized> CheckBytes<C> for ArchivedHashIndex
+impl<C: ArchiveContext + ?Sized, H: ArchiveHasher> CheckBytes<C> for ArchivedHashIndex<H>
 where
     C::Error: Error,
 {
@@ -100,8 +106,30 @@ where
             )
             .map_err(HashIndexError::ContextError)?;
 
+        // The seed is located right before the displacements
+        let start = if H::STORE_SEED {
+            let seed_ptr = context
+                .check_subtree_ptr::<Archived<u64>>(
+                    displace_rel_ptr.base(),
+                    displace_rel_ptr
+                        .offset()
+                        .wrapping_sub(size_of::<Archived<u64>>() as isize),
+                    (),
+                )
+                .map_err(HashIndexError::ContextError)?;
+            Archived::<u64>::check_bytes(seed_ptr, context)?;
+            seed_ptr.cast::<u8>()
+        } else {
+            displace_ptr.cast::<u8>()
+        };
+
         let range = context
-            .push_prefix_subtree(displace_ptr)
+            .push_prefix_subtree_range(
+                start,
+                displace_ptr
+                    .cast::<u8>()
+                    .add(len * size_of::<Archived<u32>>()),
+            )
             .map_err(HashIndexError::ContextError)?;
         let displace = <[Archived<u32>]>::check_bytes(displace_ptr, context)?;
         context
@@ -8,14 +8,14 @@ pub mod validation;
 
 use crate::{
     collections::{
-        hash_index::{ArchivedHashIndex, HashIndexResolver},
+        hash_index::{ArchiveHasher, ArchivedHashIndex, HashBuilder, HashIndexResolver},
         util::Entry,
     },
     RelPtr,
 };
 #[cfg(feature = "alloc")]
 use crate::{
-    ser::{ScratchSpace, Serializer},
+    ser::{ScratchSpace, Serializer, SpillSpace},
     Serialize,
 };
 use core::{
@@ -24,25 +24,18 @@ use core::{
 
 /// An archived `HashMap`.
 #[cfg_attr(feature = "strict", repr(C))]
-pub struct ArchivedHashMap<K, V> {
-    index: ArchivedHashIndex,
+pub struct ArchivedHashMap<K, V, H = HashBuilder> {
+    index: ArchivedHashIndex<H>,
     entries: RelPtr<Entry<K, V>>,
 }
 
-impl<K, V> ArchivedHashMap<K, V> {
+impl<K, V, H> ArchivedHashMap<K, V, H> {
     /// Gets the number of items in the hash map.
     #[inline]
     pub const fn len(&self) -> usize {
         self.index.len()
     }
 
-    /// Gets the hasher for this hashmap. The hasher for all archived hashmaps is the same for
-    /// reproducibility.
-    #[inline]
-    pub fn hasher(&self) -> seahash::SeaHasher {
-        self.index.hasher()
-    }
-
     #[inline]
     unsafe fn entry(&self, index: usize) -> &Entry<K, V> {
         &*self.entries.as_ptr().add(index)
@@ -53,6 +46,100 @@ impl<K, V> ArchivedHashMap<K, V> {
         &mut *self.entries.as_mut_ptr().add(index)
     }
 
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn raw_iter(&self) -> RawIter<K, V> {
+        RawIter::new(self.entries.as_ptr().cast(), self.len())
+    }
+
+    #[inline]
+    fn raw_iter_pin(self: Pin<&mut Self>) -> RawIterPin<K, V> {
+        unsafe {
+            let hash_map = self.get_unchecked_mut();
+            RawIterPin::new(hash_map.entries.as_mut_ptr().cast(), hash_map.len())
+        }
+    }
+
+    /// Gets an iterator over the key-value entries in the hash map.
+    #[inline]
+    pub fn iter(&self) -> Iter<K, V> {
+        Iter {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Gets an iterator over the mutable key-value entries in the hash map.
+    #[inline]
+    pub fn iter_pin(self: Pin<&mut Self>) -> IterPin<K, V> {
+        IterPin {
+            inner: self.raw_iter_pin(),
+        }
+    }
+
+    /// Gets an iterator over the keys in the hash map.
+    #[inline]
+    pub fn keys(&self) -> Keys<K, V> {
+        Keys {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Gets an iterator over the values in the hash map.
+    #[inline]
+    pub fn values(&self) -> Values<K, V> {
+        Values {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Gets an iterator over the mutable values in the hash map.
+    #[inline]
+    pub fn values_pin(self: Pin<&mut Self>) -> ValuesPin<K, V> {
+        ValuesPin {
+            inner: self.raw_iter_pin(),
+        }
+    }
+
+    /// Resolves an archived hash map from a given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a hash map
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: HashMapResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.index);
+        ArchivedHashIndex::resolve_from_len(len, pos + fp, resolver.index_resolver, fo);
+
+        let (fp, fo) = out_field!(out.entries);
+        RelPtr::emplace(pos + fp, resolver.entries_pos, fo);
+    }
+}
+
+impl<K, V, H: ArchiveHasher> ArchivedHashMap<K, V, H> {
+    /// Gets the hasher for this hashmap.
+    #[inline]
+    pub fn hasher(&self) -> H {
+        self.index.hasher()
+    }
+
+    /// Gets the seed that the hasher for this hashmap is created from.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.index.seed()
+    }
+
     #[inline]
     fn find<Q: ?Sized>(&self, k: &Q) -> Option<usize>
     where
@@ -179,100 +266,43 @@ impl<K, V> ArchivedHashMap<K, V> {
                 .map(move |index| Pin::new_unchecked(&mut hash_map.entry_mut(index).value))
         }
     }
-
-    /// Returns `true` if the map contains no elements.
-    #[inline]
-    pub const fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
-
-    #[inline]
-    fn raw_iter(&self) -> RawIter<K, V> {
-        RawIter::new(self.entries.as_ptr().cast(), self.len())
-    }
-
-    #[inline]
-    fn raw_iter_pin(self: Pin<&mut Self>) -> RawIterPin<K, V> {
-        unsafe {
-            let hash_map = self.get_unchecked_mut();
-            RawIterPin::new(hash_map.entries.as_mut_ptr().cast(), hash_map.len())
-        }
-    }
-
-    /// Gets an iterator over the key-value entries in the hash map.
-    #[inline]
-    pub fn iter(&self) -> Iter<K, V> {
-        Iter {
-            inner: self.raw_iter(),
-        }
-    }
-
-    /// Gets an iterator over the mutable key-value entries in the hash map.
-    #[inline]
-    pub fn iter_pin(self: Pin<&mut Self>) -> IterPin<K, V> {
-        IterPin {
-            inner: self.raw_iter_pin(),
-        }
-    }
-
-    /// Gets an iterator over the keys in the hash map.
-    #[inline]
-    pub fn keys(&self) -> Keys<K, V> {
-        Keys {
-            inner: self.raw_iter(),
-        }
-    }
-
-    /// Gets an iterator over the values in the hash map.
-    #[inline]
-    pub fn values(&self) -> Values<K, V> {
-        Values {
-            inner: self.raw_iter(),
-        }
-    }
-
-    /// Gets an iterator over the mutable values in the hash map.
-    #[inline]
-    pub fn values_pin(self: Pin<&mut Self>) -> ValuesPin<K, V> {
-        ValuesPin {
-            inner: self.raw_iter_pin(),
-        }
-    }
-
-    /// Resolves an archived hash map from a given length and parameters.
-    ///
-    /// # Safety
-    ///
-    /// - `len` must be the number of elements that were serialized
-    /// - `pos` must be the position of `out` within the archive
-    /// - `resolver` must be the result of serializing a hash map
-    #[inline]
-    pub unsafe fn resolve_from_len(
-        len: usize,
-        pos: usize,
-        resolver: HashMapResolver,
-        out: *mut Self,
-    ) {
-        let (fp, fo) = out_field!(out.index);
-        ArchivedHashIndex::resolve_from_len(len, pos + fp, resolver.index_resolver, fo);
-
-        let (fp, fo) = out_field!(out.entries);
-        RelPtr::emplace(pos + fp, resolver.entries_pos, fo);
-    }
 }
 
 #[cfg(feature = "alloc")]
 const _: () = {
-    impl<K, V> ArchivedHashMap<K, V> {
+    impl<K, V, H: ArchiveHasher> ArchivedHashMap<K, V, H> {
         /// Serializes an iterator of key-value pairs as a hash map.
         ///
+        /// The hash map uses a new seed from its hasher.
+        ///
         /// # Safety
         ///
         /// The keys returned by the iterator must be unique.
+        #[inline]
         pub unsafe fn serialize_from_iter<'a, KU, VU, S, I>(
             iter: I,
             serializer: &mut S,
         ) -> Result<HashMapResolver, S::Error>
+        where
+            KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+            VU: 'a + Serialize<S, Archived = V>,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        {
+            Self::serialize_from_iter_with_seed(iter, H::new_seed(), serializer)
+        }
+
+        /// Serializes an iterator of key-value pairs as a hash map using the given seed for its
+        /// hasher.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        pub unsafe fn serialize_from_iter_with_seed<'a, KU, VU, S, I>(
+            iter: I,
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<HashMapResolver, S::Error>
         where
             KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
             VU: 'a + Serialize<S, Archived = V>,
@@ -285,8 +315,12 @@ const _: () = {
 
             let mut entries = ScratchVec::new(serializer, len)?;
             entries.set_len(len);
-            let index_resolver =
-                ArchivedHashIndex::build_and_serialize(iter, serializer, &mut entries)?;
+            let index_resolver = ArchivedHashIndex::<H>::build_and_serialize_with_seed(
+                iter,
+                seed,
+                serializer,
+                &mut entries,
+            )?;
             let mut entries = entries.assume_init();
 
             // Serialize entries
@@ -312,19 +346,132 @@ const _: () = {
                 entries_pos,
             })
         }
+
+        /// Serializes an iterator of key-value pairs of unknown length as a hash map.
+        ///
+        /// The hash map uses a new seed from its hasher.
+        ///
+        /// Returns the number of entries serialized along with the resolver.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        #[inline]
+        pub unsafe fn serialize_from_stream<KU, VU, BK, BV, S, I>(
+            iter: I,
+            serializer: &mut S,
+        ) -> Result<(usize, HashMapResolver), S::Error>
+        where
+            KU: Serialize<S, Archived = K> + Hash + Eq,
+            VU: Serialize<S, Archived = V>,
+            BK: Borrow<KU>,
+            BV: Borrow<VU>,
+            S: Serializer + SpillSpace + ?Sized,
+            I: Iterator<Item = (BK, BV)>,
+        {
+            Self::serialize_from_stream_with_seed(iter, H::new_seed(), serializer)
+        }
+
+        /// Serializes an iterator of key-value pairs of unknown length as a hash map using the
+        /// given seed for its hasher.
+        ///
+        /// Keys and values are serialized as they arrive. Resolving an entry still requires its
+        /// key and value, so they are kept in memory along with their resolvers until the entries
+        /// are written. The data used to build the hash index is kept in spill space instead of
+        /// scratch space. If serialization fails, the keys and values that have not been written
+        /// yet are dropped and the spill space is truncated back to where it started.
+        ///
+        /// The keys and values are written in the order they arrive instead of the order of their
+        /// entries, so the result is only byte-for-byte the same as
+        /// [`serialize_from_iter_with_seed`](ArchivedHashMap::serialize_from_iter_with_seed) when
+        /// they don't have any dependencies.
+        ///
+        /// Returns the number of entries serialized along with the resolver.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        pub unsafe fn serialize_from_stream_with_seed<KU, VU, BK, BV, S, I>(
+            iter: I,
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<(usize, HashMapResolver), S::Error>
+        where
+            KU: Serialize<S, Archived = K> + Hash + Eq,
+            VU: Serialize<S, Archived = V>,
+            BK: Borrow<KU>,
+            BV: Borrow<VU>,
+            S: Serializer + SpillSpace + ?Sized,
+            I: Iterator<Item = (BK, BV)>,
+        {
+            #[cfg(not(feature = "std"))]
+            use alloc::vec::Vec;
+
+            let mut entries = Vec::new();
+            let mut resolvers = Vec::new();
+            for (key, value) in iter {
+                let key_resolver = key.borrow().serialize(serializer)?;
+                let value_resolver = value.borrow().serialize(serializer)?;
+                entries.push((key, value));
+                resolvers.push(Some((key_resolver, value_resolver)));
+            }
+            let len = entries.len();
+
+            let (index_resolver, slots) = ArchivedHashIndex::<H>::build_and_serialize_with_spill(
+                &entries,
+                |(key, _): &(BK, BV), hasher| key.borrow().hash(hasher),
+                seed,
+                serializer,
+            )?;
+
+            // Write entries, then free the slots whether or not that succeeded
+            let result = (|| {
+                let entries_pos = serializer.align_for::<Entry<K, V>>()?;
+                for slot in 0..len {
+                    let index = slots.get(serializer, slot)? as usize;
+                    let (key, value) = &entries[index];
+                    // Each entry is placed in exactly one slot
+                    let resolver = resolvers[index].take().unwrap();
+                    serializer.resolve_aligned(
+                        &Entry {
+                            key: key.borrow(),
+                            value: value.borrow(),
+                        },
+                        resolver,
+                    )?;
+                }
+                Ok(entries_pos)
+            })();
+            let freed = slots.free(serializer);
+            let entries_pos = result?;
+            freed?;
+
+            Ok((
+                len,
+                HashMapResolver {
+                    index_resolver,
+                    entries_pos,
+                },
+            ))
+        }
     }
 };
 
-impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ArchivedHashMap<K, V> {
+impl<K: fmt::Debug, V: fmt::Debug, H> fmt::Debug for ArchivedHashMap<K, V, H> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<K: Hash + Eq, V: Eq> Eq for ArchivedHashMap<K, V> {}
+impl<K: Hash + Eq, V: Eq, H: ArchiveHasher> Eq for ArchivedHashMap<K, V, H> {}
 
-impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V> Index<&'_ Q> for ArchivedHashMap<K, V> {
+impl<K, Q, V, H> Index<&'_ Q> for ArchivedHashMap<K, V, H>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    H: ArchiveHasher,
+{
     type Output = V;
 
     #[inline]
@@ -333,7 +480,7 @@ impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V> Index<&'_ Q> for Archiv
     }
 }
 
-impl<K: Hash + Eq, V: PartialEq> PartialEq for ArchivedHashMap<K, V> {
+impl<K: Hash + Eq, V: PartialEq, H: ArchiveHasher> PartialEq for ArchivedHashMap<K, V, H> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         if self.len() != other.len() {
@@ -566,3 +713,59 @@ pub struct HashMapResolver {
     index_resolver: HashIndexResolver,
     entries_pos: usize,
 }
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact, V: Compact, H: ArchiveHasher> Compact for ArchivedHashMap<K, V, H> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.index.compact_dependencies(compactor, resolvers);
+            let entries = unsafe { core::slice::from_raw_parts(self.entries.as_ptr(), self.len()) };
+            resolvers.push(compactor.compact_out_of_line(entries));
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.index
+                .compact_pointers(field_pos(self, out, &self.index), compactor, resolvers);
+            self.entries.compact_ptr(
+                field_pos(self, out, &self.entries),
+                resolvers.take(),
+                compactor,
+            );
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::ptr;
+
+    impl<K: Transcode, V: Transcode, H: ArchiveHasher> Transcode for ArchivedHashMap<K, V, H> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let len = ArchivedHashIndex::<H>::transcode_len(
+                ptr::addr_of_mut!((*value).index),
+                transcoder,
+            )?;
+
+            let entries = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).entries), transcoder)?;
+            <[Entry<K, V>]>::transcode(ptr::slice_from_raw_parts_mut(entries, len), transcoder)
+        }
+    }
+};
@@ -2,7 +2,7 @@
 
 use crate::{
     collections::{
-        hash_index::validation::HashIndexError,
+        hash_index::{validation::HashIndexError, ArchiveHasher},
         hash_map::ArchivedHashMap,
         util::{validation::ArchivedEntryError, Entry},
         ArchivedHashIndex,
@@ -107,10 +107,19 @@ impl<K, V, C> From<SliceCheckError<ArchivedEntryError<K, V>>> for HashMapError<K
     }
 }
 
-impl<K, V, C> CheckBytes<C> for ArchivedHashMap<K, V>
+impl<K, V, H> ArchivedHashMap<K, V, H> {
+    /// Returns the relative pointer to the entries of the hash map.
+    #[inline]
+    pub(crate) fn entries_rel_ptr(&self) -> &RelPtr<Entry<K, V>> {
+        &self.entries
+    }
+}
+
+impl<K, V, C, H> CheckBytes<C> for ArchivedHashMap<K, V, H>
 where
     K: CheckBytes<C> + Eq + Hash,
     V: CheckBytes<C>,
+    H: ArchiveHasher,
     C: ArchiveContext + ?Sized,
     C::Error: Error,
 {
@@ -120,7 +129,7 @@ where
         value: *const Self,
         context: &mut C,
     ) -> Result<&'a Self, Self::Error> {
-        let index = ArchivedHashIndex::check_bytes(ptr::addr_of!((*value).index), context)?;
+        let index = ArchivedHashIndex::<H>::check_bytes(ptr::addr_of!((*value).index), context)?;
         Layout::array::<Entry<K, V>>(index.len())?;
 
         let entries_rel_ptr = RelPtr::manual_check_bytes(ptr::addr_of!((*value).entries), context)?;
@@ -3,7 +3,10 @@
 //! During archiving, hashsets are built into minimal perfect hashsets using
 //! [compress, hash and displace](http://cmph.sourceforge.net/papers/esa09.pdf).
 
-use crate::collections::hash_map::{ArchivedHashMap, HashMapResolver, Keys};
+use crate::collections::{
+    hash_index::{ArchiveHasher, HashBuilder},
+    hash_map::{ArchivedHashMap, HashMapResolver, Keys},
+};
 #[cfg(feature = "alloc")]
 use crate::{
     ser::{ScratchSpace, Serializer},
@@ -15,15 +18,47 @@ use core::{borrow::Borrow, fmt, hash::Hash};
 /// `()`.
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
 #[repr(transparent)]
-pub struct ArchivedHashSet<K>(ArchivedHashMap<K, ()>);
+pub struct ArchivedHashSet<K, H = HashBuilder>(ArchivedHashMap<K, (), H>);
 
-impl<K> ArchivedHashSet<K> {
+impl<K, H> ArchivedHashSet<K, H> {
     /// Gets the number of items in the hash set.
     #[inline]
     pub const fn len(&self) -> usize {
         self.0.len()
     }
 
+    /// Returns whether there are no items in the hash set.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets an iterator over the keys of the underlying hash map.
+    #[inline]
+    pub fn iter(&self) -> Keys<K, ()> {
+        self.0.keys()
+    }
+
+    /// Resolves an archived hash set from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a hash map
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: HashSetResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.0);
+        ArchivedHashMap::resolve_from_len(len, pos + fp, resolver.0, fo);
+    }
+}
+
+impl<K, H: ArchiveHasher> ArchivedHashSet<K, H> {
     /// Gets the key corresponding to the given key in the hash set.
     #[inline]
     pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&K>
@@ -45,51 +80,47 @@ impl<K> ArchivedHashSet<K> {
     }
 
     /// Gets the hasher for the underlying hash map.
-    #[cfg(feature = "alloc")]
     #[inline]
-    pub fn hasher(&self) -> seahash::SeaHasher {
+    pub fn hasher(&self) -> H {
         self.0.hasher()
     }
 
-    /// Returns whether there are no items in the hash set.
+    /// Gets the seed that the hasher for the underlying hash map is created from.
     #[inline]
-    pub const fn is_empty(&self) -> bool {
-        self.0.is_empty()
+    pub fn seed(&self) -> u64 {
+        self.0.seed()
     }
 
-    /// Gets an iterator over the keys of the underlying hash map.
-    #[inline]
-    pub fn iter(&self) -> Keys<K, ()> {
-        self.0.keys()
-    }
-
-    /// Resolves an archived hash set from the given length and parameters.
+    /// Serializes an iterator of keys as a hash set.
+    ///
+    /// The hash set uses a new seed from its hasher.
     ///
     /// # Safety
     ///
-    /// - `len` must be the number of elements that were serialized
-    /// - `pos` must be the position of `out` within the archive
-    /// - `resolver` must be the result of serializing a hash map
+    /// The keys returned by the iterator must be unique.
+    #[cfg(feature = "alloc")]
     #[inline]
-    pub unsafe fn resolve_from_len(
-        len: usize,
-        pos: usize,
-        resolver: HashSetResolver,
-        out: *mut Self,
-    ) {
-        let (fp, fo) = out_field!(out.0);
-        ArchivedHashMap::resolve_from_len(len, pos + fp, resolver.0, fo);
+    pub unsafe fn serialize_from_iter<'a, KU, S, I>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<HashSetResolver, S::Error>
+    where
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        S: Serializer + ScratchSpace + ?Sized,
+        I: ExactSizeIterator<Item = &'a KU>,
+    {
+        Self::serialize_from_iter_with_seed(iter, H::new_seed(), serializer)
     }
 
-    /// Serializes an iterator of keys as a hash set.
+    /// Serializes an iterator of keys as a hash set using the given seed for its hasher.
     ///
     /// # Safety
     ///
     /// The keys returned by the iterator must be unique.
     #[cfg(feature = "alloc")]
-    #[inline]
-    pub unsafe fn serialize_from_iter<'a, KU, S, I>(
+    pub unsafe fn serialize_from_iter_with_seed<'a, KU, S, I>(
         iter: I,
+        seed: u64,
         serializer: &mut S,
     ) -> Result<HashSetResolver, S::Error>
     where
@@ -97,14 +128,17 @@ impl<K> ArchivedHashSet<K> {
         S: Serializer + ScratchSpace + ?Sized,
         I: ExactSizeIterator<Item = &'a KU>,
     {
-        Ok(HashSetResolver(ArchivedHashMap::serialize_from_iter(
-            iter.map(|x| (x, &())),
-            serializer,
-        )?))
+        Ok(HashSetResolver(
+            ArchivedHashMap::<K, (), H>::serialize_from_iter_with_seed(
+                iter.map(|x| (x, &())),
+                seed,
+                serializer,
+            )?,
+        ))
     }
 }
 
-impl<K: fmt::Debug> fmt::Debug for ArchivedHashSet<K> {
+impl<K: fmt::Debug, H> fmt::Debug for ArchivedHashSet<K, H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_set().entries(self.iter()).finish()
     }
@@ -113,11 +147,53 @@ impl<K: fmt::Debug> fmt::Debug for ArchivedHashSet<K> {
 /// The resolver for archived hash sets.
 pub struct HashSetResolver(HashMapResolver);
 
-impl<K: Hash + Eq> PartialEq for ArchivedHashSet<K> {
+impl<K: Hash + Eq, H: ArchiveHasher> PartialEq for ArchivedHashSet<K, H> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
-impl<K: Hash + Eq> Eq for ArchivedHashSet<K> {}
+impl<K: Hash + Eq, H: ArchiveHasher> Eq for ArchivedHashSet<K, H> {}
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact, H: ArchiveHasher> Compact for ArchivedHashSet<K, H> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<K: Transcode, H: ArchiveHasher> Transcode for ArchivedHashSet<K, H> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedHashMap::<K, (), H>::transcode(core::ptr::addr_of_mut!((*value).0), transcoder)
+        }
+    }
+};
@@ -244,8 +244,11 @@ const _: () = {
 
             let mut entries = ScratchVec::new(serializer, iter.len())?;
             entries.set_len(len);
-            let index_resolver =
-                ArchivedHashIndex::build_and_serialize(iter.clone(), serializer, &mut entries)?;
+            let index_resolver = ArchivedHashIndex::<HashBuilder>::build_and_serialize(
+                iter.clone(),
+                serializer,
+                &mut entries,
+            )?;
             let mut entries = entries.assume_init();
 
             // Serialize entries
@@ -412,3 +415,70 @@ pub struct IndexMapResolver {
     pivots_pos: usize,
     entries_pos: usize,
 }
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+    use core::slice;
+
+    impl<K: Compact, V: Compact> Compact for ArchivedIndexMap<K, V> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.index.compact_dependencies(compactor, resolvers);
+            let entries = unsafe { slice::from_raw_parts(self.entries.as_ptr(), self.len()) };
+            resolvers.push(compactor.compact_out_of_line(entries));
+            let pivots = unsafe { slice::from_raw_parts(self.pivots.as_ptr(), self.len()) };
+            resolvers.push(compactor.compact_out_of_line(pivots));
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.index
+                .compact_pointers(field_pos(self, out, &self.index), compactor, resolvers);
+            self.entries.compact_ptr(
+                field_pos(self, out, &self.entries),
+                resolvers.take(),
+                compactor,
+            );
+            self.pivots.compact_ptr(
+                field_pos(self, out, &self.pivots),
+                resolvers.take(),
+                compactor,
+            );
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::ptr;
+
+    impl<K: Transcode, V: Transcode> Transcode for ArchivedIndexMap<K, V> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let len = ArchivedHashIndex::<HashBuilder>::transcode_len(
+                ptr::addr_of_mut!((*value).index),
+                transcoder,
+            )?;
+
+            let pivots = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).pivots), transcoder)?;
+            <[Archived<usize>]>::transcode(ptr::slice_from_raw_parts_mut(pivots, len), transcoder)?;
+
+            let entries = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).entries), transcoder)?;
+            <[Entry<K, V>]>::transcode(ptr::slice_from_raw_parts_mut(entries, len), transcoder)
+        }
+    }
+};
@@ -134,7 +134,7 @@ where
         value: *const Self,
         context: &mut C,
     ) -> Result<&'a Self, Self::Error> {
-        let index = ArchivedHashIndex::check_bytes(ptr::addr_of!((*value).index), context)?;
+        let index = <ArchivedHashIndex>::check_bytes(ptr::addr_of!((*value).index), context)?;
 
         // Entries
         Layout::array::<Entry<K, V>>(index.len())?;
@@ -172,3 +172,48 @@ impl<K: PartialEq> PartialEq for ArchivedIndexSet<K> {
 
 /// The resolver for `IndexSet`.
 pub struct IndexSetResolver(IndexMapResolver);
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact> Compact for ArchivedIndexSet<K> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.inner.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.inner
+                .compact_pointers(field_pos(self, out, &self.inner), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<K: Transcode> Transcode for ArchivedIndexSet<K> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedIndexMap::<K, ()>::transcode(
+                core::ptr::addr_of_mut!((*value).inner),
+                transcoder,
+            )
+        }
+    }
+};
@@ -8,6 +8,10 @@ pub mod hash_set;
 // TODO: move these into a separate crate when indexmap adds rkyv support
 pub mod index_map;
 pub mod index_set;
+pub mod sorted_map;
+pub mod sorted_set;
+pub mod swiss_map;
+pub mod trie_map;
 pub mod util;
 
 pub use self::btree_map::ArchivedBTreeMap;
@@ -17,3 +21,7 @@ pub use self::hash_set::ArchivedHashSet;
 // TODO: move these into a separate crate when indexmap adds rkyv support
 pub use self::index_map::ArchivedIndexMap;
 pub use self::index_set::ArchivedIndexSet;
+pub use self::sorted_map::ArchivedSortedMap;
+pub use self::sorted_set::ArchivedSortedSet;
+pub use self::swiss_map::ArchivedSwissMap;
+pub use self::trie_map::ArchivedTrieMap;
@@ -0,0 +1,422 @@
+//! Archived sorted-vector map implementation.
+//!
+//! During archiving, sorted maps are built into a contiguous array of entries sorted by key.
+//! Lookups binary search the entries, which makes this the most compact archived map layout.
+
+#[cfg(feature = "validation")]
+pub mod validation;
+
+use crate::{
+    collections::util::Entry,
+    vec::{ArchivedVec, VecResolver},
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{ScratchSpace, Serializer},
+    Serialize,
+};
+use core::{
+    borrow::Borrow,
+    fmt,
+    iter::FusedIterator,
+    ops::{Bound, Index, RangeBounds},
+    pin::Pin,
+    slice,
+};
+
+/// An archived map laid out as a vector of entries sorted by key.
+///
+/// This can be used instead of an [`ArchivedHashMap`](crate::collections::ArchivedHashMap) or
+/// [`ArchivedBTreeMap`](crate::collections::ArchivedBTreeMap) with
+/// [`AsSortedVec`](crate::with::AsSortedVec). Sorted maps take up no more space than a vector of
+/// their entries and support range queries, but lookups take logarithmic time.
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedSortedMap<K, V> {
+    entries: ArchivedVec<Entry<K, V>>,
+}
+
+impl<K, V> ArchivedSortedMap<K, V> {
+    /// Gets the number of items in the sorted map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether there are no items in the sorted map.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Gets the entries of the sorted map as a slice sorted by key.
+    #[inline]
+    pub fn as_slice(&self) -> &[Entry<K, V>] {
+        self.entries.as_slice()
+    }
+
+    /// Gets the key-value pair at the given index in key order.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries
+            .get(index)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Gets the first key-value pair in the sorted map.
+    #[inline]
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// Gets the last key-value pair in the sorted map.
+    #[inline]
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.len()
+            .checked_sub(1)
+            .and_then(|index| self.get_index(index))
+    }
+
+    /// Binary searches the sorted map for the given key.
+    ///
+    /// If the key is found, then `Ok` is returned with its index. Otherwise, `Err` is returned with
+    /// the index where the key would be inserted.
+    #[inline]
+    pub fn binary_search<Q>(&self, k: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries
+            .binary_search_by(|entry| entry.key.borrow().cmp(k))
+    }
+
+    /// Returns whether a key is present in the sorted map.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.binary_search(k).is_ok()
+    }
+
+    /// Gets the value associated with the given key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Gets the key-value pair associated with the given key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.binary_search(k)
+            .ok()
+            .and_then(|index| self.get_index(index))
+    }
+
+    /// Gets the mutable value associated with the given key.
+    #[inline]
+    pub fn get_pin<Q>(self: Pin<&mut Self>, k: &Q) -> Option<Pin<&mut V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            let sorted_map = self.get_unchecked_mut();
+            let index = sorted_map.binary_search(k).ok()?;
+            let entries = Pin::new_unchecked(&mut sorted_map.entries)
+                .pin_mut_slice()
+                .get_unchecked_mut();
+            Some(Pin::new_unchecked(&mut entries[index].value))
+        }
+    }
+
+    /// Gets an iterator over the key-value entries in the sorted map in key order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Gets an iterator over the keys in the sorted map in order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Gets an iterator over the values in the sorted map in key order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Gets an iterator over the key-value entries with keys in the given range.
+    ///
+    /// If the start of the range is after the end of the range, the iterator is empty.
+    #[inline]
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.entries.partition_point(|e| e.key.borrow() < start),
+            Bound::Excluded(start) => self.entries.partition_point(|e| e.key.borrow() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.entries.partition_point(|e| e.key.borrow() <= end),
+            Bound::Excluded(end) => self.entries.partition_point(|e| e.key.borrow() < end),
+            Bound::Unbounded => self.len(),
+        };
+        Iter {
+            inner: self.entries[start..end.max(start)].iter(),
+        }
+    }
+
+    /// Resolves an archived sorted map from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a sorted map
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: SortedMapResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.entries);
+        ArchivedVec::resolve_from_len(len, pos + fp, resolver.0, fo);
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    use crate::ScratchVec;
+
+    impl<K, V> ArchivedSortedMap<K, V> {
+        /// Serializes an iterator of key-value pairs as a sorted map.
+        ///
+        /// The entries are sorted by key in scratch space before they are serialized, so they
+        /// may be given in any order. The archived keys must be ordered the same way as the
+        /// unarchived keys.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        pub unsafe fn serialize_from_iter<'a, KU, VU, S, I>(
+            iter: I,
+            serializer: &mut S,
+        ) -> Result<SortedMapResolver, S::Error>
+        where
+            KU: 'a + Serialize<S, Archived = K> + Ord,
+            VU: 'a + Serialize<S, Archived = V>,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        {
+            let mut entries = ScratchVec::new(serializer, iter.len())?;
+            for entry in iter {
+                entries.push(entry);
+            }
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            let resolver = ArchivedVec::<Entry<K, V>>::serialize_from_iter(
+                entries.iter().map(|&(key, value)| Entry { key, value }),
+                serializer,
+            )?;
+
+            entries.free(serializer)?;
+
+            Ok(SortedMapResolver(resolver))
+        }
+    }
+};
+
+/// The resolver for archived sorted maps.
+pub struct SortedMapResolver(VecResolver);
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ArchivedSortedMap<K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for ArchivedSortedMap<K, V> {}
+
+impl<K, Q, V> Index<&'_ Q> for ArchivedSortedMap<K, V>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).unwrap()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for ArchivedSortedMap<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+/// An iterator over the key-value pairs of an archived sorted map.
+pub struct Iter<'a, K, V> {
+    inner: slice::Iter<'a, Entry<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+/// An iterator over the keys of an archived sorted map.
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Keys<'a, K, V> {
+    #[inline]
+    pub(crate) fn new(inner: Iter<'a, K, V>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+/// An iterator over the values of an archived sorted map.
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact, V: Compact> Compact for ArchivedSortedMap<K, V> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.entries.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.entries.compact_pointers(
+                field_pos(self, out, &self.entries),
+                compactor,
+                resolvers,
+            );
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<K: Transcode, V: Transcode> Transcode for ArchivedSortedMap<K, V> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedVec::<Entry<K, V>>::transcode(
+                core::ptr::addr_of_mut!((*value).entries),
+                transcoder,
+            )
+        }
+    }
+};
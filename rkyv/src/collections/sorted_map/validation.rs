@@ -0,0 +1,75 @@
+//! Validation implementation for ArchivedSortedMap.
+
+use crate::{
+    collections::{sorted_map::ArchivedSortedMap, util::Entry},
+    validation::{owned::CheckOwnedPointerError, ArchiveContext},
+    vec::ArchivedVec,
+};
+use bytecheck::{CheckBytes, Error};
+use core::{fmt, ptr};
+
+/// Errors that can occur while checking an archived sorted map.
+#[derive(Debug)]
+pub enum SortedMapError<E> {
+    /// An error occurred while checking the entries
+    CheckEntriesError(E),
+    /// A key is not strictly greater than the key before it
+    UnsortedKey {
+        /// The index of the out-of-order key
+        index: usize,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for SortedMapError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortedMapError::CheckEntriesError(e) => write!(f, "entries check error: {}", e),
+            SortedMapError::UnsortedKey { index } => {
+                write!(f, "unsorted key: at index {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<E: Error + 'static> Error for SortedMapError<E> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                SortedMapError::CheckEntriesError(e) => Some(e as &dyn Error),
+                SortedMapError::UnsortedKey { .. } => None,
+            }
+        }
+    }
+};
+
+impl<K, V, C> CheckBytes<C> for ArchivedSortedMap<K, V>
+where
+    K: CheckBytes<C> + Ord,
+    V: CheckBytes<C>,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = SortedMapError<CheckOwnedPointerError<[Entry<K, V>], C>>;
+
+    #[inline]
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let entries =
+            ArchivedVec::<Entry<K, V>>::check_bytes(ptr::addr_of!((*value).entries), context)
+                .map_err(SortedMapError::CheckEntriesError)?;
+
+        // Keys must be strictly increasing so that binary searches find every key
+        for (index, pair) in entries.windows(2).enumerate() {
+            if pair[0].key >= pair[1].key {
+                return Err(SortedMapError::UnsortedKey { index: index + 1 });
+            }
+        }
+
+        Ok(&*value)
+    }
+}
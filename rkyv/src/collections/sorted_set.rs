@@ -0,0 +1,180 @@
+//! Archived sorted-vector set implementation.
+
+use crate::collections::sorted_map::{ArchivedSortedMap, Keys, SortedMapResolver};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{ScratchSpace, Serializer},
+    Serialize,
+};
+use core::{borrow::Borrow, fmt, ops::RangeBounds};
+
+/// An archived set laid out as a vector of sorted keys. This is a wrapper around a sorted map with
+/// the same key and a value of `()`.
+#[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[derive(Eq, PartialEq)]
+#[repr(transparent)]
+pub struct ArchivedSortedSet<K>(ArchivedSortedMap<K, ()>);
+
+impl<K> ArchivedSortedSet<K> {
+    /// Gets the number of items in the sorted set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether there are no items in the sorted set.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets the key corresponding to the given key in the sorted set.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.get_key_value(k).map(|(k, _)| k)
+    }
+
+    /// Returns whether the given key is in the sorted set.
+    #[inline]
+    pub fn contains<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.contains_key(k)
+    }
+
+    /// Gets the key at the given index in order.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&K> {
+        self.0.get_index(index).map(|(k, _)| k)
+    }
+
+    /// Gets the first key in the sorted set.
+    #[inline]
+    pub fn first(&self) -> Option<&K> {
+        self.0.first_key_value().map(|(k, _)| k)
+    }
+
+    /// Gets the last key in the sorted set.
+    #[inline]
+    pub fn last(&self) -> Option<&K> {
+        self.0.last_key_value().map(|(k, _)| k)
+    }
+
+    /// Gets an iterator over the keys of the sorted set in order.
+    #[inline]
+    pub fn iter(&self) -> Keys<'_, K, ()> {
+        self.0.keys()
+    }
+
+    /// Gets an iterator over the keys in the given range.
+    ///
+    /// If the start of the range is after the end of the range, the iterator is empty.
+    #[inline]
+    pub fn range<Q, R>(&self, range: R) -> Keys<'_, K, ()>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Keys::new(self.0.range(range))
+    }
+
+    /// Resolves an archived sorted set from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a sorted set
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: SortedSetResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.0);
+        ArchivedSortedMap::resolve_from_len(len, pos + fp, resolver.0, fo);
+    }
+
+    /// Serializes an iterator of keys as a sorted set.
+    ///
+    /// The keys may be given in any order. The archived keys must be ordered the same way as the
+    /// unarchived keys.
+    ///
+    /// # Safety
+    ///
+    /// The keys returned by the iterator must be unique.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn serialize_from_iter<'a, KU, S, I>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<SortedSetResolver, S::Error>
+    where
+        KU: 'a + Serialize<S, Archived = K> + Ord,
+        S: Serializer + ScratchSpace + ?Sized,
+        I: ExactSizeIterator<Item = &'a KU>,
+    {
+        Ok(SortedSetResolver(
+            ArchivedSortedMap::<K, ()>::serialize_from_iter(iter.map(|x| (x, &())), serializer)?,
+        ))
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for ArchivedSortedSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// The resolver for archived sorted sets.
+pub struct SortedSetResolver(SortedMapResolver);
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<K: Compact> Compact for ArchivedSortedSet<K> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<K: Transcode> Transcode for ArchivedSortedSet<K> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedSortedMap::<K, ()>::transcode(core::ptr::addr_of_mut!((*value).0), transcoder)
+        }
+    }
+};
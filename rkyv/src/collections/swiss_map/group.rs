@@ -0,0 +1,189 @@
+//! Groups of control bytes that are probed together.
+
+/// The number of control bytes in a group.
+pub const GROUP_WIDTH: usize = 16;
+
+/// The control byte of an empty bucket.
+///
+/// The control bytes of full buckets are the top seven bits of the hash of their key, so only
+/// empty buckets have the high bit set.
+pub const EMPTY: u8 = 0xff;
+
+/// Returns the control byte of a full bucket with the given hash.
+#[inline]
+pub fn tag(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+/// Returns whether the given control byte is for a full bucket.
+#[inline]
+pub fn is_full(control: u8) -> bool {
+    control & 0x80 == 0
+}
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+mod imp {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86 as arch;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64 as arch;
+
+    pub type BitMaskWord = u16;
+    pub const STRIDE: u32 = 1;
+
+    #[derive(Clone, Copy)]
+    pub struct Group(arch::__m128i);
+
+    impl Group {
+        #[inline]
+        pub unsafe fn load(ptr: *const u8) -> Self {
+            Self(arch::_mm_loadu_si128(ptr.cast()))
+        }
+
+        #[inline]
+        pub fn match_tag(self, tag: u8) -> BitMaskWord {
+            unsafe {
+                let cmp = arch::_mm_cmpeq_epi8(self.0, arch::_mm_set1_epi8(tag as i8));
+                arch::_mm_movemask_epi8(cmp) as BitMaskWord
+            }
+        }
+
+        #[inline]
+        pub fn match_empty(self) -> BitMaskWord {
+            unsafe { arch::_mm_movemask_epi8(self.0) as BitMaskWord }
+        }
+    }
+}
+
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+)))]
+mod imp {
+    use super::GROUP_WIDTH;
+
+    pub type BitMaskWord = u128;
+    pub const STRIDE: u32 = 8;
+
+    const LSB: u128 = u128::MAX / 0xff;
+    const MSB: u128 = LSB << 7;
+
+    #[derive(Clone, Copy)]
+    pub struct Group(u128);
+
+    impl Group {
+        #[inline]
+        pub unsafe fn load(ptr: *const u8) -> Self {
+            Self(u128::from_le_bytes(
+                ptr.cast::<[u8; GROUP_WIDTH]>().read_unaligned(),
+            ))
+        }
+
+        // This can report a false positive for a byte equal to `tag ^ 1` that follows a matching
+        // byte. That byte is still a full bucket, and the keys of matching buckets are always
+        // compared.
+        #[inline]
+        pub fn match_tag(self, tag: u8) -> BitMaskWord {
+            let cmp = self.0 ^ (LSB * tag as u128);
+            cmp.wrapping_sub(LSB) & !cmp & MSB
+        }
+
+        #[inline]
+        pub fn match_empty(self) -> BitMaskWord {
+            self.0 & MSB
+        }
+    }
+}
+
+/// A group of control bytes.
+#[derive(Clone, Copy)]
+pub struct Group(imp::Group);
+
+impl Group {
+    /// Loads a group of control bytes from the given pointer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads of `GROUP_WIDTH` bytes.
+    #[inline]
+    pub unsafe fn load(ptr: *const u8) -> Self {
+        Self(imp::Group::load(ptr))
+    }
+
+    /// Returns the buckets in the group that may have the given control byte.
+    #[inline]
+    pub fn match_tag(self, tag: u8) -> BitMask {
+        BitMask(self.0.match_tag(tag))
+    }
+
+    /// Returns the empty buckets in the group.
+    #[inline]
+    pub fn match_empty(self) -> BitMask {
+        BitMask(self.0.match_empty())
+    }
+}
+
+/// A set of buckets in a group.
+pub struct BitMask(imp::BitMaskWord);
+
+impl BitMask {
+    /// Returns whether any of the buckets are in the set.
+    #[inline]
+    pub fn any(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            None
+        } else {
+            let index = self.0.trailing_zeros() / imp::STRIDE;
+            self.0 &= self.0 - 1;
+            Some(index as usize)
+        }
+    }
+}
+
+/// The sequence of groups to probe for a hash.
+///
+/// Groups are probed with triangular numbers, which visits every group when the number of groups
+/// is a power of two.
+pub struct ProbeSeq {
+    group: usize,
+    stride: usize,
+    mask: usize,
+}
+
+impl ProbeSeq {
+    /// Creates a new probe sequence for the given hash in a table with `groups` groups.
+    #[inline]
+    pub fn new(hash: u64, groups: usize) -> Self {
+        let mask = groups - 1;
+        Self {
+            group: (hash & mask as u64) as usize,
+            stride: 0,
+            mask,
+        }
+    }
+
+    /// Returns the index of the first bucket in the current group.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.group * GROUP_WIDTH
+    }
+
+    /// Moves to the next group in the sequence.
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.stride += 1;
+        self.group = (self.group + self.stride) & self.mask;
+    }
+}
@@ -0,0 +1,650 @@
+//! Archived open-addressing hash map implementation.
+//!
+//! During archiving, swiss maps are built into open-addressing hash tables with a separate array of
+//! control bytes like [SwissTable](https://abseil.io/about/design/swisstables). Lookups compare a
+//! whole group of control bytes at a time and only read the entries whose control bytes match, so
+//! most lookups touch one group of control bytes and one entry.
+
+mod group;
+#[cfg(feature = "validation")]
+pub mod validation;
+
+use self::group::{is_full, tag, Group, ProbeSeq, GROUP_WIDTH};
+use crate::{
+    collections::{
+        hash_index::{ArchiveHasher, HashBuilder},
+        util::Entry,
+    },
+    Archive, Archived, RelPtr,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{ScratchSpace, Serializer},
+    Serialize,
+};
+use core::{
+    borrow::Borrow, fmt, hash::Hash, iter::FusedIterator, marker::PhantomData, ops::Index,
+    pin::Pin, slice,
+};
+
+/// An archived `HashMap` laid out as an open-addressing hash table.
+///
+/// This can be used instead of an [`ArchivedHashMap`](crate::collections::ArchivedHashMap) with
+/// [`AsSwissMap`](crate::with::AsSwissMap). Swiss maps are faster to look up, but take up more
+/// space because they leave some of their buckets empty.
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedSwissMap<K, V, H = HashBuilder> {
+    len: Archived<usize>,
+    buckets: Archived<usize>,
+    control: RelPtr<u8>,
+    entries: RelPtr<Entry<K, V>>,
+    seed: Archived<u64>,
+    _hasher: PhantomData<H>,
+}
+
+/// Returns the number of buckets for a swiss map with the given length.
+///
+/// There are always more buckets than entries so that every probe sequence ends at an empty
+/// bucket.
+#[inline]
+fn buckets_for(len: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        (len + len / 7 + 1).next_power_of_two().max(GROUP_WIDTH)
+    }
+}
+
+impl<K, V, H: ArchiveHasher> ArchivedSwissMap<K, V, H> {
+    /// Gets the number of items in the swiss map.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        from_archived!(self.len) as usize
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of buckets in the swiss map, including empty buckets.
+    #[inline]
+    pub const fn buckets(&self) -> usize {
+        from_archived!(self.buckets) as usize
+    }
+
+    /// Gets the seed that the hasher for this swiss map is created from.
+    #[inline]
+    pub const fn seed(&self) -> u64 {
+        from_archived!(self.seed)
+    }
+
+    /// Gets the hasher for this swiss map.
+    #[inline]
+    pub fn hasher(&self) -> H {
+        H::from_seed(self.seed())
+    }
+
+    #[inline]
+    fn control_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.control.as_ptr(), self.buckets()) }
+    }
+
+    #[inline]
+    unsafe fn entry(&self, index: usize) -> &Entry<K, V> {
+        &*self.entries.as_ptr().add(index)
+    }
+
+    #[inline]
+    unsafe fn entry_mut(&mut self, index: usize) -> &mut Entry<K, V> {
+        &mut *self.entries.as_mut_ptr().add(index)
+    }
+
+    #[inline]
+    fn probe<Q, F>(&self, k: &Q, mut matches: F) -> Option<usize>
+    where
+        Q: Hash + ?Sized,
+        F: FnMut(&K) -> bool,
+    {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut hasher = self.hasher();
+        k.hash(&mut hasher);
+        let hash = hasher.finish();
+        let tag = tag(hash);
+
+        let mut probe = ProbeSeq::new(hash, self.buckets() / GROUP_WIDTH);
+        loop {
+            let pos = probe.pos();
+            let group = unsafe { Group::load(self.control.as_ptr().add(pos)) };
+            for i in group.match_tag(tag) {
+                let index = pos + i;
+                if matches(unsafe { &self.entry(index).key }) {
+                    return Some(index);
+                }
+            }
+            if group.match_empty().any() {
+                return None;
+            }
+            probe.move_next();
+        }
+    }
+
+    #[inline]
+    fn find<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.probe(k, |key| key.borrow() == k)
+    }
+
+    /// Finds the key-value entry for a key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).map(move |index| {
+            let entry = unsafe { self.entry(index) };
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// Finds the mutable key-value entry for a key.
+    #[inline]
+    pub fn get_key_value_pin<Q>(self: Pin<&mut Self>, k: &Q) -> Option<(&K, Pin<&mut V>)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        unsafe {
+            let swiss_map = self.get_unchecked_mut();
+            swiss_map.find(k).map(move |index| {
+                let entry = swiss_map.entry_mut(index);
+                (&entry.key, Pin::new_unchecked(&mut entry.value))
+            })
+        }
+    }
+
+    /// Returns whether a key is present in the swiss map.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).is_some()
+    }
+
+    /// Gets the value associated with the given key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k)
+            .map(|index| unsafe { &self.entry(index).value })
+    }
+
+    /// Gets the value associated with the given key, and matching the given predicate `F`.
+    ///
+    /// This works like [`ArchivedHashMap::get_with`](crate::collections::ArchivedHashMap::get_with)
+    /// and has the same requirements for the hash of the given key.
+    #[inline]
+    pub fn get_with<Q: Hash + ?Sized, F: Fn(&K, &Q) -> bool>(
+        &self,
+        key: &Q,
+        comparison_predicate: F,
+    ) -> Option<&V> {
+        self.probe(key, |k| comparison_predicate(k, key))
+            .map(|index| unsafe { &self.entry(index).value })
+    }
+
+    /// Gets the mutable value associated with the given key.
+    #[inline]
+    pub fn get_pin<Q>(self: Pin<&mut Self>, k: &Q) -> Option<Pin<&mut V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        unsafe {
+            let swiss_map = self.get_unchecked_mut();
+            swiss_map
+                .find(k)
+                .map(move |index| Pin::new_unchecked(&mut swiss_map.entry_mut(index).value))
+        }
+    }
+
+    #[inline]
+    fn raw_iter(&self) -> RawIter<'_, K, V> {
+        RawIter {
+            control: self.control_bytes().iter(),
+            entries: self.entries.as_ptr(),
+            remaining: self.len(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Gets an iterator over the key-value entries in the swiss map.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Gets an iterator over the keys in the swiss map.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Gets an iterator over the values in the swiss map.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.raw_iter(),
+        }
+    }
+
+    /// Resolves an archived swiss map from a given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a swiss map
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: SwissMapResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.len);
+        len.resolve(pos + fp, (), fo);
+
+        let (fp, fo) = out_field!(out.buckets);
+        buckets_for(len).resolve(pos + fp, (), fo);
+
+        let (fp, fo) = out_field!(out.control);
+        RelPtr::emplace(pos + fp, resolver.control_pos, fo);
+
+        let (fp, fo) = out_field!(out.entries);
+        RelPtr::emplace(pos + fp, resolver.entries_pos, fo);
+
+        let (fp, fo) = out_field!(out.seed);
+        resolver.seed.resolve(pos + fp, (), fo);
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    use core::mem::size_of;
+
+    impl<K, V, H: ArchiveHasher> ArchivedSwissMap<K, V, H> {
+        /// Serializes an iterator of key-value pairs as a swiss map.
+        ///
+        /// The swiss map uses a new seed from its hasher.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        #[inline]
+        pub unsafe fn serialize_from_iter<'a, KU, VU, S, I>(
+            iter: I,
+            serializer: &mut S,
+        ) -> Result<SwissMapResolver, S::Error>
+        where
+            KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+            VU: 'a + Serialize<S, Archived = V>,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        {
+            Self::serialize_from_iter_with_seed(iter, H::new_seed(), serializer)
+        }
+
+        /// Serializes an iterator of key-value pairs as a swiss map using the given seed for its
+        /// hasher.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        pub unsafe fn serialize_from_iter_with_seed<'a, KU, VU, S, I>(
+            iter: I,
+            seed: u64,
+            serializer: &mut S,
+        ) -> Result<SwissMapResolver, S::Error>
+        where
+            KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+            VU: 'a + Serialize<S, Archived = V>,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        {
+            use self::group::EMPTY;
+            use crate::ScratchVec;
+
+            const ZEROES: [u8; 32] = [0; 32];
+
+            let len = iter.len();
+            let buckets = buckets_for(len);
+
+            let mut control = ScratchVec::new(serializer, buckets)?;
+            let mut slots = ScratchVec::new(serializer, buckets)?;
+            for _ in 0..buckets {
+                control.push(EMPTY);
+                slots.push(None);
+            }
+
+            // Insert each entry into the first empty bucket in its probe sequence
+            for (key, value) in iter {
+                let mut hasher = H::from_seed(seed);
+                key.hash(&mut hasher);
+                let hash = hasher.finish();
+
+                let mut probe = ProbeSeq::new(hash, buckets / GROUP_WIDTH);
+                loop {
+                    let pos = probe.pos();
+                    let group = Group::load(control.as_ptr().add(pos));
+                    if let Some(i) = group.match_empty().next() {
+                        control.as_mut_slice()[pos + i] = tag(hash);
+                        slots.as_mut_slice()[pos + i] = Some((key, value));
+                        break;
+                    }
+                    probe.move_next();
+                }
+            }
+
+            // Control bytes are written before the dependencies of the entries so that they can be
+            // validated first
+            let control_pos = serializer.align(GROUP_WIDTH)?;
+            serializer.write(control.as_slice())?;
+
+            // Serialize entries
+            let mut resolvers = ScratchVec::new(serializer, len)?;
+            for &(key, value) in slots.as_slice().iter().flatten() {
+                resolvers.push((key.serialize(serializer)?, value.serialize(serializer)?));
+            }
+
+            let entries_pos = serializer.align_for::<Entry<K, V>>()?;
+            let mut resolvers_iter = resolvers.drain(..);
+            for slot in slots.as_slice().iter() {
+                if let Some((key, value)) = *slot {
+                    let resolver = resolvers_iter.next().unwrap();
+                    serializer.resolve_aligned(&Entry { key, value }, resolver)?;
+                } else {
+                    let mut remaining = size_of::<Entry<K, V>>();
+                    while remaining > 0 {
+                        let zeroes = remaining.min(ZEROES.len());
+                        serializer.write(&ZEROES[..zeroes])?;
+                        remaining -= zeroes;
+                    }
+                }
+            }
+            drop(resolvers_iter);
+
+            // Free scratch vecs
+            resolvers.free(serializer)?;
+            slots.free(serializer)?;
+            control.free(serializer)?;
+
+            Ok(SwissMapResolver {
+                control_pos,
+                entries_pos,
+                seed,
+            })
+        }
+    }
+};
+
+impl<K: fmt::Debug, V: fmt::Debug, H: ArchiveHasher> fmt::Debug for ArchivedSwissMap<K, V, H> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, H: ArchiveHasher> Eq for ArchivedSwissMap<K, V, H> {}
+
+impl<K, Q, V, H> Index<&'_ Q> for ArchivedSwissMap<K, V, H>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    H: ArchiveHasher,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).unwrap()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, H: ArchiveHasher> PartialEq for ArchivedSwissMap<K, V, H> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            false
+        } else {
+            self.iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+        }
+    }
+}
+
+struct RawIter<'a, K, V> {
+    control: slice::Iter<'a, u8>,
+    entries: *const Entry<K, V>,
+    remaining: usize,
+    _phantom: PhantomData<(&'a K, &'a V)>,
+}
+
+impl<'a, K, V> Iterator for RawIter<'a, K, V> {
+    type Item = &'a Entry<K, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        for &control in &mut self.control {
+            let entry = self.entries;
+            self.entries = unsafe { self.entries.add(1) };
+            if is_full(control) {
+                self.remaining -= 1;
+                return Some(unsafe { &*entry });
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for RawIter<'_, K, V> {}
+impl<K, V> FusedIterator for RawIter<'_, K, V> {}
+
+/// An iterator over the key-value pairs of a swiss map.
+#[repr(transparent)]
+pub struct Iter<'a, K, V> {
+    inner: RawIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+/// An iterator over the keys of a swiss map.
+#[repr(transparent)]
+pub struct Keys<'a, K, V> {
+    inner: RawIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| &entry.key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+/// An iterator over the values of a swiss map.
+#[repr(transparent)]
+pub struct Values<'a, K, V> {
+    inner: RawIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| &entry.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+/// The resolver for archived swiss maps.
+pub struct SwissMapResolver {
+    control_pos: usize,
+    entries_pos: usize,
+    seed: u64,
+}
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+    use core::mem::size_of;
+
+    impl<K: Compact, V: Compact, H: ArchiveHasher> Compact for ArchivedSwissMap<K, V, H> {
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            let control = self.control_bytes();
+            let entries = unsafe { slice::from_raw_parts(self.entries.as_ptr(), self.buckets()) };
+            if let (Some(control_pos), Some(entries_pos)) = (
+                compactor.existing_pos(control),
+                compactor.existing_pos(entries),
+            ) {
+                resolvers.push(control_pos);
+                resolvers.push(entries_pos);
+                return;
+            }
+
+            // The entries of empty buckets are never read, so only the full entries are followed
+            compactor.align(GROUP_WIDTH);
+            resolvers.push(compactor.copy_value(control));
+
+            let mut entry_resolvers = CompactResolvers::new();
+            for entry in self.raw_iter() {
+                entry.compact_dependencies(compactor, &mut entry_resolvers);
+            }
+            let entries_pos = compactor.copy_value(entries);
+            for (index, &control) in control.iter().enumerate() {
+                if is_full(control) {
+                    let entry = unsafe { self.entry(index) };
+                    let out = entries_pos + index * size_of::<Entry<K, V>>();
+                    entry.compact_pointers(out, compactor, &mut entry_resolvers);
+                }
+            }
+            resolvers.push(entries_pos);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.control.compact_ptr(
+                field_pos(self, out, &self.control),
+                resolvers.take(),
+                compactor,
+            );
+            self.entries.compact_ptr(
+                field_pos(self, out, &self.entries),
+                resolvers.take(),
+                compactor,
+            );
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::{mem::size_of, ptr};
+
+    impl<K: Transcode, V: Transcode, H> Transcode for ArchivedSwissMap<K, V, H> {
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let len_ptr = ptr::addr_of_mut!((*value).len);
+            let buckets_ptr = ptr::addr_of_mut!((*value).buckets);
+            let buckets = from_archived!(transcoder.read(buckets_ptr)) as usize;
+            Archived::<usize>::transcode(len_ptr, transcoder)?;
+            Archived::<usize>::transcode(buckets_ptr, transcoder)?;
+            Archived::<u64>::transcode(ptr::addr_of_mut!((*value).seed), transcoder)?;
+
+            let control = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).control), transcoder)?;
+            transcoder.bounds_check_range(control, buckets)?;
+            let entries = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).entries), transcoder)?;
+            transcoder.bounds_check_range(
+                entries.cast(),
+                buckets.saturating_mul(size_of::<Entry<K, V>>()),
+            )?;
+
+            // Control bytes are the same in both endiannesses
+            for index in 0..buckets {
+                if is_full(*control.add(index)) {
+                    Entry::<K, V>::transcode(entries.add(index), transcoder)?;
+                }
+            }
+            Ok(())
+        }
+    }
+};
@@ -0,0 +1,226 @@
+//! Validation implementation for ArchivedSwissMap.
+
+use super::group::{is_full, EMPTY, GROUP_WIDTH};
+use crate::{
+    collections::{
+        hash_index::ArchiveHasher,
+        swiss_map::ArchivedSwissMap,
+        util::{validation::ArchivedEntryError, Entry},
+    },
+    validation::ArchiveContext,
+    Archived, RelPtr,
+};
+use bytecheck::{CheckBytes, Error, SliceCheckError};
+use core::{
+    alloc::{Layout, LayoutError},
+    convert::Infallible,
+    fmt,
+    hash::Hash,
+    ptr,
+};
+
+/// Errors that can occur while checking an archived swiss map.
+#[derive(Debug)]
+pub enum SwissMapError<K, V, C> {
+    /// An error occurred while checking the layouts of control bytes or entries
+    LayoutError(LayoutError),
+    /// The number of buckets is not zero or a power of two that is at least the group width
+    InvalidBuckets {
+        /// The number of buckets
+        buckets: usize,
+    },
+    /// The number of full buckets does not match the length, or there are no empty buckets
+    InvalidLength {
+        /// The length of the map
+        len: usize,
+        /// The number of buckets
+        buckets: usize,
+    },
+    /// A control byte was invalid
+    InvalidControlByte {
+        /// The index of the bucket with an invalid control byte
+        index: usize,
+        /// The value of the control byte
+        value: u8,
+    },
+    /// An error occurred while checking an entry
+    CheckEntryError(ArchivedEntryError<K, V>),
+    /// A key is not located at the correct position
+    InvalidKeyPosition {
+        /// The index of the bucket that the key is in
+        index: usize,
+    },
+    /// A bounds error occurred
+    ContextError(C),
+}
+
+impl<K: fmt::Display, V: fmt::Display, E: fmt::Display> fmt::Display for SwissMapError<K, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwissMapError::LayoutError(e) => write!(f, "layout error: {}", e),
+            SwissMapError::InvalidBuckets { buckets } => {
+                write!(f, "invalid number of buckets: {}", buckets)
+            }
+            SwissMapError::InvalidLength { len, buckets } => {
+                write!(f, "invalid length: length {} with {} buckets", len, buckets)
+            }
+            SwissMapError::InvalidControlByte { index, value } => write!(
+                f,
+                "invalid control byte: value {} at index {}",
+                value, index,
+            ),
+            SwissMapError::CheckEntryError(e) => write!(f, "entry check error: {}", e),
+            SwissMapError::InvalidKeyPosition { index } => {
+                write!(f, "invalid key position: at index {}", index)
+            }
+            SwissMapError::ContextError(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<K, V, C> Error for SwissMapError<K, V, C>
+    where
+        K: Error + 'static,
+        V: Error + 'static,
+        C: Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                SwissMapError::LayoutError(e) => Some(e as &dyn Error),
+                SwissMapError::InvalidBuckets { .. } => None,
+                SwissMapError::InvalidLength { .. } => None,
+                SwissMapError::InvalidControlByte { .. } => None,
+                SwissMapError::CheckEntryError(e) => Some(e as &dyn Error),
+                SwissMapError::InvalidKeyPosition { .. } => None,
+                SwissMapError::ContextError(e) => Some(e as &dyn Error),
+            }
+        }
+    }
+};
+
+impl<K, V, C> From<Infallible> for SwissMapError<K, V, C> {
+    #[inline]
+    fn from(_: Infallible) -> Self {
+        unsafe { core::hint::unreachable_unchecked() }
+    }
+}
+
+impl<K, V, C> From<SliceCheckError<Infallible>> for SwissMapError<K, V, C> {
+    #[inline]
+    fn from(_: SliceCheckError<Infallible>) -> Self {
+        unsafe { core::hint::unreachable_unchecked() }
+    }
+}
+
+impl<K, V, C> From<LayoutError> for SwissMapError<K, V, C> {
+    #[inline]
+    fn from(e: LayoutError) -> Self {
+        Self::LayoutError(e)
+    }
+}
+
+impl<K, V, C> From<ArchivedEntryError<K, V>> for SwissMapError<K, V, C> {
+    #[inline]
+    fn from(e: ArchivedEntryError<K, V>) -> Self {
+        Self::CheckEntryError(e)
+    }
+}
+
+impl<K, V, C, H> CheckBytes<C> for ArchivedSwissMap<K, V, H>
+where
+    K: CheckBytes<C> + Eq + Hash,
+    V: CheckBytes<C>,
+    H: ArchiveHasher,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = SwissMapError<K::Error, V::Error, C::Error>;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let len = from_archived!(*Archived::<usize>::check_bytes(
+            ptr::addr_of!((*value).len),
+            context,
+        )?) as usize;
+        let buckets = from_archived!(*Archived::<usize>::check_bytes(
+            ptr::addr_of!((*value).buckets),
+            context,
+        )?) as usize;
+        Archived::<u64>::check_bytes(ptr::addr_of!((*value).seed), context)?;
+
+        if buckets != 0 && (!buckets.is_power_of_two() || buckets < GROUP_WIDTH) {
+            return Err(SwissMapError::InvalidBuckets { buckets });
+        }
+        // Every probe sequence must end at an empty bucket
+        if len >= buckets && !(len == 0 && buckets == 0) {
+            return Err(SwissMapError::InvalidLength { len, buckets });
+        }
+        Layout::array::<Entry<K, V>>(buckets)?;
+
+        let control_rel_ptr = RelPtr::manual_check_bytes(ptr::addr_of!((*value).control), context)?;
+        let control_ptr = context
+            .check_subtree_ptr::<[u8]>(control_rel_ptr.base(), control_rel_ptr.offset(), buckets)
+            .map_err(SwissMapError::ContextError)?;
+
+        let range = context
+            .push_prefix_subtree(control_ptr)
+            .map_err(SwissMapError::ContextError)?;
+        let control = <[u8]>::check_bytes(control_ptr, context)?;
+        context
+            .pop_prefix_range(range)
+            .map_err(SwissMapError::ContextError)?;
+
+        let mut full = 0;
+        for (index, &value) in control.iter().enumerate() {
+            if is_full(value) {
+                full += 1;
+            } else if value != EMPTY {
+                return Err(SwissMapError::InvalidControlByte { index, value });
+            }
+        }
+        if full != len {
+            return Err(SwissMapError::InvalidLength { len, buckets });
+        }
+
+        let entries_rel_ptr = RelPtr::manual_check_bytes(ptr::addr_of!((*value).entries), context)?;
+        let entries_ptr = context
+            .check_subtree_ptr::<[Entry<K, V>]>(
+                entries_rel_ptr.base(),
+                entries_rel_ptr.offset(),
+                buckets,
+            )
+            .map_err(SwissMapError::ContextError)?;
+
+        let range = context
+            .push_prefix_subtree(entries_ptr)
+            .map_err(SwissMapError::ContextError)?;
+        // The entries of empty buckets are never read, so only the full entries are checked
+        let entries = entries_ptr.cast::<Entry<K, V>>();
+        for (index, &value) in control.iter().enumerate() {
+            if is_full(value) {
+                Entry::<K, V>::check_bytes(entries.add(index), context)?;
+            }
+        }
+        context
+            .pop_prefix_range(range)
+            .map_err(SwissMapError::ContextError)?;
+
+        let swiss_map = &*value;
+        for (index, &value) in control.iter().enumerate() {
+            if is_full(value) {
+                let key = &(*entries.add(index)).key;
+                if swiss_map.find(key) != Some(index) {
+                    return Err(SwissMapError::InvalidKeyPosition { index });
+                }
+            }
+        }
+
+        Ok(swiss_map)
+    }
+}
@@ -0,0 +1,667 @@
+//! Archived trie map implementation.
+//!
+//! During archiving, trie maps are built into radix tries where each edge is labeled with a
+//! string. Keys that share a prefix share the nodes for that prefix, so shared prefixes are only
+//! stored once. The nodes are laid out in breadth-first order so that the children of each node
+//! are contiguous and sorted, and the values are laid out in key order so that the values of
+//! every subtree are contiguous.
+
+#[cfg(feature = "validation")]
+pub mod validation;
+
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{ScratchSpace, Serializer},
+    Fallible, Serialize,
+};
+use crate::{
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::iter::FusedIterator;
+use core::{
+    fmt,
+    ops::{Index, Range},
+    pin::Pin,
+};
+
+/// A node in an archived trie map.
+///
+/// The label of each node and the children of each node start where the label and children of the
+/// previous node end, so only the ends are stored. The children of the root node start at index 1.
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedTrieNode {
+    label_end: Archived<usize>,
+    children_end: Archived<usize>,
+    value_start: Archived<usize>,
+    value_end: Archived<usize>,
+}
+
+impl ArchivedTrieNode {
+    #[inline]
+    fn label_end(&self) -> usize {
+        from_archived!(self.label_end) as usize
+    }
+
+    #[inline]
+    fn children_end(&self) -> usize {
+        from_archived!(self.children_end) as usize
+    }
+
+    #[inline]
+    fn values(&self) -> Range<usize> {
+        from_archived!(self.value_start) as usize..from_archived!(self.value_end) as usize
+    }
+}
+
+struct TrieNode {
+    label_end: usize,
+    children_end: usize,
+    values: Range<usize>,
+}
+
+impl Archive for TrieNode {
+    type Archived = ArchivedTrieNode;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, _: Self::Resolver, out: *mut Self::Archived) {
+        let (fp, fo) = out_field!(out.label_end);
+        self.label_end.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.children_end);
+        self.children_end.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.value_start);
+        self.values.start.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.value_end);
+        self.values.end.resolve(pos + fp, (), fo);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Fallible + ?Sized> Serialize<S> for TrieNode {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+/// An archived map from strings to values laid out as a radix trie.
+///
+/// This can be used instead of an [`ArchivedHashMap`](crate::collections::ArchivedHashMap) or
+/// [`ArchivedBTreeMap`](crate::collections::ArchivedBTreeMap) with string keys with
+/// [`AsTrie`](crate::with::AsTrie). Trie maps store shared prefixes of their keys once and support
+/// ordered iteration and iteration over the keys with a given prefix. Because keys are not stored
+/// in full, iterating over keys requires the `alloc` feature.
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedTrieMap<V> {
+    labels: ArchivedVec<u8>,
+    nodes: ArchivedVec<ArchivedTrieNode>,
+    values: ArchivedVec<V>,
+}
+
+impl<V> ArchivedTrieMap<V> {
+    /// Gets the number of items in the trie map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether there are no items in the trie map.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets the number of nodes in the trie map, including the root node.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[inline]
+    fn node(&self, index: usize) -> &ArchivedTrieNode {
+        debug_assert!(index < self.nodes.len());
+        unsafe { self.nodes.get_unchecked(index) }
+    }
+
+    #[inline]
+    fn label(&self, index: usize) -> &str {
+        let start = if index == 0 {
+            0
+        } else {
+            self.node(index - 1).label_end()
+        };
+        // Labels are checked to be valid UTF-8 that starts and ends on char boundaries
+        unsafe {
+            core::str::from_utf8_unchecked(
+                self.labels
+                    .get_unchecked(start..self.node(index).label_end()),
+            )
+        }
+    }
+
+    #[inline]
+    fn children(&self, index: usize) -> Range<usize> {
+        let start = if index == 0 {
+            1
+        } else {
+            self.node(index - 1).children_end()
+        };
+        start..self.node(index).children_end()
+    }
+
+    /// Returns the index of the value of the node, if it has one.
+    ///
+    /// The value of a node comes before the values of its children, so a node has a value if its
+    /// values do not all belong to its children.
+    #[inline]
+    fn own_value(&self, index: usize) -> Option<usize> {
+        let values = self.node(index).values();
+        let children = self.children(index);
+        if values.is_empty()
+            || (!children.is_empty() && self.node(children.start).values().start == values.start)
+        {
+            None
+        } else {
+            Some(values.start)
+        }
+    }
+
+    /// Finds the child of the given node with a label starting with the given char.
+    #[inline]
+    fn find_child(&self, index: usize, c: char) -> Option<usize> {
+        let Range { mut start, mut end } = self.children(index);
+        while start < end {
+            let mid = start + (end - start) / 2;
+            let first = self.label(mid).chars().next().unwrap_or_default();
+            match first.cmp(&c) {
+                core::cmp::Ordering::Less => start = mid + 1,
+                core::cmp::Ordering::Greater => end = mid,
+                core::cmp::Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        let mut index = 0;
+        let mut rest = key;
+        while let Some(c) = rest.chars().next() {
+            index = self.find_child(index, c)?;
+            rest = rest.strip_prefix(self.label(index))?;
+        }
+        self.own_value(index)
+    }
+
+    /// Finds the topmost node whose keys all start with the given prefix.
+    ///
+    /// Returns the index of the node and the length of the keys before the label of the node.
+    fn find_prefix(&self, prefix: &str) -> Option<(usize, usize)> {
+        let mut index = 0;
+        let mut depth = 0;
+        let mut rest = prefix;
+        while let Some(c) = rest.chars().next() {
+            let child = self.find_child(index, c)?;
+            let label = self.label(child);
+            if label.starts_with(rest) {
+                return Some((child, depth));
+            }
+            rest = rest.strip_prefix(label)?;
+            index = child;
+            depth += label.len();
+        }
+        // Only an empty prefix gets here, since a prefix that ends at the end of a label is found
+        // by the check above
+        Some((0, 0))
+    }
+
+    /// Returns whether a key is present in the trie map.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Gets the value associated with the given key.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.find(key)
+            .map(|index| unsafe { self.values.get_unchecked(index) })
+    }
+
+    /// Gets the mutable value associated with the given key.
+    #[inline]
+    pub fn get_pin(self: Pin<&mut Self>, key: &str) -> Option<Pin<&mut V>> {
+        unsafe {
+            let trie_map = self.get_unchecked_mut();
+            let index = trie_map.find(key)?;
+            let values = Pin::new_unchecked(&mut trie_map.values)
+                .pin_mut_slice()
+                .get_unchecked_mut();
+            Some(Pin::new_unchecked(values.get_unchecked_mut(index)))
+        }
+    }
+
+    /// Gets the values in the trie map in key order.
+    #[inline]
+    pub fn values(&self) -> &[V] {
+        self.values.as_slice()
+    }
+
+    /// Gets the values of the keys that start with the given prefix in key order.
+    ///
+    /// This does not need to rebuild the keys, so it can be used to count the keys with a given
+    /// prefix without the `alloc` feature.
+    #[inline]
+    pub fn prefix_values(&self, prefix: &str) -> &[V] {
+        match self.find_prefix(prefix) {
+            Some((index, _)) => unsafe { self.values.get_unchecked(self.node(index).values()) },
+            None => &[],
+        }
+    }
+
+    /// Resolves an archived trie map from the given parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of elements that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a trie map
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: TrieMapResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.labels);
+        ArchivedVec::resolve_from_len(resolver.labels_len, pos + fp, resolver.labels, fo);
+        let (fp, fo) = out_field!(out.nodes);
+        ArchivedVec::resolve_from_len(resolver.nodes_len, pos + fp, resolver.nodes, fo);
+        let (fp, fo) = out_field!(out.values);
+        ArchivedVec::resolve_from_len(len, pos + fp, resolver.values, fo);
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    use crate::ScratchVec;
+
+    /// Returns the length of the longest common prefix of two strings that ends on a char
+    /// boundary.
+    fn common_prefix_len(a: &str, b: &str) -> usize {
+        a.char_indices()
+            .zip(b.chars())
+            .find(|&((_, x), y)| x != y)
+            .map_or(a.len().min(b.len()), |((i, _), _)| i)
+    }
+
+    impl<V> ArchivedTrieMap<V> {
+        /// Gets an iterator over the key-value entries in the trie map in key order.
+        #[inline]
+        pub fn iter(&self) -> Iter<'_, V> {
+            Iter::new(self, "", Some((0, 0)))
+        }
+
+        /// Gets an iterator over the keys in the trie map in order.
+        #[inline]
+        pub fn keys(&self) -> Keys<'_, V> {
+            Keys { inner: self.iter() }
+        }
+
+        /// Gets an iterator over the key-value entries with keys that start with the given prefix
+        /// in key order.
+        #[inline]
+        pub fn prefix_iter(&self, prefix: &str) -> Iter<'_, V> {
+            let start = self.find_prefix(prefix);
+            Iter::new(self, start.map_or("", |(_, depth)| &prefix[..depth]), start)
+        }
+
+        /// Gets an iterator over the key-value entries with keys that are within the given
+        /// Levenshtein distance of the given key in key order.
+        ///
+        /// Subtries that cannot contain any matching keys are skipped.
+        #[inline]
+        pub fn fuzzy_iter(&self, key: &str, max_distance: usize) -> FuzzyIter<'_, V> {
+            let query = key.chars().collect::<Vec<_>>();
+            let row = (0..=query.len()).collect();
+            FuzzyIter {
+                trie_map: self,
+                query,
+                max_distance,
+                key: String::new(),
+                stack: vec![(0, 0, row)],
+            }
+        }
+
+        /// Serializes an iterator of key-value pairs as a trie map.
+        ///
+        /// The entries are sorted by key in scratch space before they are serialized, so they
+        /// may be given in any order.
+        ///
+        /// # Safety
+        ///
+        /// The keys returned by the iterator must be unique.
+        pub unsafe fn serialize_from_iter<'a, VU, S, I>(
+            iter: I,
+            serializer: &mut S,
+        ) -> Result<TrieMapResolver, S::Error>
+        where
+            VU: 'a + Serialize<S, Archived = V>,
+            S: Serializer + ScratchSpace + ?Sized,
+            I: ExactSizeIterator<Item = (&'a str, &'a VU)>,
+        {
+            let mut entries = ScratchVec::new(serializer, iter.len())?;
+            for entry in iter {
+                entries.push(entry);
+            }
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            // Each node is built from a range of the sorted keys and the start and end of its
+            // label in those keys. Nodes are built in order, so their children are appended after
+            // the children of all of the nodes before them.
+            let mut pending = vec![(0..entries.len(), 0..0)];
+            let mut nodes = Vec::new();
+            let mut labels = String::new();
+            while let Some((keys, label)) = pending.get(nodes.len()).cloned() {
+                if !label.is_empty() {
+                    labels.push_str(&entries[keys.start].0[label.clone()]);
+                }
+
+                let depth = label.end;
+                let mut start = keys.start;
+                if start < keys.end && entries[start].0.len() == depth {
+                    start += 1;
+                }
+                while start < keys.end {
+                    let first = &entries[start].0[depth..];
+                    let c = first.chars().next().unwrap();
+                    let mut end = start + 1;
+                    while end < keys.end && entries[end].0[depth..].starts_with(c) {
+                        end += 1;
+                    }
+                    let last = &entries[end - 1].0[depth..];
+                    let child_depth = depth + common_prefix_len(first, last);
+                    pending.push((start..end, depth..child_depth));
+                    start = end;
+                }
+
+                nodes.push(TrieNode {
+                    label_end: labels.len(),
+                    children_end: pending.len(),
+                    values: keys,
+                });
+            }
+
+            let labels_resolver =
+                ArchivedVec::<u8>::serialize_from_slice(labels.as_bytes(), serializer)?;
+            let nodes_resolver =
+                ArchivedVec::<ArchivedTrieNode>::serialize_from_slice(&nodes, serializer)?;
+            let values_resolver = ArchivedVec::<V>::serialize_from_iter::<VU, _, _, _>(
+                entries.iter().map(|&(_, value)| value),
+                serializer,
+            )?;
+
+            entries.free(serializer)?;
+
+            Ok(TrieMapResolver {
+                labels_len: labels.len(),
+                labels: labels_resolver,
+                nodes_len: nodes.len(),
+                nodes: nodes_resolver,
+                values: values_resolver,
+            })
+        }
+    }
+};
+
+#[cfg(feature = "alloc")]
+/// An iterator over the key-value pairs of an archived trie map.
+///
+/// The keys are rebuilt from the labels of the nodes as the trie is traversed.
+pub struct Iter<'a, V> {
+    trie_map: &'a ArchivedTrieMap<V>,
+    key: String,
+    stack: Vec<(usize, usize)>,
+    remaining: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> Iter<'a, V> {
+    #[inline]
+    fn new(trie_map: &'a ArchivedTrieMap<V>, key: &str, start: Option<(usize, usize)>) -> Self {
+        Self {
+            trie_map,
+            key: String::from(key),
+            stack: start.into_iter().collect(),
+            remaining: start.map_or(0, |(index, _)| trie_map.node(index).values().len()),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((index, depth)) = self.stack.pop() {
+            self.key.truncate(depth);
+            self.key.push_str(self.trie_map.label(index));
+            let depth = self.key.len();
+            self.stack.extend(
+                self.trie_map
+                    .children(index)
+                    .rev()
+                    .map(|child| (child, depth)),
+            );
+
+            if let Some(value) = self.trie_map.own_value(index) {
+                self.remaining -= 1;
+                let value = unsafe { self.trie_map.values.get_unchecked(value) };
+                return Some((self.key.clone(), value));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+#[cfg(feature = "alloc")]
+impl<'a, V> FusedIterator for Iter<'a, V> {}
+
+#[cfg(feature = "alloc")]
+/// An iterator over the keys of an archived trie map.
+pub struct Keys<'a, V> {
+    inner: Iter<'a, V>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> Iterator for Keys<'a, V> {
+    type Item = String;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> ExactSizeIterator for Keys<'a, V> {}
+#[cfg(feature = "alloc")]
+impl<'a, V> FusedIterator for Keys<'a, V> {}
+
+#[cfg(feature = "alloc")]
+/// An iterator over the key-value pairs of an archived trie map with keys that are close to a
+/// given key.
+pub struct FuzzyIter<'a, V> {
+    trie_map: &'a ArchivedTrieMap<V>,
+    query: Vec<char>,
+    max_distance: usize,
+    key: String,
+    // Each node is paired with the length of the key and the row of edit distances from the
+    // query before its label
+    stack: Vec<(usize, usize, Vec<usize>)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> Iterator for FuzzyIter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        'nodes: while let Some((index, depth, mut row)) = self.stack.pop() {
+            self.key.truncate(depth);
+            for c in self.trie_map.label(index).chars() {
+                self.key.push(c);
+                let mut diagonal = row[0];
+                row[0] += 1;
+                for (i, &q) in self.query.iter().enumerate() {
+                    let above = row[i + 1];
+                    row[i + 1] = (above + 1)
+                        .min(row[i] + 1)
+                        .min(diagonal + (q != c) as usize);
+                    diagonal = above;
+                }
+                if row.iter().all(|&d| d > self.max_distance) {
+                    continue 'nodes;
+                }
+            }
+
+            let depth = self.key.len();
+            for child in self.trie_map.children(index).rev() {
+                self.stack.push((child, depth, row.clone()));
+            }
+
+            if row[self.query.len()] <= self.max_distance {
+                if let Some(value) = self.trie_map.own_value(index) {
+                    let value = unsafe { self.trie_map.values.get_unchecked(value) };
+                    return Some((self.key.clone(), value));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, V> FusedIterator for FuzzyIter<'a, V> {}
+
+/// The resolver for archived trie maps.
+pub struct TrieMapResolver {
+    labels_len: usize,
+    labels: VecResolver,
+    nodes_len: usize,
+    nodes: VecResolver,
+    values: VecResolver,
+}
+
+impl<V: fmt::Debug> fmt::Debug for ArchivedTrieMap<V> {
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedTrieMap")
+            .field("labels", &self.labels)
+            .field("nodes", &self.nodes)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl<V> Index<&'_ str> for ArchivedTrieMap<V> {
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: &str) -> &V {
+        self.get(key).unwrap()
+    }
+}
+
+impl<V: PartialEq> PartialEq for ArchivedTrieMap<V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.labels == other.labels && self.nodes == other.nodes && self.values == other.values
+    }
+}
+
+impl<V: Eq> Eq for ArchivedTrieMap<V> {}
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<V: Compact> Compact for ArchivedTrieMap<V> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.labels.compact_dependencies(compactor, resolvers);
+            self.nodes.compact_dependencies(compactor, resolvers);
+            self.values.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.labels
+                .compact_pointers(field_pos(self, out, &self.labels), compactor, resolvers);
+            self.nodes
+                .compact_pointers(field_pos(self, out, &self.nodes), compactor, resolvers);
+            self.values
+                .compact_pointers(field_pos(self, out, &self.values), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+    use core::ptr::addr_of_mut;
+
+    impl<V: Transcode> Transcode for ArchivedTrieMap<V> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedVec::<u8>::transcode(addr_of_mut!((*value).labels), transcoder)?;
+            ArchivedVec::<ArchivedTrieNode>::transcode(addr_of_mut!((*value).nodes), transcoder)?;
+            ArchivedVec::<V>::transcode(addr_of_mut!((*value).values), transcoder)
+        }
+    }
+};
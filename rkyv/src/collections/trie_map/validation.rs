@@ -0,0 +1,204 @@
+//! Validation implementation for ArchivedTrieMap.
+
+use crate::{
+    collections::trie_map::{ArchivedTrieMap, ArchivedTrieNode},
+    validation::{owned::CheckOwnedPointerError, ArchiveContext},
+    vec::ArchivedVec,
+};
+use bytecheck::{CheckBytes, Error};
+use core::{fmt, ptr, str::Utf8Error};
+
+/// Errors that can occur while checking an archived trie map.
+#[derive(Debug)]
+pub enum TrieMapError<L, N, V> {
+    /// An error occurred while checking the labels
+    CheckLabelsError(L),
+    /// An error occurred while checking the nodes
+    CheckNodesError(N),
+    /// An error occurred while checking the values
+    CheckValuesError(V),
+    /// The labels were not valid UTF-8
+    InvalidUtf8(Utf8Error),
+    /// There was no root node
+    MissingRoot,
+    /// The label of a node was out of bounds, did not end on a char boundary, or was empty for a
+    /// node other than the root
+    InvalidLabel {
+        /// The index of the node with the invalid label
+        index: usize,
+    },
+    /// The children of a node were out of bounds or did not come after the node
+    InvalidChildren {
+        /// The index of the node with the invalid children
+        index: usize,
+    },
+    /// The labels of the children of a node did not start with strictly increasing chars
+    UnsortedChildren {
+        /// The index of the node with the unsorted children
+        index: usize,
+    },
+    /// The values of a node were not the values of its children, preceded by at most one value of
+    /// its own
+    InvalidValues {
+        /// The index of the node with the invalid values
+        index: usize,
+    },
+}
+
+impl<L, N, V> fmt::Display for TrieMapError<L, N, V>
+where
+    L: fmt::Display,
+    N: fmt::Display,
+    V: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrieMapError::CheckLabelsError(e) => write!(f, "labels check error: {}", e),
+            TrieMapError::CheckNodesError(e) => write!(f, "nodes check error: {}", e),
+            TrieMapError::CheckValuesError(e) => write!(f, "values check error: {}", e),
+            TrieMapError::InvalidUtf8(e) => write!(f, "invalid UTF-8 in labels: {}", e),
+            TrieMapError::MissingRoot => write!(f, "missing root node"),
+            TrieMapError::InvalidLabel { index } => {
+                write!(f, "invalid label: at node {}", index)
+            }
+            TrieMapError::InvalidChildren { index } => {
+                write!(f, "invalid children: at node {}", index)
+            }
+            TrieMapError::UnsortedChildren { index } => {
+                write!(f, "unsorted children: at node {}", index)
+            }
+            TrieMapError::InvalidValues { index } => {
+                write!(f, "invalid values: at node {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<L, N, V> Error for TrieMapError<L, N, V>
+    where
+        L: Error + 'static,
+        N: Error + 'static,
+        V: Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                TrieMapError::CheckLabelsError(e) => Some(e as &dyn Error),
+                TrieMapError::CheckNodesError(e) => Some(e as &dyn Error),
+                TrieMapError::CheckValuesError(e) => Some(e as &dyn Error),
+                TrieMapError::InvalidUtf8(e) => Some(e as &dyn Error),
+                TrieMapError::MissingRoot => None,
+                TrieMapError::InvalidLabel { .. } => None,
+                TrieMapError::InvalidChildren { .. } => None,
+                TrieMapError::UnsortedChildren { .. } => None,
+                TrieMapError::InvalidValues { .. } => None,
+            }
+        }
+    }
+};
+
+impl<V, C> CheckBytes<C> for ArchivedTrieMap<V>
+where
+    V: CheckBytes<C>,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = TrieMapError<
+        CheckOwnedPointerError<[u8], C>,
+        CheckOwnedPointerError<[ArchivedTrieNode], C>,
+        CheckOwnedPointerError<[V], C>,
+    >;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let labels = ArchivedVec::<u8>::check_bytes(ptr::addr_of!((*value).labels), context)
+            .map_err(TrieMapError::CheckLabelsError)?;
+        let labels = core::str::from_utf8(labels).map_err(TrieMapError::InvalidUtf8)?;
+        let nodes =
+            ArchivedVec::<ArchivedTrieNode>::check_bytes(ptr::addr_of!((*value).nodes), context)
+                .map_err(TrieMapError::CheckNodesError)?;
+        let values = ArchivedVec::<V>::check_bytes(ptr::addr_of!((*value).values), context)
+            .map_err(TrieMapError::CheckValuesError)?;
+
+        let root = nodes.first().ok_or(TrieMapError::MissingRoot)?;
+        if root.label_end() != 0 {
+            return Err(TrieMapError::InvalidLabel { index: 0 });
+        }
+        if root.values() != (0..values.len()) {
+            return Err(TrieMapError::InvalidValues { index: 0 });
+        }
+
+        // The labels and children of the nodes must tile the labels and nodes. Every node other
+        // than the root is then the child of exactly one node that comes before it.
+        let mut label_start = 0;
+        let mut children_start = 1;
+        for (index, node) in nodes.iter().enumerate() {
+            let label_end = node.label_end();
+            if label_end < label_start
+                || label_end > labels.len()
+                || !labels.is_char_boundary(label_end)
+                || (index != 0 && label_end == label_start)
+            {
+                return Err(TrieMapError::InvalidLabel { index });
+            }
+            let children_end = node.children_end();
+            if children_end < children_start
+                || children_end > nodes.len()
+                || (children_end > children_start && children_start <= index)
+            {
+                return Err(TrieMapError::InvalidChildren { index });
+            }
+            label_start = label_end;
+            children_start = children_end;
+        }
+        if children_start != nodes.len() {
+            return Err(TrieMapError::InvalidChildren {
+                index: nodes.len() - 1,
+            });
+        }
+
+        // The values of the children of each node must tile the values of the node after its own
+        // value. Since the values of the root are all of the values, every node's values are then
+        // in bounds.
+        let trie_map = &*value;
+        for (index, node) in nodes.iter().enumerate() {
+            let node_values = node.values();
+            let children = trie_map.children(index);
+            if children.is_empty() {
+                // Only the root can have no value and no children, and only when the map is empty
+                if node_values.len() != 1 && !(index == 0 && node_values.is_empty()) {
+                    return Err(TrieMapError::InvalidValues { index });
+                }
+            } else {
+                let mut next = nodes[children.start].values().start;
+                if next < node_values.start || next - node_values.start > 1 {
+                    return Err(TrieMapError::InvalidValues { index });
+                }
+                let mut prev = None;
+                for child in children {
+                    let child_values = nodes[child].values();
+                    if child_values.start != next || child_values.end < child_values.start {
+                        return Err(TrieMapError::InvalidValues { index });
+                    }
+                    next = child_values.end;
+
+                    let first = trie_map.label(child).chars().next();
+                    if prev >= first {
+                        return Err(TrieMapError::UnsortedChildren { index });
+                    }
+                    prev = first;
+                }
+                if next != node_values.end {
+                    return Err(TrieMapError::InvalidValues { index });
+                }
+            }
+        }
+
+        Ok(trie_map)
+    }
+}
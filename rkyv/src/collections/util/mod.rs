@@ -9,6 +9,16 @@ use crate::{Archive, Fallible, Serialize};
 ///
 /// This is typically used by associative containers that store keys and values together.
 #[derive(Debug, Eq)]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct Entry<K, V> {
     /// The key of the pair.
@@ -0,0 +1,139 @@
+//! Columnar archiving for slices of structs.
+//!
+//! Structs with `#[archive(columnar)]` can be archived one field at a time with the
+//! [`Columnar`](crate::with::Columnar) wrapper. Each field is archived into its own contiguous
+//! [`ArchivedVec`](crate::vec::ArchivedVec), which makes scanning a single field much faster than
+//! scanning a vector of archived structs.
+//!
+//! For a struct named `Record`, the derive generates:
+//!
+//! - `ArchivedRecordColumns`, which holds the columns. Each field has an accessor that returns its
+//!   column as a slice, and rows can be accessed with `get` and `iter`.
+//! - `ArchivedRecordRow`, which is a view of a single row with a reference to each field.
+//! - `RecordColumnsResolver`, which is the resolver for the columns.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     archived_root,
+//!     ser::{serializers::AllocSerializer, Serializer},
+//!     with::Columnar,
+//!     Archive, Deserialize, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! #[archive(columnar)]
+//! struct Record {
+//!     id: u32,
+//!     price: f64,
+//! }
+//!
+//! #[derive(Archive, Serialize, Deserialize)]
+//! struct Table {
+//!     #[with(Columnar)]
+//!     records: Vec<Record>,
+//! }
+//!
+//! let table = Table {
+//!     records: (0..10).map(|id| Record { id, price: id as f64 * 1.5 }).collect(),
+//! };
+//!
+//! let mut serializer = AllocSerializer::<256>::default();
+//! serializer.serialize_value(&table).unwrap();
+//! let bytes = serializer.into_serializer().into_inner();
+//! let archived = unsafe { archived_root::<Table>(&bytes) };
+//!
+//! let total = archived.records.price().iter().sum::<f64>();
+//! assert_eq!(total, 67.5);
+//!
+//! let row = archived.records.get(3).unwrap();
+//! assert_eq!(*row.id, 3);
+//! assert_eq!(*row.price, 4.5);
+//! ```
+
+use crate::{Archive, Fallible};
+#[cfg(feature = "validation")]
+use core::fmt;
+
+/// A type that can be archived as columns.
+///
+/// This is implemented by `#[archive(columnar)]`.
+pub trait ArchiveColumns: Archive + Sized {
+    /// The archived columns of a slice of this type.
+    ///
+    /// Every column must have the same length. With `check_bytes`, validation returns a
+    /// [`ColumnLengthError`] for columns that don't.
+    type Columns;
+    /// The resolver for the archived columns.
+    type ColumnsResolver;
+
+    /// Returns the number of rows in the given columns.
+    fn columns_len(columns: &Self::Columns) -> usize;
+
+    /// Creates the archived columns for a slice of values at the given position and writes it to
+    /// the given output pointer.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of values that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing the columns
+    unsafe fn resolve_columns(
+        len: usize,
+        pos: usize,
+        resolver: Self::ColumnsResolver,
+        out: *mut Self::Columns,
+    );
+}
+
+/// A type that can be serialized as columns.
+///
+/// This is implemented by `#[archive(columnar)]`.
+pub trait SerializeColumns<S: Fallible + ?Sized>: ArchiveColumns {
+    /// Writes the columns of the given values to the serializer and returns the resolver.
+    fn serialize_columns(
+        values: &[Self],
+        serializer: &mut S,
+    ) -> Result<Self::ColumnsResolver, S::Error>;
+}
+
+/// A type that can be deserialized from columns.
+///
+/// This is implemented by `#[archive(columnar)]`.
+pub trait DeserializeColumns<D: Fallible + ?Sized>: ArchiveColumns {
+    /// Deserializes the row at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds for any of the columns.
+    fn deserialize_row(
+        columns: &Self::Columns,
+        index: usize,
+        deserializer: &mut D,
+    ) -> Result<Self, D::Error>;
+}
+
+/// An error resulting from archived columns that don't all have the same length.
+#[cfg(feature = "validation")]
+#[derive(Debug)]
+pub struct ColumnLengthError {
+    /// The length of the first column
+    pub expected: usize,
+    /// The length of the mismatched column
+    pub found: usize,
+}
+
+#[cfg(feature = "validation")]
+impl fmt::Display for ColumnLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column length mismatch: expected {} rows, found {} rows",
+            self.expected, self.found
+        )
+    }
+}
+
+#[cfg(all(feature = "validation", feature = "std"))]
+impl std::error::Error for ColumnLengthError {}
@@ -0,0 +1,245 @@
+use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::{PhantomData, PhantomPinned},
+    mem::ManuallyDrop,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+        NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+};
+
+macro_rules! impl_leaf {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Compact for $ty {
+                #[inline]
+                fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+                #[inline]
+                fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+            }
+        )*
+    };
+}
+
+impl_leaf!(
+    (),
+    bool,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    char,
+    str,
+    PhantomPinned
+);
+impl_leaf!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128);
+impl_leaf!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128);
+
+#[cfg(has_atomics)]
+const _: () = {
+    use core::sync::atomic::{
+        AtomicBool, AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicU16, AtomicU32, AtomicU8,
+        AtomicUsize,
+    };
+
+    impl_leaf!(
+        AtomicBool,
+        AtomicI8,
+        AtomicI16,
+        AtomicI32,
+        AtomicIsize,
+        AtomicU8,
+        AtomicU16,
+        AtomicU32,
+        AtomicUsize
+    );
+};
+
+#[cfg(has_atomics_64)]
+const _: () = {
+    use core::sync::atomic::{AtomicI64, AtomicU64};
+
+    impl_leaf!(AtomicI64, AtomicU64);
+};
+
+#[cfg(feature = "rend")]
+const _: () = {
+    use crate::rend::{BigEndian, LittleEndian, NativeEndian, Primitive};
+
+    impl<T: Primitive> Compact for LittleEndian<T> {
+        #[inline]
+        fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+        #[inline]
+        fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+    }
+
+    impl<T: Primitive> Compact for BigEndian<T> {
+        #[inline]
+        fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+        #[inline]
+        fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+    }
+
+    impl<T> Compact for NativeEndian<T> {
+        #[inline]
+        fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+        #[inline]
+        fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+    }
+};
+
+#[cfg(feature = "unaligned")]
+impl<T: crate::unaligned::UnalignedPrimitive> Compact for crate::unaligned::Unaligned<T> {
+    #[inline]
+    fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+    #[inline]
+    fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+}
+
+impl<T: ?Sized> Compact for PhantomData<T> {
+    #[inline]
+    fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+    #[inline]
+    fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+}
+
+impl<T: Compact + ?Sized> Compact for ManuallyDrop<T> {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        T::compact_dependencies(self, compactor, resolvers)
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        T::compact_pointers(self, out, compactor, resolvers)
+    }
+}
+
+impl<T: Compact + ?Sized> Compact for UnsafeCell<T> {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        unsafe { &*self.get() }.compact_dependencies(compactor, resolvers)
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        unsafe { &*self.get() }.compact_pointers(out, compactor, resolvers)
+    }
+}
+
+impl<T: Compact + ?Sized> Compact for Cell<T> {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        unsafe { &*self.as_ptr() }.compact_dependencies(compactor, resolvers)
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        unsafe { &*self.as_ptr() }.compact_pointers(out, compactor, resolvers)
+    }
+}
+
+impl<T: Compact, const N: usize> Compact for [T; N] {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        self.as_slice().compact_dependencies(compactor, resolvers)
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        self.as_slice().compact_pointers(out, compactor, resolvers)
+    }
+}
+
+impl<T: Compact> Compact for [T] {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        for element in self.iter() {
+            element.compact_dependencies(compactor, resolvers);
+        }
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        for element in self.iter() {
+            element.compact_pointers(field_pos(self, out, element), compactor, resolvers);
+        }
+    }
+}
+
+#[cfg(not(feature = "strict"))]
+macro_rules! peel_tuple {
+    ($type:ident $index:tt, $($type_rest:ident $index_rest:tt,)*) => { impl_tuple! { $($type_rest $index_rest,)* } };
+}
+
+#[cfg(not(feature = "strict"))]
+macro_rules! impl_tuple {
+    () => ();
+    ($($type:ident $index:tt,)+) => {
+        impl<$($type: Compact),+> Compact for ($($type,)+) {
+            #[inline]
+            fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+                // Fields are listed in the same order that they are serialized in
+                let fields: &[&dyn Compact] = &[$(&self.$index,)+];
+                for field in fields.iter() {
+                    field.compact_dependencies(compactor, resolvers);
+                }
+            }
+
+            #[inline]
+            fn compact_pointers(&self, out: usize, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+                let fields: &[&dyn Compact] = &[$(&self.$index,)+];
+                for field in fields.iter() {
+                    field.compact_pointers(field_pos(self, out, *field), compactor, resolvers);
+                }
+            }
+        }
+
+        peel_tuple! { $($type $index,)+ }
+    };
+}
+
+#[cfg(not(feature = "strict"))]
+impl_tuple! { T11 11, T10 10, T9 9, T8 8, T7 7, T6 6, T5 5, T4 4, T3 3, T2 2, T1 1, T0 0, }
@@ -0,0 +1,243 @@
+use super::{Compact, Compactor};
+use crate::{
+    validation::{
+        validators::{
+            ArchiveError, DefaultValidator, DefaultValidatorError, PrefixRange, SuffixRange,
+        },
+        ArchiveContext, CheckArchiveError, CheckTypeError, SharedContext,
+    },
+    AlignedVec, Archive, Fallible,
+};
+use ::core::{
+    alloc::{Layout, LayoutError},
+    any::TypeId,
+    mem,
+};
+use bytecheck::CheckBytes;
+
+/// A validator for values that are extracted from a larger archive.
+///
+/// Unlike [`ArchiveValidator`](crate::validation::validators::ArchiveValidator), subtrees only have
+/// to be located before the value that points to them. Shared values are claimed by the first
+/// value that points to them, which may be a sibling of the extracted value that the traversal
+/// never visits.
+#[derive(Debug)]
+pub struct ExtractValidator<'a> {
+    bytes: &'a [u8],
+    inner: DefaultValidator<'a>,
+    subtree_end: usize,
+}
+
+impl<'a> ExtractValidator<'a> {
+    /// Creates a new validator from a byte range.
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            inner: DefaultValidator::new(bytes),
+            subtree_end: bytes.as_ptr() as usize + bytes.len(),
+        }
+    }
+}
+
+impl<'a> Fallible for ExtractValidator<'a> {
+    type Error = DefaultValidatorError;
+}
+
+impl<'a> ArchiveContext for ExtractValidator<'a> {
+    type PrefixRange = (PrefixRange, usize);
+    type SuffixRange = (SuffixRange, usize);
+
+    #[inline]
+    unsafe fn bounds_check_ptr(
+        &mut self,
+        base: *const u8,
+        offset: isize,
+    ) -> Result<*const u8, Self::Error> {
+        self.inner.bounds_check_ptr(base, offset)
+    }
+
+    #[inline]
+    unsafe fn bounds_check_layout(
+        &mut self,
+        data_address: *const u8,
+        layout: &Layout,
+    ) -> Result<(), Self::Error> {
+        self.inner.bounds_check_layout(data_address, layout)
+    }
+
+    #[inline]
+    unsafe fn bounds_check_subtree_ptr_layout(
+        &mut self,
+        data_address: *const u8,
+        layout: &Layout,
+    ) -> Result<(), Self::Error> {
+        let address = data_address as usize;
+        let subtree_range = self.bytes.as_ptr()..self.subtree_end as *const u8;
+        if address < self.bytes.as_ptr() as usize
+            || address > self.subtree_end
+            || (address == self.subtree_end && layout.size() != 0)
+        {
+            Err(DefaultValidatorError::ArchiveError(
+                ArchiveError::SubtreePointerOutOfBounds {
+                    ptr: data_address,
+                    subtree_range,
+                },
+            ))
+        } else if self.subtree_end - address < layout.size() {
+            Err(DefaultValidatorError::ArchiveError(
+                ArchiveError::SubtreePointerOverrun {
+                    ptr: data_address,
+                    size: layout.size(),
+                    subtree_range,
+                },
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    unsafe fn push_prefix_subtree_range(
+        &mut self,
+        root: *const u8,
+        end: *const u8,
+    ) -> Result<Self::PrefixRange, Self::Error> {
+        let range = self.inner.push_prefix_subtree_range(root, end)?;
+        let subtree_end = mem::replace(&mut self.subtree_end, root as usize);
+        Ok((range, subtree_end))
+    }
+
+    #[inline]
+    fn pop_prefix_range(&mut self, range: Self::PrefixRange) -> Result<(), Self::Error> {
+        self.inner.pop_prefix_range(range.0)?;
+        self.subtree_end = range.1;
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn push_suffix_subtree_range(
+        &mut self,
+        start: *const u8,
+        root: *const u8,
+    ) -> Result<Self::SuffixRange, Self::Error> {
+        let range = self.inner.push_suffix_subtree_range(start, root)?;
+        let subtree_end = mem::replace(&mut self.subtree_end, root as usize);
+        Ok((range, subtree_end))
+    }
+
+    #[inline]
+    fn pop_suffix_range(&mut self, range: Self::SuffixRange) -> Result<(), Self::Error> {
+        self.inner.pop_suffix_range(range.0)?;
+        self.subtree_end = range.1;
+        Ok(())
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn wrap_layout_error(error: LayoutError) -> Self::Error {
+        DefaultValidator::wrap_layout_error(error)
+    }
+}
+
+impl<'a> SharedContext for ExtractValidator<'a> {
+    #[inline]
+    fn register_shared_ptr(
+        &mut self,
+        ptr: *const u8,
+        type_id: TypeId,
+    ) -> Result<bool, Self::Error> {
+        self.inner.register_shared_ptr(ptr, type_id)
+    }
+}
+
+/// Copies an archived value and everything reachable from it into a new archive with that value as
+/// its root.
+///
+/// `value` is usually a reference to a part of a larger archive, like an element of an archived
+/// vec or a value in an archived map. It is first checked with the same traversal as
+/// [`check_archived_value`](crate::check_archived_value) using an [`ExtractValidator`], which
+/// verifies that everything it can reach is located inside of `archive`. Then the value and its
+/// dependencies are copied in the order they are checked, so the new archive can be validated on
+/// its own.
+///
+/// With the `compact` feature, archived types that derive `CheckBytes` with
+/// `#[archive(check_bytes)]` also derive [`Compact`], so any validated type can be extracted.
+///
+/// The value is located at the end of the returned bytes, so it can be accessed with
+/// [`archived_root`](crate::archived_root) or [`check_archived_root`](crate::check_archived_root).
+///
+/// # Panics
+///
+/// - `value` is not located inside of `archive`
+/// - A relative pointer in the new archive cannot reach its target
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{check_archived_root, compact::extract, Archive, Deserialize, Infallible, Serialize};
+///
+/// #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+/// #[archive(check_bytes)]
+/// struct Item {
+///     name: String,
+///     tags: Vec<u32>,
+/// }
+///
+/// let items = vec![
+///     Item {
+///         name: "the first item in the list".to_string(),
+///         tags: vec![1, 2],
+///     },
+///     Item {
+///         name: "the second item in the list".to_string(),
+///         tags: vec![3, 4, 5],
+///     },
+/// ];
+/// let bytes = rkyv::to_bytes::<_, 256>(&items).unwrap();
+/// let archived = check_archived_root::<Vec<Item>>(&bytes).unwrap();
+///
+/// let extracted = extract::<Item>(&bytes, &archived[1]).unwrap();
+/// assert!(extracted.len() < bytes.len());
+///
+/// let item = check_archived_root::<Item>(&extracted).unwrap();
+/// let deserialized: Item = item.deserialize(&mut Infallible).unwrap();
+/// assert_eq!(deserialized, items.into_iter().nth(1).unwrap());
+/// ```
+pub fn extract<'a, U: Archive + ?Sized>(
+    archive: &'a [u8],
+    value: &U::Archived,
+) -> Result<AlignedVec, CheckTypeError<U::Archived, ExtractValidator<'a>>>
+where
+    U::Archived: CheckBytes<ExtractValidator<'a>> + Compact,
+{
+    let ptr = value as *const U::Archived;
+    let start = archive.as_ptr() as usize;
+    let pos = ptr as usize;
+    assert!(
+        pos >= start && pos + mem::size_of::<U::Archived>() <= start + archive.len(),
+        "the extracted value must be located inside of the archive",
+    );
+
+    let mut validator = ExtractValidator::new(archive);
+    unsafe {
+        let range = validator
+            .push_prefix_subtree(ptr)
+            .map_err(CheckArchiveError::ContextError)?;
+        CheckBytes::check_bytes(ptr, &mut validator).map_err(CheckArchiveError::CheckBytesError)?;
+        validator
+            .pop_prefix_range(range)
+            .map_err(CheckArchiveError::ContextError)?;
+        validator
+            .finish()
+            .map_err(CheckArchiveError::ContextError)?;
+    }
+
+    let mut compactor = Compactor::new();
+    compactor.compact_out_of_line(value);
+    Ok(compactor.into_inner())
+}
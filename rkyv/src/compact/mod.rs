@@ -0,0 +1,367 @@
+//! Compaction of existing archives.
+//!
+//! Archives can contain bytes that are not reachable from their root. Archives assembled by hand
+//! with [`archived_value`](crate::archived_value) may leave values behind, and editing an archive in
+//! place (for example by setting an [`ArchivedOptionBox`](crate::niche::option_box::ArchivedOptionBox)
+//! to `None`) orphans the values that used to be pointed to. [`compact`] walks an archive from its
+//! root and writes a new archive that contains only the reachable values, with every relative
+//! pointer rewritten to match.
+//!
+//! Values keep their alignment and are written in the same order that they are serialized in, so
+//! compacted archives can still be validated. Values that are pointed to by multiple shared
+//! pointers are only written once.
+//!
+//! The same traversal can start from any value inside of an archive. [`ArchiveUpdater`] uses it to
+//! append new versions of archives that only copy the values that changed.
+//!
+//! With the `validation` feature, `extract` copies an archived value and everything reachable from
+//! it into a standalone archive with that value as its root, which can be sent elsewhere without
+//! deserializing and reserializing it. The value is checked with the same traversal as validation
+//! before it is copied.
+//!
+//! Compaction is implemented on archived types through [`Compact`], which can be derived for
+//! archived types with `#[archive(compact)]`. Archived types that derive `CheckBytes` with
+//! `#[archive(check_bytes)]` also derive `Compact`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use rkyv::{
+//!     archived_root,
+//!     compact::compact,
+//!     ser::{serializers::AllocSerializer, Serializer},
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(compact)]
+//! struct Example {
+//!     name: String,
+//!     values: Vec<u32>,
+//! }
+//!
+//! let mut serializer = AllocSerializer::<256>::default();
+//!
+//! // This value is written to the archive but never pointed to
+//! serializer
+//!     .serialize_value(&"some garbage that nothing points to".to_string())
+//!     .unwrap();
+//! serializer
+//!     .serialize_value(&Example {
+//!         name: "a name that is too long to inline".to_string(),
+//!         values: vec![1, 2, 3],
+//!     })
+//!     .unwrap();
+//! let bytes = serializer.into_serializer().into_inner();
+//!
+//! let compacted = unsafe { compact::<Example>(&bytes) };
+//! assert!(compacted.len() < bytes.len());
+//!
+//! let archived = unsafe { archived_root::<Example>(&compacted) };
+//! assert_eq!(archived.name, "a name that is too long to inline");
+//! assert_eq!(archived.values.as_slice(), &[1, 2, 3]);
+//! ```
+
+mod core;
+#[cfg(feature = "validation")]
+mod extract;
+mod update;
+
+use crate::{archived_root, archived_value, rel_ptr::Offset, AlignedVec, Archive};
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use ::core::{alloc::Layout, mem, ops::Range, ptr, slice};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "validation")]
+pub use self::extract::{extract, ExtractValidator};
+pub use self::update::ArchiveUpdater;
+pub use rkyv_derive::Compact;
+
+/// Returns the position in the new archive of a field of a value that was copied to `out`.
+///
+/// `field` must be located inside of `value`.
+#[inline]
+pub fn field_pos<T: ?Sized, F: ?Sized>(value: &T, out: usize, field: &F) -> usize {
+    out + (field as *const F as *const u8 as usize - value as *const T as *const u8 as usize)
+}
+
+/// The positions that the dependencies of some values were written to.
+///
+/// [`Compact::compact_dependencies`] pushes positions in the order that it writes dependencies,
+/// and [`Compact::compact_pointers`] takes them back out in the same order.
+#[derive(Debug, Default)]
+pub struct CompactResolvers {
+    positions: Vec<usize>,
+    next: usize,
+}
+
+impl CompactResolvers {
+    /// Creates a new empty set of resolvers.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the position of a dependency.
+    #[inline]
+    pub fn push(&mut self, pos: usize) {
+        self.positions.push(pos);
+    }
+
+    /// Takes the next position that was pushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every position has already been taken.
+    #[inline]
+    pub fn take(&mut self) -> usize {
+        let result = self.positions[self.next];
+        self.next += 1;
+        result
+    }
+}
+
+/// A context for copying the reachable values of an archive into a new archive.
+///
+/// Values are read through references into the source archive. Positions are always positions in
+/// the new archive.
+#[derive(Debug, Default)]
+pub struct Compactor {
+    out: AlignedVec,
+    base: usize,
+    shared: HashMap<usize, usize>,
+    reuse: Option<Reuse>,
+}
+
+/// The state of a compactor that is appending an updated version of an archive to its end.
+#[derive(Debug)]
+struct Reuse {
+    start: usize,
+    path: Vec<usize>,
+    target: Range<usize>,
+    target_pos: Option<usize>,
+}
+
+impl Compactor {
+    /// Creates a new compactor.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new compactor with at least the given capacity for the new archive.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            out: AlignedVec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the length of the new archive so far.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.base + self.out.len()
+    }
+
+    /// Consumes the compactor and returns the new archive.
+    #[inline]
+    pub fn into_inner(self) -> AlignedVec {
+        self.out
+    }
+
+    /// Returns the position of the given value in the archive being updated if it can be reused
+    /// instead of copied.
+    ///
+    /// This always returns `None` unless the compactor is updating an archive. Values can be reused
+    /// if they don't contain any of the values on the path to the updated value.
+    #[inline]
+    pub fn existing_pos<T: ?Sized>(&self, value: &T) -> Option<usize> {
+        let reuse = self.reuse.as_ref()?;
+        let start = value as *const T as *const u8 as usize;
+        let end = start + mem::size_of_val(value);
+        if reuse
+            .path
+            .iter()
+            .any(|&address| address >= start && address < end)
+        {
+            None
+        } else {
+            Some(start - reuse.start)
+        }
+    }
+
+    /// Pads the new archive with zeroes until its length is a multiple of `align`.
+    #[inline]
+    pub fn align(&mut self, align: usize) {
+        let padding = (align - self.pos() % align) % align;
+        self.out.resize(self.out.len() + padding, 0);
+    }
+
+    /// Reserves space for a value with the given layout at the end of the new archive and returns
+    /// its position.
+    ///
+    /// The reserved space is zeroed.
+    #[inline]
+    pub fn reserve(&mut self, layout: Layout) -> usize {
+        self.align(layout.align());
+        let pos = self.pos();
+        self.out.resize(self.out.len() + layout.size(), 0);
+        pos
+    }
+
+    /// Copies the bytes of the given value to the end of the new archive without following any of
+    /// its pointers, then returns its position.
+    #[inline]
+    pub fn copy_value<T: ?Sized>(&mut self, value: &T) -> usize {
+        let layout = Layout::for_value(value);
+        let out = self.reserve(layout);
+        let bytes = unsafe { slice::from_raw_parts(value as *const T as *const u8, layout.size()) };
+        let start = out - self.base;
+        self.out[start..start + layout.size()].copy_from_slice(bytes);
+
+        if let Some(reuse) = self.reuse.as_mut() {
+            let address = value as *const T as *const u8 as usize;
+            if reuse.target.start >= address && reuse.target.end <= address + layout.size() {
+                reuse.target_pos = Some(out + (reuse.target.start - address));
+            }
+        }
+
+        out
+    }
+
+    /// Writes a value to the new archive at `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes for the value were not reserved.
+    #[inline]
+    pub fn write<T: Copy>(&mut self, out: usize, value: T) {
+        let start = out - self.base;
+        let bytes = &mut self.out[start..start + mem::size_of::<T>()];
+        unsafe {
+            ptr::write_unaligned(bytes.as_mut_ptr().cast::<T>(), value);
+        }
+    }
+
+    /// Writes the offset of a relative pointer at `out` so that it points to `target`.
+    ///
+    /// # Panics
+    ///
+    /// - The offset between `out` and `target` does not fit in the offset type
+    /// - The bytes for the offset were not reserved
+    #[inline]
+    pub fn write_offset<O: Offset>(&mut self, out: usize, target: usize) {
+        self.write(out, O::between(out, target).unwrap());
+    }
+
+    /// Copies the given value and all of its dependencies to the new archive, then returns the
+    /// position of the value in the new archive.
+    #[inline]
+    pub fn compact_out_of_line<T: Compact + ?Sized>(&mut self, value: &T) -> usize {
+        if let Some(pos) = self.existing_pos(value) {
+            return pos;
+        }
+
+        let mut resolvers = CompactResolvers::new();
+        value.compact_dependencies(self, &mut resolvers);
+        let out = self.copy_value(value);
+        value.compact_pointers(out, self, &mut resolvers);
+        out
+    }
+
+    /// Copies a shared value and all of its dependencies to the new archive unless it has already
+    /// been copied, then returns the position of the value in the new archive.
+    #[inline]
+    pub fn compact_shared<T: Compact + ?Sized>(&mut self, value: &T) -> usize {
+        let address = value as *const T as *const u8 as usize;
+        if let Some(out) = self.shared.get(&address) {
+            *out
+        } else {
+            let out = self.compact_out_of_line(value);
+            self.shared.insert(address, out);
+            out
+        }
+    }
+}
+
+/// An archived type that can be copied into a new archive.
+///
+/// Compaction happens in two passes for each value, like serializing and resolving. First,
+/// [`compact_dependencies`](Compact::compact_dependencies) copies the values that the archived
+/// value points to and records their new positions. Then the bytes of the value are copied to the
+/// new archive, and [`compact_pointers`](Compact::compact_pointers) rewrites the relative pointers
+/// in the copy using those positions.
+///
+/// This trait can be derived with `#[derive(Compact)]`, or derived for archived types with
+/// `#[archive(compact)]`.
+pub trait Compact {
+    /// Copies the values that this value points to into the new archive, and pushes their
+    /// positions in the new archive to `resolvers`.
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers);
+
+    /// Rewrites the relative pointers of the copy of this value at `out` in the new archive.
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    );
+}
+
+/// Copies the archived value at the given position and everything reachable from it into a new
+/// archive.
+///
+/// The value is located at the end of the returned bytes, so it can be accessed with
+/// [`archived_root`](crate::archived_root).
+///
+/// # Panics
+///
+/// Panics if a relative pointer in the new archive cannot reach its target. Values are copied in
+/// the order they were serialized in, so this only happens for archives that were assembled out of
+/// order.
+///
+/// # Safety
+///
+/// The bytes must contain a valid archived `T` at `pos`, and the same safety requirements as
+/// [`archived_value`](crate::archived_value) apply.
+#[inline]
+pub unsafe fn compact_value<T: Archive + ?Sized>(bytes: &[u8], pos: usize) -> AlignedVec
+where
+    T::Archived: Compact,
+{
+    let mut compactor = Compactor::with_capacity(bytes.len());
+    compactor.compact_out_of_line(archived_value::<T>(bytes, pos));
+    compactor.into_inner()
+}
+
+/// Copies the archived root of the given bytes and everything reachable from it into a new
+/// archive.
+///
+/// This is a wrapper for [`compact_value`] that calculates the position of the root like
+/// [`archived_root`](crate::archived_root). The root of the new archive is located at the end of
+/// the returned bytes.
+///
+/// # Panics
+///
+/// Panics if a relative pointer in the new archive cannot reach its target. Values are copied in
+/// the order they were serialized in, so this only happens for archives that were assembled out of
+/// order.
+///
+/// # Safety
+///
+/// The bytes must contain a valid archived `T` at the end of the slice, and the same safety
+/// requirements as [`archived_root`](crate::archived_root) apply.
+#[inline]
+pub unsafe fn compact<T: Archive + ?Sized>(bytes: &[u8]) -> AlignedVec
+where
+    T::Archived: Compact,
+{
+    let mut compactor = Compactor::with_capacity(bytes.len());
+    compactor.compact_out_of_line(archived_root::<T>(bytes));
+    compactor.into_inner()
+}
@@ -0,0 +1,211 @@
+use crate::{
+    compact::{Compact, Compactor, Reuse},
+    ser::Serializer,
+    Serialize,
+};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::{
+    mem::{self, MaybeUninit},
+    slice,
+};
+
+/// Updates part of an existing archive by appending a new version of it.
+///
+/// Updating a value serializes the new value, then copies only the values on the path from the root
+/// to the updated value. Everything else is left where it is and pointed to by the copies, so the
+/// new root shares unchanged values with the old one like a persistent data structure. The old root
+/// is left unchanged and can still be accessed at its original position.
+///
+/// The serializer must start writing at the end of the archive. For example, an archive file can be
+/// updated in place by appending to it with
+/// [`WriteSerializer::with_pos`](crate::ser::serializers::WriteSerializer::with_pos).
+///
+/// Updated archives point back to values that were serialized before their new dependencies, so
+/// they may fail validation. [`compact`](crate::compact::compact) can be used to rewrite an updated
+/// archive so that it can be validated again.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{
+///     archived_root,
+///     compact::ArchiveUpdater,
+///     ser::serializers::{AlignedSerializer, AllocScratch, CompositeSerializer, SharedSerializeMap},
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(compact)]
+/// struct Item {
+///     name: String,
+///     count: u32,
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(compact)]
+/// struct Inventory {
+///     owner: String,
+///     items: Vec<Item>,
+/// }
+///
+/// let inventory = Inventory {
+///     owner: "the owner of the inventory".to_string(),
+///     items: vec![
+///         Item {
+///             name: "a very sharp sword".to_string(),
+///             count: 1,
+///         },
+///         Item {
+///             name: "some round shields".to_string(),
+///             count: 3,
+///         },
+///     ],
+/// };
+/// let bytes = rkyv::to_bytes::<_, 256>(&inventory).unwrap();
+/// let archived = unsafe { archived_root::<Inventory>(&bytes) };
+///
+/// let serializer = CompositeSerializer::new(
+///     AlignedSerializer::new(bytes.clone()),
+///     AllocScratch::default(),
+///     SharedSerializeMap::default(),
+/// );
+/// let mut updater = ArchiveUpdater::new(&bytes, serializer);
+/// let new_item = Item {
+///     name: "a very dull sword".to_string(),
+///     count: 1,
+/// };
+/// updater
+///     .update::<_, Item>(archived, &archived.items[0], &new_item)
+///     .unwrap();
+/// let updated = updater.into_serializer().into_serializer().into_inner();
+///
+/// let archived = unsafe { archived_root::<Inventory>(&updated) };
+/// assert_eq!(archived.owner, "the owner of the inventory");
+/// assert_eq!(archived.items[0].name, "a very dull sword");
+/// assert_eq!(archived.items[1].name, "some round shields");
+/// ```
+#[derive(Debug)]
+pub struct ArchiveUpdater<'a, S> {
+    archive: &'a [u8],
+    serializer: S,
+    path: Vec<usize>,
+}
+
+impl<'a, S: Serializer> ArchiveUpdater<'a, S> {
+    /// Creates a new updater for the given archive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the serializer does not start writing at the end of the archive.
+    #[inline]
+    pub fn new(archive: &'a [u8], serializer: S) -> Self {
+        assert_eq!(
+            serializer.pos(),
+            archive.len(),
+            "the serializer must start writing at the end of the archive",
+        );
+        Self {
+            archive,
+            serializer,
+            path: Vec::new(),
+        }
+    }
+
+    /// Consumes the updater and returns the serializer.
+    #[inline]
+    pub fn into_serializer(self) -> S {
+        self.serializer
+    }
+
+    #[inline]
+    fn address_of<T: ?Sized>(&self, value: &T) -> usize {
+        let start = self.archive.as_ptr() as usize;
+        let address = value as *const T as *const u8 as usize;
+        assert!(
+            address >= start && address + mem::size_of_val(value) <= start + self.archive.len(),
+            "the value must be located inside of the archive",
+        );
+        address
+    }
+
+    /// Adds a value to the path from the root to the next updated value.
+    ///
+    /// The root and the updated value are always on the path. Every value that is reached by
+    /// following a relative pointer between them must contain a value on the path, so the values
+    /// in between have to be added when they would not. For example, updating the value of an
+    /// element of an `ArchivedVec<ArchivedBox<T>>` requires adding the `ArchivedBox<T>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not located inside of the archive.
+    #[inline]
+    pub fn through<T: ?Sized>(&mut self, value: &T) -> &mut Self {
+        let address = self.address_of(value);
+        self.path.push(address);
+        self
+    }
+
+    /// Replaces `target` with `value` and writes a new version of `root`, then returns the
+    /// position of the new root.
+    ///
+    /// The new root is the last value written, so it can be accessed with
+    /// [`archived_root`](crate::archived_root) if nothing else is serialized after it. Values
+    /// added with [`through`](ArchiveUpdater::through) are removed from the path after updating.
+    ///
+    /// # Panics
+    ///
+    /// - `root` or `target` is not located inside of the archive
+    /// - The path from `root` to `target` is missing a value
+    /// - A relative pointer in the new version cannot reach its target
+    pub fn update<T, U>(
+        &mut self,
+        root: &T,
+        target: &U::Archived,
+        value: &U,
+    ) -> Result<usize, S::Error>
+    where
+        T: Compact + ?Sized,
+        U: Serialize<S>,
+    {
+        let root_address = self.address_of(root);
+        let target_address = self.address_of(target);
+        let resolver = value.serialize(&mut self.serializer)?;
+
+        let mut path = mem::take(&mut self.path);
+        path.push(root_address);
+        path.push(target_address);
+        let mut compactor = Compactor {
+            base: self.serializer.pos(),
+            reuse: Some(Reuse {
+                start: self.archive.as_ptr() as usize,
+                path,
+                target: target_address..target_address + mem::size_of::<U::Archived>(),
+                target_pos: None,
+            }),
+            ..Compactor::default()
+        };
+        let root_pos = compactor.compact_out_of_line(root);
+        let target_pos = compactor
+            .reuse
+            .as_ref()
+            .and_then(|reuse| reuse.target_pos)
+            .expect("the path from the root to the updated value is missing a value");
+
+        // The copy of the old value is overwritten with the new value
+        let mut resolved = MaybeUninit::<U::Archived>::uninit();
+        let bytes = unsafe {
+            resolved.as_mut_ptr().write_bytes(0, 1);
+            value.resolve(target_pos, resolver, resolved.as_mut_ptr());
+            slice::from_raw_parts(
+                resolved.as_ptr().cast::<u8>(),
+                mem::size_of::<U::Archived>(),
+            )
+        };
+        let start = target_pos - compactor.base;
+        compactor.out[start..start + bytes.len()].copy_from_slice(bytes);
+
+        self.serializer.write(&compactor.out)?;
+        Ok(root_pos)
+    }
+}
@@ -25,10 +25,12 @@ pub auto trait ArchiveCopy {}
 // (), PhantomData, PhantomPinned, bool, i8, u8, NonZeroI8, and NonZeroU8 are always ArchiveCopy
 impl<T: ?Sized> ArchiveCopy for PhantomData<T> {}
 
-// Multibyte integers are not ArchiveCopy if the target does not match the archive endianness
+// Multibyte integers are not ArchiveCopy if the target does not match the archive endianness or if
+// they are archived without alignment
 #[cfg(any(
     all(target_endian = "little", feature = "archive_be"),
     all(target_endian = "big", feature = "archive_le"),
+    feature = "unaligned",
 ))]
 const _: () = {
     impl !ArchiveCopy for i16 {}
@@ -108,10 +110,12 @@ unsafe impl ArchiveCopySafe for u8 {}
 unsafe impl ArchiveCopySafe for NonZeroI8 {}
 unsafe impl ArchiveCopySafe for NonZeroU8 {}
 
-// Multibyte integers are ArchiveCopySafe if the target matches the archived endianness
+// Multibyte integers are ArchiveCopySafe if the target matches the archived endianness and they are
+// archived with alignment
 #[cfg(not(any(
     all(target_endian = "little", feature = "archive_be"),
     all(target_endian = "big", feature = "archive_le"),
+    feature = "unaligned",
 )))]
 const _: () = {
     unsafe impl ArchiveCopySafe for i16 {}
@@ -0,0 +1,170 @@
+//! Reading archives with a layout that is only known at runtime.
+//!
+//! A [`Schema`] describes the layout of an archived type: the names, offsets, and kinds of its
+//! fields, the tags of its enum variants, and the relative pointers of its vecs, strings, boxes,
+//! options, and maps. Tools that need to inspect archives from many programs can build schemas at
+//! runtime and navigate archives with [`root`] without compiling against the archived types.
+//!
+//! Every read is bounds checked with the same checks as [`ArchiveValidator`], so a wrong schema or
+//! a corrupted archive returns a [`DynamicError`] instead of reading outside of the archive. Only
+//! the memory that is read is checked, so unlike [`check_archived_root`] this does not validate the
+//! whole archive up front.
+//!
+//! # Example
+//! ```
+//! use rkyv::{
+//!     dynamic::{self, schema::{Field, Schema, StructSchema}},
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Service {
+//!     name: String,
+//!     port: u16,
+//!     peers: Vec<u32>,
+//! }
+//!
+//! let bytes = rkyv::to_bytes::<_, 256>(&Service {
+//!     name: "gateway".to_string(),
+//!     port: 8080,
+//!     peers: vec![1, 2, 3],
+//! })
+//! .unwrap();
+//!
+//! // The field offsets come from the program that wrote the archive
+//! let mut service = core::mem::MaybeUninit::<ArchivedService>::uninit();
+//! let out = service.as_mut_ptr();
+//! let schema = Schema::Struct(StructSchema::new(
+//!     "Service",
+//!     vec![
+//!         Field::new("name", rkyv::out_field!(out.name).0, Schema::String),
+//!         Field::new("port", rkyv::out_field!(out.port).0, Schema::U16),
+//!         Field::new("peers", rkyv::out_field!(out.peers).0, Schema::vec(Schema::U32)),
+//!     ],
+//! ));
+//!
+//! let service = dynamic::root(&bytes, &schema).unwrap();
+//! assert_eq!(service.get_field("name").unwrap().as_str().unwrap(), "gateway");
+//! assert_eq!(service.get_field("port").unwrap().as_u64().unwrap(), 8080);
+//! assert_eq!(service.get_field("peers").unwrap().index(2).unwrap().as_u64().unwrap(), 3);
+//! assert!(service.get_field("host").is_err());
+//! ```
+//!
+//! [`ArchiveValidator`]: crate::validation::validators::ArchiveValidator
+//! [`check_archived_root`]: crate::check_archived_root
+
+pub mod schema;
+mod value;
+
+use self::schema::Schema;
+pub use self::value::DynamicValue;
+use crate::validation::validators::ArchiveError;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::{fmt, str::Utf8Error};
+
+/// Errors that can occur while reading an archive with a [`Schema`].
+#[derive(Debug)]
+pub enum DynamicError {
+    /// A bounds check on the archive failed
+    Archive(ArchiveError),
+    /// The value was read as a different kind of type than its schema describes
+    TypeMismatch {
+        /// The kind of type that was requested
+        expected: &'static str,
+        /// The kind of type described by the schema
+        found: &'static str,
+    },
+    /// A struct or enum variant has no field with the given name
+    FieldNotFound(String),
+    /// An index was past the end of a sequence
+    IndexOutOfBounds {
+        /// The requested index
+        index: usize,
+        /// The length of the sequence
+        len: usize,
+    },
+    /// An enum or option had a tag that doesn't match any variant
+    InvalidTag(u64),
+    /// A `bool` had a value other than 0 or 1
+    InvalidBool(u8),
+    /// A `char` was not a valid unicode scalar value
+    InvalidChar(u32),
+    /// A string had a length that its representation can't hold
+    InvalidLength(usize),
+    /// A string was not valid UTF-8
+    InvalidUtf8(Utf8Error),
+    /// An integer didn't fit in the requested type
+    OutOfRange,
+    /// Computing the size or position of a value overflowed
+    Overflow,
+    /// A map had a different number of entries than its length, or more entries than the archive
+    /// can hold
+    MalformedMap,
+}
+
+impl From<ArchiveError> for DynamicError {
+    #[inline]
+    fn from(e: ArchiveError) -> Self {
+        DynamicError::Archive(e)
+    }
+}
+
+impl fmt::Display for DynamicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynamicError::Archive(e) => write!(f, "{}", e),
+            DynamicError::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "type mismatch: expected {} but found {}",
+                    expected, found
+                )
+            }
+            DynamicError::FieldNotFound(name) => write!(f, "field not found: {}", name),
+            DynamicError::IndexOutOfBounds { index, len } => {
+                write!(f, "index out of bounds: index {} but length {}", index, len)
+            }
+            DynamicError::InvalidTag(tag) => write!(f, "invalid tag: {}", tag),
+            DynamicError::InvalidBool(b) => write!(f, "invalid bool: {}", b),
+            DynamicError::InvalidChar(c) => write!(f, "invalid char: {:#x}", c),
+            DynamicError::InvalidLength(len) => write!(f, "invalid string length: {}", len),
+            DynamicError::InvalidUtf8(e) => write!(f, "invalid UTF-8: {}", e),
+            DynamicError::OutOfRange => write!(f, "integer out of range"),
+            DynamicError::Overflow => write!(f, "position overflowed"),
+            DynamicError::MalformedMap => write!(f, "malformed map"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DynamicError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DynamicError::Archive(e) => Some(e as &dyn std::error::Error),
+            DynamicError::InvalidUtf8(e) => Some(e as &dyn std::error::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the root value of an archive, read according to `schema`.
+///
+/// Like [`archived_root`](crate::archived_root), the root is expected at the end of the archive.
+pub fn root<'a>(bytes: &'a [u8], schema: &'a Schema) -> Result<DynamicValue<'a>, DynamicError> {
+    let pos = bytes.len().saturating_sub(schema.layout().size());
+    DynamicValue::new(bytes, pos, schema)
+}
+
+/// Returns the value at the given position in an archive, read according to `schema`.
+///
+/// Like [`archived_value`](crate::archived_value), `pos` is the position returned when the value
+/// was serialized.
+#[inline]
+pub fn value_at<'a>(
+    bytes: &'a [u8],
+    pos: usize,
+    schema: &'a Schema,
+) -> Result<DynamicValue<'a>, DynamicError> {
+    DynamicValue::new(bytes, pos, schema)
+}
@@ -0,0 +1,465 @@
+//! Runtime descriptions of archived layouts.
+
+use crate::{
+    boxed::ArchivedBox, collections::btree_map::ArchivedBTreeMap,
+    collections::hash_map::ArchivedHashMap, string::ArchivedString, vec::ArchivedVec, Archived,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::alloc::Layout;
+
+/// A description of the layout of an archived type.
+///
+/// Primitives are described by the type that was archived, so [`Schema::U32`] describes an
+/// `Archived<u32>` and follows the endianness, alignment, and `*size` width features that the
+/// reader was compiled with. Containers hold the schema of the types they point to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schema {
+    /// `()`
+    Unit,
+    /// `bool`
+    Bool,
+    /// `i8`
+    I8,
+    /// `i16`
+    I16,
+    /// `i32`
+    I32,
+    /// `i64`
+    I64,
+    /// `u8`
+    U8,
+    /// `u16`
+    U16,
+    /// `u32`
+    U32,
+    /// `u64`
+    U64,
+    /// `f32`
+    F32,
+    /// `f64`
+    F64,
+    /// `char`
+    Char,
+    /// `isize`
+    Isize,
+    /// `usize`
+    Usize,
+    /// A fixed-size array of elements
+    Array(Box<Schema>, usize),
+    /// A struct with named fields
+    Struct(StructSchema),
+    /// An enum with a tag
+    Enum(EnumSchema),
+    /// A `Vec` of elements
+    Vec(Box<Schema>),
+    /// A `String`
+    String,
+    /// A `Box` of a sized value
+    Box(Box<Schema>),
+    /// A `Box<str>`
+    BoxedStr,
+    /// A `Box<[T]>` of elements
+    BoxedSlice(Box<Schema>),
+    /// An `Option`
+    Option(Box<Schema>),
+    /// A `HashMap`
+    HashMap(Box<MapSchema>),
+    /// A `BTreeMap`
+    BTreeMap(Box<MapSchema>),
+}
+
+impl Schema {
+    /// Returns a schema for an array of `len` elements.
+    #[inline]
+    pub fn array(element: Schema, len: usize) -> Self {
+        Schema::Array(Box::new(element), len)
+    }
+
+    /// Returns a schema for a `Vec` of elements.
+    #[inline]
+    pub fn vec(element: Schema) -> Self {
+        Schema::Vec(Box::new(element))
+    }
+
+    /// Returns a schema for a `Box` of a sized value.
+    #[inline]
+    pub fn boxed(inner: Schema) -> Self {
+        Schema::Box(Box::new(inner))
+    }
+
+    /// Returns a schema for a `Box<[T]>` of elements.
+    #[inline]
+    pub fn boxed_slice(element: Schema) -> Self {
+        Schema::BoxedSlice(Box::new(element))
+    }
+
+    /// Returns a schema for an `Option`.
+    #[inline]
+    pub fn option(inner: Schema) -> Self {
+        Schema::Option(Box::new(inner))
+    }
+
+    /// Returns a schema for a `HashMap`.
+    #[inline]
+    pub fn hash_map(map: MapSchema) -> Self {
+        Schema::HashMap(Box::new(map))
+    }
+
+    /// Returns a schema for a `BTreeMap`.
+    #[inline]
+    pub fn btree_map(map: MapSchema) -> Self {
+        Schema::BTreeMap(Box::new(map))
+    }
+
+    /// Returns the layout of the archived type described by this schema.
+    pub fn layout(&self) -> Layout {
+        match self {
+            Schema::Unit => Layout::new::<()>(),
+            Schema::Bool => Layout::new::<bool>(),
+            Schema::I8 => Layout::new::<i8>(),
+            Schema::I16 => Layout::new::<Archived<i16>>(),
+            Schema::I32 => Layout::new::<Archived<i32>>(),
+            Schema::I64 => Layout::new::<Archived<i64>>(),
+            Schema::U8 => Layout::new::<u8>(),
+            Schema::U16 => Layout::new::<Archived<u16>>(),
+            Schema::U32 => Layout::new::<Archived<u32>>(),
+            Schema::U64 => Layout::new::<Archived<u64>>(),
+            Schema::F32 => Layout::new::<Archived<f32>>(),
+            Schema::F64 => Layout::new::<Archived<f64>>(),
+            Schema::Char => Layout::new::<Archived<char>>(),
+            Schema::Isize => Layout::new::<Archived<isize>>(),
+            Schema::Usize => Layout::new::<Archived<usize>>(),
+            Schema::Array(element, len) => {
+                let element = element.layout();
+                Layout::from_size_align(element.size() * len, element.align()).unwrap()
+            }
+            Schema::Struct(schema) => schema.layout(),
+            Schema::Enum(schema) => schema.layout(),
+            Schema::Vec(_) => Layout::new::<ArchivedVec<u8>>(),
+            Schema::String => Layout::new::<ArchivedString>(),
+            Schema::Box(_) => Layout::new::<ArchivedBox<u8>>(),
+            Schema::BoxedStr => Layout::new::<ArchivedBox<str>>(),
+            Schema::BoxedSlice(_) => Layout::new::<ArchivedBox<[u8]>>(),
+            Schema::Option(inner) => Layout::new::<u8>()
+                .extend(inner.layout())
+                .unwrap()
+                .0
+                .pad_to_align(),
+            Schema::HashMap(_) => Layout::new::<ArchivedHashMap<u8, u8>>(),
+            Schema::BTreeMap(_) => Layout::new::<ArchivedBTreeMap<u8, u8>>(),
+        }
+    }
+
+    /// Returns a short name for the kind of type described by this schema.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Schema::Unit => "unit",
+            Schema::Bool => "bool",
+            Schema::I8 => "i8",
+            Schema::I16 => "i16",
+            Schema::I32 => "i32",
+            Schema::I64 => "i64",
+            Schema::U8 => "u8",
+            Schema::U16 => "u16",
+            Schema::U32 => "u32",
+            Schema::U64 => "u64",
+            Schema::F32 => "f32",
+            Schema::F64 => "f64",
+            Schema::Char => "char",
+            Schema::Isize => "isize",
+            Schema::Usize => "usize",
+            Schema::Array(..) => "array",
+            Schema::Struct(_) => "struct",
+            Schema::Enum(_) => "enum",
+            Schema::Vec(_) => "vec",
+            Schema::String => "string",
+            Schema::Box(_) => "box",
+            Schema::BoxedStr => "boxed str",
+            Schema::BoxedSlice(_) => "boxed slice",
+            Schema::Option(_) => "option",
+            Schema::HashMap(_) => "hash map",
+            Schema::BTreeMap(_) => "btree map",
+        }
+    }
+}
+
+/// A field of a struct or enum variant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    /// The name of the field. Fields of tuple structs are named by their position.
+    pub name: String,
+    /// The offset of the field from the start of the struct or enum
+    pub offset: usize,
+    /// The schema of the field
+    pub schema: Schema,
+}
+
+impl Field {
+    /// Returns a new field at the given offset.
+    #[inline]
+    pub fn new(name: impl Into<String>, offset: usize, schema: Schema) -> Self {
+        Self {
+            name: name.into(),
+            offset,
+            schema,
+        }
+    }
+}
+
+/// Returns the layout of `base` extended to cover all of `fields`.
+fn fields_layout(base: Layout, fields: &[Field]) -> Layout {
+    let (size, align) = fields
+        .iter()
+        .fold((base.size(), base.align()), |(size, align), f| {
+            let layout = f.schema.layout();
+            (
+                usize::max(size, f.offset + layout.size()),
+                usize::max(align, layout.align()),
+            )
+        });
+    Layout::from_size_align(size, align).unwrap().pad_to_align()
+}
+
+/// Lays out `fields` in order after `base` the way `#[repr(C)]` would.
+fn repr_c_fields(base: Layout, fields: Vec<(String, Schema)>) -> Vec<Field> {
+    let mut layout = base;
+    fields
+        .into_iter()
+        .map(|(name, schema)| {
+            let (next, offset) = layout.extend(schema.layout()).unwrap();
+            layout = next;
+            Field::new(name, offset, schema)
+        })
+        .collect()
+}
+
+/// The layout of a struct.
+///
+/// Archived structs are not `#[repr(C)]` unless the `strict` feature is enabled, so in general the
+/// field offsets have to come from the program that produced the archive (for example with
+/// [`out_field!`](crate::out_field) on an uninitialized archived value).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructSchema {
+    name: String,
+    fields: Vec<Field>,
+    layout: Layout,
+}
+
+impl StructSchema {
+    /// Returns a new struct schema with fields at the given offsets.
+    pub fn new(name: impl Into<String>, fields: Vec<Field>) -> Self {
+        let layout = fields_layout(Layout::new::<()>(), &fields);
+        Self {
+            name: name.into(),
+            fields,
+            layout,
+        }
+    }
+
+    /// Returns a new struct schema with fields laid out in order as if by `#[repr(C)]`.
+    pub fn repr_c(name: impl Into<String>, fields: Vec<(String, Schema)>) -> Self {
+        Self::new(name, repr_c_fields(Layout::new::<()>(), fields))
+    }
+
+    /// Returns the name of the struct.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the fields of the struct.
+    #[inline]
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Returns the field with the given name, if any.
+    #[inline]
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Returns the layout of the struct.
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+/// The integer type of an enum tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagRepr {
+    /// `u8`
+    U8,
+    /// `u16`
+    U16,
+    /// `u32`
+    U32,
+    /// `u64`
+    U64,
+}
+
+impl TagRepr {
+    /// Returns the layout of the tag.
+    pub fn layout(&self) -> Layout {
+        match self {
+            TagRepr::U8 => Layout::new::<u8>(),
+            TagRepr::U16 => Layout::new::<Archived<u16>>(),
+            TagRepr::U32 => Layout::new::<Archived<u32>>(),
+            TagRepr::U64 => Layout::new::<Archived<u64>>(),
+        }
+    }
+}
+
+/// A variant of an enum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantSchema {
+    /// The name of the variant
+    pub name: String,
+    /// The value of the tag for this variant
+    pub tag: u64,
+    /// The fields of the variant, with offsets from the start of the enum
+    pub fields: Vec<Field>,
+}
+
+impl VariantSchema {
+    /// Returns a new variant with the given tag and fields.
+    #[inline]
+    pub fn new(name: impl Into<String>, tag: u64, fields: Vec<Field>) -> Self {
+        Self {
+            name: name.into(),
+            tag,
+            fields,
+        }
+    }
+
+    /// Returns the field with the given name, if any.
+    #[inline]
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// The layout of an enum.
+///
+/// The tag is stored at the start of the enum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumSchema {
+    name: String,
+    tag: TagRepr,
+    variants: Vec<VariantSchema>,
+    layout: Layout,
+}
+
+impl EnumSchema {
+    /// Returns a new enum schema with the given tag and variants.
+    pub fn new(name: impl Into<String>, tag: TagRepr, variants: Vec<VariantSchema>) -> Self {
+        let layout = variants
+            .iter()
+            .fold(tag.layout(), |layout, v| fields_layout(layout, &v.fields));
+        Self {
+            name: name.into(),
+            tag,
+            variants,
+            layout,
+        }
+    }
+
+    /// Returns a new enum schema laid out the way archived enums are derived.
+    ///
+    /// Variants are numbered in order starting from zero and each is laid out as a `#[repr(C)]`
+    /// struct of the tag followed by its fields.
+    pub fn repr_c(name: impl Into<String>, variants: Vec<(String, Vec<(String, Schema)>)>) -> Self {
+        let tag = if variants.len() <= 256 {
+            TagRepr::U8
+        } else {
+            TagRepr::U16
+        };
+        let variants = variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, fields))| {
+                VariantSchema::new(name, i as u64, repr_c_fields(tag.layout(), fields))
+            })
+            .collect();
+        Self::new(name, tag, variants)
+    }
+
+    /// Returns the name of the enum.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the integer type of the tag.
+    #[inline]
+    pub fn tag(&self) -> TagRepr {
+        self.tag
+    }
+
+    /// Returns the variants of the enum.
+    #[inline]
+    pub fn variants(&self) -> &[VariantSchema] {
+        &self.variants
+    }
+
+    /// Returns the variant with the given tag, if any.
+    #[inline]
+    pub fn variant(&self, tag: u64) -> Option<&VariantSchema> {
+        self.variants.iter().find(|v| v.tag == tag)
+    }
+
+    /// Returns the layout of the enum.
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+/// The layout of the entries of a hash map or B-tree map.
+///
+/// Like structs, map entries are only `#[repr(C)]` with the `strict` feature enabled, so the key
+/// and value offsets should come from the program that produced the archive. Hash map entries are
+/// [`Entry`](crate::collections::util::Entry)s, and the offsets in B-tree map entries are returned
+/// by [`ArchivedBTreeMap::entry_offsets`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapSchema {
+    key: Field,
+    value: Field,
+    entry: Layout,
+}
+
+impl MapSchema {
+    /// Returns a new map schema with keys and values at the given offsets in each entry.
+    pub fn new(key: Schema, key_offset: usize, value: Schema, value_offset: usize) -> Self {
+        let key = Field::new("key", key_offset, key);
+        let value = Field::new("value", value_offset, value);
+        let entry = fields_layout(Layout::new::<()>(), &[key.clone(), value.clone()]);
+        Self { key, value, entry }
+    }
+
+    /// Returns a new map schema with the key followed by the value in each entry.
+    pub fn repr_c(key: Schema, value: Schema) -> Self {
+        let (_, value_offset) = key.layout().extend(value.layout()).unwrap();
+        Self::new(key, 0, value, value_offset)
+    }
+
+    /// Returns the schema and offset of the keys.
+    #[inline]
+    pub fn key(&self) -> &Field {
+        &self.key
+    }
+
+    /// Returns the schema and offset of the values.
+    #[inline]
+    pub fn value(&self) -> &Field {
+        &self.value
+    }
+
+    /// Returns the layout of each entry.
+    #[inline]
+    pub fn entry_layout(&self) -> Layout {
+        self.entry
+    }
+}
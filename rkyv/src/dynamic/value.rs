@@ -0,0 +1,602 @@
+//! Bounds-checked values read with a runtime schema.
+
+use super::{
+    schema::{Field, MapSchema, Schema, TagRepr},
+    DynamicError,
+};
+use crate::{
+    collections::{btree_map::ArchivedBTreeMap, hash_map::ArchivedHashMap},
+    string::repr::{ArchivedStringRepr, INLINE_CAPACITY},
+    validation::{validators::ArchiveValidator, ArchiveContext},
+    vec::ArchivedVec,
+    Archived, RelPtr,
+};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::{alloc::Layout, fmt, slice, str};
+
+/// A value in an archive, read according to a [`Schema`].
+///
+/// Every access bounds checks the memory it reads against the archive, so navigating a value with
+/// the wrong schema returns errors instead of reading outside of the archive. Formatting a value
+/// prints it in a Rust-like syntax, and the alternate flag (`{:#}`) prints it over multiple lines.
+#[derive(Clone, Copy)]
+pub struct DynamicValue<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    schema: &'a Schema,
+}
+
+impl<'a> DynamicValue<'a> {
+    /// Returns the value described by `schema` at the given position in the archive.
+    pub fn new(bytes: &'a [u8], pos: usize, schema: &'a Schema) -> Result<Self, DynamicError> {
+        let mut validator = ArchiveValidator::new(bytes);
+        let offset = isize::try_from(pos).map_err(|_| DynamicError::Overflow)?;
+        unsafe {
+            let ptr = validator.bounds_check_ptr(bytes.as_ptr(), offset)?;
+            validator.bounds_check_layout(ptr, &schema.layout())?;
+        }
+        Ok(Self { bytes, pos, schema })
+    }
+
+    /// Returns the schema of the value.
+    #[inline]
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Returns the position of the value in the archive.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn ptr(&self) -> *const u8 {
+        // SAFETY: `pos` was bounds checked when the value was created.
+        unsafe { self.bytes.as_ptr().add(self.pos) }
+    }
+
+    #[inline]
+    fn validator(&self) -> ArchiveValidator<'a> {
+        ArchiveValidator::new(self.bytes)
+    }
+
+    /// Returns the value described by `schema` at `ptr`, which must point into the archive.
+    fn at(&self, ptr: *const u8, schema: &'a Schema) -> Result<Self, DynamicError> {
+        let pos = ptr as usize - self.bytes.as_ptr() as usize;
+        Self::new(self.bytes, pos, schema)
+    }
+
+    /// Returns the value of a field whose offset is relative to `base`.
+    fn field_at(&self, base: *const u8, field: &'a Field) -> Result<Self, DynamicError> {
+        let offset = isize::try_from(field.offset).map_err(|_| DynamicError::Overflow)?;
+        let ptr = unsafe { self.validator().bounds_check_ptr(base, offset)? };
+        self.at(ptr, &field.schema)
+    }
+
+    /// Follows a relative pointer to an array of `len` elements with the given layout.
+    fn follow<T: crate::ArchivePointee + ?Sized>(
+        &self,
+        rel_ptr: &RelPtr<T>,
+        element: Layout,
+        len: usize,
+    ) -> Result<*const u8, DynamicError> {
+        let size = element
+            .pad_to_align()
+            .size()
+            .checked_mul(len)
+            .ok_or(DynamicError::Overflow)?;
+        let layout = Layout::from_size_align(size, element.align())
+            .map_err(ArchiveValidator::wrap_layout_error)?;
+        let mut validator = self.validator();
+        unsafe {
+            let ptr = validator.bounds_check_ptr(rel_ptr.base(), rel_ptr.offset())?;
+            validator.bounds_check_layout(ptr, &layout)?;
+            Ok(ptr)
+        }
+    }
+
+    fn mismatch(&self, expected: &'static str) -> DynamicError {
+        DynamicError::TypeMismatch {
+            expected,
+            found: self.schema.kind(),
+        }
+    }
+
+    /// Reads the primitive of type `T` at the start of the value.
+    ///
+    /// # Safety
+    ///
+    /// The schema of the value must describe a `T`.
+    #[inline]
+    unsafe fn read<T>(&self) -> T {
+        self.ptr().cast::<T>().read_unaligned()
+    }
+
+    fn read_tag(&self, tag: TagRepr) -> u64 {
+        // SAFETY: enum layouts always include their tag
+        unsafe {
+            match tag {
+                TagRepr::U8 => u64::from(self.read::<u8>()),
+                TagRepr::U16 => u64::from(from_archived!(self.read::<Archived<u16>>())),
+                TagRepr::U32 => u64::from(from_archived!(self.read::<Archived<u32>>())),
+                TagRepr::U64 => from_archived!(self.read::<Archived<u64>>()),
+            }
+        }
+    }
+
+    /// Returns the name of the active variant of an enum.
+    pub fn variant(&self) -> Result<&'a str, DynamicError> {
+        match self.schema {
+            Schema::Enum(schema) => {
+                let tag = self.read_tag(schema.tag());
+                let variant = schema.variant(tag).ok_or(DynamicError::InvalidTag(tag))?;
+                Ok(&variant.name)
+            }
+            _ => Err(self.mismatch("enum")),
+        }
+    }
+
+    /// Returns the fields of a struct or of the active variant of an enum.
+    pub fn fields(&self) -> Result<&'a [Field], DynamicError> {
+        match self.schema {
+            Schema::Struct(schema) => Ok(schema.fields()),
+            Schema::Enum(schema) => {
+                let tag = self.read_tag(schema.tag());
+                let variant = schema.variant(tag).ok_or(DynamicError::InvalidTag(tag))?;
+                Ok(&variant.fields)
+            }
+            _ => Err(self.mismatch("struct or enum")),
+        }
+    }
+
+    /// Returns the field of a struct or of the active variant of an enum with the given name.
+    pub fn get_field(&self, name: &str) -> Result<Self, DynamicError> {
+        let field = self
+            .fields()?
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| DynamicError::FieldNotFound(name.into()))?;
+        self.field_at(self.ptr(), field)
+    }
+
+    /// Returns the number of elements of an array, vec, or boxed slice, the number of bytes of a
+    /// string, or the number of entries of a map.
+    pub fn len(&self) -> Result<usize, DynamicError> {
+        match self.schema {
+            Schema::Array(_, len) => Ok(*len),
+            Schema::Vec(_) => Ok(unsafe { self.read_vec() }.len()),
+            Schema::BoxedSlice(_) | Schema::BoxedStr => {
+                Ok(from_archived!(*unsafe { self.read_boxed_slice() }.metadata()) as usize)
+            }
+            Schema::String => Ok(unsafe { self.read_string() }.len()),
+            Schema::HashMap(_) => Ok(unsafe { self.read_hash_map() }.len()),
+            Schema::BTreeMap(_) => Ok(unsafe { self.read_btree_map() }.len()),
+            _ => Err(self.mismatch("sequence, string, or map")),
+        }
+    }
+
+    /// Returns whether [`len`](DynamicValue::len) is zero.
+    #[inline]
+    pub fn is_empty(&self) -> Result<bool, DynamicError> {
+        Ok(self.len()? == 0)
+    }
+
+    // The following readers all require the schema of the value to describe the type they read.
+
+    unsafe fn read_vec(&self) -> &'a ArchivedVec<u8> {
+        &*self.ptr().cast()
+    }
+
+    unsafe fn read_boxed_slice(&self) -> &'a RelPtr<[u8]> {
+        &*self.ptr().cast()
+    }
+
+    unsafe fn read_string(&self) -> &'a ArchivedStringRepr {
+        &*self.ptr().cast()
+    }
+
+    unsafe fn read_hash_map(&self) -> &'a ArchivedHashMap<u8, u8> {
+        &*self.ptr().cast()
+    }
+
+    unsafe fn read_btree_map(&self) -> &'a ArchivedBTreeMap<u8, u8> {
+        &*self.ptr().cast()
+    }
+
+    /// Returns the element at the given index of an array, vec, or boxed slice.
+    pub fn index(&self, index: usize) -> Result<Self, DynamicError> {
+        let (element, start, len) = match self.schema {
+            Schema::Array(element, len) => (&**element, self.ptr(), *len),
+            Schema::Vec(element) => {
+                let vec = unsafe { self.read_vec() };
+                let start = self.follow(vec.rel_ptr(), element.layout(), vec.len())?;
+                (&**element, start, vec.len())
+            }
+            Schema::BoxedSlice(element) => {
+                let rel_ptr = unsafe { self.read_boxed_slice() };
+                let len = from_archived!(*rel_ptr.metadata()) as usize;
+                (
+                    &**element,
+                    self.follow(rel_ptr, element.layout(), len)?,
+                    len,
+                )
+            }
+            _ => return Err(self.mismatch("array, vec, or boxed slice")),
+        };
+        if index >= len {
+            return Err(DynamicError::IndexOutOfBounds { index, len });
+        }
+        let stride = element.layout().pad_to_align().size();
+        self.at(unsafe { start.add(stride * index) }, element)
+    }
+
+    /// Returns the value inside an option, or `None` if the option is empty.
+    pub fn as_option(&self) -> Result<Option<Self>, DynamicError> {
+        match self.schema {
+            Schema::Option(inner) => match unsafe { self.read::<u8>() } {
+                0 => Ok(None),
+                1 => {
+                    let offset = Layout::new::<u8>()
+                        .extend(inner.layout())
+                        .map_err(ArchiveValidator::wrap_layout_error)?
+                        .1;
+                    Ok(Some(self.at(unsafe { self.ptr().add(offset) }, inner)?))
+                }
+                tag => Err(DynamicError::InvalidTag(u64::from(tag))),
+            },
+            _ => Err(self.mismatch("option")),
+        }
+    }
+
+    /// Returns the value pointed to by a box.
+    pub fn unbox(&self) -> Result<Self, DynamicError> {
+        match self.schema {
+            Schema::Box(inner) => {
+                let rel_ptr = unsafe { &*self.ptr().cast::<RelPtr<u8>>() };
+                self.at(self.follow(rel_ptr, inner.layout(), 1)?, inner)
+            }
+            _ => Err(self.mismatch("box")),
+        }
+    }
+
+    /// Returns the string of a `String` or `Box<str>`.
+    pub fn as_str(&self) -> Result<&'a str, DynamicError> {
+        let bytes = match self.schema {
+            Schema::String => {
+                let repr = unsafe { self.read_string() };
+                if repr.is_inline() {
+                    if repr.len() > INLINE_CAPACITY {
+                        return Err(DynamicError::InvalidLength(repr.len()));
+                    }
+                    // SAFETY: inline strings are stored inside the representation, which was
+                    // bounds checked when the value was created.
+                    unsafe { slice::from_raw_parts(repr.as_ptr(), repr.len()) }
+                } else {
+                    let offset = unsafe { repr.out_of_line_offset() };
+                    let mut validator = self.validator();
+                    let base = (repr as *const ArchivedStringRepr).cast::<u8>();
+                    unsafe {
+                        let ptr = validator.bounds_check_ptr(base, offset)?;
+                        let layout = Layout::array::<u8>(repr.len())
+                            .map_err(ArchiveValidator::wrap_layout_error)?;
+                        validator.bounds_check_layout(ptr, &layout)?;
+                        slice::from_raw_parts(ptr, repr.len())
+                    }
+                }
+            }
+            Schema::BoxedStr => {
+                let rel_ptr = unsafe { self.read_boxed_slice() };
+                let len = from_archived!(*rel_ptr.metadata()) as usize;
+                let ptr = self.follow(rel_ptr, Layout::new::<u8>(), len)?;
+                unsafe { slice::from_raw_parts(ptr, len) }
+            }
+            _ => return Err(self.mismatch("string")),
+        };
+        str::from_utf8(bytes).map_err(DynamicError::InvalidUtf8)
+    }
+
+    /// Returns the entries of a hash map or B-tree map.
+    ///
+    /// Hash map entries are returned in storage order and B-tree map entries in key order.
+    pub fn entries(&self) -> Result<Vec<(Self, Self)>, DynamicError> {
+        match self.schema {
+            Schema::HashMap(map) => {
+                let hash_map = unsafe { self.read_hash_map() };
+                let len = hash_map.len();
+                let layout = map.entry_layout();
+                let stride = layout.pad_to_align().size();
+                check_zero_sized_len(stride, len)?;
+                let start = self.follow(hash_map.entries_rel_ptr(), layout, len)?;
+                (0..len)
+                    .map(|i| self.entry(map, unsafe { start.add(stride * i) }))
+                    .collect()
+            }
+            Schema::BTreeMap(map) => {
+                let btree_map = unsafe { self.read_btree_map() };
+                let layout = map.entry_layout();
+                let stride = layout.pad_to_align().size();
+                check_zero_sized_len(stride, btree_map.len())?;
+                // The lengths of the map and its leaves can't be trusted, so entries are only
+                // reserved once their leaf has been bounds checked. The leaves of a well-formed map
+                // are never empty and don't overlap, so their entries can't cover more bytes than
+                // the archive has.
+                let mut entries = Vec::new();
+                let mut checked = 0usize;
+                let mut validator = self.validator();
+                let visited = unsafe {
+                    ArchivedBTreeMap::<u8, u8>::visit_raw_leaves(
+                        btree_map,
+                        layout,
+                        &mut validator,
+                        |start, len| {
+                            checked = stride
+                                .checked_mul(len)
+                                .and_then(|size| checked.checked_add(size))
+                                .ok_or(DynamicError::Overflow)?;
+                            if len == 0 || checked > self.bytes.len() {
+                                return Err(DynamicError::MalformedMap);
+                            }
+                            entries.reserve(len);
+                            for i in 0..len {
+                                entries.push(self.entry(map, start.add(stride * i))?);
+                            }
+                            Ok(true)
+                        },
+                    )?
+                };
+                if visited != btree_map.len() {
+                    return Err(DynamicError::MalformedMap);
+                }
+                Ok(entries)
+            }
+            _ => Err(self.mismatch("map")),
+        }
+    }
+
+    fn entry(&self, map: &'a MapSchema, ptr: *const u8) -> Result<(Self, Self), DynamicError> {
+        Ok((
+            self.field_at(ptr, map.key())?,
+            self.field_at(ptr, map.value())?,
+        ))
+    }
+
+    /// Returns the value of the map entry whose key is the given string.
+    ///
+    /// Returns `None` if there is no such entry.
+    pub fn get_key(&self, key: &str) -> Result<Option<Self>, DynamicError> {
+        for (k, v) in self.entries()? {
+            if k.as_str()? == key {
+                return Ok(Some(v));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the value of a `bool`.
+    pub fn as_bool(&self) -> Result<bool, DynamicError> {
+        match self.schema {
+            Schema::Bool => match unsafe { self.read::<u8>() } {
+                0 => Ok(false),
+                1 => Ok(true),
+                b => Err(DynamicError::InvalidBool(b)),
+            },
+            _ => Err(self.mismatch("bool")),
+        }
+    }
+
+    /// Returns the value of a `char`.
+    pub fn as_char(&self) -> Result<char, DynamicError> {
+        match self.schema {
+            Schema::Char => {
+                let c = from_archived!(unsafe { self.read::<Archived<u32>>() });
+                char::from_u32(c).ok_or(DynamicError::InvalidChar(c))
+            }
+            _ => Err(self.mismatch("char")),
+        }
+    }
+
+    /// Returns the value of an integer as a `u64`.
+    pub fn as_u64(&self) -> Result<u64, DynamicError> {
+        unsafe {
+            match self.schema {
+                Schema::U8 => Ok(u64::from(self.read::<u8>())),
+                Schema::U16 => Ok(u64::from(from_archived!(self.read::<Archived<u16>>()))),
+                Schema::U32 => Ok(u64::from(from_archived!(self.read::<Archived<u32>>()))),
+                Schema::U64 => Ok(from_archived!(self.read::<Archived<u64>>())),
+                Schema::Usize => Ok(from_archived!(self.read::<Archived<usize>>()) as u64),
+                Schema::I8 | Schema::I16 | Schema::I32 | Schema::I64 | Schema::Isize => {
+                    u64::try_from(self.as_i64()?).map_err(|_| DynamicError::OutOfRange)
+                }
+                _ => Err(self.mismatch("integer")),
+            }
+        }
+    }
+
+    /// Returns the value of an integer as an `i64`.
+    pub fn as_i64(&self) -> Result<i64, DynamicError> {
+        unsafe {
+            match self.schema {
+                Schema::I8 => Ok(i64::from(self.read::<i8>())),
+                Schema::I16 => Ok(i64::from(from_archived!(self.read::<Archived<i16>>()))),
+                Schema::I32 => Ok(i64::from(from_archived!(self.read::<Archived<i32>>()))),
+                Schema::I64 => Ok(from_archived!(self.read::<Archived<i64>>())),
+                Schema::Isize => Ok(from_archived!(self.read::<Archived<isize>>()) as i64),
+                Schema::U8 | Schema::U16 | Schema::U32 | Schema::U64 | Schema::Usize => {
+                    i64::try_from(self.as_u64()?).map_err(|_| DynamicError::OutOfRange)
+                }
+                _ => Err(self.mismatch("integer")),
+            }
+        }
+    }
+
+    /// Returns the value of a floating-point number as an `f64`.
+    pub fn as_f64(&self) -> Result<f64, DynamicError> {
+        unsafe {
+            match self.schema {
+                Schema::F32 => Ok(f64::from(from_archived!(self.read::<Archived<f32>>()))),
+                Schema::F64 => Ok(from_archived!(self.read::<Archived<f64>>())),
+                _ => Err(self.mismatch("float")),
+            }
+        }
+    }
+
+    fn fmt_value(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        match self.fmt_checked(f, depth) {
+            Ok(result) => result,
+            Err(e) => write!(f, "<invalid: {}>", e),
+        }
+    }
+
+    /// Formats the value, returning an error if the value couldn't be read.
+    fn fmt_checked(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        depth: usize,
+    ) -> Result<fmt::Result, DynamicError> {
+        Ok(match self.schema {
+            Schema::Unit => f.write_str("()"),
+            Schema::Bool => write!(f, "{}", self.as_bool()?),
+            Schema::I8 | Schema::I16 | Schema::I32 | Schema::I64 | Schema::Isize => {
+                write!(f, "{}", self.as_i64()?)
+            }
+            Schema::U8 | Schema::U16 | Schema::U32 | Schema::U64 | Schema::Usize => {
+                write!(f, "{}", self.as_u64()?)
+            }
+            Schema::F32 | Schema::F64 => write!(f, "{:?}", self.as_f64()?),
+            Schema::Char => write!(f, "{:?}", self.as_char()?),
+            Schema::String | Schema::BoxedStr => write!(f, "{:?}", self.as_str()?),
+            Schema::Struct(schema) => f
+                .write_str(schema.name())
+                .and_then(|()| self.fmt_fields(f, depth)),
+            Schema::Enum(_) => {
+                let variant = self.variant()?;
+                f.write_str(variant)
+                    .and_then(|()| self.fmt_fields(f, depth))
+            }
+            Schema::Array(..) | Schema::Vec(_) | Schema::BoxedSlice(_) => fmt_list(
+                f,
+                depth,
+                "[",
+                "]",
+                false,
+                self.len()?,
+                |f, i, depth| match self.index(i) {
+                    Ok(element) => element.fmt_value(f, depth),
+                    Err(e) => write!(f, "<invalid: {}>", e),
+                },
+            ),
+            Schema::Box(_) => return Ok(self.unbox()?.fmt_value(f, depth)),
+            Schema::Option(_) => match self.as_option()? {
+                None => f.write_str("None"),
+                Some(value) => f
+                    .write_str("Some(")
+                    .and_then(|()| value.fmt_value(f, depth))
+                    .and_then(|()| f.write_str(")")),
+            },
+            Schema::HashMap(_) | Schema::BTreeMap(_) => {
+                let entries = self.entries()?;
+                fmt_list(f, depth, "{", "}", false, entries.len(), |f, i, depth| {
+                    let (key, value) = &entries[i];
+                    key.fmt_value(f, depth)?;
+                    f.write_str(": ")?;
+                    value.fmt_value(f, depth)
+                })
+            }
+        })
+    }
+
+    fn fmt_fields(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let fields = match self.fields() {
+            Ok(fields) => fields,
+            Err(e) => return write!(f, " <invalid: {}>", e),
+        };
+        if fields.is_empty() {
+            return Ok(());
+        }
+        f.write_str(" ")?;
+        fmt_list(f, depth, "{", "}", true, fields.len(), |f, i, depth| {
+            write!(f, "{}: ", fields[i].name)?;
+            match self.field_at(self.ptr(), &fields[i]) {
+                Ok(value) => value.fmt_value(f, depth),
+                Err(e) => write!(f, "<invalid: {}>", e),
+            }
+        })
+    }
+}
+
+/// Checks that a map with zero-sized entries has at most one entry.
+///
+/// Entries without any bytes can't be bounds checked, and a map can only have one zero-sized key.
+fn check_zero_sized_len(stride: usize, len: usize) -> Result<(), DynamicError> {
+    if stride == 0 && len > 1 {
+        Err(DynamicError::MalformedMap)
+    } else {
+        Ok(())
+    }
+}
+
+/// Writes `len` items between `open` and `close`, one per line if the alternate flag is set.
+fn fmt_list<F>(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    open: &str,
+    close: &str,
+    padded: bool,
+    len: usize,
+    mut fmt_item: F,
+) -> fmt::Result
+where
+    F: FnMut(&mut fmt::Formatter<'_>, usize, usize) -> fmt::Result,
+{
+    f.write_str(open)?;
+    if len == 0 {
+        return f.write_str(close);
+    }
+    let pretty = f.alternate();
+    for i in 0..len {
+        if pretty {
+            f.write_str("\n")?;
+            fmt_indent(f, depth + 1)?;
+        } else if i > 0 {
+            f.write_str(", ")?;
+        } else if padded {
+            f.write_str(" ")?;
+        }
+        fmt_item(f, i, depth + 1)?;
+        if pretty {
+            f.write_str(",")?;
+        }
+    }
+    if pretty {
+        f.write_str("\n")?;
+        fmt_indent(f, depth)?;
+    } else if padded {
+        f.write_str(" ")?;
+    }
+    f.write_str(close)
+}
+
+fn fmt_indent(f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str("    ")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for DynamicValue<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_value(f, 0)
+    }
+}
+
+impl fmt::Debug for DynamicValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicValue")
+            .field("pos", &self.pos)
+            .field("schema", &self.schema)
+            .finish()
+    }
+}
@@ -202,3 +202,63 @@ const _: () = {
         }
     }
 };
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl Compact for CStr {
+        #[inline]
+        fn compact_dependencies(&self, _: &mut Compactor, _: &mut CompactResolvers) {}
+
+        #[inline]
+        fn compact_pointers(&self, _: usize, _: &mut Compactor, _: &mut CompactResolvers) {}
+    }
+
+    impl Compact for ArchivedCString {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl Transcode for CStr {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            transcoder.bounds_check_range(value.cast(), ptr_meta::metadata(value))
+        }
+    }
+
+    impl Transcode for ArchivedCString {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            RelPtr::<CStr>::transcode(value.cast(), transcoder)
+        }
+    }
+};
@@ -69,10 +69,19 @@ where
 }
 
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ArchivedBitArray<A = [Archived<usize>; 1], O = Lsb0>
 where
-    A: BitViewSized,
     O: BitOrder,
 {
     inner: A,
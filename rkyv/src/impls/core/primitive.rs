@@ -45,12 +45,18 @@ macro_rules! impl_primitive {
     };
     (@multibyte $type:ty) => {
         const _: () = {
-            #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+            #[cfg(not(any(
+                feature = "archive_le",
+                feature = "archive_be",
+                feature = "unaligned"
+            )))]
             type Archived = $type;
-            #[cfg(feature = "archive_le")]
+            #[cfg(all(feature = "archive_le", not(feature = "unaligned")))]
             type Archived = crate::rend::LittleEndian<$type>;
-            #[cfg(feature = "archive_be")]
+            #[cfg(all(feature = "archive_be", not(feature = "unaligned")))]
             type Archived = crate::rend::BigEndian<$type>;
+            #[cfg(feature = "unaligned")]
+            type Archived = crate::unaligned::Unaligned<$type>;
 
             impl Archive for $type {
                 type Archived = Archived;
@@ -106,12 +112,18 @@ macro_rules! impl_atomic {
     };
     (@multibyte $type:ty, $prim:ty) => {
         impl Archive for $type {
-            #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+            #[cfg(not(any(
+                feature = "archive_le",
+                feature = "archive_be",
+                feature = "unaligned"
+            )))]
             type Archived = $prim;
-            #[cfg(feature = "archive_le")]
+            #[cfg(all(feature = "archive_le", not(feature = "unaligned")))]
             type Archived = crate::rend::LittleEndian<$prim>;
-            #[cfg(feature = "archive_be")]
+            #[cfg(all(feature = "archive_be", not(feature = "unaligned")))]
             type Archived = crate::rend::BigEndian<$prim>;
+            #[cfg(feature = "unaligned")]
+            type Archived = crate::unaligned::Unaligned<$prim>;
 
             type Resolver = ();
 
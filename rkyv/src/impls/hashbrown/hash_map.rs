@@ -1,5 +1,8 @@
 use crate::{
-    collections::hash_map::{ArchivedHashMap, HashMapResolver},
+    collections::{
+        hash_index::ArchiveHasher,
+        hash_map::{ArchivedHashMap, HashMapResolver},
+    },
     ser::{ScratchSpace, Serializer},
     Archive, Deserialize, Fallible, Serialize,
 };
@@ -31,15 +34,25 @@ where
 {
     #[inline]
     fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
-        unsafe { ArchivedHashMap::serialize_from_iter(self.iter(), serializer) }
+        unsafe {
+            ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
+                self.iter(),
+                serializer,
+            )
+        }
     }
 }
 
-impl<K: Archive + Hash + Eq, V: Archive, D: Fallible + ?Sized, S: Default + BuildHasher>
-    Deserialize<HashMap<K, V, S>, D> for ArchivedHashMap<K::Archived, V::Archived>
+impl<K, V, D, S, H> Deserialize<HashMap<K, V, S>, D>
+    for ArchivedHashMap<K::Archived, V::Archived, H>
 where
+    K: Archive + Hash + Eq,
     K::Archived: Deserialize<K, D> + Hash + Eq,
+    V: Archive,
     V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+    S: Default + BuildHasher,
+    H: ArchiveHasher,
 {
     #[inline]
     fn deserialize(&self, deserializer: &mut D) -> Result<HashMap<K, V, S>, D::Error> {
@@ -51,8 +64,13 @@ where
     }
 }
 
-impl<K: Hash + Eq + Borrow<AK>, V, AK: Hash + Eq, AV: PartialEq<V>, S: BuildHasher>
-    PartialEq<HashMap<K, V, S>> for ArchivedHashMap<AK, AV>
+impl<K, V, AK, AV, S, H> PartialEq<HashMap<K, V, S>> for ArchivedHashMap<AK, AV, H>
+where
+    K: Hash + Eq + Borrow<AK>,
+    AK: Hash + Eq,
+    AV: PartialEq<V>,
+    S: BuildHasher,
+    H: ArchiveHasher,
 {
     #[inline]
     fn eq(&self, other: &HashMap<K, V, S>) -> bool {
@@ -65,11 +83,15 @@ impl<K: Hash + Eq + Borrow<AK>, V, AK: Hash + Eq, AV: PartialEq<V>, S: BuildHash
     }
 }
 
-impl<K: Hash + Eq + Borrow<AK>, V, AK: Hash + Eq, AV: PartialEq<V>>
-    PartialEq<ArchivedHashMap<AK, AV>> for HashMap<K, V>
+impl<K, V, AK, AV, H> PartialEq<ArchivedHashMap<AK, AV, H>> for HashMap<K, V>
+where
+    K: Hash + Eq + Borrow<AK>,
+    AK: Hash + Eq,
+    AV: PartialEq<V>,
+    H: ArchiveHasher,
 {
     #[inline]
-    fn eq(&self, other: &ArchivedHashMap<AK, AV>) -> bool {
+    fn eq(&self, other: &ArchivedHashMap<AK, AV, H>) -> bool {
         other.eq(self)
     }
 }
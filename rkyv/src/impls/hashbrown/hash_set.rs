@@ -1,5 +1,8 @@
 use crate::{
-    collections::hash_set::{ArchivedHashSet, HashSetResolver},
+    collections::{
+        hash_index::ArchiveHasher,
+        hash_set::{ArchivedHashSet, HashSetResolver},
+    },
     ser::{ScratchSpace, Serializer},
     Archive, Deserialize, Fallible, Serialize,
 };
@@ -30,16 +33,17 @@ where
 {
     #[inline]
     fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
-        unsafe { ArchivedHashSet::serialize_from_iter(self.iter(), serializer) }
+        unsafe { ArchivedHashSet::<K::Archived>::serialize_from_iter(self.iter(), serializer) }
     }
 }
 
-impl<K, D, S> Deserialize<HashSet<K, S>, D> for ArchivedHashSet<K::Archived>
+impl<K, D, S, H> Deserialize<HashSet<K, S>, D> for ArchivedHashSet<K::Archived, H>
 where
     K: Archive + Hash + Eq,
     K::Archived: Deserialize<K, D> + Hash + Eq,
     D: Fallible + ?Sized,
     S: Default + BuildHasher,
+    H: ArchiveHasher,
 {
     #[inline]
     fn deserialize(&self, deserializer: &mut D) -> Result<HashSet<K, S>, D::Error> {
@@ -51,8 +55,12 @@ where
     }
 }
 
-impl<K: Hash + Eq + Borrow<AK>, AK: Hash + Eq, S: BuildHasher> PartialEq<HashSet<K, S>>
-    for ArchivedHashSet<AK>
+impl<K, AK, S, H> PartialEq<HashSet<K, S>> for ArchivedHashSet<AK, H>
+where
+    K: Hash + Eq + Borrow<AK>,
+    AK: Hash + Eq,
+    S: BuildHasher,
+    H: ArchiveHasher,
 {
     #[inline]
     fn eq(&self, other: &HashSet<K, S>) -> bool {
@@ -64,11 +72,15 @@ impl<K: Hash + Eq + Borrow<AK>, AK: Hash + Eq, S: BuildHasher> PartialEq<HashSet
     }
 }
 
-impl<K: Hash + Eq + Borrow<AK>, AK: Hash + Eq, S: BuildHasher> PartialEq<ArchivedHashSet<AK>>
-    for HashSet<K, S>
+impl<K, AK, S, H> PartialEq<ArchivedHashSet<AK, H>> for HashSet<K, S>
+where
+    K: Hash + Eq + Borrow<AK>,
+    AK: Hash + Eq,
+    S: BuildHasher,
+    H: ArchiveHasher,
 {
     #[inline]
-    fn eq(&self, other: &ArchivedHashSet<AK>) -> bool {
+    fn eq(&self, other: &ArchivedHashSet<AK, H>) -> bool {
         other.eq(self)
     }
 }
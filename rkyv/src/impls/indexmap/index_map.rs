@@ -116,4 +116,38 @@ mod tests {
         check_archived_root::<IndexMap<String, i32>>(result.as_ref())
             .expect("failed to validate archived index map");
     }
+
+    #[cfg(all(feature = "transcode", feature = "strict"))]
+    #[test]
+    fn transcode_width_index_map() {
+        use crate::transcode::width::{transcode_width_root, PointerWidth};
+
+        let value = (0..100usize)
+            .rev()
+            .map(|i| (format!("a key that is not inlined {}", i), i))
+            .collect::<IndexMap<String, usize>>();
+
+        let mut serializer = AllocSerializer::<4096>::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let wide = transcode_width_root::<IndexMap<String, usize>>(
+            &original,
+            PointerWidth::ARCHIVED,
+            PointerWidth::Size64,
+        )
+        .unwrap();
+        let restored = transcode_width_root::<IndexMap<String, usize>>(
+            &wide,
+            PointerWidth::Size64,
+            PointerWidth::ARCHIVED,
+        )
+        .unwrap();
+        assert_eq!(restored.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<IndexMap<String, usize>>(restored.as_ref()) };
+        assert_eq!(archived.get("a key that is not inlined 42"), Some(&42));
+        let deserialized: IndexMap<String, usize> = archived.deserialize(&mut Infallible).unwrap();
+        assert!(value.iter().eq(deserialized.iter()));
+    }
 }
@@ -105,4 +105,34 @@ mod tests {
         check_archived_root::<IndexSet<String>>(result.as_ref())
             .expect("failed to validate archived index set");
     }
+
+    #[cfg(feature = "transcode")]
+    #[test]
+    fn transcode_width_index_set() {
+        use crate::transcode::width::{transcode_width_root, PointerWidth};
+
+        let value = (0..100usize).rev().collect::<IndexSet<usize>>();
+
+        let mut serializer = AllocSerializer::<4096>::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let wide = transcode_width_root::<IndexSet<usize>>(
+            &original,
+            PointerWidth::ARCHIVED,
+            PointerWidth::Size64,
+        )
+        .unwrap();
+        let restored = transcode_width_root::<IndexSet<usize>>(
+            &wide,
+            PointerWidth::Size64,
+            PointerWidth::ARCHIVED,
+        )
+        .unwrap();
+        assert_eq!(restored.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<IndexSet<usize>>(restored.as_ref()) };
+        let deserialized: IndexSet<usize> = archived.deserialize(&mut Infallible).unwrap();
+        assert!(value.iter().eq(deserialized.iter()));
+    }
 }
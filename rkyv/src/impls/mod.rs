@@ -3,6 +3,8 @@ mod alloc;
 mod core;
 #[cfg(feature = "rend")]
 mod rend;
+#[cfg(feature = "serde")]
+pub(crate) mod serde;
 #[cfg(feature = "std")]
 mod std;
 
@@ -0,0 +1,279 @@
+#[cfg(feature = "unaligned")]
+use crate::unaligned::{Unaligned, UnalignedPrimitive};
+use crate::{
+    boxed::ArchivedBox,
+    collections::{
+        btree_set::ArchivedBTreeSet, hash_index::ArchiveHasher, ArchivedBTreeMap, ArchivedHashMap,
+        ArchivedHashSet, ArchivedIndexMap, ArchivedIndexSet,
+    },
+    option::ArchivedOption,
+    string::ArchivedString,
+    time::ArchivedDuration,
+    vec::ArchivedVec,
+    ArchivePointee, Archived,
+};
+use ::serde::{
+    ser::{SerializeStruct, SerializeTuple},
+    Serialize, Serializer,
+};
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroU128, NonZeroU16, NonZeroU32,
+    NonZeroU64,
+};
+
+impl Serialize for ArchivedString {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<T: Serialize> Serialize for ArchivedVec<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<T: ArchivePointee + Serialize + ?Sized> Serialize for ArchivedBox<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<T: Serialize> Serialize for ArchivedOption<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ArchivedOption::Some(value) => serializer.serialize_some(value),
+            ArchivedOption::None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<K: Serialize, V: Serialize, H: ArchiveHasher> Serialize for ArchivedHashMap<K, V, H> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<K: Serialize, H: ArchiveHasher> Serialize for ArchivedHashSet<K, H> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for ArchivedBTreeMap<K, V> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<K: Serialize> Serialize for ArchivedBTreeSet<K> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for ArchivedIndexMap<K, V> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<K: Serialize> Serialize for ArchivedIndexSet<K> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl Serialize for ArchivedDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Matches the format of `Duration`
+        let mut state = serializer.serialize_struct("Duration", 2)?;
+        state.serialize_field("secs", &self.as_secs())?;
+        state.serialize_field("nanos", &self.subsec_nanos())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "unaligned")]
+impl<T: UnalignedPrimitive + Serialize> Serialize for Unaligned<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_native().serialize(serializer)
+    }
+}
+
+/// An archived value made of archived primitives that can be serialized with serde.
+///
+/// With `archive_le` and `archive_be`, multibyte primitives are archived as `rend` types, which
+/// can't implement `Serialize`. This trait serializes them (and slices, arrays, vectors, boxes, and
+/// options of them) through their native values instead. Use it with [`serialize_primitive`] or
+/// [`AsPrimitive`].
+pub trait SerializePrimitive {
+    /// Serializes the native value of this archived value with the given serializer.
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// Serializes an archived primitive through its native value.
+///
+/// This can be used with `#[serde(serialize_with = "rkyv::serialize_primitive")]`.
+/// `#[archive(serde)]` applies it to fields of primitive types automatically.
+#[inline]
+pub fn serialize_primitive<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: SerializePrimitive + ?Sized,
+    S: Serializer,
+{
+    value.serialize_primitive(serializer)
+}
+
+/// A wrapper that implements `Serialize` for archived primitives.
+#[repr(transparent)]
+pub struct AsPrimitive<'a, T: ?Sized>(pub &'a T);
+
+impl<T: SerializePrimitive + ?Sized> Serialize for AsPrimitive<'_, T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_primitive(serializer)
+    }
+}
+
+macro_rules! impl_serialize_primitive {
+    ($($prim:ty),* $(,)?) => {
+        $(
+            impl SerializePrimitive for Archived<$prim> {
+                #[inline]
+                fn serialize_primitive<S: Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    from_archived!(*self).serialize(serializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_primitive!(
+    i16,
+    i32,
+    i64,
+    i128,
+    u16,
+    u32,
+    u64,
+    u128,
+    f32,
+    f64,
+    char,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+);
+
+impl<T: SerializePrimitive> SerializePrimitive for [T] {
+    #[inline]
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(AsPrimitive))
+    }
+}
+
+impl<T: SerializePrimitive, const N: usize> SerializePrimitive for [T; N] {
+    #[inline]
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Matches the format of arrays
+        let mut state = serializer.serialize_tuple(N)?;
+        for value in self.iter() {
+            state.serialize_element(&AsPrimitive(value))?;
+        }
+        state.end()
+    }
+}
+
+impl<T: SerializePrimitive> SerializePrimitive for ArchivedVec<T> {
+    #[inline]
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize_primitive(serializer)
+    }
+}
+
+impl<T: ArchivePointee + SerializePrimitive + ?Sized> SerializePrimitive for ArchivedBox<T> {
+    #[inline]
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize_primitive(serializer)
+    }
+}
+
+impl<T: SerializePrimitive> SerializePrimitive for ArchivedOption<T> {
+    #[inline]
+    fn serialize_primitive<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ArchivedOption::Some(value) => serializer.serialize_some(&AsPrimitive(value)),
+            ArchivedOption::None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use crate::net::{
+        ArchivedIpAddr, ArchivedIpv4Addr, ArchivedIpv6Addr, ArchivedSocketAddr,
+        ArchivedSocketAddrV4, ArchivedSocketAddrV6,
+    };
+
+    impl Serialize for ArchivedIpv4Addr {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_ipv4().serialize(serializer)
+        }
+    }
+
+    impl Serialize for ArchivedIpv6Addr {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_ipv6().serialize(serializer)
+        }
+    }
+
+    impl Serialize for ArchivedIpAddr {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_ipaddr().serialize(serializer)
+        }
+    }
+
+    impl Serialize for ArchivedSocketAddrV4 {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_socket_addr_v4().serialize(serializer)
+        }
+    }
+
+    impl Serialize for ArchivedSocketAddrV6 {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_socket_addr_v6().serialize(serializer)
+        }
+    }
+
+    impl Serialize for ArchivedSocketAddr {
+        #[inline]
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_socket_addr().serialize(serializer)
+        }
+    }
+};
@@ -27,6 +27,38 @@ impl<D: Fallible + ?Sized> Deserialize<Uuid, D> for Uuid {
     }
 }
 
+#[cfg(feature = "compact")]
+impl crate::compact::Compact for Uuid {
+    #[inline]
+    fn compact_dependencies(
+        &self,
+        _: &mut crate::compact::Compactor,
+        _: &mut crate::compact::CompactResolvers,
+    ) {
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        _: usize,
+        _: &mut crate::compact::Compactor,
+        _: &mut crate::compact::CompactResolvers,
+    ) {
+    }
+}
+
+// Uuid is stored as bytes, so it is the same in all endiannesses
+#[cfg(feature = "transcode")]
+impl crate::transcode::Transcode for Uuid {
+    #[inline]
+    unsafe fn transcode(
+        _: *mut Self,
+        _: &mut crate::transcode::Transcoder<'_>,
+    ) -> Result<(), crate::transcode::TranscodeError> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod rkyv_tests {
     use crate::{
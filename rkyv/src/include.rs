@@ -0,0 +1,336 @@
+//! Embedding archives in binaries at compile time.
+//!
+//! Archives are written by a build script with [`write_included`] and embedded with
+//! [`include_archive!`](crate::include_archive). The written file contains the archive followed by
+//! an 8-byte little-endian [layout fingerprint](layout_fingerprint), which is checked before the
+//! embedded archive is accessed. The archive is also validated the first time it is accessed,
+//! since the fingerprint can't tell apart types with the same size and alignment.
+//!
+//! The build script must depend on rkyv with the same format features (`size_*`, `archive_le`,
+//! `archive_be`, `strict`, and `unaligned`) as the crate including the archive. If they differ,
+//! accessing the archive will fail instead of reading garbage.
+
+#[cfg(feature = "alloc")]
+use crate::{ser::serializers::AllocSerializer, AlignedVec, Fallible, Serialize};
+use crate::{AlignedBytes, Archive, FixedUsize};
+use core::{fmt, marker::PhantomData, mem};
+
+/// The number of bytes used to store the layout fingerprint after an included archive.
+pub const FINGERPRINT_LEN: usize = mem::size_of::<u64>();
+
+/// Returns a fingerprint of the archived layout of `T`.
+///
+/// The fingerprint covers the size and alignment of the archived type along with the format
+/// features that rkyv was built with. It will change if the archived type is changed in a way that
+/// alters its size or alignment, or if the archive was written with different format features.
+/// Changes that keep the size and alignment the same (e.g. reordering fields of the same type) are
+/// not detected.
+pub fn layout_fingerprint<T: Archive>() -> u64 {
+    let endianness = if cfg!(feature = "archive_le") {
+        b'l'
+    } else if cfg!(feature = "archive_be") {
+        b'b'
+    } else if cfg!(target_endian = "little") {
+        b'L'
+    } else {
+        b'B'
+    };
+
+    let mut bytes = [0u8; 20];
+    bytes[0..8].copy_from_slice(&(mem::size_of::<T::Archived>() as u64).to_le_bytes());
+    bytes[8..16].copy_from_slice(&(mem::align_of::<T::Archived>() as u64).to_le_bytes());
+    bytes[16] = mem::size_of::<FixedUsize>() as u8;
+    bytes[17] = endianness;
+    bytes[18] = cfg!(feature = "unaligned") as u8;
+    bytes[19] = cfg!(feature = "strict") as u8;
+
+    seahash::hash(&bytes)
+}
+
+/// Serializes the given value and appends its layout fingerprint so it can be embedded with
+/// [`include_archive!`](crate::include_archive).
+///
+/// The const generic parameter `N` specifies the number of bytes to pre-allocate as scratch space,
+/// like [`to_bytes`](crate::to_bytes).
+#[cfg(feature = "alloc")]
+pub fn to_included_bytes<T, const N: usize>(
+    value: &T,
+) -> Result<AlignedVec, <AllocSerializer<N> as Fallible>::Error>
+where
+    T: Serialize<AllocSerializer<N>>,
+{
+    let mut bytes = crate::to_bytes::<T, N>(value)?;
+    bytes.extend_from_slice(&layout_fingerprint::<T>().to_le_bytes());
+    Ok(bytes)
+}
+
+/// Serializes the given value and writes it to a file that can be embedded with
+/// [`include_archive!`](crate::include_archive).
+///
+/// This is intended to be called from a build script, usually with a path inside of `OUT_DIR`.
+///
+/// # Examples
+/// ```
+/// use rkyv::{include::write_included, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// struct Table {
+///     values: Vec<u32>,
+/// }
+///
+/// let path = std::env::temp_dir().join("rkyv_write_included_example.bin");
+/// let table = Table { values: vec![1, 2, 3] };
+/// write_included::<_, 256, _>(&table, &path).expect("failed to write table");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn write_included<T, const N: usize, P>(value: &T, path: P) -> std::io::Result<()>
+where
+    T: Serialize<AllocSerializer<N>>,
+    P: AsRef<std::path::Path>,
+{
+    // `io::Error::other` needs a newer compiler than the rest of the crate
+    #[allow(clippy::io_other_error)]
+    let bytes = to_included_bytes::<T, N>(value)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    std::fs::write(path, &bytes)
+}
+
+/// Errors that can occur when accessing an included archive.
+#[derive(Debug, PartialEq, Eq)]
+pub enum IncludeError {
+    /// The included bytes were too short to contain the archived type and a layout fingerprint
+    TooShort {
+        /// The length of the included bytes
+        len: usize,
+        /// The minimum length of the included bytes
+        min_len: usize,
+    },
+    /// The layout fingerprint did not match the fingerprint of the archived type
+    FingerprintMismatch {
+        /// The fingerprint of the archived type
+        expected: u64,
+        /// The fingerprint stored with the included archive
+        found: u64,
+    },
+    /// The archived type requires a higher alignment than included archives have
+    Underaligned {
+        /// The alignment required by the archived type
+        expected_align: usize,
+        /// The alignment of included archives
+        actual_align: usize,
+    },
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::TooShort { len, min_len } => write!(
+                f,
+                "included archive too short: need at least {} bytes but have {} bytes",
+                min_len, len
+            ),
+            IncludeError::FingerprintMismatch { expected, found } => write!(
+                f,
+                "included archive layout fingerprint mismatch: expected {:#018x}, found {:#018x}",
+                expected, found
+            ),
+            IncludeError::Underaligned {
+                expected_align,
+                actual_align,
+            } => write!(
+                f,
+                "included archive underaligned: need alignment {} but have alignment {}",
+                expected_align, actual_align
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IncludeError {}
+
+/// An archive embedded in a binary by [`include_archive!`](crate::include_archive).
+///
+/// `N` is the length of the included bytes, including the layout fingerprint.
+pub struct IncludedArchive<T: Archive, const N: usize> {
+    bytes: AlignedBytes<N>,
+    #[cfg(feature = "validation")]
+    checked: core::sync::atomic::AtomicBool,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Archive, const N: usize> IncludedArchive<T, N> {
+    /// Creates a new included archive from the given bytes.
+    #[inline]
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self {
+            bytes: AlignedBytes(bytes),
+            #[cfg(feature = "validation")]
+            checked: core::sync::atomic::AtomicBool::new(false),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the archive bytes without the layout fingerprint.
+    #[inline]
+    pub fn archive_bytes(&self) -> &[u8] {
+        &self.bytes.0[..N.saturating_sub(FINGERPRINT_LEN)]
+    }
+
+    /// Checks that the included bytes end with the layout fingerprint of `T` and that they are
+    /// aligned enough for `T`.
+    ///
+    /// This does not check the archive itself, so a matching fingerprint does not make the archive
+    /// safe to access.
+    pub fn check_fingerprint(&self) -> Result<(), IncludeError> {
+        let min_len = mem::size_of::<T::Archived>() + FINGERPRINT_LEN;
+        if N < min_len {
+            return Err(IncludeError::TooShort { len: N, min_len });
+        }
+
+        let mut found = [0u8; FINGERPRINT_LEN];
+        found.copy_from_slice(&self.bytes.0[N - FINGERPRINT_LEN..]);
+        let found = u64::from_le_bytes(found);
+        let expected = layout_fingerprint::<T>();
+        if found != expected {
+            return Err(IncludeError::FingerprintMismatch { expected, found });
+        }
+
+        let actual_align = mem::align_of::<AlignedBytes<N>>();
+        let expected_align = mem::align_of::<T::Archived>();
+        if expected_align > actual_align {
+            return Err(IncludeError::Underaligned {
+                expected_align,
+                actual_align,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the layout fingerprint and returns the archived value without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The included bytes must have been produced by [`to_included_bytes`] or [`write_included`]
+    /// for a `T`.
+    pub unsafe fn try_get_unchecked(&'static self) -> Result<&'static T::Archived, IncludeError> {
+        self.check_fingerprint()?;
+        // SAFETY: The caller has guaranteed that the archive was written for a `T`, and the
+        // fingerprint check guarantees that its root is stored at the end of the archive bytes.
+        Ok(crate::archived_root::<T>(self.archive_bytes()))
+    }
+
+    /// Checks the layout fingerprint and returns the archived value without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The included bytes must have been produced by [`to_included_bytes`] or [`write_included`]
+    /// for a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout fingerprint does not match or the archived type is too aligned.
+    #[inline]
+    pub unsafe fn get_unchecked(&'static self) -> &'static T::Archived {
+        match self.try_get_unchecked() {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+const _: () = {
+    use crate::validation::{validators::DefaultValidator, CheckTypeError};
+    use bytecheck::CheckBytes;
+    use core::sync::atomic::Ordering;
+
+    impl<T: Archive, const N: usize> IncludedArchive<T, N>
+    where
+        T::Archived: CheckBytes<DefaultValidator<'static>>,
+    {
+        /// Checks the layout fingerprint and validates the archive, then returns the archived
+        /// value.
+        ///
+        /// The archive is only validated the first time this succeeds. Later calls return the
+        /// archived value without validating it again.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the layout fingerprint does not match or the archive is invalid.
+        #[inline]
+        pub fn get(&'static self) -> &'static T::Archived {
+            if let Err(e) = self.check_fingerprint() {
+                panic!("{}", e);
+            }
+            match self.check_bytes() {
+                Ok(value) => value,
+                Err(e) => panic!("failed to check included archive: {}", e),
+            }
+        }
+
+        /// Validates the archive and returns the archived value.
+        ///
+        /// The archive is only validated the first time this succeeds. Later calls return the
+        /// archived value without validating it again. The layout fingerprint is not checked.
+        pub fn check_bytes(
+            &'static self,
+        ) -> Result<&'static T::Archived, CheckTypeError<T::Archived, DefaultValidator<'static>>>
+        {
+            let bytes = self.archive_bytes();
+            if self.checked.load(Ordering::Acquire) {
+                // SAFETY: The archive was already validated.
+                Ok(unsafe { crate::archived_root::<T>(bytes) })
+            } else {
+                let value = crate::check_archived_root::<T>(bytes)?;
+                self.checked.store(true, Ordering::Release);
+                Ok(value)
+            }
+        }
+    }
+};
+
+/// Embeds an archive written by [`write_included`](crate::include::write_included) and returns a
+/// `&'static` reference to its archived value.
+///
+/// The bytes are placed in an aligned `static`. The [layout
+/// fingerprint](crate::include::layout_fingerprint) is checked each time the archive is accessed,
+/// and the archive is validated the first time it is accessed. This form requires the `validation`
+/// feature and panics if either check fails.
+///
+/// `include_archive!(unsafe Type, "path")` only checks the layout fingerprint. The fingerprint
+/// can't tell apart types with the same archived size and alignment, so the caller must guarantee
+/// that the archive was written for a `Type`.
+///
+/// The path is resolved like [`include_bytes!`].
+///
+/// # Examples
+///
+/// In `build.rs`:
+/// ```ignore
+/// let path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("table.bin");
+/// rkyv::include::write_included::<_, 256, _>(&make_table(), path).unwrap();
+/// ```
+///
+/// In the crate:
+/// ```ignore
+/// fn table() -> &'static rkyv::Archived<Table> {
+///     rkyv::include_archive!(Table, concat!(env!("OUT_DIR"), "/table.bin"))
+/// }
+/// ```
+#[macro_export]
+macro_rules! include_archive {
+    (@static $ty:ty, $path:expr) => {{
+        static ARCHIVE: $crate::include::IncludedArchive<$ty, { include_bytes!($path).len() }> =
+            $crate::include::IncludedArchive::new(*include_bytes!($path));
+        &ARCHIVE
+    }};
+    (unsafe $ty:ty, $path:expr $(,)?) => {
+        unsafe { $crate::include_archive!(@static $ty, $path).get_unchecked() }
+    };
+    ($ty:ty, $path:expr $(,)?) => {
+        $crate::include_archive!(@static $ty, $path).get()
+    };
+}
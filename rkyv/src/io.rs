@@ -0,0 +1,473 @@
+//! Length-prefixed framing for sending archives over streams.
+//!
+//! Each frame is a 4-byte little-endian length followed by that many bytes of archive. Frames are
+//! read into an aligned buffer, so their roots can be accessed in place once they are validated.
+//!
+//! With the `tokio-util` feature, [`FrameCodec`] provides the same framing as a `tokio-util` codec.
+
+use crate::{
+    ser::{
+        serializers::{AlignedSerializer, AllocSerializer, FallbackScratch, SharedSerializeMap},
+        Serializer,
+    },
+    AlignedVec, Fallible, Serialize,
+};
+use core::{convert::Infallible, fmt, mem};
+use std::io::{self, ErrorKind, Read, Write};
+
+/// The number of bytes used to store the length of a frame.
+pub const FRAME_HEADER_LEN: usize = mem::size_of::<u32>();
+
+/// The default maximum length of a frame in bytes.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Errors that can occur while writing frames.
+#[derive(Debug)]
+pub enum FrameWriteError<E = Infallible> {
+    /// An I/O error occurred
+    Io(io::Error),
+    /// The frame was longer than the maximum frame length
+    TooLarge {
+        /// The length of the frame
+        len: usize,
+        /// The maximum frame length
+        max_len: usize,
+    },
+    /// The value failed to serialize
+    Serialize(E),
+}
+
+impl<E> From<io::Error> for FrameWriteError<E> {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        FrameWriteError::Io(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for FrameWriteError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameWriteError::Io(e) => write!(f, "frame I/O error: {}", e),
+            FrameWriteError::TooLarge { len, max_len } => write!(
+                f,
+                "frame too large: {} bytes is more than the maximum of {} bytes",
+                len, max_len
+            ),
+            FrameWriteError::Serialize(e) => write!(f, "frame serialize error: {}", e),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for FrameWriteError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameWriteError::Io(e) => Some(e as &dyn std::error::Error),
+            FrameWriteError::TooLarge { .. } => None,
+            FrameWriteError::Serialize(e) => Some(e as &dyn std::error::Error),
+        }
+    }
+}
+
+/// Errors that can occur while reading frames.
+#[derive(Debug)]
+pub enum FrameReadError<E = Infallible> {
+    /// An I/O error occurred
+    Io(io::Error),
+    /// The frame was longer than the maximum frame length
+    TooLarge {
+        /// The length of the frame
+        len: usize,
+        /// The maximum frame length
+        max_len: usize,
+    },
+    /// The frame failed validation
+    Check(E),
+}
+
+impl<E> From<io::Error> for FrameReadError<E> {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        FrameReadError::Io(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for FrameReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameReadError::Io(e) => write!(f, "frame I/O error: {}", e),
+            FrameReadError::TooLarge { len, max_len } => write!(
+                f,
+                "frame too large: {} bytes is more than the maximum of {} bytes",
+                len, max_len
+            ),
+            FrameReadError::Check(e) => write!(f, "frame check error: {}", e),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for FrameReadError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameReadError::Io(e) => Some(e as &dyn std::error::Error),
+            FrameReadError::TooLarge { .. } => None,
+            FrameReadError::Check(e) => Some(e as &dyn std::error::Error),
+        }
+    }
+}
+
+/// The error type returned when reading a validated root from a frame.
+#[cfg(feature = "validation")]
+pub type FrameCheckError<'a, T> = FrameReadError<
+    crate::validation::CheckTypeError<
+        <T as crate::Archive>::Archived,
+        crate::validation::validators::DefaultValidator<'a>,
+    >,
+>;
+
+#[inline]
+fn check_len<E>(len: usize, max_len: usize) -> Result<u32, FrameWriteError<E>> {
+    match u32::try_from(len) {
+        Ok(header) if len <= max_len => Ok(header),
+        _ => Err(FrameWriteError::TooLarge { len, max_len }),
+    }
+}
+
+/// Writes length-prefixed frames to a [`Write`].
+///
+/// The writer reuses a single buffer to serialize values into.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     io::{FrameReader, FrameWriter},
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(check_bytes)]
+/// struct Event {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let mut writer = FrameWriter::new(Vec::new());
+/// writer.write_value::<_, 256>(&Event { id: 1, name: "start".to_string() }).unwrap();
+/// writer.write_value::<_, 256>(&Event { id: 2, name: "stop".to_string() }).unwrap();
+/// let stream = writer.into_inner();
+///
+/// let mut reader = FrameReader::new(stream.as_slice());
+/// assert_eq!(reader.read_root::<Event>().unwrap().unwrap().name, "start");
+/// assert_eq!(reader.read_root::<Event>().unwrap().unwrap().name, "stop");
+/// assert!(reader.read_root::<Event>().unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    inner: W,
+    buffer: AlignedVec,
+    max_frame_len: usize,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Creates a new frame writer with the default maximum frame length.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self::with_max_frame_len(inner, DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a new frame writer with the given maximum frame length.
+    #[inline]
+    pub fn with_max_frame_len(inner: W, max_frame_len: usize) -> Self {
+        Self {
+            inner,
+            buffer: AlignedVec::new(),
+            max_frame_len,
+        }
+    }
+
+    /// Returns the maximum frame length.
+    #[inline]
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Writes the given archive bytes as a frame.
+    pub fn write_frame(&mut self, bytes: &[u8]) -> Result<(), FrameWriteError> {
+        let header = check_len(bytes.len(), self.max_frame_len)?;
+        self.inner.write_all(&header.to_le_bytes())?;
+        self.inner.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Serializes the given value and writes it as a frame.
+    ///
+    /// The const generic parameter `N` specifies the number of bytes to pre-allocate as scratch
+    /// space, like [`to_bytes`](crate::to_bytes).
+    pub fn write_value<T, const N: usize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), FrameWriteError<<AllocSerializer<N> as Fallible>::Error>>
+    where
+        T: Serialize<AllocSerializer<N>>,
+    {
+        let mut buffer = mem::take(&mut self.buffer);
+        buffer.clear();
+        let mut serializer = AllocSerializer::<N>::new(
+            AlignedSerializer::new(buffer),
+            FallbackScratch::default(),
+            SharedSerializeMap::default(),
+        );
+        let result = serializer.serialize_value(value);
+        self.buffer = serializer.into_serializer().into_inner();
+        result.map_err(FrameWriteError::Serialize)?;
+
+        let header = check_len(self.buffer.len(), self.max_frame_len)?;
+        self.inner.write_all(&header.to_le_bytes())?;
+        self.inner.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    #[inline]
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the frame writer and returns the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads length-prefixed frames from a [`Read`].
+///
+/// The reader reuses a single aligned buffer for every frame and rejects frames that are longer
+/// than its maximum frame length before reading them.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+    buffer: AlignedVec,
+    max_frame_len: usize,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Creates a new frame reader with the default maximum frame length.
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self::with_max_frame_len(inner, DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a new frame reader with the given maximum frame length.
+    #[inline]
+    pub fn with_max_frame_len(inner: R, max_frame_len: usize) -> Self {
+        Self {
+            inner,
+            buffer: AlignedVec::new(),
+            max_frame_len,
+        }
+    }
+
+    /// Returns the maximum frame length.
+    #[inline]
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Reads the next frame and returns its bytes, or `None` if the stream ended between frames.
+    ///
+    /// The returned bytes are aligned to [`AlignedVec::ALIGNMENT`].
+    pub fn read_frame(&mut self) -> Result<Option<&[u8]>, FrameReadError> {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        let mut filled = 0;
+        while filled < FRAME_HEADER_LEN {
+            match self.inner.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let len = u32::from_le_bytes(header) as usize;
+        if len > self.max_frame_len {
+            return Err(FrameReadError::TooLarge {
+                len,
+                max_len: self.max_frame_len,
+            });
+        }
+
+        self.buffer.clear();
+        self.buffer.resize(len, 0);
+        self.inner.read_exact(&mut self.buffer)?;
+        Ok(Some(&self.buffer))
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the frame reader and returns the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(feature = "validation")]
+const _: () = {
+    use crate::{validation::validators::DefaultValidator, Archive};
+    use bytecheck::CheckBytes;
+
+    impl<R: Read> FrameReader<R> {
+        /// Reads the next frame and returns its validated root, or `None` if the stream ended
+        /// between frames.
+        pub fn read_root<'a, T: Archive>(
+            &'a mut self,
+        ) -> Result<Option<&'a T::Archived>, FrameCheckError<'a, T>>
+        where
+            T::Archived: CheckBytes<DefaultValidator<'a>>,
+        {
+            let bytes = match self.read_frame() {
+                Ok(Some(bytes)) => bytes,
+                Ok(None) => return Ok(None),
+                Err(FrameReadError::Io(e)) => return Err(FrameReadError::Io(e)),
+                Err(FrameReadError::TooLarge { len, max_len }) => {
+                    return Err(FrameReadError::TooLarge { len, max_len })
+                }
+            };
+            crate::check_archived_root::<T>(bytes)
+                .map(Some)
+                .map_err(FrameReadError::Check)
+        }
+    }
+};
+
+/// A `tokio-util` codec for length-prefixed frames.
+///
+/// Decoded frames are copied into an [`AlignedVec`] so their roots can be validated and accessed
+/// with [`check_archived_root`](crate::check_archived_root). Archive bytes are encoded as frames.
+///
+/// # Examples
+/// ```
+/// use bytes::BytesMut;
+/// use rkyv::{check_archived_root, io::FrameCodec, to_bytes};
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = FrameCodec::new();
+/// let mut stream = BytesMut::new();
+/// let bytes = to_bytes::<_, 256>(&vec![1u32, 2, 3]).unwrap();
+/// codec.encode(bytes.as_slice(), &mut stream).unwrap();
+///
+/// // Incomplete frames are left in the buffer
+/// let mut partial = stream.split_to(6);
+/// assert!(codec.decode(&mut partial).unwrap().is_none());
+/// partial.unsplit(stream);
+///
+/// let frame = codec.decode(&mut partial).unwrap().unwrap();
+/// let archived = check_archived_root::<Vec<u32>>(&frame).unwrap();
+/// assert_eq!(archived.as_slice(), &[1, 2, 3]);
+/// assert!(partial.is_empty());
+/// ```
+#[cfg(feature = "tokio-util")]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameCodec {
+    max_frame_len: usize,
+}
+
+#[cfg(feature = "tokio-util")]
+const _: () = {
+    use bytes::{Buf, BufMut, BytesMut};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    impl FrameCodec {
+        /// Creates a new frame codec with the default maximum frame length.
+        #[inline]
+        pub fn new() -> Self {
+            Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+        }
+
+        /// Creates a new frame codec with the given maximum frame length.
+        #[inline]
+        pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+            Self { max_frame_len }
+        }
+
+        /// Returns the maximum frame length.
+        #[inline]
+        pub fn max_frame_len(&self) -> usize {
+            self.max_frame_len
+        }
+    }
+
+    impl Default for FrameCodec {
+        #[inline]
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Decoder for FrameCodec {
+        type Item = AlignedVec;
+        type Error = FrameReadError;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            if src.len() < FRAME_HEADER_LEN {
+                return Ok(None);
+            }
+
+            let mut header = [0u8; FRAME_HEADER_LEN];
+            header.copy_from_slice(&src[..FRAME_HEADER_LEN]);
+            let len = u32::from_le_bytes(header) as usize;
+            if len > self.max_frame_len {
+                return Err(FrameReadError::TooLarge {
+                    len,
+                    max_len: self.max_frame_len,
+                });
+            }
+
+            if src.len() < FRAME_HEADER_LEN + len {
+                src.reserve(FRAME_HEADER_LEN + len - src.len());
+                return Ok(None);
+            }
+
+            src.advance(FRAME_HEADER_LEN);
+            let mut frame = AlignedVec::with_capacity(len);
+            frame.extend_from_slice(&src[..len]);
+            src.advance(len);
+            Ok(Some(frame))
+        }
+    }
+
+    impl<'a> Encoder<&'a [u8]> for FrameCodec {
+        type Error = FrameWriteError;
+
+        fn encode(&mut self, item: &'a [u8], dst: &mut BytesMut) -> Result<(), Self::Error> {
+            let header = check_len(item.len(), self.max_frame_len)?;
+            dst.reserve(FRAME_HEADER_LEN + item.len());
+            dst.put_u32_le(header);
+            dst.put_slice(item);
+            Ok(())
+        }
+    }
+};
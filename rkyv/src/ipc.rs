@@ -0,0 +1,610 @@
+//! Ring buffers for passing archived messages between processes through shared memory.
+//!
+//! A [`Ring`] is created in a memory region that is mapped into several processes (e.g. a memfd
+//! or a file mapping) and opened by the other processes that use it. Producers reserve a slot and
+//! serialize a message directly into it, and consumers receive a validated view of the archived
+//! message in place.
+//!
+//! The ring is a bounded multi-producer multi-consumer queue, so it can be used for SPSC and MPSC
+//! channels alike. Every slot has a sequence number which tracks whose turn it is to use the slot:
+//!
+//! - A producer claims a free slot, writes its message, and then publishes the slot by storing its
+//!   sequence number with release ordering.
+//! - A consumer claims a published slot after loading its sequence number with acquire ordering,
+//!   so the message bytes are fully visible to it. The slot is reclaimed for producers when the
+//!   received [`Message`] is dropped.
+//!
+//! Messages from other processes are untrusted, so they are always validated before they are
+//! returned. If a process exits while it holds a slot, the ring stops at that slot.
+
+use crate::{
+    ser::{
+        serializers::{
+            AllocScratch, BufferSerializer, CompositeSerializer, FallbackScratch, HeapScratch,
+            SharedSerializeMap,
+        },
+        Serializer,
+    },
+    validation::{validators::DefaultValidator, CheckTypeError},
+    Archive, Fallible, Serialize,
+};
+use bytecheck::CheckBytes;
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    ptr::NonNull,
+    slice,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"rkyvring");
+// The length of a slot that was reserved but never committed
+const ABANDONED: u64 = u64::MAX;
+
+/// The alignment that the memory region of a ring must have.
+pub const REGION_ALIGN: usize = 64;
+
+/// The alignment of the message bytes in each slot.
+pub const SLOT_ALIGN: usize = mem::size_of::<SlotHeader>();
+
+#[repr(C, align(64))]
+struct CachePadded<T>(T);
+
+#[repr(C)]
+struct RingHeader {
+    magic: CachePadded<AtomicU64>,
+    slot_size: AtomicU64,
+    slot_count: AtomicU64,
+    enqueue: CachePadded<AtomicU64>,
+    dequeue: CachePadded<AtomicU64>,
+}
+
+#[repr(C)]
+struct SlotHeader {
+    sequence: AtomicU64,
+    len: AtomicU64,
+}
+
+/// A serializer that writes directly into a ring slot.
+///
+/// `SlotSerializer` takes the amount of scratch space to allocate before spilling allocations over
+/// into heap memory, like [`AllocSerializer`](crate::ser::serializers::AllocSerializer).
+pub type SlotSerializer<'a, const N: usize> = CompositeSerializer<
+    BufferSerializer<&'a mut [u8]>,
+    FallbackScratch<HeapScratch<N>, AllocScratch>,
+    SharedSerializeMap,
+>;
+
+/// The error type returned by a [`SlotSerializer`].
+pub type SlotSerializerError<'a, const N: usize> = <SlotSerializer<'a, N> as Fallible>::Error;
+
+/// The error type returned when a received message fails validation.
+pub type RecvError<'a, T> = CheckTypeError<<T as Archive>::Archived, DefaultValidator<'a>>;
+
+/// Errors that can occur when creating or opening a ring.
+#[derive(Debug)]
+pub enum RingError {
+    /// The memory region was not aligned to [`REGION_ALIGN`]
+    Unaligned {
+        /// The pointer to the memory region
+        ptr: *const u8,
+    },
+    /// The slot size was zero
+    InvalidSlotSize,
+    /// The slot count was not a nonzero power of two
+    InvalidSlotCount {
+        /// The slot count
+        slot_count: usize,
+    },
+    /// The memory region was too small to hold the ring
+    RegionTooSmall {
+        /// The length of the memory region
+        len: usize,
+        /// The length required by the ring, or `None` if it overflowed
+        required_len: Option<usize>,
+    },
+    /// The memory region did not contain a ring
+    NotARing {
+        /// The value found where the ring header should be
+        magic: u64,
+    },
+}
+
+impl fmt::Display for RingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RingError::Unaligned { ptr } => write!(
+                f,
+                "ring region {:p} is not aligned to {} bytes",
+                ptr, REGION_ALIGN
+            ),
+            RingError::InvalidSlotSize => write!(f, "ring slot size must be nonzero"),
+            RingError::InvalidSlotCount { slot_count } => write!(
+                f,
+                "ring slot count must be a nonzero power of two, but was {}",
+                slot_count
+            ),
+            RingError::RegionTooSmall {
+                len,
+                required_len: Some(required_len),
+            } => write!(
+                f,
+                "ring region too small: need {} bytes but have {} bytes",
+                required_len, len
+            ),
+            RingError::RegionTooSmall {
+                len,
+                required_len: None,
+            } => write!(f, "ring region too small: have {} bytes", len),
+            RingError::NotARing { magic } => {
+                write!(
+                    f,
+                    "region does not contain a ring: found magic {:#018x}",
+                    magic
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RingError {}
+
+/// Errors that can occur when sending a message.
+#[derive(Debug)]
+pub enum SendError<E> {
+    /// All of the slots in the ring are in use
+    Full,
+    /// The message failed to serialize
+    Serialize(E),
+}
+
+impl<E: fmt::Display> fmt::Display for SendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Full => write!(f, "ring is full"),
+            SendError::Serialize(e) => write!(f, "failed to serialize message: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for SendError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SendError::Full => None,
+            SendError::Serialize(e) => Some(e as &dyn std::error::Error),
+        }
+    }
+}
+
+/// A ring buffer of archived messages in shared memory.
+///
+/// # Examples
+/// ```
+/// use core::ptr::NonNull;
+/// use rkyv::{ipc::Ring, AlignedVec, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(check_bytes)]
+/// struct Event {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// // This would usually be a shared memory mapping instead
+/// let len = Ring::required_len(256, 4).unwrap();
+/// let mut region = AlignedVec::<64>::with_capacity_aligned(len);
+/// region.resize(len, 0);
+/// let ptr = NonNull::new(region.as_mut_ptr()).unwrap();
+///
+/// let producer = unsafe { Ring::create(ptr, len, 256, 4).unwrap() };
+/// let consumer = unsafe { Ring::open(ptr, len).unwrap() };
+///
+/// let event = Event { id: 7, name: "spawn".to_string() };
+/// producer.try_send::<_, 0>(&event).unwrap();
+///
+/// let message = consumer.try_recv::<Event>().unwrap().unwrap();
+/// assert_eq!(message.id, 7);
+/// assert_eq!(message.name, "spawn");
+/// ```
+pub struct Ring<'a> {
+    header: NonNull<RingHeader>,
+    slots: NonNull<u8>,
+    slot_size: usize,
+    stride: usize,
+    mask: u64,
+    _phantom: PhantomData<&'a RingHeader>,
+}
+
+// SAFETY: All shared state in the ring is accessed through atomics or owned by a single slot
+// reservation at a time.
+unsafe impl Send for Ring<'_> {}
+// SAFETY: See above.
+unsafe impl Sync for Ring<'_> {}
+
+impl<'a> Ring<'a> {
+    #[inline]
+    fn stride(slot_size: usize) -> Option<usize> {
+        let padded = slot_size.checked_add(SLOT_ALIGN - 1)? & !(SLOT_ALIGN - 1);
+        padded.checked_add(mem::size_of::<SlotHeader>())
+    }
+
+    /// Returns the length of the memory region needed for a ring with the given slot size and
+    /// slot count, or `None` if it overflows.
+    #[inline]
+    pub fn required_len(slot_size: usize, slot_count: usize) -> Option<usize> {
+        Self::stride(slot_size)?
+            .checked_mul(slot_count)?
+            .checked_add(mem::size_of::<RingHeader>())
+    }
+
+    fn check_region(
+        ptr: NonNull<u8>,
+        len: usize,
+        slot_size: usize,
+        slot_count: usize,
+    ) -> Result<(), RingError> {
+        if ptr.as_ptr() as usize & (REGION_ALIGN - 1) != 0 {
+            return Err(RingError::Unaligned { ptr: ptr.as_ptr() });
+        }
+        if slot_size == 0 {
+            return Err(RingError::InvalidSlotSize);
+        }
+        if !slot_count.is_power_of_two() {
+            return Err(RingError::InvalidSlotCount { slot_count });
+        }
+        match Self::required_len(slot_size, slot_count) {
+            Some(required_len) if len >= required_len => Ok(()),
+            required_len => Err(RingError::RegionTooSmall { len, required_len }),
+        }
+    }
+
+    #[inline]
+    unsafe fn from_parts(ptr: NonNull<u8>, slot_size: usize, slot_count: usize) -> Self {
+        Self {
+            header: ptr.cast(),
+            slots: NonNull::new_unchecked(ptr.as_ptr().add(mem::size_of::<RingHeader>())),
+            slot_size,
+            stride: Self::stride(slot_size).unwrap(),
+            mask: slot_count as u64 - 1,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new ring in the given memory region.
+    ///
+    /// The region must be at least [`required_len`](Ring::required_len) bytes long and aligned to
+    /// [`REGION_ALIGN`]. Each slot can hold a message of up to `slot_size` bytes, and `slot_count`
+    /// must be a power of two.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be valid for reads and writes of `len` bytes for the lifetime `'a`.
+    /// - The region must only be accessed through rings for the lifetime `'a`.
+    /// - No other process may use the region while the ring is being created.
+    pub unsafe fn create(
+        ptr: NonNull<u8>,
+        len: usize,
+        slot_size: usize,
+        slot_count: usize,
+    ) -> Result<Self, RingError> {
+        Self::check_region(ptr, len, slot_size, slot_count)?;
+
+        let header = ptr.cast::<RingHeader>().as_ptr();
+        header.write(RingHeader {
+            magic: CachePadded(AtomicU64::new(0)),
+            slot_size: AtomicU64::new(slot_size as u64),
+            slot_count: AtomicU64::new(slot_count as u64),
+            enqueue: CachePadded(AtomicU64::new(0)),
+            dequeue: CachePadded(AtomicU64::new(0)),
+        });
+
+        let ring = Self::from_parts(ptr, slot_size, slot_count);
+        for i in 0..slot_count {
+            ring.slot_ptr(i as u64).write(SlotHeader {
+                sequence: AtomicU64::new(i as u64),
+                len: AtomicU64::new(ABANDONED),
+            });
+        }
+        // Publish the initialized ring to processes that open it
+        (*header).magic.0.store(MAGIC, Ordering::Release);
+
+        Ok(ring)
+    }
+
+    /// Opens a ring that was created in the given memory region.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be valid for reads and writes of `len` bytes for the lifetime `'a`.
+    /// - The region must only be accessed through rings for the lifetime `'a`.
+    pub unsafe fn open(ptr: NonNull<u8>, len: usize) -> Result<Self, RingError> {
+        if ptr.as_ptr() as usize & (REGION_ALIGN - 1) != 0 {
+            return Err(RingError::Unaligned { ptr: ptr.as_ptr() });
+        }
+        if len < mem::size_of::<RingHeader>() {
+            return Err(RingError::RegionTooSmall {
+                len,
+                required_len: Some(mem::size_of::<RingHeader>()),
+            });
+        }
+
+        let header = &*ptr.cast::<RingHeader>().as_ptr();
+        let magic = header.magic.0.load(Ordering::Acquire);
+        if magic != MAGIC {
+            return Err(RingError::NotARing { magic });
+        }
+        let slot_size = header.slot_size.load(Ordering::Relaxed) as usize;
+        let slot_count = header.slot_count.load(Ordering::Relaxed) as usize;
+        Self::check_region(ptr, len, slot_size, slot_count)?;
+
+        Ok(Self::from_parts(ptr, slot_size, slot_count))
+    }
+
+    /// Returns the maximum length of a message in bytes.
+    #[inline]
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    /// Returns the number of slots in the ring.
+    #[inline]
+    pub fn slot_count(&self) -> usize {
+        self.mask as usize + 1
+    }
+
+    #[inline]
+    fn header(&self) -> &RingHeader {
+        unsafe { self.header.as_ref() }
+    }
+
+    #[inline]
+    unsafe fn slot_ptr(&self, pos: u64) -> *mut SlotHeader {
+        let index = (pos & self.mask) as usize;
+        self.slots.as_ptr().add(index * self.stride).cast()
+    }
+
+    #[inline]
+    fn slot(&self, pos: u64) -> &SlotHeader {
+        unsafe { &*self.slot_ptr(pos) }
+    }
+
+    #[inline]
+    unsafe fn slot_bytes(&self, pos: u64) -> *mut u8 {
+        self.slot_ptr(pos)
+            .cast::<u8>()
+            .add(mem::size_of::<SlotHeader>())
+    }
+
+    /// Reserves a slot to write a message into, or returns `None` if the ring is full.
+    pub fn try_reserve(&self) -> Option<SlotWriter<'_>> {
+        let enqueue = &self.header().enqueue.0;
+        let mut pos = enqueue.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos) as i64;
+            if diff == 0 {
+                match enqueue.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: Claiming the position gives this writer exclusive access to the
+                        // slot until it is published.
+                        let bytes = unsafe {
+                            slice::from_raw_parts_mut(self.slot_bytes(pos), self.slot_size)
+                        };
+                        return Some(SlotWriter {
+                            slot,
+                            bytes,
+                            sequence: pos.wrapping_add(1),
+                            committed: false,
+                        });
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = enqueue.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Serializes a message directly into a free slot and publishes it.
+    ///
+    /// The const generic parameter `N` specifies the number of bytes to pre-allocate as scratch
+    /// space.
+    pub fn try_send<T, const N: usize>(
+        &self,
+        value: &T,
+    ) -> Result<(), SendError<SlotSerializerError<'_, N>>>
+    where
+        T: for<'b> Serialize<SlotSerializer<'b, N>>,
+    {
+        self.try_reserve()
+            .ok_or(SendError::Full)?
+            .serialize::<T, N>(value)
+            .map_err(SendError::Serialize)
+    }
+
+    /// Receives the next message, or returns `None` if the ring is empty.
+    ///
+    /// The message is validated before it is returned. Its slot is reclaimed when the returned
+    /// [`Message`] is dropped, or immediately if it fails validation.
+    pub fn try_recv<T: Archive>(&self) -> Option<Result<Message<'_, T>, RecvError<'_, T>>>
+    where
+        T::Archived: for<'b> CheckBytes<DefaultValidator<'b>>,
+    {
+        let dequeue = &self.header().dequeue.0;
+        let mut pos = dequeue.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(pos.wrapping_add(1)) as i64;
+            if diff == 0 {
+                match dequeue.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let release = pos.wrapping_add(self.mask + 1);
+                        let len = slot.len.load(Ordering::Relaxed);
+                        if len == ABANDONED {
+                            slot.sequence.store(release, Ordering::Release);
+                            pos = dequeue.load(Ordering::Relaxed);
+                            continue;
+                        }
+
+                        // The length comes from another process, so it can't be trusted
+                        let len = usize::try_from(len)
+                            .map_or(self.slot_size, |len| usize::min(len, self.slot_size));
+                        // SAFETY: Claiming the position gives this consumer exclusive access to
+                        // the slot until it is released.
+                        let bytes = unsafe { slice::from_raw_parts(self.slot_bytes(pos), len) };
+                        return Some(match crate::check_archived_root::<T>(bytes) {
+                            Ok(value) => Ok(Message {
+                                slot,
+                                release,
+                                value,
+                            }),
+                            Err(e) => {
+                                slot.sequence.store(release, Ordering::Release);
+                                Err(e)
+                            }
+                        });
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = dequeue.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Ring<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ring")
+            .field("slot_size", &self.slot_size())
+            .field("slot_count", &self.slot_count())
+            .finish()
+    }
+}
+
+/// A reserved slot in a [`Ring`].
+///
+/// The message is published when the writer is committed. If the writer is dropped without being
+/// committed, the slot is skipped by consumers.
+pub struct SlotWriter<'a> {
+    slot: &'a SlotHeader,
+    bytes: &'a mut [u8],
+    sequence: u64,
+    committed: bool,
+}
+
+impl<'a> SlotWriter<'a> {
+    /// Returns the bytes of the slot.
+    ///
+    /// The bytes are aligned to [`SLOT_ALIGN`]. The root of the archived message must be stored at
+    /// the end of the committed bytes.
+    #[inline]
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        self.bytes
+    }
+
+    /// Publishes the first `len` bytes of the slot as a message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the slot size.
+    #[inline]
+    pub fn commit(mut self, len: usize) {
+        assert!(
+            len <= self.bytes.len(),
+            "message length exceeds the slot size"
+        );
+        self.publish(len as u64);
+    }
+
+    /// Serializes a message into the slot and publishes it.
+    ///
+    /// If serialization fails, the slot is skipped by consumers.
+    pub fn serialize<T, const N: usize>(
+        mut self,
+        value: &T,
+    ) -> Result<(), SlotSerializerError<'a, N>>
+    where
+        T: for<'b> Serialize<SlotSerializer<'b, N>>,
+    {
+        let mut serializer = SlotSerializer::<N>::new(
+            BufferSerializer::new(&mut *self.bytes),
+            FallbackScratch::default(),
+            SharedSerializeMap::default(),
+        );
+        serializer.serialize_value(value)?;
+        let len = serializer.pos();
+        self.publish(len as u64);
+        Ok(())
+    }
+
+    #[inline]
+    fn publish(&mut self, len: u64) {
+        self.slot.len.store(len, Ordering::Relaxed);
+        self.slot.sequence.store(self.sequence, Ordering::Release);
+        self.committed = true;
+    }
+}
+
+impl Drop for SlotWriter<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.committed {
+            self.publish(ABANDONED);
+        }
+    }
+}
+
+/// A validated message received from a [`Ring`].
+///
+/// The message's slot is reclaimed when it is dropped.
+pub struct Message<'a, T: Archive> {
+    slot: &'a SlotHeader,
+    release: u64,
+    value: &'a T::Archived,
+}
+
+impl<T: Archive> Deref for Message<'_, T> {
+    type Target = T::Archived;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<T: Archive> fmt::Debug for Message<'_, T>
+where
+    T::Archived: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Archive> Drop for Message<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.slot.sequence.store(self.release, Ordering::Release);
+    }
+}
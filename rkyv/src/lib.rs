@@ -59,10 +59,24 @@
 //!   compatibility optimized for big-endian architectures.
 //! - `archive_le`: Forces archives into a little-endian format. This guarantees cross-endian
 //!   compatibility optimized for little-endian architectures.
+//! - `compact`: Enables removing unreachable bytes from existing archives with
+//!   [`compact`](compact::compact). Together with `validation`, also enables extracting archived
+//!   values into standalone archives with `compact::extract`.
 //! - `copy`: Enables copy optimizations for packed copyable data types. Requires nightly.
 //! - `copy_unsafe`: Automatically opts all potentially copyable types into copy optimization. This
 //!   broadly improves performance but may cause uninitialized bytes to be copied to the output.
 //!   Requires nightly.
+//! - `futures-io`: Enables streaming archives to a `futures-io` writer with
+//!   [`AsyncWriteSerializer`](ser::serializers::AsyncWriteSerializer).
+//! - `ipc`: Enables passing archived messages between processes through shared memory with
+//!   [`Ring`](ipc::Ring).
+//! - `serde`: Implements serde's `Serialize` for archived types, so archived values can be written
+//!   to any serde format without deserializing them first. Derived archived types implement it
+//!   with `#[archive(serde)]`. Archived primitives from `rend` (used by `archive_le` and
+//!   `archive_be`) can't implement `Serialize`, so `#[archive(serde)]` serializes fields of
+//!   primitive types through [`serialize_primitive`] instead. Also enables
+//!   serializing any serde type as an archived [`Value`](value::Value) with
+//!   [`value::to_bytes`].
 //! - `size_16`: Archives integral `*size` types as 16-bit integers. This is intended to be used
 //!   only for small archives and may not handle large, more general data.
 //! - `size_32`: Archives integral `*size` types as 32-bit integers. Enabled by default.
@@ -76,7 +90,17 @@
 //!   *Note*: Enabling `strict` will disable [`Archive`] implementations for tuples, as tuples
 //!   do not have a C type layout. Making a generic `Tuple<T1, T2>` and deriving [`Archive`] for it
 //!   should provide similar functionality.
-//! - `validation`: Enables validation support through `bytecheck`.
+//! - `tokio`: Enables streaming archives to a `tokio` writer with
+//!   [`AsyncWriteSerializer`](ser::serializers::AsyncWriteSerializer).
+//! - `tokio-util`: Enables [`FrameCodec`](io::FrameCodec) for framing archives with `tokio-util`
+//!   codecs.
+//! - `transcode`: Enables converting existing archives between endiannesses with
+//!   [`Transcode`](transcode::Transcode).
+//! - `unaligned`: Archives multibyte primitives as [`Unaligned`](unaligned::Unaligned) values so
+//!   that every archived type has an alignment of 1. Archives can then be accessed from any byte
+//!   slice regardless of its alignment.
+//! - `validation`: Enables validation support through `bytecheck`. Also enables reading archives
+//!   with a layout that is only known at runtime through [`dynamic`].
 //!
 //! ## Crate support
 //!
@@ -145,37 +169,61 @@ pub mod macros;
 pub mod bitvec;
 pub mod boxed;
 pub mod collections;
+pub mod columnar;
+#[cfg(feature = "compact")]
+pub mod compact;
 #[cfg(feature = "copy")]
 pub mod copy;
 pub mod de;
+#[cfg(feature = "validation")]
+pub mod dynamic;
 // This is pretty unfortunate. CStr doesn't rely on the rest of std, but it's not in core.
 // If CStr ever gets moved into `core` then this module will no longer need cfg(feature = "std")
 #[cfg(feature = "std")]
 pub mod ffi;
 mod impls;
+pub mod include;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(all(feature = "ipc", has_atomics_64))]
+pub mod ipc;
 pub mod net;
 pub mod niche;
 pub mod ops;
 pub mod option;
+pub mod packed;
 pub mod rc;
 pub mod rel_ptr;
 pub mod result;
 pub mod ser;
 pub mod string;
 pub mod time;
+#[cfg(feature = "transcode")]
+pub mod transcode;
+#[cfg(feature = "unaligned")]
+pub mod unaligned;
 pub mod util;
 #[cfg(feature = "validation")]
 pub mod validation;
+#[cfg(feature = "alloc")]
+pub mod value;
 pub mod vec;
+#[cfg(target_pointer_width = "64")]
+pub mod wide;
 pub mod with;
 
 #[cfg(feature = "rend")]
 pub use rend;
+#[cfg(feature = "serde")]
+pub use serde;
 
 #[cfg(feature = "validation")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "validation")))]
 pub use bytecheck::{self, CheckBytes};
 use core::alloc::Layout;
+#[cfg(feature = "serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+pub use impls::serde::{serialize_primitive, AsPrimitive, SerializePrimitive};
 use ptr_meta::Pointee;
 pub use rkyv_derive::{Archive, Deserialize, Serialize};
 pub use util::*;
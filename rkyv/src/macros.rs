@@ -70,11 +70,11 @@ macro_rules! out_field {
 #[macro_export]
 macro_rules! from_archived {
     ($expr:expr) => {{
-        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be", feature = "unaligned")))]
         {
             $expr
         }
-        #[cfg(any(feature = "archive_le", feature = "archive_be"))]
+        #[cfg(any(feature = "archive_le", feature = "archive_be", feature = "unaligned"))]
         {
             ($expr).value()
         }
@@ -95,18 +95,22 @@ pub use crate::rend::NativeEndian;
 #[macro_export]
 macro_rules! to_archived {
     ($expr:expr) => {{
-        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be", feature = "unaligned")))]
         {
             $expr
         }
-        #[cfg(feature = "archive_le")]
+        #[cfg(all(feature = "archive_le", not(feature = "unaligned")))]
         {
             $crate::macros::NativeEndian { value: $expr }.to_le()
         }
-        #[cfg(feature = "archive_be")]
+        #[cfg(all(feature = "archive_be", not(feature = "unaligned")))]
         {
             $crate::macros::NativeEndian { value: $expr }.to_be()
         }
+        #[cfg(feature = "unaligned")]
+        {
+            $crate::unaligned::Unaligned::from_native($expr)
+        }
     }};
 }
 
@@ -4,6 +4,16 @@ use crate::Archived;
 
 /// An archived [`Ipv4Addr`](std::net::Ipv4Addr).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(transparent)]
 pub struct ArchivedIpv4Addr {
@@ -20,6 +30,16 @@ impl ArchivedIpv4Addr {
 
 /// An archived [`Ipv6Addr`](std::net::Ipv6Addr).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(transparent)]
 pub struct ArchivedIpv6Addr {
@@ -45,6 +65,16 @@ impl ArchivedIpv6Addr {
 
 /// An archived [`IpAddr`](std::net::IpAddr).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(u8)]
 pub enum ArchivedIpAddr {
@@ -72,6 +102,16 @@ impl ArchivedIpAddr {
 
 /// An archived [`SocketAddrV4`](std::net::SocketAddrV4).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedSocketAddrV4 {
@@ -95,6 +135,16 @@ impl ArchivedSocketAddrV4 {
 
 /// An archived [`SocketAddrV6`](std::net::SocketAddrV6).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedSocketAddrV6 {
@@ -136,6 +186,16 @@ impl ArchivedSocketAddrV6 {
 
 /// An archived [`SocketAddr`](std::net::SocketAddr).
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(u8)]
 pub enum ArchivedSocketAddr {
@@ -211,3 +211,62 @@ pub enum OptionBoxResolver<T> {
     /// The resolver for the `ArchivedBox`
     Some(BoxResolver<T>),
 }
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<T: ArchivePointee + Compact + ?Sized> Compact for ArchivedOptionBox<T> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            if let Some(inner) = self.as_ref() {
+                inner.compact_dependencies(compactor, resolvers);
+            }
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            // A null pointer represents `None` and stays null when it is copied
+            if let Some(inner) = self.as_ref() {
+                inner.compact_pointers(field_pos(self, out, inner), compactor, resolvers);
+            }
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::{
+        transcode::{PointeeSize, Transcode, TranscodeError, Transcoder},
+        RelPtr,
+    };
+
+    impl<T> Transcode for ArchivedOptionBox<T>
+    where
+        T: ArchivePointee + PointeeSize + Transcode + ?Sized,
+        T::ArchivedMetadata: Copy + Transcode,
+    {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let rel_ptr = value.cast::<RelPtr<T>>();
+            let ptr = RelPtr::transcode_ptr(rel_ptr, transcoder)?;
+            // A null pointer represents `None` and points to the relative pointer itself
+            if ptr.cast::<u8>() != rel_ptr.cast::<u8>() {
+                T::transcode(ptr, transcoder)?;
+            }
+            Ok(())
+        }
+    }
+};
@@ -166,6 +166,40 @@ macro_rules! impl_archived_option_nonzero {
                 self.as_ref().partial_cmp(&other.as_ref())
             }
         }
+
+        #[cfg(feature = "compact")]
+        impl crate::compact::Compact for $ar {
+            #[inline]
+            fn compact_dependencies(
+                &self,
+                _: &mut crate::compact::Compactor,
+                _: &mut crate::compact::CompactResolvers,
+            ) {
+            }
+
+            #[inline]
+            fn compact_pointers(
+                &self,
+                _: usize,
+                _: &mut crate::compact::Compactor,
+                _: &mut crate::compact::CompactResolvers,
+            ) {
+            }
+        }
+
+        #[cfg(feature = "transcode")]
+        impl crate::transcode::Transcode for $ar {
+            #[inline]
+            unsafe fn transcode(
+                value: *mut Self,
+                transcoder: &mut crate::transcode::Transcoder<'_>,
+            ) -> Result<(), crate::transcode::TranscodeError> {
+                <Archived<$ne> as crate::transcode::Transcode>::transcode(
+                    core::ptr::addr_of_mut!((*value).inner),
+                    transcoder,
+                )
+            }
+        }
     };
 }
 
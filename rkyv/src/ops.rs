@@ -8,6 +8,16 @@ use core::{
 /// An archived [`Range`](::core::ops::Range).
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedRange<T> {
     /// The lower bound of the range (inclusive).
@@ -63,6 +73,16 @@ impl<T> RangeBounds<T> for ArchivedRange<T> {
 /// An archived [`RangeInclusive`](::core::ops::RangeInclusive).
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedRangeInclusive<T> {
     /// The lower bound of the range (inclusive).
@@ -117,6 +137,16 @@ impl<T> RangeBounds<T> for ArchivedRangeInclusive<T> {
 /// An archived [`RangeFrom`](::core::ops::RangeFrom).
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedRangeFrom<T> {
     /// The lower bound of the range (inclusive).
@@ -158,6 +188,16 @@ impl<T> RangeBounds<T> for ArchivedRangeFrom<T> {
 /// An archived [`RangeTo`](::core::ops::RangeTo).
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedRangeTo<T> {
     /// The upper bound of the range (exclusive).
@@ -199,6 +239,16 @@ impl<T> RangeBounds<T> for ArchivedRangeTo<T> {
 /// An archived [`RangeToInclusive`](::core::ops::RangeToInclusive).
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedRangeToInclusive<T> {
     /// The upper bound of the range (inclusive).
@@ -14,6 +14,16 @@ use core::{
 /// representation to allow for archiving.
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[repr(u8)]
 pub enum ArchivedOption<T> {
     /// No value
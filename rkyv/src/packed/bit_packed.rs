@@ -0,0 +1,248 @@
+//! Archived bit-packed integer array implementation.
+
+#[cfg(feature = "alloc")]
+use crate::{
+    packed::{bit_width, BitWriter},
+    ser::{ScratchSpace, Serializer},
+};
+use crate::{
+    packed::{read_bits, PackedInt},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived,
+};
+use core::{cmp::Ordering, fmt, iter::FusedIterator, marker::PhantomData, ops::Range};
+
+/// An archived array of integers that are all stored in the same number of bits.
+///
+/// Values are stored as their difference from the smallest value, so the number of bits only
+/// depends on the range of the values. This can be used with
+/// [`BitPacked`](crate::with::BitPacked).
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedBitPacked<T> {
+    pub(crate) words: ArchivedVec<Archived<u64>>,
+    pub(crate) len: Archived<usize>,
+    pub(crate) base: Archived<u64>,
+    pub(crate) bit_width: u8,
+    pub(crate) _phantom: PhantomData<T>,
+}
+
+impl<T> ArchivedBitPacked<T> {
+    /// Returns the number of values in the packed array.
+    #[inline]
+    pub fn len(&self) -> usize {
+        from_archived!(self.len) as usize
+    }
+
+    /// Returns whether the packed array is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bits that each value is stored in.
+    #[inline]
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width as u32
+    }
+
+    /// Returns the packed words of the array.
+    #[inline]
+    pub fn words(&self) -> &[Archived<u64>] {
+        self.words.as_slice()
+    }
+
+    /// Resolves an archived bit-packed array from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of values that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a bit-packed array
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: BitPackedResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.words);
+        ArchivedVec::resolve_from_len(resolver.words_len, pos + fp, resolver.words, fo);
+        let (fp, fo) = out_field!(out.len);
+        len.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.base);
+        resolver.base.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.bit_width);
+        (resolver.bit_width as u8).resolve(pos + fp, (), fo);
+    }
+}
+
+impl<T: PackedInt> ArchivedBitPacked<T> {
+    /// Gets the value at the given index without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the packed array.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> T {
+        let bit_width = self.bit_width();
+        let packed = read_bits(self.words(), index * bit_width as usize, bit_width);
+        T::from_u64(from_archived!(self.base).wrapping_add(packed))
+    }
+
+    /// Gets the value at the given index.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index < self.len() {
+            unsafe { Some(self.get_unchecked(index)) }
+        } else {
+            None
+        }
+    }
+
+    /// Gets the first value in the packed array.
+    #[inline]
+    pub fn first(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    /// Gets the last value in the packed array.
+    #[inline]
+    pub fn last(&self) -> Option<T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Gets an iterator over the values in the packed array.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            packed: self,
+            range: 0..self.len(),
+        }
+    }
+
+    /// Binary searches the packed array for the given value.
+    ///
+    /// The values must be sorted. This behaves like [`slice::binary_search`].
+    pub fn binary_search(&self, value: T) -> Result<usize, usize> {
+        let mut left = 0;
+        let mut right = self.len();
+        while left < right {
+            let mid = left + (right - left) / 2;
+            match unsafe { self.get_unchecked(mid) }.cmp(&value) {
+                Ordering::Less => left = mid + 1,
+                Ordering::Greater => right = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(left)
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    impl<T: PackedInt> ArchivedBitPacked<T> {
+        /// Serializes a bit-packed array from a slice of values.
+        pub fn serialize_from_slice<S>(
+            values: &[T],
+            serializer: &mut S,
+        ) -> Result<BitPackedResolver, S::Error>
+        where
+            S: ScratchSpace + Serializer + ?Sized,
+        {
+            let base = values.iter().min().map_or(0, |min| min.to_u64());
+            let max = values.iter().max().map_or(0, |max| max.to_u64());
+            let bit_width = bit_width(max - base);
+
+            let mut writer = BitWriter::new();
+            for value in values {
+                writer.write(value.to_u64() - base, bit_width);
+            }
+
+            Ok(BitPackedResolver {
+                words_len: writer.words.len(),
+                words: ArchivedVec::<Archived<u64>>::serialize_from_slice(
+                    &writer.words,
+                    serializer,
+                )?,
+                base,
+                bit_width,
+            })
+        }
+    }
+};
+
+impl<T: PackedInt + fmt::Debug> fmt::Debug for ArchivedBitPacked<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PackedInt> PartialEq for ArchivedBitPacked<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PackedInt> Eq for ArchivedBitPacked<T> {}
+
+impl<T: PackedInt> PartialEq<[T]> for ArchivedBitPacked<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.iter().eq(other.iter().copied())
+    }
+}
+
+/// An iterator over the values of a bit-packed array.
+pub struct Iter<'a, T> {
+    packed: &'a ArchivedBitPacked<T>,
+    range: Range<usize>,
+}
+
+impl<T: PackedInt> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range
+            .next()
+            .map(|index| unsafe { self.packed.get_unchecked(index) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T: PackedInt> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range
+            .next_back()
+            .map(|index| unsafe { self.packed.get_unchecked(index) })
+    }
+}
+
+impl<T: PackedInt> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T: PackedInt> FusedIterator for Iter<'_, T> {}
+
+/// The resolver for archived bit-packed arrays.
+pub struct BitPackedResolver {
+    words_len: usize,
+    words: VecResolver,
+    base: u64,
+    bit_width: u32,
+}
@@ -0,0 +1,409 @@
+//! Archived delta-packed integer array implementation.
+
+#[cfg(feature = "alloc")]
+use crate::{
+    packed::{bit_width, BitWriter},
+    ser::{ScratchSpace, Serializer},
+    Fallible, Serialize,
+};
+use crate::{
+    packed::{read_bits, PackedInt},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt, iter::FusedIterator, marker::PhantomData};
+
+/// A block of an archived delta-packed array.
+///
+/// Each block starts with its first value and stores the differences between the rest of its
+/// consecutive values. The differences are stored as their offset from the smallest difference in
+/// the block, all in the same number of bits.
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedDeltaBlock {
+    anchor: Archived<u64>,
+    min_delta: Archived<u64>,
+    word_start: Archived<usize>,
+    bit_width: u8,
+}
+
+impl ArchivedDeltaBlock {
+    /// Returns the first value in the block.
+    #[inline]
+    pub fn anchor(&self) -> u64 {
+        from_archived!(self.anchor)
+    }
+
+    /// Returns the smallest difference between consecutive values in the block.
+    ///
+    /// Differences wrap around, so a decreasing value has a difference close to `u64::MAX`.
+    #[inline]
+    pub fn min_delta(&self) -> u64 {
+        from_archived!(self.min_delta)
+    }
+
+    /// Returns the index of the first packed word of the block.
+    #[inline]
+    pub fn word_start(&self) -> usize {
+        from_archived!(self.word_start) as usize
+    }
+
+    /// Returns the number of bits that each difference is stored in.
+    #[inline]
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width as u32
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct DeltaBlock {
+    anchor: u64,
+    min_delta: u64,
+    word_start: usize,
+    bit_width: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl Archive for DeltaBlock {
+    type Archived = ArchivedDeltaBlock;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, _: Self::Resolver, out: *mut Self::Archived) {
+        let (fp, fo) = out_field!(out.anchor);
+        self.anchor.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.min_delta);
+        self.min_delta.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.word_start);
+        self.word_start.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.bit_width);
+        (self.bit_width as u8).resolve(pos + fp, (), fo);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Fallible + ?Sized> Serialize<S> for DeltaBlock {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+/// An archived array of integers that are stored as the differences between consecutive values.
+///
+/// Values are split into blocks of [`BLOCK_LEN`](ArchivedDeltaPacked::BLOCK_LEN) values that each
+/// start with their first value, so getting a value only needs to unpack the block that it is in.
+/// Sorted values with small gaps, like ids and timestamps, take very few bits to store. This can be
+/// used with [`DeltaPacked`](crate::with::DeltaPacked).
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedDeltaPacked<T> {
+    pub(crate) blocks: ArchivedVec<ArchivedDeltaBlock>,
+    pub(crate) words: ArchivedVec<Archived<u64>>,
+    pub(crate) len: Archived<usize>,
+    pub(crate) _phantom: PhantomData<T>,
+}
+
+impl<T> ArchivedDeltaPacked<T> {
+    /// The number of values in each block.
+    pub const BLOCK_LEN: usize = 128;
+
+    /// Returns the number of values in the packed array.
+    #[inline]
+    pub fn len(&self) -> usize {
+        from_archived!(self.len) as usize
+    }
+
+    /// Returns whether the packed array is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the blocks of the packed array.
+    #[inline]
+    pub fn blocks(&self) -> &[ArchivedDeltaBlock] {
+        self.blocks.as_slice()
+    }
+
+    /// Returns the packed words of the array.
+    #[inline]
+    pub fn words(&self) -> &[Archived<u64>] {
+        self.words.as_slice()
+    }
+
+    /// Resolves an archived delta-packed array from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of values that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing a delta-packed array
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: DeltaPackedResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.blocks);
+        ArchivedVec::resolve_from_len(resolver.blocks_len, pos + fp, resolver.blocks, fo);
+        let (fp, fo) = out_field!(out.words);
+        ArchivedVec::resolve_from_len(resolver.words_len, pos + fp, resolver.words, fo);
+        let (fp, fo) = out_field!(out.len);
+        len.resolve(pos + fp, (), fo);
+    }
+}
+
+impl<T: PackedInt> ArchivedDeltaPacked<T> {
+    /// Gets the value at the given index without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the packed array.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> T {
+        let block = self.blocks.get_unchecked(index / Self::BLOCK_LEN);
+        let offset = index % Self::BLOCK_LEN;
+        let bit_width = block.bit_width();
+
+        let mut value = block
+            .anchor()
+            .wrapping_add(block.min_delta().wrapping_mul(offset as u64));
+        let mut bit = block.word_start() * 64;
+        for _ in 0..offset {
+            value = value.wrapping_add(read_bits(self.words(), bit, bit_width));
+            bit += bit_width as usize;
+        }
+        T::from_u64(value)
+    }
+
+    /// Gets the value at the given index.
+    ///
+    /// This unpacks the values in the block before the value.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index < self.len() {
+            unsafe { Some(self.get_unchecked(index)) }
+        } else {
+            None
+        }
+    }
+
+    /// Gets the first value in the packed array.
+    #[inline]
+    pub fn first(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    /// Gets the last value in the packed array.
+    #[inline]
+    pub fn last(&self) -> Option<T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Gets an iterator over the values in the packed array.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self, 0, self.len())
+    }
+
+    /// Binary searches the packed array for the given value.
+    ///
+    /// The values must be sorted. This searches the first values of the blocks and then unpacks
+    /// the one block that may contain the value. This behaves like [`slice::binary_search`].
+    pub fn binary_search(&self, value: T) -> Result<usize, usize> {
+        let block = self
+            .blocks()
+            .partition_point(|block| T::from_u64(block.anchor()) <= value);
+        if block == 0 {
+            return Err(0);
+        }
+
+        let start = (block - 1) * Self::BLOCK_LEN;
+        let end = usize::min(start + Self::BLOCK_LEN, self.len());
+        for (index, x) in (start..end).zip(Iter::new(self, start, end)) {
+            match x.cmp(&value) {
+                Ordering::Less => (),
+                Ordering::Equal => return Ok(index),
+                Ordering::Greater => return Err(index),
+            }
+        }
+        Err(end)
+    }
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    impl<T: PackedInt> ArchivedDeltaPacked<T> {
+        /// Serializes a delta-packed array from a slice of values.
+        pub fn serialize_from_slice<S>(
+            values: &[T],
+            serializer: &mut S,
+        ) -> Result<DeltaPackedResolver, S::Error>
+        where
+            S: ScratchSpace + Serializer + ?Sized,
+        {
+            let mut blocks = Vec::with_capacity(values.chunks(Self::BLOCK_LEN).len());
+            let mut writer = BitWriter::new();
+            for chunk in values.chunks(Self::BLOCK_LEN) {
+                let deltas = chunk
+                    .windows(2)
+                    .map(|pair| pair[1].to_u64().wrapping_sub(pair[0].to_u64()));
+                // Differences are compared as signed so that blocks of decreasing values also pack
+                // well
+                let min_delta = deltas.clone().min_by_key(|&d| d as i64).unwrap_or(0);
+                let max_packed = deltas.clone().map(|d| d.wrapping_sub(min_delta)).max();
+                let bit_width = bit_width(max_packed.unwrap_or(0));
+
+                writer.align();
+                blocks.push(DeltaBlock {
+                    anchor: chunk[0].to_u64(),
+                    min_delta,
+                    word_start: writer.words.len(),
+                    bit_width,
+                });
+                for delta in deltas {
+                    writer.write(delta.wrapping_sub(min_delta), bit_width);
+                }
+            }
+
+            Ok(DeltaPackedResolver {
+                blocks_len: blocks.len(),
+                blocks: ArchivedVec::<ArchivedDeltaBlock>::serialize_from_slice(
+                    &blocks, serializer,
+                )?,
+                words_len: writer.words.len(),
+                words: ArchivedVec::<Archived<u64>>::serialize_from_slice(
+                    &writer.words,
+                    serializer,
+                )?,
+            })
+        }
+    }
+};
+
+impl<T: PackedInt + fmt::Debug> fmt::Debug for ArchivedDeltaPacked<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PackedInt> PartialEq for ArchivedDeltaPacked<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PackedInt> Eq for ArchivedDeltaPacked<T> {}
+
+impl<T: PackedInt> PartialEq<[T]> for ArchivedDeltaPacked<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+/// An iterator over the values of a delta-packed array.
+pub struct Iter<'a, T> {
+    packed: &'a ArchivedDeltaPacked<T>,
+    index: usize,
+    end: usize,
+    block_end: usize,
+    value: u64,
+    bit: usize,
+    min_delta: u64,
+    bit_width: u32,
+}
+
+impl<'a, T: PackedInt> Iter<'a, T> {
+    /// Creates an iterator over the values from `start` to `end`. `start` must be the start of a
+    /// block.
+    #[inline]
+    fn new(packed: &'a ArchivedDeltaPacked<T>, start: usize, end: usize) -> Self {
+        debug_assert_eq!(start % ArchivedDeltaPacked::<T>::BLOCK_LEN, 0);
+        Self {
+            packed,
+            index: start,
+            end,
+            block_end: start,
+            value: 0,
+            bit: 0,
+            min_delta: 0,
+            bit_width: 0,
+        }
+    }
+}
+
+impl<T: PackedInt> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.end {
+            return None;
+        }
+
+        if self.index == self.block_end {
+            let block = unsafe {
+                self.packed
+                    .blocks
+                    .get_unchecked(self.index / ArchivedDeltaPacked::<T>::BLOCK_LEN)
+            };
+            self.block_end += ArchivedDeltaPacked::<T>::BLOCK_LEN;
+            self.value = block.anchor();
+            self.bit = block.word_start() * 64;
+            self.min_delta = block.min_delta();
+            self.bit_width = block.bit_width();
+        } else {
+            let packed = unsafe { read_bits(self.packed.words(), self.bit, self.bit_width) };
+            self.value = self.value.wrapping_add(self.min_delta).wrapping_add(packed);
+            self.bit += self.bit_width as usize;
+        }
+        self.index += 1;
+
+        Some(T::from_u64(self.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<T: PackedInt> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T: PackedInt> FusedIterator for Iter<'_, T> {}
+
+/// The resolver for archived delta-packed arrays.
+pub struct DeltaPackedResolver {
+    blocks_len: usize,
+    blocks: VecResolver,
+    words_len: usize,
+    words: VecResolver,
+}
@@ -0,0 +1,432 @@
+//! Archived Elias-Fano encoded sequence implementation.
+
+use crate::{
+    bitvec::ArchivedBitVec,
+    packed::read_bits,
+    vec::{ArchivedVec, VecResolver},
+    Archive, Archived,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    packed::{bit_width, BitWriter},
+    ser::{ScratchSpace, Serializer},
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+use bitvec::{order::Lsb0, slice::BitSlice};
+use core::{fmt, iter::FusedIterator};
+
+/// The number of set or unset bits between the select samples of an archived Elias-Fano sequence.
+pub const SELECT_SAMPLE: usize = 256;
+
+/// An archived non-decreasing sequence of `u64`s in Elias-Fano encoding.
+///
+/// Each value is split into its high bits, which are stored in unary in an
+/// [`ArchivedBitVec`](crate::bitvec::ArchivedBitVec), and its low bits, which are bit packed. This
+/// takes less than two bits per value more than the low bits. The positions of every
+/// [`SELECT_SAMPLE`]th set and unset bit of the high bits are stored alongside so that values can
+/// be looked up by index or by value without decoding the whole sequence. This can be used with
+/// [`EliasFano`](crate::with::EliasFano).
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedEliasFano {
+    pub(crate) lower: ArchivedVec<Archived<u64>>,
+    pub(crate) upper: ArchivedBitVec<u8, Lsb0>,
+    pub(crate) select_ones: ArchivedVec<Archived<usize>>,
+    pub(crate) select_zeros: ArchivedVec<Archived<usize>>,
+    pub(crate) len: Archived<usize>,
+    pub(crate) lower_bits: u8,
+}
+
+impl ArchivedEliasFano {
+    /// Returns the number of values in the sequence.
+    #[inline]
+    pub fn len(&self) -> usize {
+        from_archived!(self.len) as usize
+    }
+
+    /// Returns whether the sequence is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of low bits of each value that are bit packed.
+    #[inline]
+    pub fn lower_bits(&self) -> u32 {
+        self.lower_bits as u32
+    }
+
+    /// Returns the unary-coded high bits of the values.
+    #[inline]
+    pub fn upper_bits(&self) -> &BitSlice<u8, Lsb0> {
+        &self.upper
+    }
+
+    /// Returns the number of distinct high bit values that the sequence has room for.
+    #[inline]
+    fn buckets(&self) -> usize {
+        self.upper_bits().len() - self.len()
+    }
+
+    /// Returns the position of the `rank`th bit equal to `bit` at or after `start` in the high
+    /// bits.
+    ///
+    /// Whole bytes are skipped by counting their bits instead of visiting each bit.
+    #[inline]
+    fn scan(&self, start: usize, mut rank: usize, bit: bool) -> usize {
+        let bytes = self.upper.inner.as_slice();
+        let load = |index: usize| if bit { bytes[index] } else { !bytes[index] };
+
+        let mut index = start / 8;
+        let mut byte = load(index) & (0xff << (start % 8));
+        loop {
+            let count = byte.count_ones() as usize;
+            if rank < count {
+                for _ in 0..rank {
+                    byte &= byte - 1;
+                }
+                return index * 8 + byte.trailing_zeros() as usize;
+            }
+            rank -= count;
+            index += 1;
+            byte = load(index);
+        }
+    }
+
+    /// Returns the position of the set bit with the given rank in the high bits.
+    #[inline]
+    fn select_one(&self, rank: usize) -> usize {
+        let start = from_archived!(self.select_ones[rank / SELECT_SAMPLE]) as usize;
+        self.scan(start, rank % SELECT_SAMPLE, true)
+    }
+
+    /// Returns the position of the unset bit with the given rank in the high bits.
+    #[inline]
+    fn select_zero(&self, rank: usize) -> usize {
+        let start = from_archived!(self.select_zeros[rank / SELECT_SAMPLE]) as usize;
+        self.scan(start, rank % SELECT_SAMPLE, false)
+    }
+
+    /// Returns the value at the given index with its high bits at the given position.
+    #[inline]
+    fn value_at(&self, index: usize, pos: usize) -> u64 {
+        let lower_bits = self.lower_bits();
+        let high = (pos - index) as u64;
+        let low = unsafe {
+            read_bits(
+                self.lower.as_slice(),
+                index * lower_bits as usize,
+                lower_bits,
+            )
+        };
+        high << lower_bits | low
+    }
+
+    /// Gets the value at the given index without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the sequence.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> u64 {
+        self.value_at(index, self.select_one(index))
+    }
+
+    /// Gets the value at the given index.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index < self.len() {
+            unsafe { Some(self.get_unchecked(index)) }
+        } else {
+            None
+        }
+    }
+
+    /// Gets the first value in the sequence.
+    #[inline]
+    pub fn first(&self) -> Option<u64> {
+        self.get(0)
+    }
+
+    /// Gets the last value in the sequence.
+    #[inline]
+    pub fn last(&self) -> Option<u64> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Gets an iterator over the values in the sequence.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            elias_fano: self,
+            index: 0,
+            pos: 0,
+        }
+    }
+
+    /// Returns the index and value of the first value that is greater than or equal to `value`.
+    fn lower_bound(&self, value: u64) -> (usize, Option<u64>) {
+        let lower_bits = self.lower_bits();
+        let high = value >> lower_bits;
+        if high >= self.buckets() as u64 {
+            return (self.len(), None);
+        }
+
+        // The values with the same high bits start right after the unset bit that ends the bucket
+        // before them
+        let high = high as usize;
+        let pos = if high == 0 {
+            0
+        } else {
+            self.select_zero(high - 1) + 1
+        };
+        let start = pos - high;
+        let iter = Iter {
+            elias_fano: self,
+            index: start,
+            pos,
+        };
+        for (index, x) in (start..).zip(iter) {
+            if x >= value {
+                return (index, Some(x));
+            }
+        }
+        (self.len(), None)
+    }
+
+    /// Returns the smallest value in the sequence that is greater than or equal to `value`.
+    #[inline]
+    pub fn successor(&self, value: u64) -> Option<u64> {
+        self.lower_bound(value).1
+    }
+
+    /// Returns the largest value in the sequence that is less than or equal to `value`.
+    #[inline]
+    pub fn predecessor(&self, value: u64) -> Option<u64> {
+        let index = match value.checked_add(1) {
+            Some(next) => self.lower_bound(next).0,
+            None => self.len(),
+        };
+        index
+            .checked_sub(1)
+            .map(|index| unsafe { self.get_unchecked(index) })
+    }
+
+    /// Resolves an archived Elias-Fano sequence from the given length and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be the number of values that were serialized
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing an Elias-Fano sequence
+    #[inline]
+    pub unsafe fn resolve_from_len(
+        len: usize,
+        pos: usize,
+        resolver: EliasFanoResolver,
+        out: *mut Self,
+    ) {
+        let (fp, fo) = out_field!(out.lower);
+        ArchivedVec::resolve_from_len(resolver.lower_len, pos + fp, resolver.lower, fo);
+        let (fp, fo) = out_field!(out.upper);
+        {
+            let (ip, io) = out_field!(fo.inner);
+            ArchivedVec::resolve_from_len(resolver.upper_len, pos + fp + ip, resolver.upper, io);
+            let (bp, bo) = out_field!(fo.bit_len);
+            resolver.upper_bits.resolve(pos + fp + bp, (), bo);
+        }
+        let (fp, fo) = out_field!(out.select_ones);
+        ArchivedVec::resolve_from_len(resolver.select_ones_len, pos + fp, resolver.select_ones, fo);
+        let (fp, fo) = out_field!(out.select_zeros);
+        ArchivedVec::resolve_from_len(
+            resolver.select_zeros_len,
+            pos + fp,
+            resolver.select_zeros,
+            fo,
+        );
+        let (fp, fo) = out_field!(out.len);
+        len.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.lower_bits);
+        (resolver.lower_bits as u8).resolve(pos + fp, (), fo);
+    }
+}
+
+/// Returns the number of bytes needed to store the given number of bits.
+#[inline]
+pub(crate) fn byte_count(bits: usize) -> usize {
+    bits / 8 + (bits & 7 != 0) as usize
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    impl ArchivedEliasFano {
+        /// Serializes an Elias-Fano sequence from an iterator of values.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the values are not in non-decreasing order.
+        pub fn serialize_from_iter<I, S>(
+            values: I,
+            serializer: &mut S,
+        ) -> Result<EliasFanoResolver, S::Error>
+        where
+            I: ExactSizeIterator<Item = u64> + Clone,
+            S: ScratchSpace + Serializer + ?Sized,
+        {
+            let len = values.len();
+            let max = values.clone().last().unwrap_or(0);
+            // Splitting the values so that there are about as many buckets as values keeps the
+            // high bits under two bits per value
+            let lower_bits = if len == 0 {
+                0
+            } else {
+                bit_width(max / len as u64).saturating_sub(1)
+            };
+            let buckets = if len == 0 {
+                0
+            } else {
+                (max >> lower_bits) as usize + 1
+            };
+            let upper_bits = len + buckets;
+
+            let mut lower = BitWriter::new();
+            let mut upper = vec![0u8; byte_count(upper_bits)];
+            let mut select_ones = Vec::new();
+            let mut select_zeros = Vec::new();
+
+            let mut zeros = 0;
+            let mut push_zeros = |until: usize, ones: usize, select_zeros: &mut Vec<usize>| {
+                while zeros < until {
+                    if zeros % SELECT_SAMPLE == 0 {
+                        select_zeros.push(zeros + ones);
+                    }
+                    zeros += 1;
+                }
+            };
+
+            let mut prev = 0;
+            for (index, value) in values.enumerate() {
+                assert!(
+                    value >= prev,
+                    "values serialized as Elias-Fano must be in non-decreasing order"
+                );
+                prev = value;
+
+                let high = (value >> lower_bits) as usize;
+                push_zeros(high, index, &mut select_zeros);
+                let pos = high + index;
+                upper[pos / 8] |= 1 << (pos % 8);
+                if index % SELECT_SAMPLE == 0 {
+                    select_ones.push(pos);
+                }
+
+                let mask = (1u64 << lower_bits) - 1;
+                lower.write(value & mask, lower_bits);
+            }
+            push_zeros(buckets, len, &mut select_zeros);
+
+            Ok(EliasFanoResolver {
+                lower_len: lower.words.len(),
+                lower: ArchivedVec::<Archived<u64>>::serialize_from_slice(
+                    &lower.words,
+                    serializer,
+                )?,
+                upper_len: upper.len(),
+                upper: ArchivedVec::<u8>::serialize_from_slice(&upper, serializer)?,
+                upper_bits,
+                select_ones_len: select_ones.len(),
+                select_ones: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &select_ones,
+                    serializer,
+                )?,
+                select_zeros_len: select_zeros.len(),
+                select_zeros: ArchivedVec::<Archived<usize>>::serialize_from_slice(
+                    &select_zeros,
+                    serializer,
+                )?,
+                lower_bits,
+            })
+        }
+    }
+};
+
+impl fmt::Debug for ArchivedEliasFano {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for ArchivedEliasFano {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for ArchivedEliasFano {}
+
+impl PartialEq<[u64]> for ArchivedEliasFano {
+    #[inline]
+    fn eq(&self, other: &[u64]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+/// An iterator over the values of an Elias-Fano sequence.
+pub struct Iter<'a> {
+    elias_fano: &'a ArchivedEliasFano,
+    index: usize,
+    pos: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.elias_fano.len() {
+            return None;
+        }
+
+        let pos = self.elias_fano.scan(self.pos, 0, true);
+        let value = self.elias_fano.value_at(self.index, pos);
+        self.index += 1;
+        self.pos = pos + 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elias_fano.len() - self.index;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+/// The resolver for archived Elias-Fano sequences.
+pub struct EliasFanoResolver {
+    lower_len: usize,
+    lower: VecResolver,
+    upper_len: usize,
+    upper: VecResolver,
+    upper_bits: usize,
+    select_ones_len: usize,
+    select_ones: VecResolver,
+    select_zeros_len: usize,
+    select_zeros: VecResolver,
+    lower_bits: u32,
+}
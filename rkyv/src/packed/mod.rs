@@ -0,0 +1,149 @@
+//! Archived integer arrays that are compressed with bit packing.
+//!
+//! Packed arrays store each integer in only as many bits as it needs, so they are much smaller
+//! than an [`ArchivedVec`](crate::vec::ArchivedVec) of full-width integers. They still support
+//! random access, iteration, and binary search without unpacking the whole array.
+//!
+//! - [`ArchivedBitPacked`] stores every value in the same number of bits after subtracting the
+//!   smallest value. It can be used with [`BitPacked`](crate::with::BitPacked).
+//! - [`ArchivedDeltaPacked`] stores the differences between consecutive values in blocks, which is
+//!   much smaller for sorted values like ids and timestamps. It can be used with
+//!   [`DeltaPacked`](crate::with::DeltaPacked).
+//! - `ArchivedEliasFano` stores non-decreasing sequences in Elias-Fano encoding and supports
+//!   successor and predecessor queries. It can be used with `EliasFano` and requires the `bitvec`
+//!   feature.
+
+pub mod bit_packed;
+pub mod delta_packed;
+#[cfg(feature = "bitvec")]
+pub mod elias_fano;
+#[cfg(feature = "validation")]
+pub mod validation;
+
+use crate::Archived;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "bitvec")]
+pub use self::elias_fano::ArchivedEliasFano;
+pub use self::{bit_packed::ArchivedBitPacked, delta_packed::ArchivedDeltaPacked};
+
+/// An unsigned integer type that can be stored in a packed array.
+pub trait PackedInt: Copy + Ord {
+    /// The maximum value of the integer type.
+    const MAX: Self;
+
+    /// The number of bits in the integer type.
+    const BITS: u32;
+
+    /// Widens the integer to a `u64`.
+    fn to_u64(self) -> u64;
+
+    /// Truncates a `u64` to the integer type.
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! impl_packed_int {
+    ($($ty:ty),*) => {
+        $(
+            impl PackedInt for $ty {
+                const MAX: Self = <$ty>::MAX;
+                const BITS: u32 = <$ty>::BITS;
+
+                #[inline]
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+
+                #[inline]
+                fn from_u64(value: u64) -> Self {
+                    value as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_packed_int!(u8, u16, u32, u64);
+
+/// Returns the number of bits needed to store the given value.
+#[cfg(feature = "alloc")]
+#[inline]
+fn bit_width(value: u64) -> u32 {
+    u64::BITS - value.leading_zeros()
+}
+
+/// Returns the number of words needed to store `len` values of `bit_width` bits each, or `None`
+/// if it overflows.
+#[cfg(feature = "validation")]
+#[inline]
+fn word_count(len: usize, bit_width: u32) -> Option<usize> {
+    len.checked_mul(bit_width as usize)
+        .map(|bits| bits / 64 + (bits % 64 != 0) as usize)
+}
+
+/// Reads `bit_width` bits starting at the given bit from packed words.
+///
+/// # Safety
+///
+/// The bits must be in bounds of `words`.
+#[inline]
+unsafe fn read_bits(words: &[Archived<u64>], bit: usize, bit_width: u32) -> u64 {
+    if bit_width == 0 {
+        return 0;
+    }
+
+    let index = bit / 64;
+    let offset = (bit % 64) as u32;
+    let mut value = from_archived!(*words.get_unchecked(index)) >> offset;
+    if offset + bit_width > 64 {
+        value |= from_archived!(*words.get_unchecked(index + 1)) << (64 - offset);
+    }
+    if bit_width < 64 {
+        value &= (1 << bit_width) - 1;
+    }
+    value
+}
+
+/// Writes values of varying bit widths into packed words.
+#[cfg(feature = "alloc")]
+struct BitWriter {
+    words: Vec<u64>,
+    bits: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl BitWriter {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            bits: 0,
+        }
+    }
+
+    /// Writes a value that fits in `bit_width` bits.
+    #[inline]
+    fn write(&mut self, value: u64, bit_width: u32) {
+        debug_assert!(bit_width == 64 || value >> bit_width == 0);
+
+        if bit_width == 0 {
+            return;
+        }
+
+        if self.bits == 0 {
+            self.words.push(0);
+        }
+        *self.words.last_mut().unwrap() |= value << self.bits;
+        if self.bits + bit_width > 64 {
+            self.words.push(value >> (64 - self.bits));
+        }
+        self.bits = (self.bits + bit_width) % 64;
+    }
+
+    /// Pads the written bits to the end of the current word.
+    #[inline]
+    fn align(&mut self) {
+        self.bits = 0;
+    }
+}
@@ -0,0 +1,464 @@
+//! Validation implementations for packed arrays.
+
+use crate::{
+    packed::{
+        delta_packed::ArchivedDeltaBlock, word_count, ArchivedBitPacked, ArchivedDeltaPacked,
+        PackedInt,
+    },
+    validation::{owned::CheckOwnedPointerError, ArchiveContext},
+    vec::ArchivedVec,
+    Archived,
+};
+use bytecheck::{CheckBytes, Error};
+use core::{fmt, ptr};
+
+/// Errors that can occur while checking an archived bit-packed array.
+#[derive(Debug)]
+pub enum BitPackedError<W> {
+    /// An error occurred while checking the packed words
+    CheckWordsError(W),
+    /// The bit width was larger than the bit width of the integer type
+    InvalidBitWidth(u32),
+    /// The base value was larger than the maximum value of the integer type
+    InvalidBase(u64),
+    /// The number of packed words did not match the length and bit width
+    WordCountMismatch {
+        /// The number of words needed for the length and bit width
+        expected: usize,
+        /// The actual number of words
+        actual: usize,
+    },
+}
+
+impl<W: fmt::Display> fmt::Display for BitPackedError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitPackedError::CheckWordsError(e) => write!(f, "words check error: {}", e),
+            BitPackedError::InvalidBitWidth(bit_width) => {
+                write!(f, "invalid bit width: {}", bit_width)
+            }
+            BitPackedError::InvalidBase(base) => write!(f, "invalid base: {}", base),
+            BitPackedError::WordCountMismatch { expected, actual } => write!(
+                f,
+                "word count mismatch: expected {} words, found {} words",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Errors that can occur while checking an archived delta-packed array.
+#[derive(Debug)]
+pub enum DeltaPackedError<B, W> {
+    /// An error occurred while checking the blocks
+    CheckBlocksError(B),
+    /// An error occurred while checking the packed words
+    CheckWordsError(W),
+    /// The number of blocks did not match the length
+    BlockCountMismatch {
+        /// The number of blocks needed for the length
+        expected: usize,
+        /// The actual number of blocks
+        actual: usize,
+    },
+    /// The bit width of a block was larger than 64
+    InvalidBitWidth {
+        /// The index of the block with the invalid bit width
+        index: usize,
+    },
+    /// The anchor of a block was larger than the maximum value of the integer type
+    InvalidAnchor {
+        /// The index of the block with the invalid anchor
+        index: usize,
+    },
+    /// The packed words of a block did not start right after the words of the block before it
+    InvalidBlockStart {
+        /// The index of the block with the invalid start
+        index: usize,
+    },
+    /// The number of packed words did not match the blocks
+    WordCountMismatch {
+        /// The number of words needed for the blocks
+        expected: usize,
+        /// The actual number of words
+        actual: usize,
+    },
+}
+
+impl<B: fmt::Display, W: fmt::Display> fmt::Display for DeltaPackedError<B, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeltaPackedError::CheckBlocksError(e) => write!(f, "blocks check error: {}", e),
+            DeltaPackedError::CheckWordsError(e) => write!(f, "words check error: {}", e),
+            DeltaPackedError::BlockCountMismatch { expected, actual } => write!(
+                f,
+                "block count mismatch: expected {} blocks, found {} blocks",
+                expected, actual
+            ),
+            DeltaPackedError::InvalidBitWidth { index } => {
+                write!(f, "invalid bit width: at block {}", index)
+            }
+            DeltaPackedError::InvalidAnchor { index } => {
+                write!(f, "invalid anchor: at block {}", index)
+            }
+            DeltaPackedError::InvalidBlockStart { index } => {
+                write!(f, "invalid block start: at block {}", index)
+            }
+            DeltaPackedError::WordCountMismatch { expected, actual } => write!(
+                f,
+                "word count mismatch: expected {} words, found {} words",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Errors that can occur while checking an archived Elias-Fano sequence.
+#[cfg(feature = "bitvec")]
+#[derive(Debug)]
+pub enum EliasFanoError<L, U, S> {
+    /// An error occurred while checking the low bits
+    CheckLowerError(L),
+    /// An error occurred while checking the high bits
+    CheckUpperError(U),
+    /// An error occurred while checking the select samples of the set bits
+    CheckSelectOnesError(S),
+    /// An error occurred while checking the select samples of the unset bits
+    CheckSelectZerosError(S),
+    /// The number of low bits was 64 or more
+    InvalidLowerBits(u32),
+    /// The number of words of low bits did not match the length and number of low bits
+    WordCountMismatch {
+        /// The number of words needed for the length and number of low bits
+        expected: usize,
+        /// The actual number of words
+        actual: usize,
+    },
+    /// The high bits did not fit in their bytes, did not have one set bit per value, or did not
+    /// end with an unset bit
+    InvalidUpperBits,
+    /// A select sample was missing or did not point at the bit that it samples
+    InvalidSelectSample {
+        /// The rank of the sampled bit
+        rank: usize,
+        /// Whether the sampled bit is set
+        ones: bool,
+    },
+    /// A value was less than the value before it
+    Unsorted {
+        /// The index of the value
+        index: usize,
+    },
+}
+
+#[cfg(feature = "bitvec")]
+impl<L, U, S> fmt::Display for EliasFanoError<L, U, S>
+where
+    L: fmt::Display,
+    U: fmt::Display,
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EliasFanoError::CheckLowerError(e) => write!(f, "low bits check error: {}", e),
+            EliasFanoError::CheckUpperError(e) => write!(f, "high bits check error: {}", e),
+            EliasFanoError::CheckSelectOnesError(e) => {
+                write!(f, "set bit select samples check error: {}", e)
+            }
+            EliasFanoError::CheckSelectZerosError(e) => {
+                write!(f, "unset bit select samples check error: {}", e)
+            }
+            EliasFanoError::InvalidLowerBits(lower_bits) => {
+                write!(f, "invalid number of low bits: {}", lower_bits)
+            }
+            EliasFanoError::WordCountMismatch { expected, actual } => write!(
+                f,
+                "word count mismatch: expected {} words, found {} words",
+                expected, actual
+            ),
+            EliasFanoError::InvalidUpperBits => write!(f, "invalid high bits"),
+            EliasFanoError::InvalidSelectSample { rank, ones } => write!(
+                f,
+                "invalid select sample: for {} bit {}",
+                if *ones { "set" } else { "unset" },
+                rank
+            ),
+            EliasFanoError::Unsorted { index } => {
+                write!(
+                    f,
+                    "value at index {} is less than the value before it",
+                    index
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<W: Error + 'static> Error for BitPackedError<W> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                BitPackedError::CheckWordsError(e) => Some(e as &dyn Error),
+                BitPackedError::InvalidBitWidth(_) => None,
+                BitPackedError::InvalidBase(_) => None,
+                BitPackedError::WordCountMismatch { .. } => None,
+            }
+        }
+    }
+
+    impl<B: Error + 'static, W: Error + 'static> Error for DeltaPackedError<B, W> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                DeltaPackedError::CheckBlocksError(e) => Some(e as &dyn Error),
+                DeltaPackedError::CheckWordsError(e) => Some(e as &dyn Error),
+                DeltaPackedError::BlockCountMismatch { .. } => None,
+                DeltaPackedError::InvalidBitWidth { .. } => None,
+                DeltaPackedError::InvalidAnchor { .. } => None,
+                DeltaPackedError::InvalidBlockStart { .. } => None,
+                DeltaPackedError::WordCountMismatch { .. } => None,
+            }
+        }
+    }
+
+    #[cfg(feature = "bitvec")]
+    impl<L, U, S> Error for EliasFanoError<L, U, S>
+    where
+        L: Error + 'static,
+        U: Error + 'static,
+        S: Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                EliasFanoError::CheckLowerError(e) => Some(e as &dyn Error),
+                EliasFanoError::CheckUpperError(e) => Some(e as &dyn Error),
+                EliasFanoError::CheckSelectOnesError(e) => Some(e as &dyn Error),
+                EliasFanoError::CheckSelectZerosError(e) => Some(e as &dyn Error),
+                EliasFanoError::InvalidLowerBits(_) => None,
+                EliasFanoError::WordCountMismatch { .. } => None,
+                EliasFanoError::InvalidUpperBits => None,
+                EliasFanoError::InvalidSelectSample { .. } => None,
+                EliasFanoError::Unsorted { .. } => None,
+            }
+        }
+    }
+};
+
+impl<T, C> CheckBytes<C> for ArchivedBitPacked<T>
+where
+    T: PackedInt,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = BitPackedError<CheckOwnedPointerError<[Archived<u64>], C>>;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let words =
+            ArchivedVec::<Archived<u64>>::check_bytes(ptr::addr_of!((*value).words), context)
+                .map_err(BitPackedError::CheckWordsError)?;
+        let len = from_archived!(*ptr::addr_of!((*value).len)) as usize;
+        let base = from_archived!(*ptr::addr_of!((*value).base));
+        let bit_width = *ptr::addr_of!((*value).bit_width) as u32;
+
+        if bit_width > T::BITS {
+            return Err(BitPackedError::InvalidBitWidth(bit_width));
+        }
+        if base > T::MAX.to_u64() {
+            return Err(BitPackedError::InvalidBase(base));
+        }
+        // Every value must be in bounds of the words, which also rules out an overflowing length
+        let expected = word_count(len, bit_width).ok_or(BitPackedError::WordCountMismatch {
+            expected: usize::MAX,
+            actual: words.len(),
+        })?;
+        if words.len() != expected {
+            return Err(BitPackedError::WordCountMismatch {
+                expected,
+                actual: words.len(),
+            });
+        }
+
+        Ok(&*value)
+    }
+}
+
+impl<T, C> CheckBytes<C> for ArchivedDeltaPacked<T>
+where
+    T: PackedInt,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = DeltaPackedError<
+        CheckOwnedPointerError<[ArchivedDeltaBlock], C>,
+        CheckOwnedPointerError<[Archived<u64>], C>,
+    >;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let blocks =
+            ArchivedVec::<ArchivedDeltaBlock>::check_bytes(ptr::addr_of!((*value).blocks), context)
+                .map_err(DeltaPackedError::CheckBlocksError)?;
+        let words =
+            ArchivedVec::<Archived<u64>>::check_bytes(ptr::addr_of!((*value).words), context)
+                .map_err(DeltaPackedError::CheckWordsError)?;
+        let len = from_archived!(*ptr::addr_of!((*value).len)) as usize;
+
+        let block_len = ArchivedDeltaPacked::<T>::BLOCK_LEN;
+        let expected = if len == 0 {
+            0
+        } else {
+            (len - 1) / block_len + 1
+        };
+        if blocks.len() != expected {
+            return Err(DeltaPackedError::BlockCountMismatch {
+                expected,
+                actual: blocks.len(),
+            });
+        }
+
+        // The words of the blocks must tile the packed words in order so that every packed delta
+        // is in bounds
+        let mut word_start = 0;
+        for (index, block) in blocks.iter().enumerate() {
+            if block.bit_width() > 64 {
+                return Err(DeltaPackedError::InvalidBitWidth { index });
+            }
+            if block.anchor() > T::MAX.to_u64() {
+                return Err(DeltaPackedError::InvalidAnchor { index });
+            }
+            if block.word_start() != word_start {
+                return Err(DeltaPackedError::InvalidBlockStart { index });
+            }
+            let deltas = usize::min(len - index * block_len, block_len) - 1;
+            // Block lengths and bit widths are small enough that this can't overflow
+            word_start += word_count(deltas, block.bit_width()).unwrap();
+        }
+        if word_start != words.len() {
+            return Err(DeltaPackedError::WordCountMismatch {
+                expected: word_start,
+                actual: words.len(),
+            });
+        }
+
+        Ok(&*value)
+    }
+}
+
+#[cfg(feature = "bitvec")]
+const _: () = {
+    use crate::{
+        bitvec::ArchivedBitVec,
+        packed::elias_fano::{byte_count, ArchivedEliasFano, SELECT_SAMPLE},
+    };
+    use bitvec::order::Lsb0;
+
+    impl<C> CheckBytes<C> for ArchivedEliasFano
+    where
+        C: ArchiveContext + ?Sized,
+        C::Error: Error,
+    {
+        type Error = EliasFanoError<
+            CheckOwnedPointerError<[Archived<u64>], C>,
+            <ArchivedBitVec<u8, Lsb0> as CheckBytes<C>>::Error,
+            CheckOwnedPointerError<[Archived<usize>], C>,
+        >;
+
+        unsafe fn check_bytes<'a>(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<&'a Self, Self::Error> {
+            let lower =
+                ArchivedVec::<Archived<u64>>::check_bytes(ptr::addr_of!((*value).lower), context)
+                    .map_err(EliasFanoError::CheckLowerError)?;
+            let upper =
+                ArchivedBitVec::<u8, Lsb0>::check_bytes(ptr::addr_of!((*value).upper), context)
+                    .map_err(EliasFanoError::CheckUpperError)?;
+            let select_ones = ArchivedVec::<Archived<usize>>::check_bytes(
+                ptr::addr_of!((*value).select_ones),
+                context,
+            )
+            .map_err(EliasFanoError::CheckSelectOnesError)?;
+            let select_zeros = ArchivedVec::<Archived<usize>>::check_bytes(
+                ptr::addr_of!((*value).select_zeros),
+                context,
+            )
+            .map_err(EliasFanoError::CheckSelectZerosError)?;
+            let len = from_archived!(*ptr::addr_of!((*value).len)) as usize;
+            let lower_bits = *ptr::addr_of!((*value).lower_bits) as u32;
+
+            if lower_bits >= 64 {
+                return Err(EliasFanoError::InvalidLowerBits(lower_bits));
+            }
+            let expected =
+                word_count(len, lower_bits).ok_or(EliasFanoError::WordCountMismatch {
+                    expected: usize::MAX,
+                    actual: lower.len(),
+                })?;
+            if lower.len() != expected {
+                return Err(EliasFanoError::WordCountMismatch {
+                    expected,
+                    actual: lower.len(),
+                });
+            }
+
+            // The high bits must have exactly one set bit per value and end with the unset bit of
+            // the last bucket
+            let bit_len = from_archived!(upper.bit_len) as usize;
+            if upper.inner.len() != byte_count(bit_len) {
+                return Err(EliasFanoError::InvalidUpperBits);
+            }
+            let upper_bits = upper.as_bitslice();
+            if upper_bits.count_ones() != len || upper_bits.last().map_or(len != 0, |bit| *bit) {
+                return Err(EliasFanoError::InvalidUpperBits);
+            }
+
+            // The select samples must point at every sampled set and unset bit
+            let mut ones = 0;
+            for (rank, pos) in upper_bits.iter_ones().enumerate().step_by(SELECT_SAMPLE) {
+                match select_ones.get(ones) {
+                    Some(sample) if from_archived!(*sample) as usize == pos => ones += 1,
+                    _ => return Err(EliasFanoError::InvalidSelectSample { rank, ones: true }),
+                }
+            }
+            if ones != select_ones.len() {
+                return Err(EliasFanoError::InvalidSelectSample {
+                    rank: ones * SELECT_SAMPLE,
+                    ones: true,
+                });
+            }
+            let mut zeros = 0;
+            for (rank, pos) in upper_bits.iter_zeros().enumerate().step_by(SELECT_SAMPLE) {
+                match select_zeros.get(zeros) {
+                    Some(sample) if from_archived!(*sample) as usize == pos => zeros += 1,
+                    _ => return Err(EliasFanoError::InvalidSelectSample { rank, ones: false }),
+                }
+            }
+            if zeros != select_zeros.len() {
+                return Err(EliasFanoError::InvalidSelectSample {
+                    rank: zeros * SELECT_SAMPLE,
+                    ones: false,
+                });
+            }
+
+            // The high bits are always sorted, but the low bits of values that share their high
+            // bits can be in any order
+            let value = &*value;
+            let mut last = 0;
+            for (index, next) in value.iter().enumerate() {
+                if next < last {
+                    return Err(EliasFanoError::Unsorted { index });
+                }
+                last = next;
+            }
+
+            Ok(value)
+        }
+    }
+};
@@ -0,0 +1,48 @@
+//! Compaction implementations for shared pointers.
+
+use super::{ArchivedRc, ArchivedRcWeak};
+use crate::{
+    compact::{field_pos, Compact, CompactResolvers, Compactor},
+    ArchivePointee,
+};
+
+impl<T: ArchivePointee + Compact + ?Sized, F> Compact for ArchivedRc<T, F> {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        // Every pointer to a shared value must be rewritten, but the value itself must only be
+        // copied once
+        resolvers.push(compactor.compact_shared(self.get()));
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        self.0
+            .compact_ptr(field_pos(self, out, &self.0), resolvers.take(), compactor);
+    }
+}
+
+impl<T: ArchivePointee + Compact + ?Sized, F> Compact for ArchivedRcWeak<T, F> {
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        if let Some(rc) = self.upgrade() {
+            rc.compact_dependencies(compactor, resolvers);
+        }
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        if let Some(rc) = self.upgrade() {
+            rc.compact_pointers(field_pos(self, out, rc), compactor, resolvers);
+        }
+    }
+}
@@ -1,10 +1,14 @@
 //! Archived versions of shared pointers.
 
+#[cfg(feature = "compact")]
+mod compact;
+#[cfg(feature = "transcode")]
+mod transcode;
 #[cfg(feature = "validation")]
 pub mod validation;
 
 use crate::{
-    ser::{Serializer, SharedSerializeRegistry},
+    ser::{InternRegistry, Serializer, SharedSerializeRegistry},
     ArchivePointee, ArchiveUnsized, MetadataResolver, RelPtr, SerializeUnsized,
 };
 use core::{borrow::Borrow, cmp, fmt, hash, marker::PhantomData, ops::Deref, pin::Pin, ptr};
@@ -77,6 +81,34 @@ impl<T: ArchivePointee + ?Sized, F> ArchivedRc<T, F> {
     }
 }
 
+impl<F> ArchivedRc<str, F> {
+    /// Serializes an archived `Rc` from an interned string.
+    ///
+    /// If a string with the same contents has already been interned, the archived `Rc` will point
+    /// to the previously serialized string.
+    #[inline]
+    pub fn serialize_interned_from_str<S: Serializer + InternRegistry + ?Sized>(
+        value: &str,
+        serializer: &mut S,
+    ) -> Result<RcResolver<MetadataResolver<str>>, S::Error>
+    where
+        str: SerializeUnsized<S>,
+    {
+        let pos = serializer.serialize_interned(value)?;
+
+        // Empty strings don't write any data, so pad the serializer to keep the position of the
+        // string unique like with other shared values.
+        if serializer.pos() == pos {
+            serializer.pad(1)?;
+        }
+
+        Ok(RcResolver {
+            pos,
+            metadata_resolver: value.serialize_metadata(serializer)?,
+        })
+    }
+}
+
 impl<T: ArchivePointee + ?Sized, F> AsRef<T> for ArchivedRc<T, F> {
     #[inline]
     fn as_ref(&self) -> &T {
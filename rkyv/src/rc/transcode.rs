@@ -0,0 +1,51 @@
+//! Transcoding implementations for shared pointers.
+
+use super::{ArchivedRc, ArchivedRcWeak, ArchivedRcWeakTag, ArchivedRcWeakVariantSome};
+use crate::{
+    transcode::{PointeeSize, Transcode, TranscodeError, Transcoder},
+    ArchivePointee, RelPtr,
+};
+use core::ptr;
+
+impl<T, F> Transcode for ArchivedRc<T, F>
+where
+    T: ArchivePointee + PointeeSize + Transcode + ?Sized,
+    T::ArchivedMetadata: Copy + Transcode,
+{
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let ptr = RelPtr::transcode_ptr(ptr::addr_of_mut!((*value).0), transcoder)?;
+        // Every pointer to a shared value must be transcoded, but the value itself must only be
+        // transcoded once
+        if transcoder.register_shared(ptr.cast()) {
+            T::transcode(ptr, transcoder)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, F> Transcode for ArchivedRcWeak<T, F>
+where
+    T: ArchivePointee + PointeeSize + Transcode + ?Sized,
+    T::ArchivedMetadata: Copy + Transcode,
+{
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let tag = *value.cast::<u8>();
+        if tag == ArchivedRcWeakTag::Some as u8 {
+            let value = value.cast::<ArchivedRcWeakVariantSome<T, F>>();
+            ArchivedRc::transcode(ptr::addr_of_mut!((*value).1), transcoder)?;
+        } else if tag != ArchivedRcWeakTag::None as u8 {
+            return Err(TranscodeError::InvalidTag {
+                pos: transcoder.position(value.cast()),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! Compaction implementations for relative pointers
+
+use crate::{
+    compact::{field_pos, Compact, CompactResolvers, Compactor},
+    rel_ptr::{Offset, RelPtr},
+    ArchivePointee,
+};
+
+impl<T: ArchivePointee + ?Sized, O: Offset> RelPtr<T, O> {
+    /// Rewrites the copy of this relative pointer at `out` in the new archive so that it points to
+    /// `target`.
+    ///
+    /// The metadata of the relative pointer is copied along with the rest of the value, so only the
+    /// offset is rewritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset between `out` and `target` does not fit in the offset type.
+    #[inline]
+    pub fn compact_ptr(&self, out: usize, target: usize, compactor: &mut Compactor) {
+        compactor.write_offset::<O>(field_pos(self, out, &self.raw_ptr.offset), target);
+    }
+}
+
+impl<T, O> Compact for RelPtr<T, O>
+where
+    T: ArchivePointee + Compact + ?Sized,
+    O: Offset,
+{
+    #[inline]
+    fn compact_dependencies(&self, compactor: &mut Compactor, resolvers: &mut CompactResolvers) {
+        resolvers.push(compactor.compact_out_of_line(unsafe { &*self.as_ptr() }));
+    }
+
+    #[inline]
+    fn compact_pointers(
+        &self,
+        out: usize,
+        compactor: &mut Compactor,
+        resolvers: &mut CompactResolvers,
+    ) {
+        self.compact_ptr(out, resolvers.take(), compactor);
+    }
+}
@@ -1,5 +1,9 @@
 //! Relative pointer implementations and options.
 
+#[cfg(feature = "compact")]
+mod compact;
+#[cfg(feature = "transcode")]
+mod transcode;
 #[cfg(feature = "validation")]
 mod validation;
 
@@ -0,0 +1,61 @@
+//! Transcoding implementations for relative pointers
+
+use crate::{
+    rel_ptr::{Offset, RelPtr},
+    transcode::{PointeeSize, Transcode, TranscodeError, Transcoder},
+    ArchivePointee,
+};
+use core::ptr;
+
+impl<T: ArchivePointee + PointeeSize + ?Sized, O: Offset + Transcode> RelPtr<T, O>
+where
+    T::ArchivedMetadata: Copy + Transcode,
+{
+    /// Transcodes the offset and metadata of the given relative pointer and returns the pointer it
+    /// resolves to, without transcoding the value it points to. The value it points to is checked
+    /// to lie entirely within the archive.
+    ///
+    /// This is done rather than transcoding the pointee to allow types with shared or optional
+    /// pointees to control whether the pointee is transcoded.
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to a valid relative pointer inside the archive that `transcoder` was
+    /// created for.
+    #[inline]
+    pub unsafe fn transcode_ptr(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<*mut T, TranscodeError> {
+        let raw_ptr = ptr::addr_of_mut!((*value).raw_ptr);
+        let offset_ptr = ptr::addr_of_mut!((*raw_ptr).offset);
+        let metadata_ptr = ptr::addr_of_mut!((*value).metadata);
+
+        let offset = transcoder.read(offset_ptr).to_isize();
+        let metadata = transcoder.read(metadata_ptr);
+
+        O::transcode(offset_ptr, transcoder)?;
+        T::ArchivedMetadata::transcode(metadata_ptr, transcoder)?;
+
+        let target = transcoder.bounds_check_ptr(raw_ptr.cast(), offset)?;
+        let target = ptr_meta::from_raw_parts_mut(target.cast(), T::pointer_metadata(&metadata));
+        transcoder.bounds_check_pointee(target)?;
+        Ok(target)
+    }
+}
+
+impl<T, O> Transcode for RelPtr<T, O>
+where
+    T: ArchivePointee + PointeeSize + Transcode + ?Sized,
+    T::ArchivedMetadata: Copy + Transcode,
+    O: Offset + Transcode,
+{
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let target = Self::transcode_ptr(value, transcoder)?;
+        T::transcode(target, transcoder)
+    }
+}
@@ -10,6 +10,16 @@ use core::{
 /// ([`Err`](ArchivedResult::Err)).
 #[derive(Debug)]
 #[cfg_attr(feature = "validation", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[repr(u8)]
 pub enum ArchivedResult<T, E> {
     /// Contains the success value
@@ -22,12 +22,17 @@ pub trait Serializer: Fallible {
 
     /// Advances the given number of bytes as padding.
     #[inline]
-    fn pad(&mut self, padding: usize) -> Result<(), Self::Error> {
+    fn pad(&mut self, mut padding: usize) -> Result<(), Self::Error> {
         const MAX_ZEROES: usize = 32;
         const ZEROES: [u8; MAX_ZEROES] = [0; MAX_ZEROES];
-        debug_assert!(padding < MAX_ZEROES);
 
-        self.write(&ZEROES[0..padding])
+        // Types with a large alignment may need more padding than fits in a single write
+        while padding > 0 {
+            let len = usize::min(padding, MAX_ZEROES);
+            self.write(&ZEROES[0..len])?;
+            padding -= len;
+        }
+        Ok(())
     }
 
     /// Aligns the position of the serializer to the given alignment.
@@ -141,6 +146,39 @@ pub trait ScratchSpace: Fallible {
     unsafe fn pop_scratch(&mut self, ptr: NonNull<u8>, layout: Layout) -> Result<(), Self::Error>;
 }
 
+/// A serializer that can spill data to temporary storage.
+///
+/// Spill space holds data that streaming builders would otherwise keep in scratch space, like the
+/// tables used to build the hash index of an unknown number of entries. Unlike scratch space, spilled data is addressed by its
+/// position and does not need to be in memory, so it may be backed by something like a file.
+///
+/// Spilled data is used like a stack: builders truncate the spill space back to the position they
+/// started at when they finish, whether or not they succeed.
+///
+/// Only fully-initialized bytes are spilled, usually the bytes of
+/// [`SpillRecord`](crate::SpillRecord)s. Spill spaces must give back exactly the bytes that were
+/// spilled.
+pub trait SpillSpace: Fallible {
+    /// Returns the total length of the spilled data.
+    fn spill_pos(&self) -> usize;
+
+    /// Appends the given bytes to the spilled data and returns the position they were written at.
+    fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Overwrites previously-spilled bytes at the given position.
+    ///
+    /// The overwritten bytes must lie entirely within the spilled data.
+    fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Reads previously-spilled bytes at the given position.
+    ///
+    /// The read bytes must lie entirely within the spilled data.
+    fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Discards all of the spilled data after the given position.
+    fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error>;
+}
+
 /// A registry that tracks serialized shared memory.
 ///
 /// This trait is required to serialize shared pointers.
@@ -186,3 +224,35 @@ pub trait SharedSerializeRegistry: Fallible {
         }
     }
 }
+
+/// A registry that tracks serialized interned strings.
+///
+/// Strings with the same contents are only serialized once, and every interned string with those
+/// contents points to the same bytes. This trait is required to serialize
+/// [`Interned`](crate::with::Interned) fields.
+pub trait InternRegistry: Fallible {
+    /// Gets the position of a previously-added interned string.
+    ///
+    /// Returns `None` if the string has not yet been added.
+    fn get_interned(&self, value: &str) -> Option<usize>;
+
+    /// Adds the position of an interned string to the registry.
+    fn add_interned(&mut self, value: &str, pos: usize) -> Result<(), Self::Error>;
+
+    /// Archives the given string and returns its position. If a string with the same contents has
+    /// already been added then it returns the position of the previously added string.
+    #[inline]
+    fn serialize_interned(&mut self, value: &str) -> Result<usize, Self::Error>
+    where
+        Self: Serializer,
+        str: SerializeUnsized<Self>,
+    {
+        if let Some(pos) = self.get_interned(value) {
+            Ok(pos)
+        } else {
+            let pos = value.serialize_unsized(self)?;
+            self.add_interned(value, pos)?;
+            Ok(pos)
+        }
+    }
+}
@@ -1,16 +1,21 @@
 use crate::{
-    ser::{serializers::BufferScratch, ScratchSpace, Serializer, SharedSerializeRegistry},
+    ser::{
+        serializers::BufferScratch, InternRegistry, ScratchSpace, Serializer,
+        SharedSerializeRegistry, SpillSpace,
+    },
     AlignedBytes, AlignedVec, Archive, ArchiveUnsized, Fallible, RelPtr,
 };
 #[cfg(not(feature = "std"))]
-use ::alloc::{alloc, boxed::Box, vec::Vec};
+use ::alloc::{alloc, boxed::Box, string::String, vec::Vec};
 #[cfg(feature = "std")]
 use ::std::alloc;
 use core::{
     alloc::Layout,
     borrow::{Borrow, BorrowMut},
     convert::Infallible,
-    fmt, mem,
+    fmt,
+    marker::PhantomData,
+    mem,
     ptr::NonNull,
 };
 #[cfg(not(feature = "std"))]
@@ -22,13 +27,30 @@ use std::collections::hash_map;
 ///
 /// This serializer makes it easier for the compiler to perform emplacement optimizations and may
 /// give better performance than a basic `WriteSerializer`.
+///
+/// `ALIGN` is the alignment of the underlying `AlignedVec`. Serializing an archived type with a
+/// higher alignment is a compile error:
+///
+/// ```compile_fail
+/// use rkyv::{
+///     ser::{serializers::AlignedSerializer, Serializer},
+///     AlignedVec, Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(align = 64)]
+/// struct Aligned(u32);
+///
+/// let mut serializer = AlignedSerializer::new(AlignedVec::new());
+/// serializer.serialize_value(&Aligned(42)).unwrap();
+/// ```
 #[derive(Debug)]
-pub struct AlignedSerializer<A> {
+pub struct AlignedSerializer<A, const ALIGN: usize = 16> {
     inner: A,
 }
 
-impl<A: Borrow<AlignedVec>> AlignedSerializer<A> {
-    /// Creates a new `AlignedSerializer` by wrapping a `Borrow<AlignedVec>`.
+impl<A: Borrow<AlignedVec<ALIGN>>, const ALIGN: usize> AlignedSerializer<A, ALIGN> {
+    /// Creates a new `AlignedSerializer` by wrapping a `Borrow<AlignedVec<ALIGN>>`.
     #[inline]
     pub fn new(inner: A) -> Self {
         Self { inner }
@@ -41,7 +63,7 @@ impl<A: Borrow<AlignedVec>> AlignedSerializer<A> {
     }
 }
 
-impl<A: Default> Default for AlignedSerializer<A> {
+impl<A: Default, const ALIGN: usize> Default for AlignedSerializer<A, ALIGN> {
     #[inline]
     fn default() -> Self {
         Self {
@@ -50,11 +72,14 @@ impl<A: Default> Default for AlignedSerializer<A> {
     }
 }
 
-impl<A> Fallible for AlignedSerializer<A> {
+impl<A, const ALIGN: usize> Fallible for AlignedSerializer<A, ALIGN> {
     type Error = Infallible;
 }
 
-impl<A: Borrow<AlignedVec> + BorrowMut<AlignedVec>> Serializer for AlignedSerializer<A> {
+impl<A, const ALIGN: usize> Serializer for AlignedSerializer<A, ALIGN>
+where
+    A: Borrow<AlignedVec<ALIGN>> + BorrowMut<AlignedVec<ALIGN>>,
+{
     #[inline]
     fn pos(&self) -> usize {
         self.inner.borrow().len()
@@ -72,6 +97,8 @@ impl<A: Borrow<AlignedVec> + BorrowMut<AlignedVec>> Serializer for AlignedSerial
         value: &T,
         resolver: T::Resolver,
     ) -> Result<usize, Self::Error> {
+        let () = AlignmentCheck::<T::Archived, ALIGN>::CHECK;
+
         let pos = self.pos();
         debug_assert_eq!(pos & (mem::align_of::<T::Archived>() - 1), 0);
         let vec = self.inner.borrow_mut();
@@ -108,6 +135,17 @@ impl<A: Borrow<AlignedVec> + BorrowMut<AlignedVec>> Serializer for AlignedSerial
     }
 }
 
+// Fails to compile when a type aligned above `ALIGN` is serialized with an `AlignedSerializer`,
+// since its archived value would not be aligned in the resulting buffer
+struct AlignmentCheck<T, const ALIGN: usize>(PhantomData<T>);
+
+impl<T, const ALIGN: usize> AlignmentCheck<T, ALIGN> {
+    const CHECK: () = assert!(
+        mem::align_of::<T>() <= ALIGN,
+        "the archived type is aligned above the alignment of the AlignedSerializer",
+    );
+}
+
 /// Fixed-size scratch space allocated on the heap.
 #[derive(Debug)]
 pub struct HeapScratch<const N: usize> {
@@ -323,11 +361,65 @@ impl ScratchSpace for AllocScratch {
     }
 }
 
+/// Spill space that keeps spilled data in heap memory.
+///
+/// This spill space does not reduce memory usage, but it lets streaming builders run without any
+/// external storage.
+#[derive(Debug, Default)]
+pub struct AllocSpill {
+    bytes: Vec<u8>,
+}
+
+impl AllocSpill {
+    /// Creates a new, empty spill space.
+    pub fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+}
+
+impl Fallible for AllocSpill {
+    type Error = Infallible;
+}
+
+impl SpillSpace for AllocSpill {
+    #[inline]
+    fn spill_pos(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline]
+    fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        let pos = self.bytes.len();
+        self.bytes.extend_from_slice(bytes);
+        Ok(pos)
+    }
+
+    #[inline]
+    fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bytes[pos..pos + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        bytes.copy_from_slice(&self.bytes[pos..pos + bytes.len()]);
+        Ok(())
+    }
+
+    #[inline]
+    fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error> {
+        self.bytes.truncate(pos);
+        Ok(())
+    }
+}
+
 /// An error that can occur while serializing shared pointers.
 #[derive(Debug)]
 pub enum SharedSerializeMapError {
     /// A shared pointer was added multiple times
     DuplicateSharedPointer(*const u8),
+    /// An interned string was added multiple times
+    DuplicateInternedString(String),
 }
 
 // SAFETY: SharedSerializeMapError is safe to send to another thread
@@ -342,6 +434,7 @@ impl fmt::Display for SharedSerializeMapError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::DuplicateSharedPointer(p) => write!(f, "duplicate shared pointer: {:p}", p),
+            Self::DuplicateInternedString(s) => write!(f, "duplicate interned string: {:?}", s),
         }
     }
 }
@@ -353,10 +446,11 @@ const _: () = {
     impl Error for SharedSerializeMapError {}
 };
 
-/// An adapter that adds shared serialization support to a serializer.
+/// An adapter that adds shared serialization and string interning support to a serializer.
 #[derive(Debug)]
 pub struct SharedSerializeMap {
     shared_resolvers: hash_map::HashMap<*const u8, usize>,
+    interned: hash_map::HashMap<String, usize>,
 }
 
 // SAFETY: SharedSerializeMap is safe to send to another thread
@@ -373,6 +467,7 @@ impl SharedSerializeMap {
     pub fn new() -> Self {
         Self {
             shared_resolvers: hash_map::HashMap::new(),
+            interned: hash_map::HashMap::new(),
         }
     }
 }
@@ -405,3 +500,20 @@ impl SharedSerializeRegistry for SharedSerializeMap {
         }
     }
 }
+
+impl InternRegistry for SharedSerializeMap {
+    fn get_interned(&self, value: &str) -> Option<usize> {
+        self.interned.get(value).copied()
+    }
+
+    fn add_interned(&mut self, value: &str, pos: usize) -> Result<(), Self::Error> {
+        if self.interned.contains_key(value) {
+            Err(SharedSerializeMapError::DuplicateInternedString(
+                value.into(),
+            ))
+        } else {
+            self.interned.insert(value.into(), pos);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{
+    ser::{serializers::CompositeSerializer, Serializer},
+    vec::{ArchivedVec, VecResolver},
+    Fallible, Serialize,
+};
+use ::core::{
+    convert::Infallible,
+    fmt,
+    future::poll_fn,
+    mem::{self, MaybeUninit},
+    pin::Pin,
+    slice,
+    task::{Context, Poll},
+};
+use ::std::{
+    io::{self, ErrorKind},
+    vec::Vec,
+};
+
+/// The default number of bytes an [`AsyncWriteSerializer`] buffers before writing them out.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A writer that an [`AsyncWriteSerializer`] can write chunks to.
+///
+/// This is implemented for [`FuturesIo`] with the `futures-io` feature and [`TokioIo`] with the
+/// `tokio` feature.
+pub trait AsyncSink {
+    /// Attempts to write some bytes from the buffer, returning how many bytes were written.
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to flush any bytes buffered by the writer.
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// Adapts a `futures-io` [`AsyncWrite`](futures_io::AsyncWrite) into an [`AsyncSink`].
+#[cfg(feature = "futures-io")]
+#[derive(Debug)]
+pub struct FuturesIo<W>(pub W);
+
+#[cfg(feature = "futures-io")]
+impl<W: futures_io::AsyncWrite + Unpin> AsyncSink for FuturesIo<W> {
+    #[inline]
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Adapts a `tokio` [`AsyncWrite`](tokio::io::AsyncWrite) into an [`AsyncSink`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioIo<W>(pub W);
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> AsyncSink for TokioIo<W> {
+    #[inline]
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Errors that can occur while serializing to an async writer.
+#[derive(Debug)]
+pub enum AsyncSerializeError<E> {
+    /// An I/O error occurred
+    Io(io::Error),
+    /// The value failed to serialize
+    Serialize(E),
+}
+
+impl<E> From<io::Error> for AsyncSerializeError<E> {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        AsyncSerializeError::Io(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AsyncSerializeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsyncSerializeError::Io(e) => write!(f, "async write error: {}", e),
+            AsyncSerializeError::Serialize(e) => write!(f, "async serialize error: {}", e),
+        }
+    }
+}
+
+impl<E: ::std::error::Error + 'static> ::std::error::Error for AsyncSerializeError<E> {
+    fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+        match self {
+            AsyncSerializeError::Io(e) => Some(e as &dyn ::std::error::Error),
+            AsyncSerializeError::Serialize(e) => Some(e as &dyn ::std::error::Error),
+        }
+    }
+}
+
+/// Writes all of the given bytes to the sink.
+async fn write_all<W: AsyncSink>(inner: &mut W, mut bytes: &[u8]) -> io::Result<()> {
+    while !bytes.is_empty() {
+        let remaining = bytes;
+        let written = poll_fn(|cx| inner.poll_write(cx, remaining)).await?;
+        if written == 0 {
+            return Err(ErrorKind::WriteZero.into());
+        }
+        bytes = &remaining[written..];
+    }
+    Ok(())
+}
+
+/// Wraps an [`AsyncSink`] and equips it with [`Serializer`].
+///
+/// Writes made while serializing are buffered in memory, and the buffered bytes are written out to
+/// the sink at await points with [`flush_chunk`](AsyncWriteSerializer::flush_chunk) and
+/// [`flush`](AsyncWriteSerializer::flush). The position of the serializer includes both the bytes
+/// that have already been written out and the bytes that are still buffered.
+///
+/// Serializing can't await, so a value that is serialized in a single call is buffered in full
+/// before any of it can be written out. Only the points between calls are flush points. To keep the
+/// buffer bounded while serializing a large sequence, combine the serializer with scratch space and
+/// a shared registry (as in [`AsyncAllocSerializer`]) and use
+/// [`serialize_seq_root`](CompositeSerializer::serialize_seq_root), which writes out chunks between
+/// elements. [`serialize_root_buffered`](CompositeSerializer::serialize_root_buffered) buffers the
+/// entire archive.
+///
+/// # Examples
+/// ```
+/// use rkyv::ser::{
+///     serializers::{AsyncSink, AsyncWriteSerializer},
+///     Serializer,
+/// };
+///
+/// async fn write_bytes<W: AsyncSink>(sink: W) -> W {
+///     let mut serializer = AsyncWriteSerializer::new(sink);
+///     serializer.write(&[0u8, 1u8, 2u8, 3u8]).unwrap();
+///     assert_eq!(serializer.pos(), 4);
+///     assert_eq!(serializer.buffered(), &[0u8, 1u8, 2u8, 3u8]);
+///
+///     serializer.flush().await.unwrap();
+///     assert_eq!(serializer.pos(), 4);
+///     assert!(serializer.buffered().is_empty());
+///     serializer.into_inner()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsyncWriteSerializer<W> {
+    inner: W,
+    buffer: Vec<u8>,
+    written: usize,
+    chunk_size: usize,
+}
+
+impl<W> AsyncWriteSerializer<W> {
+    /// Creates a new serializer from a sink with the default chunk size.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self::with_chunk_size(inner, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a new serializer from a sink that writes out chunks once at least `chunk_size`
+    /// bytes are buffered.
+    #[inline]
+    pub fn with_chunk_size(inner: W, chunk_size: usize) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            written: 0,
+            chunk_size,
+        }
+    }
+
+    /// Returns the number of bytes buffered before a chunk is written out.
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the bytes that have been serialized but not yet written out.
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns whether enough bytes are buffered to write out a chunk.
+    #[inline]
+    pub fn is_chunk_full(&self) -> bool {
+        self.buffer.len() >= self.chunk_size
+    }
+
+    /// Returns a reference to the underlying sink.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying sink.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the serializer and returns the underlying sink.
+    ///
+    /// Any buffered bytes are discarded, so the serializer should be flushed first.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncSink> AsyncWriteSerializer<W> {
+    /// Writes out the buffered bytes if enough are buffered to fill a chunk.
+    pub async fn flush_chunk(&mut self) -> io::Result<()> {
+        if self.is_chunk_full() {
+            self.write_buffered().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes out all of the buffered bytes and flushes the underlying sink.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered().await?;
+        poll_fn(|cx| self.inner.poll_flush(cx)).await
+    }
+
+    async fn write_buffered(&mut self) -> io::Result<()> {
+        write_all(&mut self.inner, &self.buffer).await?;
+        self.written += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W> Fallible for AsyncWriteSerializer<W> {
+    type Error = Infallible;
+}
+
+impl<W> Serializer for AsyncWriteSerializer<W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.written + self.buffer.len()
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.buffer.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl<W, C, H> CompositeSerializer<AsyncWriteSerializer<W>, C, H> {
+    /// Returns a reference to the async write serializer.
+    #[inline]
+    pub fn async_serializer(&self) -> &AsyncWriteSerializer<W> {
+        &self.serializer
+    }
+}
+
+impl<W, C, H> CompositeSerializer<AsyncWriteSerializer<W>, C, H>
+where
+    W: AsyncSink,
+    C: Fallible,
+    H: Fallible,
+{
+    /// Writes out the buffered bytes if enough are buffered to fill a chunk.
+    #[inline]
+    pub async fn flush_chunk(&mut self) -> io::Result<()> {
+        self.serializer.flush_chunk().await
+    }
+
+    /// Writes out all of the buffered bytes and flushes the underlying sink.
+    #[inline]
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.serializer.flush().await
+    }
+
+    /// Serializes the given value as the root of the archive, then writes out the archive.
+    ///
+    /// `serialize` can't await, so the whole archive is buffered in memory before any of it is
+    /// written out. This uses as much memory as serializing into an `AlignedVec`. To stream a large
+    /// archive in bounded chunks, use [`serialize_seq_root`](Self::serialize_seq_root) or
+    /// serialize values one at a time and call [`flush_chunk`](Self::flush_chunk) between them.
+    ///
+    /// Returns the position of the archived root.
+    pub async fn serialize_root_buffered<T: Serialize<Self>>(
+        &mut self,
+        value: &T,
+    ) -> Result<usize, AsyncSerializeError<<Self as Fallible>::Error>> {
+        let pos = self
+            .serialize_value(value)
+            .map_err(AsyncSerializeError::Serialize)?;
+        self.flush().await?;
+        Ok(pos)
+    }
+
+    /// Serializes the given elements as an archived `Vec` at the root of the archive and writes out
+    /// the archive.
+    ///
+    /// A chunk is written out between elements whenever enough bytes are buffered, so only about
+    /// one chunk and one element are buffered at a time. The archive can be accessed as a `Vec<T>`.
+    ///
+    /// Returns the position of the archived root.
+    pub async fn serialize_seq_root<T: Serialize<Self>>(
+        &mut self,
+        items: &[T],
+    ) -> Result<usize, AsyncSerializeError<<Self as Fallible>::Error>> {
+        let mut resolvers = Vec::with_capacity(items.len());
+        for item in items {
+            resolvers.push(
+                item.serialize(self)
+                    .map_err(AsyncSerializeError::Serialize)?,
+            );
+            self.flush_chunk().await?;
+        }
+
+        let pos = self
+            .align_for::<T::Archived>()
+            .map_err(AsyncSerializeError::Serialize)?;
+        for (item, resolver) in items.iter().zip(resolvers) {
+            // SAFETY: The resolver is the result of serializing the item, and the serializer is
+            // aligned for the item because every element has the same size.
+            unsafe {
+                self.resolve_aligned(item, resolver)
+                    .map_err(AsyncSerializeError::Serialize)?;
+            }
+            self.flush_chunk().await?;
+        }
+
+        let root_pos = self
+            .align_for::<ArchivedVec<T::Archived>>()
+            .map_err(AsyncSerializeError::Serialize)?;
+        let mut resolved = MaybeUninit::<ArchivedVec<T::Archived>>::uninit();
+        // SAFETY: The elements were written contiguously starting at `pos`, and the resolved vec is
+        // zeroed before it is written so that it doesn't contain uninitialized padding.
+        unsafe {
+            resolved.as_mut_ptr().write_bytes(0, 1);
+            ArchivedVec::resolve_from_len(
+                items.len(),
+                root_pos,
+                VecResolver::from_pos(pos),
+                resolved.as_mut_ptr(),
+            );
+            let bytes = slice::from_raw_parts(
+                resolved.as_ptr().cast::<u8>(),
+                mem::size_of::<ArchivedVec<T::Archived>>(),
+            );
+            self.write(bytes).map_err(AsyncSerializeError::Serialize)?;
+        }
+
+        self.flush().await?;
+        Ok(root_pos)
+    }
+}
+
+/// A general-purpose serializer that streams archives to an [`AsyncSink`].
+///
+/// Like [`AllocSerializer`](crate::ser::serializers::AllocSerializer), `AsyncAllocSerializer` takes
+/// the amount of scratch space to allocate before spilling allocations over into heap memory.
+///
+/// # Examples
+/// ```
+/// use rkyv::ser::serializers::{AsyncAllocSerializer, AsyncSink, AsyncWriteSerializer};
+///
+/// async fn write_values<W: AsyncSink>(sink: W, values: &[String]) -> W {
+///     let mut serializer = AsyncAllocSerializer::<_, 256>::new(
+///         AsyncWriteSerializer::with_chunk_size(sink, 1024),
+///         Default::default(),
+///         Default::default(),
+///     );
+///     serializer.serialize_seq_root(values).await.unwrap();
+///     serializer.into_serializer().into_inner()
+/// }
+/// ```
+pub type AsyncAllocSerializer<W, const N: usize> = CompositeSerializer<
+    AsyncWriteSerializer<W>,
+    super::FallbackScratch<super::HeapScratch<N>, super::AllocScratch>,
+    super::SharedSerializeMap,
+>;
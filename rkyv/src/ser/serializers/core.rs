@@ -1,5 +1,5 @@
 use crate::{
-    ser::{ScratchSpace, Serializer},
+    ser::{ScratchSpace, Serializer, SpillSpace},
     Fallible,
 };
 use core::{
@@ -332,6 +332,122 @@ impl<M: ScratchSpace, F: ScratchSpace> ScratchSpace for FallbackScratch<M, F> {
     }
 }
 
+/// The error type returned by a [`SpillScratch`].
+#[derive(Debug)]
+pub enum SpillScratchError<C, P> {
+    /// An error occurred while using scratch space
+    ScratchError(C),
+    /// An error occurred while using spill space
+    SpillError(P),
+}
+
+impl<C: fmt::Display, P: fmt::Display> fmt::Display for SpillScratchError<C, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScratchError(e) => write!(f, "scratch space error: {}", e),
+            Self::SpillError(e) => write!(f, "spill space error: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl<C: Error + 'static, P: Error + 'static> Error for SpillScratchError<C, P> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::ScratchError(e) => Some(e as &dyn Error),
+                Self::SpillError(e) => Some(e as &dyn Error),
+            }
+        }
+    }
+};
+
+/// Combines scratch space with spill space so that both can be used as the scratch of a
+/// [`CompositeSerializer`](crate::ser::serializers::CompositeSerializer).
+#[derive(Debug)]
+pub struct SpillScratch<C, P> {
+    scratch: C,
+    spill: P,
+}
+
+impl<C, P> SpillScratch<C, P> {
+    /// Creates spill scratch from a scratch space and a spill space.
+    pub fn new(scratch: C, spill: P) -> Self {
+        Self { scratch, spill }
+    }
+
+    /// Consumes the spill scratch and returns the scratch space and spill space.
+    pub fn into_inner(self) -> (C, P) {
+        (self.scratch, self.spill)
+    }
+}
+
+impl<C: Default, P: Default> Default for SpillScratch<C, P> {
+    fn default() -> Self {
+        Self {
+            scratch: C::default(),
+            spill: P::default(),
+        }
+    }
+}
+
+impl<C: Fallible, P: Fallible> Fallible for SpillScratch<C, P> {
+    type Error = SpillScratchError<C::Error, P::Error>;
+}
+
+impl<C: ScratchSpace, P: Fallible> ScratchSpace for SpillScratch<C, P> {
+    #[inline]
+    unsafe fn push_scratch(&mut self, layout: Layout) -> Result<NonNull<[u8]>, Self::Error> {
+        self.scratch
+            .push_scratch(layout)
+            .map_err(SpillScratchError::ScratchError)
+    }
+
+    #[inline]
+    unsafe fn pop_scratch(&mut self, ptr: NonNull<u8>, layout: Layout) -> Result<(), Self::Error> {
+        self.scratch
+            .pop_scratch(ptr, layout)
+            .map_err(SpillScratchError::ScratchError)
+    }
+}
+
+impl<C: Fallible, P: SpillSpace> SpillSpace for SpillScratch<C, P> {
+    #[inline]
+    fn spill_pos(&self) -> usize {
+        self.spill.spill_pos()
+    }
+
+    #[inline]
+    fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        self.spill
+            .spill(bytes)
+            .map_err(SpillScratchError::SpillError)
+    }
+
+    #[inline]
+    fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.spill
+            .respill(pos, bytes)
+            .map_err(SpillScratchError::SpillError)
+    }
+
+    #[inline]
+    fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.spill
+            .unspill(pos, bytes)
+            .map_err(SpillScratchError::SpillError)
+    }
+
+    #[inline]
+    fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error> {
+        self.spill
+            .truncate_spill(pos)
+            .map_err(SpillScratchError::SpillError)
+    }
+}
+
 /// A passthrough scratch space allocator that tracks scratch space usage.
 #[derive(Debug)]
 pub struct ScratchTracker<T> {
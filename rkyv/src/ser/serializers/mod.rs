@@ -2,6 +2,8 @@
 
 #[cfg(feature = "alloc")]
 mod alloc;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+mod async_write;
 mod core;
 #[cfg(feature = "std")]
 mod std;
@@ -9,7 +11,7 @@ mod std;
 #[cfg(feature = "alloc")]
 use crate::AlignedVec;
 use crate::{
-    ser::{ScratchSpace, Serializer, SharedSerializeRegistry},
+    ser::{InternRegistry, ScratchSpace, Serializer, SharedSerializeRegistry, SpillSpace},
     AlignedBytes, Archive, ArchiveUnsized, Fallible, Infallible,
 };
 use ::core::{alloc::Layout, fmt, ptr::NonNull};
@@ -18,6 +20,9 @@ use ::core::{alloc::Layout, fmt, ptr::NonNull};
 #[cfg(feature = "alloc")]
 pub use self::alloc::*;
 #[doc(inline)]
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub use self::async_write::*;
+#[doc(inline)]
 pub use self::core::*;
 #[doc(inline)]
 #[cfg(feature = "std")]
@@ -192,6 +197,41 @@ impl<S: Fallible, C: ScratchSpace, H: Fallible> ScratchSpace for CompositeSerial
     }
 }
 
+impl<S: Fallible, C: SpillSpace, H: Fallible> SpillSpace for CompositeSerializer<S, C, H> {
+    #[inline]
+    fn spill_pos(&self) -> usize {
+        self.scratch.spill_pos()
+    }
+
+    #[inline]
+    fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        self.scratch
+            .spill(bytes)
+            .map_err(CompositeSerializerError::ScratchSpaceError)
+    }
+
+    #[inline]
+    fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.scratch
+            .respill(pos, bytes)
+            .map_err(CompositeSerializerError::ScratchSpaceError)
+    }
+
+    #[inline]
+    fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.scratch
+            .unspill(pos, bytes)
+            .map_err(CompositeSerializerError::ScratchSpaceError)
+    }
+
+    #[inline]
+    fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error> {
+        self.scratch
+            .truncate_spill(pos)
+            .map_err(CompositeSerializerError::ScratchSpaceError)
+    }
+}
+
 impl<S: Fallible, C: Fallible, H: SharedSerializeRegistry> SharedSerializeRegistry
     for CompositeSerializer<S, C, H>
 {
@@ -208,6 +248,20 @@ impl<S: Fallible, C: Fallible, H: SharedSerializeRegistry> SharedSerializeRegist
     }
 }
 
+impl<S: Fallible, C: Fallible, H: InternRegistry> InternRegistry for CompositeSerializer<S, C, H> {
+    #[inline]
+    fn get_interned(&self, value: &str) -> Option<usize> {
+        self.shared.get_interned(value)
+    }
+
+    #[inline]
+    fn add_interned(&mut self, value: &str, pos: usize) -> Result<(), Self::Error> {
+        self.shared
+            .add_interned(value, pos)
+            .map_err(CompositeSerializerError::SharedError)
+    }
+}
+
 /// A serializer suitable for environments where allocations cannot be made.
 ///
 /// `CoreSerializer` takes two arguments: the amount of serialization memory to allocate and the
@@ -1,5 +1,8 @@
-use crate::{ser::Serializer, Fallible};
-use std::io;
+use crate::{
+    ser::{Serializer, SpillSpace},
+    Fallible,
+};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 /// Wraps a type that implements [`io::Write`](std::io::Write) and equips it with [`Serializer`].
 ///
@@ -59,3 +62,133 @@ impl<W: io::Write> Serializer for WriteSerializer<W> {
         Ok(())
     }
 }
+
+/// Spill space that writes spilled data to a seekable reader and writer, usually a temporary file.
+///
+/// Spilled data is buffered in memory and written out once the buffer fills up. Only the most
+/// recently spilled bytes are kept in memory, so the amount of memory used stays bounded by the
+/// buffer capacity no matter how much data is spilled.
+///
+/// # Examples
+/// ```
+/// use rkyv::ser::{serializers::FileSpill, SpillSpace};
+///
+/// use std::io::Cursor;
+///
+/// let mut spill = FileSpill::with_capacity(Cursor::new(Vec::new()), 4);
+/// let pos = spill.spill(&[1, 2, 3, 4, 5, 6]).unwrap();
+/// let mut bytes = [0; 3];
+/// spill.unspill(pos + 2, &mut bytes).unwrap();
+/// assert_eq!(bytes, [3, 4, 5]);
+/// ```
+#[derive(Debug)]
+pub struct FileSpill<F> {
+    inner: F,
+    flushed: usize,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl<F> FileSpill<F> {
+    /// The default number of bytes to buffer in memory.
+    pub const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+    /// Creates a new spill space that writes to the given file.
+    ///
+    /// The file is written from the beginning and any existing contents are overwritten.
+    #[inline]
+    pub fn new(inner: F) -> Self {
+        Self::with_capacity(inner, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new spill space that writes to the given file and buffers up to `capacity` bytes
+    /// in memory.
+    #[inline]
+    pub fn with_capacity(inner: F, capacity: usize) -> Self {
+        Self {
+            inner,
+            flushed: 0,
+            buffer: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Consumes the spill space and returns the file used to create it.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+impl<F: Write + Seek> FileSpill<F> {
+    fn flush_buffer(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.inner.seek(SeekFrom::Start(self.flushed as u64))?;
+            self.inner.write_all(&self.buffer)?;
+            self.flushed += self.buffer.len();
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<F> Fallible for FileSpill<F> {
+    type Error = io::Error;
+}
+
+impl<F: Read + Write + Seek> SpillSpace for FileSpill<F> {
+    #[inline]
+    fn spill_pos(&self) -> usize {
+        self.flushed + self.buffer.len()
+    }
+
+    fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        let pos = self.spill_pos();
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= self.capacity {
+            self.flush_buffer()?;
+        }
+        Ok(pos)
+    }
+
+    fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        debug_assert!(pos + bytes.len() <= self.spill_pos());
+        if pos >= self.flushed {
+            let start = pos - self.flushed;
+            self.buffer[start..start + bytes.len()].copy_from_slice(bytes);
+        } else {
+            if pos + bytes.len() > self.flushed {
+                self.flush_buffer()?;
+            }
+            self.inner.seek(SeekFrom::Start(pos as u64))?;
+            self.inner.write_all(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        debug_assert!(pos + bytes.len() <= self.spill_pos());
+        if pos >= self.flushed {
+            let start = pos - self.flushed;
+            bytes.copy_from_slice(&self.buffer[start..start + bytes.len()]);
+        } else {
+            if pos + bytes.len() > self.flushed {
+                self.flush_buffer()?;
+            }
+            self.inner.seek(SeekFrom::Start(pos as u64))?;
+            self.inner.read_exact(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error> {
+        if pos >= self.flushed {
+            self.buffer.truncate(pos - self.flushed);
+        } else {
+            // Bytes past the new end of the spilled data are left in the file and overwritten later
+            self.buffer.clear();
+            self.flushed = pos;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+//! Archived interned strings.
+
+use crate::rc::ArchivedRc;
+
+/// The flavor type for interned strings.
+pub struct InternedFlavor;
+
+/// An archived interned string.
+///
+/// Every distinct string is only serialized once, and all of the interned strings with the same
+/// contents point to it. The serialized strings are shared across the whole archive, so interning
+/// strings that are repeated many times can save a lot of space. This can be used with
+/// [`Interned`](crate::with::Interned).
+pub type ArchivedInternedString = ArchivedRc<str, InternedFlavor>;
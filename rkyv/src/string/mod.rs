@@ -1,5 +1,6 @@
 //! Archived versions of string types.
 
+pub mod interned;
 pub mod repr;
 
 use crate::{Fallible, SerializeUnsized};
@@ -12,6 +13,8 @@ use core::{
 };
 use repr::{ArchivedStringRepr, INLINE_CAPACITY};
 
+pub use self::interned::{ArchivedInternedString, InternedFlavor};
+
 /// An archived [`String`].
 ///
 /// This has inline and out-of-line representations. Short strings will use the available space
@@ -269,3 +272,45 @@ const _: () = {
         }
     }
 };
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl Compact for ArchivedString {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl Transcode for ArchivedString {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            ArchivedStringRepr::transcode(value.cast(), transcoder)
+        }
+    }
+};
@@ -150,8 +150,26 @@ impl ArchivedStringRepr {
     /// - `out` must point to a valid location to write the out-of-line representation.
     #[inline]
     pub unsafe fn emplace_out_of_line(value: &str, pos: usize, target: usize, out: *mut Self) {
+        Self::emplace_out_of_line_from_len(value.len(), pos, target, out);
+    }
+
+    /// Emplaces a new out-of-line representation for a string of the given length.
+    ///
+    /// # Safety
+    ///
+    /// - `len` must be greater than [`INLINE_CAPACITY`].
+    /// - `pos` must be the location of the representation within the archive.
+    /// - `target` must be the location of the `len` serialized bytes of the string.
+    /// - `out` must point to a valid location to write the out-of-line representation.
+    #[inline]
+    pub(crate) unsafe fn emplace_out_of_line_from_len(
+        len: usize,
+        pos: usize,
+        target: usize,
+        out: *mut Self,
+    ) {
         let out_len = ptr::addr_of_mut!((*out).out_of_line.len);
-        out_len.write(to_archived!(value.len() as FixedUsize));
+        out_len.write(to_archived!(len as FixedUsize));
 
         let out_offset = ptr::addr_of_mut!((*out).out_of_line.offset);
         let offset = crate::rel_ptr::signed_offset(pos, target).unwrap();
@@ -199,3 +217,67 @@ const _: () = {
         }
     }
 };
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl Compact for ArchivedStringRepr {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            if !self.is_inline() {
+                resolvers.push(compactor.compact_out_of_line(self.as_str()));
+            }
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            if !self.is_inline() {
+                // The bytes of the string are always copied before the representation, so the
+                // offset stays negative
+                let offset = crate::rel_ptr::signed_offset(out, resolvers.take()).unwrap();
+                let offset_pos = field_pos(self, out, unsafe { &self.out_of_line.offset });
+                compactor.write(offset_pos, (offset as FixedIsize).to_le_bytes());
+            }
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl Transcode for ArchivedStringRepr {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            // The offset is always stored in little-endian format, so whether the representation is
+            // inline does not depend on the endianness of the archive
+            if *ptr::addr_of!((*value).inline.len) & 0x80 == 0 {
+                return Ok(());
+            }
+
+            let len_ptr = ptr::addr_of_mut!((*value).out_of_line.len);
+            let len = from_archived!(transcoder.read(len_ptr)) as usize;
+            let offset = FixedIsize::from_le_bytes(ptr::read_unaligned(ptr::addr_of!(
+                (*value).out_of_line.offset
+            ))) as isize;
+
+            Archived::<usize>::transcode(len_ptr, transcoder)?;
+
+            let bytes = transcoder.bounds_check_ptr(value.cast(), offset)?;
+            transcoder.bounds_check_range(bytes, len)
+        }
+    }
+};
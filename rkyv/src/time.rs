@@ -4,6 +4,16 @@ use crate::Archived;
 
 /// An archived [`Duration`](core::time::Duration).
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "compact",
+    derive(crate::compact::Compact),
+    compact(crate = "crate")
+)]
+#[cfg_attr(
+    feature = "transcode",
+    derive(crate::transcode::Transcode),
+    transcode(crate = "crate")
+)]
 #[cfg_attr(feature = "strict", repr(C))]
 pub struct ArchivedDuration {
     secs: Archived<u64>,
@@ -140,7 +150,7 @@ const _: () = {
             let secs = from_archived!(duration.secs);
 
             if secs
-                .checked_add((duration.nanos / 1_000_000_000) as u64)
+                .checked_add((from_archived!(duration.nanos) / 1_000_000_000) as u64)
                 .is_none()
             {
                 Err(DurationError)
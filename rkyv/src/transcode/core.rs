@@ -0,0 +1,217 @@
+use crate::transcode::{swap_bytes, Transcode, TranscodeError, Transcoder};
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::{PhantomData, PhantomPinned},
+    mem::{self, ManuallyDrop},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+        NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+};
+
+macro_rules! impl_noop {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Transcode for $ty {
+                #[inline]
+                unsafe fn transcode(_: *mut Self, _: &mut Transcoder<'_>) -> Result<(), TranscodeError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_swap {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Transcode for $ty {
+                #[inline]
+                unsafe fn transcode(value: *mut Self, _: &mut Transcoder<'_>) -> Result<(), TranscodeError> {
+                    swap_bytes(value);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_noop!((), bool, i8, u8, NonZeroI8, NonZeroU8, PhantomPinned);
+impl_swap!(i16, i32, i64, i128, isize, u16, u32, u64, u128, usize, f32, f64, char);
+impl_swap!(NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128);
+impl_swap!(NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128);
+
+#[cfg(has_atomics)]
+const _: () = {
+    use core::sync::atomic::{
+        AtomicBool, AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicU16, AtomicU32, AtomicU8,
+        AtomicUsize,
+    };
+
+    impl_noop!(AtomicBool, AtomicI8, AtomicU8);
+    impl_swap!(
+        AtomicI16,
+        AtomicI32,
+        AtomicIsize,
+        AtomicU16,
+        AtomicU32,
+        AtomicUsize
+    );
+};
+
+#[cfg(has_atomics_64)]
+const _: () = {
+    use core::sync::atomic::{AtomicI64, AtomicU64};
+
+    impl_swap!(AtomicI64, AtomicU64);
+};
+
+#[cfg(feature = "rend")]
+const _: () = {
+    use crate::rend::{BigEndian, LittleEndian, NativeEndian, Primitive};
+
+    impl<T: Primitive> Transcode for LittleEndian<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            _: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            swap_bytes(value);
+            Ok(())
+        }
+    }
+
+    impl<T: Primitive> Transcode for BigEndian<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            _: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            swap_bytes(value);
+            Ok(())
+        }
+    }
+
+    impl<T> Transcode for NativeEndian<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            _: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            swap_bytes(value);
+            Ok(())
+        }
+    }
+};
+
+#[cfg(feature = "unaligned")]
+impl<T: crate::unaligned::UnalignedPrimitive> Transcode for crate::unaligned::Unaligned<T> {
+    #[inline]
+    unsafe fn transcode(value: *mut Self, _: &mut Transcoder<'_>) -> Result<(), TranscodeError> {
+        swap_bytes(value);
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> Transcode for PhantomData<T> {
+    #[inline]
+    unsafe fn transcode(_: *mut Self, _: &mut Transcoder<'_>) -> Result<(), TranscodeError> {
+        Ok(())
+    }
+}
+
+impl<T: Transcode + ?Sized> Transcode for ManuallyDrop<T> {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        T::transcode(value as *mut T, transcoder)
+    }
+}
+
+impl<T: Transcode + ?Sized> Transcode for UnsafeCell<T> {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        T::transcode(UnsafeCell::raw_get(value), transcoder)
+    }
+}
+
+impl<T: Transcode + ?Sized> Transcode for Cell<T> {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        T::transcode(value as *mut T, transcoder)
+    }
+}
+
+impl<T: Transcode, const N: usize> Transcode for [T; N] {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let element = value.cast::<T>();
+        for i in 0..N {
+            T::transcode(element.add(i), transcoder)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Transcode> Transcode for [T] {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let len = ptr_meta::metadata(value);
+        let element = value.cast::<T>();
+        transcoder.bounds_check_range(element.cast(), mem::size_of::<T>() * len)?;
+        for i in 0..len {
+            T::transcode(element.add(i), transcoder)?;
+        }
+        Ok(())
+    }
+}
+
+impl Transcode for str {
+    #[inline]
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        transcoder.bounds_check_range(value.cast(), ptr_meta::metadata(value))
+    }
+}
+
+#[cfg(not(feature = "strict"))]
+macro_rules! peel_tuple {
+    ($type:ident $index:tt, $($type_rest:ident $index_rest:tt,)*) => { impl_tuple! { $($type_rest $index_rest,)* } };
+}
+
+#[cfg(not(feature = "strict"))]
+macro_rules! impl_tuple {
+    () => ();
+    ($($type:ident $index:tt,)+) => {
+        impl<$($type: Transcode),+> Transcode for ($($type,)+) {
+            #[inline]
+            unsafe fn transcode(value: *mut Self, transcoder: &mut Transcoder<'_>) -> Result<(), TranscodeError> {
+                $(
+                    Transcode::transcode(core::ptr::addr_of_mut!((*value).$index), transcoder)?;
+                )+
+                Ok(())
+            }
+        }
+
+        peel_tuple! { $($type $index,)+ }
+    };
+}
+
+#[cfg(not(feature = "strict"))]
+impl_tuple! { T11 11, T10 10, T9 9, T8 8, T7 7, T6 6, T5 5, T4 4, T3 3, T2 2, T1 1, T0 0, }
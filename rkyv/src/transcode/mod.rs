@@ -0,0 +1,475 @@
+//! Endianness transcoding for existing archives.
+//!
+//! Archives are written in the endianness chosen at compile time by the `archive_le` and
+//! `archive_be` features, or in the native endianness if neither is enabled. [`Transcode`]
+//! rewrites an archive of some type from one endianness to the other so that it can be read by
+//! programs built with the other choice. Every value keeps its position in the archive, so only the
+//! bytes of primitives, endian-aware types, and relative pointers change.
+//!
+//! ## Examples
+//!
+//! ```
+//! use rkyv::{
+//!     archived_root,
+//!     ser::{serializers::AllocSerializer, Serializer},
+//!     transcode::{transcode_root_in_place, Endianness},
+//!     AlignedVec, Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(transcode)]
+//! struct Example {
+//!     id: u32,
+//!     name: String,
+//!     values: Vec<u64>,
+//! }
+//!
+//! let value = Example {
+//!     id: 42,
+//!     name: "a name that is too long to inline".to_string(),
+//!     values: vec![1, 2, 3],
+//! };
+//!
+//! let mut serializer = AllocSerializer::<256>::default();
+//! serializer.serialize_value(&value).unwrap();
+//! let mut bytes = serializer.into_serializer().into_inner();
+//!
+//! // Convert the archive to the other endianness...
+//! unsafe {
+//!     transcode_root_in_place::<Example>(&mut bytes, Endianness::ARCHIVED).unwrap();
+//! }
+//!
+//! // ...and back again
+//! unsafe {
+//!     transcode_root_in_place::<Example>(&mut bytes, Endianness::ARCHIVED.opposite()).unwrap();
+//! }
+//!
+//! let archived = unsafe { archived_root::<Example>(&bytes) };
+//! assert_eq!(archived.id, 42);
+//! assert_eq!(archived.name, "a name that is too long to inline");
+//! assert_eq!(archived.values.as_slice(), &[1, 2, 3]);
+//! ```
+
+mod core;
+pub mod width;
+
+use self::width::PointerWidth;
+use crate::{AlignedVec, Archive};
+use ::core::{fmt, marker::PhantomData, mem, mem::MaybeUninit, ptr, slice};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashSet;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+pub use rkyv_derive::Transcode;
+
+/// The byte order of an archive.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+impl Endianness {
+    /// The endianness that archives are read and written with by this build of rkyv.
+    #[cfg(feature = "archive_le")]
+    pub const ARCHIVED: Self = Endianness::Little;
+    /// The endianness that archives are read and written with by this build of rkyv.
+    #[cfg(feature = "archive_be")]
+    pub const ARCHIVED: Self = Endianness::Big;
+    /// The endianness that archives are read and written with by this build of rkyv.
+    #[cfg(all(
+        not(any(feature = "archive_le", feature = "archive_be")),
+        target_endian = "little"
+    ))]
+    pub const ARCHIVED: Self = Endianness::Little;
+    /// The endianness that archives are read and written with by this build of rkyv.
+    #[cfg(all(
+        not(any(feature = "archive_le", feature = "archive_be")),
+        target_endian = "big"
+    ))]
+    pub const ARCHIVED: Self = Endianness::Big;
+
+    /// Returns the other endianness.
+    #[inline]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+}
+
+/// The direction of a transcoding relative to [`Endianness::ARCHIVED`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// The archive is being converted from the opposite endianness into the archived endianness.
+    ///
+    /// Values must be transcoded before they can be read.
+    Import,
+    /// The archive is being converted from the archived endianness into the opposite endianness.
+    ///
+    /// Values must be read before they are transcoded.
+    Export,
+}
+
+/// Errors that can occur while transcoding an archive.
+#[derive(Debug)]
+pub enum TranscodeError {
+    /// A relative pointer pointed outside of the archive
+    OutOfBounds {
+        /// The position of the relative pointer
+        pos: usize,
+        /// The offset of the relative pointer
+        offset: isize,
+    },
+    /// A range of bytes extended past the end of the archive
+    Overrun {
+        /// The position of the range
+        pos: usize,
+        /// The size of the range
+        size: usize,
+        /// The length of the archive
+        archive_len: usize,
+    },
+    /// An enum tag did not match any variant
+    InvalidTag {
+        /// The position of the tag
+        pos: usize,
+    },
+    /// A value was too large to fit in the new pointer width
+    Overflow {
+        /// The position of the value
+        pos: usize,
+        /// The value that overflowed
+        value: i128,
+        /// The pointer width that the value did not fit in
+        width: PointerWidth,
+    },
+    /// A value overlapped another value that had already been transcoded, which can only happen if
+    /// relative pointers form a cycle or point into each other's values
+    Overlap {
+        /// The position of the value
+        pos: usize,
+        /// The size of the value
+        size: usize,
+    },
+    /// Values were nested more deeply than the maximum depth
+    ExceededMaximumDepth {
+        /// The position of the value that was too deep
+        pos: usize,
+        /// The maximum depth that values may be nested to
+        max_depth: usize,
+    },
+}
+
+impl fmt::Display for TranscodeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscodeError::OutOfBounds { pos, offset } => write!(
+                f,
+                "relative pointer at {} with offset {} points outside of the archive",
+                pos, offset
+            ),
+            TranscodeError::Overrun {
+                pos,
+                size,
+                archive_len,
+            } => write!(
+                f,
+                "{} bytes at {} overrun the end of the archive (length {})",
+                size, pos, archive_len
+            ),
+            TranscodeError::InvalidTag { pos } => {
+                write!(f, "invalid enum tag at {}", pos)
+            }
+            TranscodeError::Overflow { pos, value, width } => write!(
+                f,
+                "value {} at {} does not fit in {} bytes",
+                value,
+                pos,
+                width.bytes()
+            ),
+            TranscodeError::Overlap { pos, size } => write!(
+                f,
+                "{} bytes at {} overlap a value that was already transcoded",
+                size, pos
+            ),
+            TranscodeError::ExceededMaximumDepth { pos, max_depth } => write!(
+                f,
+                "value at {} is nested more than the maximum depth of {}",
+                pos, max_depth
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TranscodeError {}
+
+/// Reverses the bytes of the value at the given pointer.
+///
+/// # Safety
+///
+/// `value` must point to `size_of::<T>()` bytes that are valid for reads and writes. The pointer
+/// does not need to be aligned.
+#[inline]
+pub unsafe fn swap_bytes<T>(value: *mut T) {
+    slice::from_raw_parts_mut(value.cast::<u8>(), mem::size_of::<T>()).reverse();
+}
+
+/// A context for transcoding an archive in place.
+///
+/// The transcoder tracks the bounds of the archive and the shared pointers that have already been
+/// transcoded.
+pub struct Transcoder<'a> {
+    start: *mut u8,
+    len: usize,
+    direction: Direction,
+    shared: HashSet<usize>,
+    _phantom: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> Transcoder<'a> {
+    /// Creates a new transcoder for the given bytes.
+    #[inline]
+    pub fn new(bytes: &'a mut [u8], direction: Direction) -> Self {
+        Self {
+            start: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            direction,
+            shared: HashSet::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the direction of the transcoding.
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the position of the given pointer in the archive.
+    #[inline]
+    pub fn position(&self, ptr: *const u8) -> usize {
+        (ptr as usize).wrapping_sub(self.start as usize)
+    }
+
+    /// Reads the value at the given pointer in the archived endianness, regardless of whether it
+    /// has been transcoded yet.
+    ///
+    /// # Safety
+    ///
+    /// - `value` must point to a value that has not been transcoded yet. The pointer does not need
+    ///   to be aligned.
+    /// - `T` must be a primitive or endian-aware type whose bytes can be reversed as a whole.
+    #[inline]
+    pub unsafe fn read<T: Copy>(&self, value: *const T) -> T {
+        let mut result = MaybeUninit::<T>::uninit();
+        ptr::copy_nonoverlapping(
+            value.cast::<u8>(),
+            result.as_mut_ptr().cast::<u8>(),
+            mem::size_of::<T>(),
+        );
+        if self.direction == Direction::Import {
+            swap_bytes(result.as_mut_ptr());
+        }
+        result.assume_init()
+    }
+
+    /// Checks that a relative pointer points to an address within the archive.
+    ///
+    /// # Safety
+    ///
+    /// `base` must be inside the archive this transcoder was created for.
+    #[inline]
+    pub unsafe fn bounds_check_ptr(
+        &self,
+        base: *mut u8,
+        offset: isize,
+    ) -> Result<*mut u8, TranscodeError> {
+        let pos = self.position(base);
+        let target = pos as isize + offset;
+        if target < 0 || target as usize > self.len {
+            Err(TranscodeError::OutOfBounds { pos, offset })
+        } else {
+            Ok(self.start.offset(target))
+        }
+    }
+
+    /// Checks that `size` bytes starting at the given pointer are located within the archive.
+    #[inline]
+    pub fn bounds_check_range(&self, ptr: *const u8, size: usize) -> Result<(), TranscodeError> {
+        let pos = self.position(ptr);
+        if pos > self.len || self.len - pos < size {
+            Err(TranscodeError::Overrun {
+                pos,
+                size,
+                archive_len: self.len,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the value at the given pointer is located entirely within the archive.
+    #[inline]
+    pub fn bounds_check_pointee<T: PointeeSize + ?Sized>(
+        &self,
+        ptr: *const T,
+    ) -> Result<(), TranscodeError> {
+        let size = T::pointee_size(ptr_meta::metadata(ptr)).unwrap_or(usize::MAX);
+        self.bounds_check_range(ptr.cast(), size)
+    }
+
+    /// Registers the address of a shared value.
+    ///
+    /// Returns `true` if this is the first time the address was registered, in which case the
+    /// caller should transcode the shared value.
+    #[inline]
+    pub fn register_shared(&mut self, ptr: *const u8) -> bool {
+        self.shared.insert(ptr as usize)
+    }
+}
+
+/// A type that can be converted between endiannesses in place.
+///
+/// Implementations must transcode every byte of the value whose meaning depends on endianness,
+/// follow any relative pointers to transcode the values they point to, and leave the positions of
+/// all values unchanged. Use [`Transcoder::read`] to read lengths and offsets so that they are
+/// interpreted correctly in both [`Direction`]s.
+///
+/// This trait can be derived with `#[derive(Transcode)]`, or derived for archived types with
+/// `#[archive(transcode)]`.
+pub trait Transcode {
+    /// Transcodes the value at the given pointer, along with all of the values it owns.
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to a valid archived value inside the archive that `transcoder` was
+    /// created for, in the endianness given by the transcoder's direction. The pointer does not
+    /// need to be aligned.
+    unsafe fn transcode(
+        value: *mut Self,
+        transcoder: &mut Transcoder<'_>,
+    ) -> Result<(), TranscodeError>;
+}
+
+/// A type whose size can be computed from its pointer metadata.
+///
+/// This is implemented for all sized types, slices, and `str`, and lets relative pointers range
+/// check the values they point to before those values are transcoded.
+pub trait PointeeSize: ptr_meta::Pointee {
+    /// Returns the size of a value with the given metadata, or `None` if it overflows.
+    fn pointee_size(metadata: <Self as ptr_meta::Pointee>::Metadata) -> Option<usize>;
+}
+
+impl<T> PointeeSize for T {
+    #[inline]
+    fn pointee_size(_: ()) -> Option<usize> {
+        Some(mem::size_of::<T>())
+    }
+}
+
+impl<T> PointeeSize for [T] {
+    #[inline]
+    fn pointee_size(len: usize) -> Option<usize> {
+        mem::size_of::<T>().checked_mul(len)
+    }
+}
+
+impl PointeeSize for str {
+    #[inline]
+    fn pointee_size(len: usize) -> Option<usize> {
+        Some(len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl PointeeSize for ::std::ffi::CStr {
+    #[inline]
+    fn pointee_size(len: usize) -> Option<usize> {
+        Some(len)
+    }
+}
+
+#[inline]
+fn direction_from(from: Endianness) -> Direction {
+    if from == Endianness::ARCHIVED {
+        Direction::Export
+    } else {
+        Direction::Import
+    }
+}
+
+/// Transcodes the archived value at the given position from the given endianness to the other
+/// endianness.
+///
+/// # Safety
+///
+/// The bytes must contain a valid archived `T` at `pos` in the endianness `from`.
+#[inline]
+pub unsafe fn transcode_value_in_place<T: Archive + ?Sized>(
+    bytes: &mut [u8],
+    pos: usize,
+    from: Endianness,
+) -> Result<(), TranscodeError>
+where
+    T::Archived: Transcode,
+{
+    let mut transcoder = Transcoder::new(bytes, direction_from(from));
+    let value = transcoder.start.wrapping_add(pos);
+    transcoder.bounds_check_range(value, mem::size_of::<T::Archived>())?;
+    T::Archived::transcode(value.cast(), &mut transcoder)
+}
+
+/// Transcodes the archived root of the given bytes from the given endianness to the other
+/// endianness.
+///
+/// This is a wrapper for [`transcode_value_in_place`] that calculates the position of the root
+/// like [`archived_root`](crate::archived_root).
+///
+/// # Safety
+///
+/// The bytes must contain a valid archived `T` at the end of the slice in the endianness `from`.
+#[inline]
+pub unsafe fn transcode_root_in_place<T: Archive + ?Sized>(
+    bytes: &mut [u8],
+    from: Endianness,
+) -> Result<(), TranscodeError>
+where
+    T::Archived: Transcode,
+{
+    let size = mem::size_of::<T::Archived>();
+    if bytes.len() < size {
+        return Err(TranscodeError::Overrun {
+            pos: 0,
+            size,
+            archive_len: bytes.len(),
+        });
+    }
+    transcode_value_in_place::<T>(bytes, bytes.len() - size, from)
+}
+
+/// Copies the given bytes into a new buffer and transcodes the archived root from the given
+/// endianness to the other endianness.
+///
+/// # Safety
+///
+/// The bytes must contain a valid archived `T` at the end of the slice in the endianness `from`.
+#[inline]
+pub unsafe fn transcode_root<T: Archive + ?Sized>(
+    bytes: &[u8],
+    from: Endianness,
+) -> Result<AlignedVec, TranscodeError>
+where
+    T::Archived: Transcode,
+{
+    let mut result = AlignedVec::with_capacity(bytes.len());
+    result.extend_from_slice(bytes);
+    transcode_root_in_place::<T>(&mut result, from)?;
+    Ok(result)
+}
@@ -0,0 +1,283 @@
+use crate::transcode::{
+    width::{
+        struct_layout, PointerWidth, TranscodeWidth, TranscodeWidthUnsized, WidthResolvers,
+        WidthTranscoder,
+    },
+    TranscodeError,
+};
+#[cfg(all(not(feature = "std"), has_atomics))]
+use ::alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use ::alloc::{boxed::Box, rc::Rc, string::String, vec::Vec};
+use core::alloc::Layout;
+#[cfg(feature = "std")]
+use std::rc::Rc;
+#[cfg(all(feature = "std", has_atomics))]
+use std::sync::Arc;
+
+#[inline]
+fn wide_ptr_layout(width: PointerWidth) -> Layout {
+    struct_layout(&[width.layout(), width.layout()], &mut [0; 2])
+}
+
+impl<T: TranscodeWidth> TranscodeWidthUnsized for T {
+    #[inline]
+    fn archived_ptr_layout(width: PointerWidth) -> Layout {
+        width.layout()
+    }
+
+    #[inline]
+    fn transcode_pointee(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError> {
+        let target = transcoder.read_rel_ptr(pos)?;
+        transcoder.transcode_out_of_line::<T>(target)
+    }
+
+    #[inline]
+    fn transcode_ptr(
+        pos: usize,
+        out: usize,
+        target: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        transcoder.write_rel_ptr(pos, out, target)
+    }
+}
+
+impl<T: TranscodeWidth> TranscodeWidthUnsized for [T] {
+    #[inline]
+    fn archived_ptr_layout(width: PointerWidth) -> Layout {
+        wide_ptr_layout(width)
+    }
+
+    #[inline]
+    fn transcode_pointee(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError> {
+        let target = transcoder.read_rel_ptr(pos)?;
+        let len = transcoder.read_usize(pos + transcoder.from().bytes())?;
+        transcoder.transcode_slice::<T>(target, len as usize)
+    }
+
+    #[inline]
+    fn transcode_ptr(
+        pos: usize,
+        out: usize,
+        target: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let len_pos = pos + transcoder.from().bytes();
+        let len = transcoder.read_usize(len_pos)?;
+        transcoder.write_rel_ptr(pos, out, target)?;
+        transcoder.write_usize(len_pos, out + transcoder.to().bytes(), len)
+    }
+}
+
+impl TranscodeWidthUnsized for str {
+    #[inline]
+    fn archived_ptr_layout(width: PointerWidth) -> Layout {
+        wide_ptr_layout(width)
+    }
+
+    #[inline]
+    fn transcode_pointee(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError> {
+        let target = transcoder.read_rel_ptr(pos)?;
+        let len = transcoder.read_usize(pos + transcoder.from().bytes())?;
+        transcoder.transcode_bytes(target, len as usize)
+    }
+
+    #[inline]
+    fn transcode_ptr(
+        pos: usize,
+        out: usize,
+        target: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        <[u8]>::transcode_ptr(pos, out, target, transcoder)
+    }
+}
+
+impl<T: TranscodeWidthUnsized + ?Sized> TranscodeWidth for Box<T> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        T::archived_ptr_layout(width)
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        resolvers.push(T::transcode_pointee(pos, transcoder)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        T::transcode_ptr(pos, out, resolvers.take(), transcoder)
+    }
+}
+
+impl<T: TranscodeWidth> TranscodeWidth for Vec<T> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        Box::<[T]>::archived_layout(width)
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        Box::<[T]>::transcode_dependencies(pos, transcoder, resolvers)
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        Box::<[T]>::transcode_value(pos, out, transcoder, resolvers)
+    }
+}
+
+// Archived strings store short strings inline and long strings out-of-line. Both representations
+// take up two pointer-width integers, with the last byte of the inline representation holding the
+// length. Out-of-line strings store the length followed by an offset that is always little-endian
+// and always negative, so the high bit of the last byte marks the representation as out-of-line.
+
+#[inline]
+fn inline_capacity(width: PointerWidth) -> usize {
+    2 * width.bytes() - 1
+}
+
+/// Returns the position and length of the bytes of the archived string at `pos`.
+#[inline]
+fn read_string(
+    transcoder: &WidthTranscoder<'_>,
+    pos: usize,
+) -> Result<(usize, u64), TranscodeError> {
+    let width = transcoder.from().bytes();
+    let repr = transcoder.read_bytes(pos, 2 * width)?;
+    let last = repr[2 * width - 1];
+    if last & 0x80 == 0 {
+        Ok((pos, last as u64))
+    } else {
+        let len = transcoder.read_usize(pos)?;
+        let offset = repr[width..]
+            .iter()
+            .rev()
+            .fold(0u64, |offset, byte| offset << 8 | *byte as u64);
+        let shift = 64 - 8 * width;
+        let offset = ((offset << shift) as i64 >> shift) as isize;
+        let target = pos as isize + offset;
+        if target < 0 {
+            Err(TranscodeError::OutOfBounds { pos, offset })
+        } else {
+            Ok((target as usize, len))
+        }
+    }
+}
+
+impl TranscodeWidth for String {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        wide_ptr_layout(width)
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let (target, len) = read_string(transcoder, pos)?;
+        if len as usize > inline_capacity(transcoder.to()) {
+            resolvers.push(transcoder.transcode_bytes(target, len as usize)?);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let (target, len) = read_string(transcoder, pos)?;
+        let width = transcoder.to().bytes();
+        if len as usize <= inline_capacity(transcoder.to()) {
+            let bytes = transcoder.read_bytes(target, len as usize)?;
+            transcoder.write_bytes(out, bytes);
+            transcoder.write_bytes(out + 2 * width - 1, &[len as u8]);
+            Ok(())
+        } else {
+            transcoder.write_usize(pos, out, len)?;
+            let offset = resolvers.take() as i64 - out as i64;
+            let shift = 64 - 8 * width;
+            if (offset << shift) >> shift != offset {
+                return Err(TranscodeError::Overflow {
+                    pos,
+                    value: offset as i128,
+                    width: transcoder.to(),
+                });
+            }
+            transcoder.write_bytes(out + width, &offset.to_le_bytes()[..width]);
+            Ok(())
+        }
+    }
+}
+
+macro_rules! impl_shared {
+    ($ty:ident) => {
+        impl<T: TranscodeWidthUnsized + ?Sized> TranscodeWidth for $ty<T> {
+            #[inline]
+            fn archived_layout(width: PointerWidth) -> Layout {
+                T::archived_ptr_layout(width)
+            }
+
+            #[inline]
+            fn transcode_dependencies(
+                pos: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                let target = transcoder.read_rel_ptr(pos)?;
+                let out = transcoder
+                    .transcode_shared(target, |transcoder| T::transcode_pointee(pos, transcoder))?;
+                resolvers.push(out);
+                Ok(())
+            }
+
+            #[inline]
+            fn transcode_value(
+                pos: usize,
+                out: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                T::transcode_ptr(pos, out, resolvers.take(), transcoder)
+            }
+        }
+    };
+}
+
+impl_shared!(Rc);
+#[cfg(has_atomics)]
+impl_shared!(Arc);
@@ -0,0 +1,643 @@
+use crate::{
+    collections::hash_index::{ArchiveHasher, HashBuilder},
+    transcode::{
+        width::{struct_layout, PointerWidth, TranscodeWidth, WidthResolvers, WidthTranscoder},
+        TranscodeError,
+    },
+    Archived,
+};
+use core::{alloc::Layout, marker::PhantomData};
+#[cfg(all(feature = "indexmap", feature = "strict"))]
+use indexmap::IndexMap;
+#[cfg(feature = "indexmap")]
+use indexmap::IndexSet;
+#[cfg(all(feature = "std", feature = "strict"))]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+// Archived hash maps, index maps, and their sets store their entries as `Entry<K, V>`, and B-tree
+// maps store leaf entries with the same layout. Entries are only `repr(C)` with the `strict`
+// feature, so entries with values are only supported when it is enabled. Entries without values
+// only have one field that takes up space, so their key is always at the start.
+struct EntryWidth<K, V>(PhantomData<(K, V)>);
+
+impl<K: TranscodeWidth, V: TranscodeWidth> EntryWidth<K, V> {
+    #[inline]
+    fn layout(width: PointerWidth) -> (Layout, [usize; 2]) {
+        let mut offsets = [0; 2];
+        let fields = [K::archived_layout(width), V::archived_layout(width)];
+        (struct_layout(&fields, &mut offsets), offsets)
+    }
+}
+
+impl<K: TranscodeWidth, V: TranscodeWidth> TranscodeWidth for EntryWidth<K, V> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        Self::layout(width).0
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let (_, offsets) = Self::layout(transcoder.from());
+        K::transcode_dependencies(pos + offsets[0], transcoder, resolvers)?;
+        V::transcode_dependencies(pos + offsets[1], transcoder, resolvers)
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let (_, from) = Self::layout(transcoder.from());
+        let (_, to) = Self::layout(transcoder.to());
+        K::transcode_value(pos + from[0], out + to[0], transcoder, resolvers)?;
+        V::transcode_value(pos + from[1], out + to[1], transcoder, resolvers)
+    }
+}
+
+// Hash indexes store their length and a relative pointer to their displacements. If the hasher
+// stores its seed, the seed is located right before the displacements.
+
+#[inline]
+fn hash_index_layout(width: PointerWidth) -> Layout {
+    struct_layout(&[width.layout(), width.layout()], &mut [0; 2])
+}
+
+/// Transcodes the seed and displacements of the hash index at `pos`, then returns the length of
+/// the hash index.
+#[inline]
+fn transcode_hash_index_dependencies(
+    pos: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<usize, TranscodeError> {
+    let len = transcoder.read_usize(pos)? as usize;
+    let displace_pos = pos + transcoder.from().bytes();
+    let displace = transcoder.read_rel_ptr(displace_pos)?;
+    if HashBuilder::STORE_SEED {
+        let seed = Layout::new::<Archived<u64>>();
+        let seed_pos = displace
+            .checked_sub(seed.size())
+            .ok_or(TranscodeError::OutOfBounds {
+                pos: displace_pos,
+                offset: -(seed.size() as isize),
+            })?;
+        transcoder.claim(seed_pos, seed.size())?;
+        let bytes = transcoder.read_bytes(seed_pos, seed.size())?;
+        let out = transcoder.reserve(seed);
+        transcoder.write_bytes(out, bytes);
+    }
+    resolvers.push(transcoder.transcode_slice::<u32>(displace, len)?);
+    Ok(len)
+}
+
+#[inline]
+fn transcode_hash_index_value(
+    pos: usize,
+    out: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<(), TranscodeError> {
+    let len = transcoder.read_usize(pos)?;
+    transcoder.write_usize(pos, out, len)?;
+    let (from, to) = (transcoder.from().bytes(), transcoder.to().bytes());
+    transcoder.write_rel_ptr(pos + from, out + to, resolvers.take())
+}
+
+// Archived hash maps store their hash index followed by a relative pointer to their entries, and
+// archived hash sets are archived hash maps with `()` values.
+
+#[inline]
+fn hash_map_layout(width: PointerWidth) -> (Layout, [usize; 2]) {
+    let mut offsets = [0; 2];
+    let layout = struct_layout(&[hash_index_layout(width), width.layout()], &mut offsets);
+    (layout, offsets)
+}
+
+#[inline]
+fn transcode_hash_map_dependencies<K: TranscodeWidth, V: TranscodeWidth>(
+    pos: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<(), TranscodeError> {
+    let (_, offsets) = hash_map_layout(transcoder.from());
+    let len = transcode_hash_index_dependencies(pos + offsets[0], transcoder, resolvers)?;
+    let entries = transcoder.read_rel_ptr(pos + offsets[1])?;
+    resolvers.push(transcoder.transcode_slice::<EntryWidth<K, V>>(entries, len)?);
+    Ok(())
+}
+
+#[inline]
+fn transcode_hash_map_value(
+    pos: usize,
+    out: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<(), TranscodeError> {
+    let (_, from) = hash_map_layout(transcoder.from());
+    let (_, to) = hash_map_layout(transcoder.to());
+    transcode_hash_index_value(pos + from[0], out + to[0], transcoder, resolvers)?;
+    transcoder.write_rel_ptr(pos + from[1], out + to[1], resolvers.take())
+}
+
+#[cfg(feature = "strict")]
+macro_rules! impl_hash_map {
+    ($ty:ident) => {
+        impl<K: TranscodeWidth, V: TranscodeWidth, S> TranscodeWidth for $ty<K, V, S> {
+            #[inline]
+            fn archived_layout(width: PointerWidth) -> Layout {
+                hash_map_layout(width).0
+            }
+
+            #[inline]
+            fn transcode_dependencies(
+                pos: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                transcode_hash_map_dependencies::<K, V>(pos, transcoder, resolvers)
+            }
+
+            #[inline]
+            fn transcode_value(
+                pos: usize,
+                out: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                transcode_hash_map_value(pos, out, transcoder, resolvers)
+            }
+        }
+    };
+}
+
+macro_rules! impl_hash_set {
+    ($ty:ident) => {
+        impl<K: TranscodeWidth, S> TranscodeWidth for $ty<K, S> {
+            #[inline]
+            fn archived_layout(width: PointerWidth) -> Layout {
+                hash_map_layout(width).0
+            }
+
+            #[inline]
+            fn transcode_dependencies(
+                pos: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                transcode_hash_map_dependencies::<K, ()>(pos, transcoder, resolvers)
+            }
+
+            #[inline]
+            fn transcode_value(
+                pos: usize,
+                out: usize,
+                transcoder: &mut WidthTranscoder<'_>,
+                resolvers: &mut WidthResolvers,
+            ) -> Result<(), TranscodeError> {
+                transcode_hash_map_value(pos, out, transcoder, resolvers)
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "std", feature = "strict"))]
+impl_hash_map!(HashMap);
+#[cfg(feature = "std")]
+impl_hash_set!(HashSet);
+
+#[cfg(feature = "hashbrown")]
+const _: () = {
+    #[cfg(feature = "strict")]
+    use hashbrown::HashMap;
+    use hashbrown::HashSet;
+
+    #[cfg(feature = "strict")]
+    impl_hash_map!(HashMap);
+    impl_hash_set!(HashSet);
+};
+
+// Archived index maps store their hash index, a relative pointer to their pivots, and a relative
+// pointer to their entries. The entries are written before the pivots.
+
+#[cfg(feature = "indexmap")]
+#[inline]
+fn index_map_layout(width: PointerWidth) -> (Layout, [usize; 3]) {
+    let mut offsets = [0; 3];
+    let fields = [hash_index_layout(width), width.layout(), width.layout()];
+    (struct_layout(&fields, &mut offsets), offsets)
+}
+
+#[cfg(feature = "indexmap")]
+#[inline]
+fn transcode_index_map_dependencies<K: TranscodeWidth, V: TranscodeWidth>(
+    pos: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<(), TranscodeError> {
+    let (_, offsets) = index_map_layout(transcoder.from());
+    let len = transcode_hash_index_dependencies(pos + offsets[0], transcoder, resolvers)?;
+    let entries = transcoder.read_rel_ptr(pos + offsets[2])?;
+    resolvers.push(transcoder.transcode_slice::<EntryWidth<K, V>>(entries, len)?);
+    let pivots = transcoder.read_rel_ptr(pos + offsets[1])?;
+    resolvers.push(transcoder.transcode_slice::<usize>(pivots, len)?);
+    Ok(())
+}
+
+#[cfg(feature = "indexmap")]
+#[inline]
+fn transcode_index_map_value(
+    pos: usize,
+    out: usize,
+    transcoder: &mut WidthTranscoder<'_>,
+    resolvers: &mut WidthResolvers,
+) -> Result<(), TranscodeError> {
+    let (_, from) = index_map_layout(transcoder.from());
+    let (_, to) = index_map_layout(transcoder.to());
+    transcode_hash_index_value(pos + from[0], out + to[0], transcoder, resolvers)?;
+    let entries = resolvers.take();
+    let pivots = resolvers.take();
+    transcoder.write_rel_ptr(pos + from[1], out + to[1], pivots)?;
+    transcoder.write_rel_ptr(pos + from[2], out + to[2], entries)
+}
+
+#[cfg(all(feature = "indexmap", feature = "strict"))]
+impl<K: TranscodeWidth, V: TranscodeWidth, S> TranscodeWidth for IndexMap<K, V, S> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        index_map_layout(width).0
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        transcode_index_map_dependencies::<K, V>(pos, transcoder, resolvers)
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        transcode_index_map_value(pos, out, transcoder, resolvers)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: TranscodeWidth, S> TranscodeWidth for IndexSet<K, S> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        index_map_layout(width).0
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        transcode_index_map_dependencies::<K, ()>(pos, transcoder, resolvers)
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        transcode_index_map_value(pos, out, transcoder, resolvers)
+    }
+}
+
+// Archived B-tree maps store their length and a relative pointer to their root node. Each node
+// starts with a header that holds its kind and number of entries, the distance back to the start
+// of its block, and a relative pointer. Node headers are only `repr(C)` with the `strict` feature,
+// so B-tree maps and sets are only supported when it is enabled.
+//
+// Nodes are written one level at a time starting from the leaves, and each level is written from
+// its greatest node to its least. Every node is preceded by the dependencies of its entries, which
+// together with the node make up its block.
+
+#[cfg(feature = "strict")]
+const _: () = {
+    #[cfg(not(feature = "std"))]
+    use ::alloc::{
+        collections::{BTreeMap, BTreeSet},
+        vec,
+        vec::Vec,
+    };
+    use core::mem;
+    #[cfg(feature = "std")]
+    use std::collections::{BTreeMap, BTreeSet};
+
+    const INNER_NODE_FLAG: u64 = 0x80_00;
+
+    #[inline]
+    fn node_header_layout(width: PointerWidth) -> (Layout, [usize; 3]) {
+        let mut offsets = [0; 3];
+        let fields = [
+            Layout::new::<Archived<u16>>(),
+            width.layout(),
+            width.layout(),
+        ];
+        (struct_layout(&fields, &mut offsets), offsets)
+    }
+
+    /// Returns the layout of a node with `len` entries of the given layout, and the offset of its
+    /// entries.
+    #[inline]
+    fn node_layout(width: PointerWidth, entry: Layout, len: usize) -> (Layout, usize) {
+        let (header, _) = node_header_layout(width);
+        let entry = entry.pad_to_align();
+        let (_, offset) = header.extend(entry).unwrap();
+        let align = usize::max(header.align(), entry.align());
+        let layout = Layout::from_size_align(offset + entry.size() * len, align).unwrap();
+        (layout, offset)
+    }
+
+    #[inline]
+    fn inner_entry_layout<K: TranscodeWidth>(width: PointerWidth) -> (Layout, [usize; 2]) {
+        let mut offsets = [0; 2];
+        let fields = [width.layout(), K::archived_layout(width)];
+        (struct_layout(&fields, &mut offsets), offsets)
+    }
+
+    /// A node in the source archive.
+    struct SourceNode {
+        pos: usize,
+        len: usize,
+        /// The index of the least child of the node in the next level down
+        children: usize,
+    }
+
+    /// Reads and claims the nodes of the B-tree with the given root, then returns them level by
+    /// level from the root to the leaves. Each level is in order by key.
+    fn read_levels<K: TranscodeWidth, V: TranscodeWidth>(
+        root: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<Vec<Vec<SourceNode>>, TranscodeError> {
+        let from = transcoder.from();
+        let (_, header) = node_header_layout(from);
+        let (inner_entry, inner_offsets) = inner_entry_layout::<K>(from);
+        let leaf_entry = EntryWidth::<K, V>::archived_layout(from);
+
+        let mut levels = Vec::new();
+        let mut next = vec![root];
+        loop {
+            let mut level = Vec::with_capacity(next.len());
+            let mut children = Vec::new();
+            let mut level_is_inner = None;
+            for pos in next {
+                let meta = transcoder.read_uint(pos + header[0], 2)?;
+                let is_inner = meta & INNER_NODE_FLAG != 0;
+                // Every node in a level must be the same kind
+                if *level_is_inner.get_or_insert(is_inner) != is_inner {
+                    return Err(TranscodeError::InvalidTag { pos });
+                }
+                let len = (meta & !INNER_NODE_FLAG) as usize;
+
+                let entry = if is_inner { inner_entry } else { leaf_entry };
+                let (layout, entries) = node_layout(from, entry, len);
+                transcoder.claim(pos, layout.size())?;
+
+                level.push(SourceNode {
+                    pos,
+                    len,
+                    children: children.len(),
+                });
+                if is_inner {
+                    children.push(transcoder.read_rel_ptr(pos + header[2])?);
+                    let stride = inner_entry.pad_to_align().size();
+                    for i in 0..len {
+                        let ptr_pos = pos + entries + i * stride + inner_offsets[0];
+                        children.push(transcoder.read_rel_ptr(ptr_pos)?);
+                    }
+                }
+            }
+            levels.push(level);
+
+            if level_is_inner != Some(true) {
+                return Ok(levels);
+            }
+            next = children;
+        }
+    }
+
+    /// Writes the header of a node to `out` in the new archive.
+    #[inline]
+    fn write_node_header(
+        pos: usize,
+        out: usize,
+        block_start: usize,
+        target: Option<usize>,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<(), TranscodeError> {
+        let (_, from) = node_header_layout(transcoder.from());
+        let (_, to) = node_header_layout(transcoder.to());
+        let meta = transcoder.read_bytes(pos + from[0], 2)?;
+        transcoder.write_bytes(out + to[0], meta);
+        transcoder.write_usize(pos + from[1], out + to[1], (out - block_start) as u64)?;
+        // The last leaf node points to itself
+        let target = target.unwrap_or(out + to[2]);
+        transcoder.write_rel_ptr(pos + from[2], out + to[2], target)
+    }
+
+    /// Writes a leaf node and the dependencies of its entries, then returns the position of the
+    /// node in the new archive. `next` is the position of the next leaf node in the new archive.
+    fn write_leaf<K: TranscodeWidth, V: TranscodeWidth>(
+        node: &SourceNode,
+        next: Option<usize>,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError> {
+        let (from, to) = (transcoder.from(), transcoder.to());
+        let from_entry = EntryWidth::<K, V>::archived_layout(from);
+        let to_entry = EntryWidth::<K, V>::archived_layout(to);
+        let (_, from_entries) = node_layout(from, from_entry, node.len);
+        let (to_layout, to_entries) = node_layout(to, to_entry, node.len);
+        let from_stride = from_entry.pad_to_align().size();
+        let to_stride = to_entry.pad_to_align().size();
+
+        let block_start = transcoder.pos();
+        let mut resolvers = WidthResolvers::new();
+        for i in (0..node.len).rev() {
+            let entry_pos = node.pos + from_entries + i * from_stride;
+            EntryWidth::<K, V>::transcode_dependencies(entry_pos, transcoder, &mut resolvers)?;
+        }
+
+        let out = transcoder.reserve(to_layout);
+        write_node_header(node.pos, out, block_start, next, transcoder)?;
+        for i in (0..node.len).rev() {
+            EntryWidth::<K, V>::transcode_value(
+                node.pos + from_entries + i * from_stride,
+                out + to_entries + i * to_stride,
+                transcoder,
+                &mut resolvers,
+            )?;
+        }
+        Ok(out)
+    }
+
+    /// Writes an inner node and the dependencies of its keys, then returns the position of the node
+    /// in the new archive. `children` are the positions of the next level down in the new archive.
+    fn write_inner<K: TranscodeWidth>(
+        node: &SourceNode,
+        children: &[usize],
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError> {
+        let (from, to) = (transcoder.from(), transcoder.to());
+        let (from_entry, from_offsets) = inner_entry_layout::<K>(from);
+        let (to_entry, to_offsets) = inner_entry_layout::<K>(to);
+        let (_, from_entries) = node_layout(from, from_entry, node.len);
+        let (to_layout, to_entries) = node_layout(to, to_entry, node.len);
+        let from_stride = from_entry.pad_to_align().size();
+        let to_stride = to_entry.pad_to_align().size();
+
+        let block_start = transcoder.pos();
+        let mut resolvers = WidthResolvers::new();
+        for i in (0..node.len).rev() {
+            let key_pos = node.pos + from_entries + i * from_stride + from_offsets[1];
+            K::transcode_dependencies(key_pos, transcoder, &mut resolvers)?;
+        }
+
+        // Inner nodes are aligned the same way that they are when serialized, which also aligns
+        // them for the unarchived node header
+        let out = transcoder.reserve(to_layout.align_to(mem::align_of::<usize>()).unwrap());
+        let least_child = children[node.children];
+        write_node_header(node.pos, out, block_start, Some(least_child), transcoder)?;
+        for i in (0..node.len).rev() {
+            let entry_pos = node.pos + from_entries + i * from_stride;
+            let entry_out = out + to_entries + i * to_stride;
+            let child = children[node.children + 1 + i];
+            transcoder.write_rel_ptr(
+                entry_pos + from_offsets[0],
+                entry_out + to_offsets[0],
+                child,
+            )?;
+            K::transcode_value(
+                entry_pos + from_offsets[1],
+                entry_out + to_offsets[1],
+                transcoder,
+                &mut resolvers,
+            )?;
+        }
+        Ok(out)
+    }
+
+    #[inline]
+    fn btree_map_layout(width: PointerWidth) -> Layout {
+        struct_layout(&[width.layout(), width.layout()], &mut [0; 2])
+    }
+
+    fn transcode_btree_map_dependencies<K: TranscodeWidth, V: TranscodeWidth>(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        // Empty maps point their root to the start of the archive
+        if transcoder.read_usize(pos)? == 0 {
+            resolvers.push(0);
+            return Ok(());
+        }
+
+        let root = transcoder.read_rel_ptr(pos + transcoder.from().bytes())?;
+        let root_out = transcoder.nested(pos, |transcoder| {
+            let levels = read_levels::<K, V>(root, transcoder)?;
+
+            let mut outs = Vec::new();
+            for (depth, level) in levels.iter().enumerate().rev() {
+                let children = mem::replace(&mut outs, vec![0; level.len()]);
+                let mut next = None;
+                for (i, node) in level.iter().enumerate().rev() {
+                    let out = if depth == levels.len() - 1 {
+                        write_leaf::<K, V>(node, next, transcoder)?
+                    } else {
+                        write_inner::<K>(node, &children, transcoder)?
+                    };
+                    outs[i] = out;
+                    next = Some(out);
+                }
+            }
+            Ok(outs[0])
+        })?;
+        resolvers.push(root_out);
+        Ok(())
+    }
+
+    #[inline]
+    fn transcode_btree_map_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let len = transcoder.read_usize(pos)?;
+        transcoder.write_usize(pos, out, len)?;
+        let (from, to) = (transcoder.from().bytes(), transcoder.to().bytes());
+        transcoder.write_rel_ptr(pos + from, out + to, resolvers.take())
+    }
+
+    impl<K: TranscodeWidth, V: TranscodeWidth> TranscodeWidth for BTreeMap<K, V> {
+        #[inline]
+        fn archived_layout(width: PointerWidth) -> Layout {
+            btree_map_layout(width)
+        }
+
+        #[inline]
+        fn transcode_dependencies(
+            pos: usize,
+            transcoder: &mut WidthTranscoder<'_>,
+            resolvers: &mut WidthResolvers,
+        ) -> Result<(), TranscodeError> {
+            transcode_btree_map_dependencies::<K, V>(pos, transcoder, resolvers)
+        }
+
+        #[inline]
+        fn transcode_value(
+            pos: usize,
+            out: usize,
+            transcoder: &mut WidthTranscoder<'_>,
+            resolvers: &mut WidthResolvers,
+        ) -> Result<(), TranscodeError> {
+            transcode_btree_map_value(pos, out, transcoder, resolvers)
+        }
+    }
+
+    impl<K: TranscodeWidth> TranscodeWidth for BTreeSet<K> {
+        #[inline]
+        fn archived_layout(width: PointerWidth) -> Layout {
+            btree_map_layout(width)
+        }
+
+        #[inline]
+        fn transcode_dependencies(
+            pos: usize,
+            transcoder: &mut WidthTranscoder<'_>,
+            resolvers: &mut WidthResolvers,
+        ) -> Result<(), TranscodeError> {
+            transcode_btree_map_dependencies::<K, ()>(pos, transcoder, resolvers)
+        }
+
+        #[inline]
+        fn transcode_value(
+            pos: usize,
+            out: usize,
+            transcoder: &mut WidthTranscoder<'_>,
+            resolvers: &mut WidthResolvers,
+        ) -> Result<(), TranscodeError> {
+            transcode_btree_map_value(pos, out, transcoder, resolvers)
+        }
+    }
+};
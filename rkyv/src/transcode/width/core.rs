@@ -0,0 +1,309 @@
+use crate::{
+    transcode::{
+        width::{
+            array_layout, struct_layout, union_layout, PointerWidth, TranscodeWidth,
+            WidthResolvers, WidthTranscoder,
+        },
+        TranscodeError,
+    },
+    Archived,
+};
+use core::{
+    alloc::Layout,
+    marker::{PhantomData, PhantomPinned},
+    mem::ManuallyDrop,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+    time::Duration,
+};
+
+macro_rules! impl_fixed {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl TranscodeWidth for $ty {
+                #[inline]
+                fn archived_layout(_: PointerWidth) -> Layout {
+                    Layout::new::<Archived<$ty>>()
+                }
+
+                #[inline]
+                fn transcode_dependencies(
+                    _: usize,
+                    _: &mut WidthTranscoder<'_>,
+                    _: &mut WidthResolvers,
+                ) -> Result<(), TranscodeError> {
+                    Ok(())
+                }
+
+                #[inline]
+                fn transcode_value(
+                    pos: usize,
+                    out: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    _: &mut WidthResolvers,
+                ) -> Result<(), TranscodeError> {
+                    let bytes = transcoder.read_bytes(pos, Layout::new::<Archived<$ty>>().size())?;
+                    transcoder.write_bytes(out, bytes);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed!(
+    (),
+    bool,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    f32,
+    f64,
+    char
+);
+impl_fixed!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128);
+impl_fixed!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128);
+impl_fixed!(PhantomPinned, Duration);
+
+macro_rules! impl_size {
+    ($read:ident, $write:ident, $($ty:ty),* $(,)?) => {
+        $(
+            impl TranscodeWidth for $ty {
+                #[inline]
+                fn archived_layout(width: PointerWidth) -> Layout {
+                    width.layout()
+                }
+
+                #[inline]
+                fn transcode_dependencies(
+                    _: usize,
+                    _: &mut WidthTranscoder<'_>,
+                    _: &mut WidthResolvers,
+                ) -> Result<(), TranscodeError> {
+                    Ok(())
+                }
+
+                #[inline]
+                fn transcode_value(
+                    pos: usize,
+                    out: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    _: &mut WidthResolvers,
+                ) -> Result<(), TranscodeError> {
+                    let value = transcoder.$read(pos)?;
+                    transcoder.$write(pos, out, value)
+                }
+            }
+        )*
+    };
+}
+
+impl_size!(read_usize, write_usize, usize, NonZeroUsize);
+impl_size!(read_isize, write_isize, isize, NonZeroIsize);
+
+impl<T: ?Sized> TranscodeWidth for PhantomData<T> {
+    #[inline]
+    fn archived_layout(_: PointerWidth) -> Layout {
+        Layout::new::<PhantomData<T>>()
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        _: usize,
+        _: &mut WidthTranscoder<'_>,
+        _: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn transcode_value(
+        _: usize,
+        _: usize,
+        _: &mut WidthTranscoder<'_>,
+        _: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        Ok(())
+    }
+}
+
+impl<T: TranscodeWidth> TranscodeWidth for ManuallyDrop<T> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        T::archived_layout(width)
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        T::transcode_dependencies(pos, transcoder, resolvers)
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        T::transcode_value(pos, out, transcoder, resolvers)
+    }
+}
+
+impl<T: TranscodeWidth, const N: usize> TranscodeWidth for [T; N] {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        array_layout(T::archived_layout(width), N)
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let stride = T::archived_layout(transcoder.from()).pad_to_align().size();
+        for i in 0..N {
+            T::transcode_dependencies(pos + i * stride, transcoder, resolvers)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let from_stride = T::archived_layout(transcoder.from()).pad_to_align().size();
+        let to_stride = T::archived_layout(transcoder.to()).pad_to_align().size();
+        for i in 0..N {
+            T::transcode_value(
+                pos + i * from_stride,
+                out + i * to_stride,
+                transcoder,
+                resolvers,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Archived options and results are `repr(u8)` enums, which are laid out like a union of `repr(C)`
+// structs that each start with the tag.
+
+#[inline]
+fn variant_layout(field: Layout) -> (Layout, usize) {
+    let mut offsets = [0; 2];
+    let layout = struct_layout(&[Layout::new::<u8>(), field], &mut offsets);
+    (layout, offsets[1])
+}
+
+#[inline]
+fn read_tag(transcoder: &WidthTranscoder<'_>, pos: usize) -> Result<u8, TranscodeError> {
+    Ok(transcoder.read_bytes(pos, 1)?[0])
+}
+
+impl<T: TranscodeWidth> TranscodeWidth for Option<T> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        let (some, _) = variant_layout(T::archived_layout(width));
+        union_layout(&[Layout::new::<u8>(), some])
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        match read_tag(transcoder, pos)? {
+            0 => Ok(()),
+            1 => {
+                let (_, offset) = variant_layout(T::archived_layout(transcoder.from()));
+                T::transcode_dependencies(pos + offset, transcoder, resolvers)
+            }
+            _ => Err(TranscodeError::InvalidTag { pos }),
+        }
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let tag = read_tag(transcoder, pos)?;
+        transcoder.write_bytes(out, &[tag]);
+        if tag == 1 {
+            let (_, from) = variant_layout(T::archived_layout(transcoder.from()));
+            let (_, to) = variant_layout(T::archived_layout(transcoder.to()));
+            T::transcode_value(pos + from, out + to, transcoder, resolvers)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: TranscodeWidth, E: TranscodeWidth> TranscodeWidth for Result<T, E> {
+    #[inline]
+    fn archived_layout(width: PointerWidth) -> Layout {
+        let (ok, _) = variant_layout(T::archived_layout(width));
+        let (err, _) = variant_layout(E::archived_layout(width));
+        union_layout(&[ok, err])
+    }
+
+    #[inline]
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let from = transcoder.from();
+        match read_tag(transcoder, pos)? {
+            0 => {
+                let (_, offset) = variant_layout(T::archived_layout(from));
+                T::transcode_dependencies(pos + offset, transcoder, resolvers)
+            }
+            1 => {
+                let (_, offset) = variant_layout(E::archived_layout(from));
+                E::transcode_dependencies(pos + offset, transcoder, resolvers)
+            }
+            _ => Err(TranscodeError::InvalidTag { pos }),
+        }
+    }
+
+    #[inline]
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError> {
+        let (from, to) = (transcoder.from(), transcoder.to());
+        let tag = read_tag(transcoder, pos)?;
+        transcoder.write_bytes(out, &[tag]);
+        if tag == 0 {
+            let (_, from) = variant_layout(T::archived_layout(from));
+            let (_, to) = variant_layout(T::archived_layout(to));
+            T::transcode_value(pos + from, out + to, transcoder, resolvers)
+        } else {
+            let (_, from) = variant_layout(E::archived_layout(from));
+            let (_, to) = variant_layout(E::archived_layout(to));
+            E::transcode_value(pos + from, out + to, transcoder, resolvers)
+        }
+    }
+}
@@ -0,0 +1,641 @@
+//! Pointer width transcoding for existing archives.
+//!
+//! Archived `usize`, `isize`, and relative pointers are stored using the integer size selected by
+//! the `size_16`, `size_32`, and `size_64` features. Changing the size changes the layout of every
+//! type that contains them, so an archive written with one size cannot be read by a program built
+//! with another. [`transcode_width_root`] re-lays out an archive for a different pointer width by
+//! walking it from the root and writing a new archive.
+//!
+//! Width transcoding is implemented on the unarchived types through [`TranscodeWidth`] because
+//! their archived types are not enough to tell an archived `usize` from an archived `u32`. It can
+//! be derived with `#[archive(transcode_width)]`, which requires archived structs to be
+//! `repr(C)` or `repr(transparent)` so that their layout can be computed for any pointer width.
+//!
+//! Both the source and the result are in the archived endianness. To change the endianness too,
+//! combine this with [`transcode_root_in_place`](crate::transcode::transcode_root_in_place).
+//!
+//! ## Examples
+//!
+//! ```
+//! use rkyv::{
+//!     archived_root,
+//!     ser::{serializers::AllocSerializer, Serializer},
+//!     transcode::width::{transcode_width_root, PointerWidth},
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(transcode_width)]
+//! #[archive_attr(repr(C))]
+//! struct Example {
+//!     id: u32,
+//!     name: String,
+//!     values: Vec<usize>,
+//! }
+//!
+//! let value = Example {
+//!     id: 42,
+//!     name: "a name that is too long to inline".to_string(),
+//!     values: vec![1, 2, 3],
+//! };
+//!
+//! let mut serializer = AllocSerializer::<256>::default();
+//! serializer.serialize_value(&value).unwrap();
+//! let bytes = serializer.into_serializer().into_inner();
+//!
+//! // Widen the archive to 64-bit pointers...
+//! let wide = transcode_width_root::<Example>(
+//!     &bytes,
+//!     PointerWidth::ARCHIVED,
+//!     PointerWidth::Size64,
+//! )
+//! .unwrap();
+//!
+//! // ...and narrow it back again
+//! let narrow = transcode_width_root::<Example>(
+//!     &wide,
+//!     PointerWidth::Size64,
+//!     PointerWidth::ARCHIVED,
+//! )
+//! .unwrap();
+//!
+//! let archived = unsafe { archived_root::<Example>(&narrow) };
+//! assert_eq!(archived.id, 42);
+//! assert_eq!(archived.name, "a name that is too long to inline");
+//! assert_eq!(archived.values.as_slice(), &[1, 2, 3]);
+//! ```
+//!
+//! ## Collections and tuples
+//!
+//! Hash sets and index sets can always be transcoded. Hash maps, index maps, B-tree maps, and
+//! B-tree sets can only be transcoded with the `strict` feature, because their entries and B-tree
+//! nodes are only `repr(C)` when it is enabled.
+//!
+//! Tuples can't be transcoded. Archived tuples use the Rust representation, so their layout for
+//! another pointer width can't be computed. Use a `repr(C)` struct instead:
+//!
+//! ```compile_fail
+//! use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+//!
+//! // `TranscodeWidth` is not implemented for `(u32, usize)`
+//! transcode_width_root::<(u32, usize)>(&[], PointerWidth::ARCHIVED, PointerWidth::Size64);
+//! ```
+
+mod alloc;
+mod collections;
+mod core;
+
+use crate::{
+    transcode::{Endianness, TranscodeError},
+    AlignedVec, FixedUsize,
+};
+#[cfg(not(feature = "std"))]
+use ::alloc::{collections::BTreeMap, vec::Vec};
+use ::core::{alloc::Layout, mem};
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, HashMap};
+
+/// The size of the integers used for archived `usize`, `isize`, and relative pointers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PointerWidth {
+    /// 16-bit integers, as selected by the `size_16` feature
+    Size16,
+    /// 32-bit integers, as selected by the `size_32` feature
+    Size32,
+    /// 64-bit integers, as selected by the `size_64` feature
+    Size64,
+}
+
+impl PointerWidth {
+    /// The pointer width that archives are read and written with by this build of rkyv.
+    pub const ARCHIVED: Self = match mem::size_of::<FixedUsize>() {
+        2 => PointerWidth::Size16,
+        4 => PointerWidth::Size32,
+        _ => PointerWidth::Size64,
+    };
+
+    /// Returns the number of bytes in an integer of this width.
+    #[inline]
+    pub const fn bytes(self) -> usize {
+        match self {
+            PointerWidth::Size16 => 2,
+            PointerWidth::Size32 => 4,
+            PointerWidth::Size64 => 8,
+        }
+    }
+
+    /// Returns the layout of an archived `usize` or `isize` of this width.
+    #[inline]
+    pub fn layout(self) -> Layout {
+        #[cfg(not(feature = "unaligned"))]
+        let align = self.bytes();
+        #[cfg(feature = "unaligned")]
+        let align = 1;
+        Layout::from_size_align(self.bytes(), align).unwrap()
+    }
+}
+
+/// Computes the layout of a `repr(C)` struct with fields of the given layouts.
+///
+/// The offset of each field is written to the corresponding element of `offsets`.
+#[inline]
+pub fn struct_layout(fields: &[Layout], offsets: &mut [usize]) -> Layout {
+    let mut layout = Layout::from_size_align(0, 1).unwrap();
+    for (field, offset) in fields.iter().zip(offsets.iter_mut()) {
+        let (extended, field_offset) = layout.extend(*field).unwrap();
+        layout = extended;
+        *offset = field_offset;
+    }
+    layout.pad_to_align()
+}
+
+/// Computes the layout of a `repr(C)` union or the data of a `repr(u*)`/`repr(i*)` enum with
+/// variants of the given layouts.
+#[inline]
+pub fn union_layout(variants: &[Layout]) -> Layout {
+    let (size, align) = variants.iter().fold((0, 1), |(size, align), variant| {
+        (size.max(variant.size()), align.max(variant.align()))
+    });
+    Layout::from_size_align(size, align).unwrap().pad_to_align()
+}
+
+/// Computes the layout of an array of `len` values of the given layout.
+#[inline]
+pub fn array_layout(element: Layout, len: usize) -> Layout {
+    let element = element.pad_to_align();
+    Layout::from_size_align(element.size() * len, element.align()).unwrap()
+}
+
+/// The positions that the dependencies of some values were written to.
+///
+/// [`TranscodeWidth::transcode_dependencies`] pushes positions in the order that it writes
+/// dependencies, and [`TranscodeWidth::transcode_value`] takes them back out in the same order.
+#[derive(Debug, Default)]
+pub struct WidthResolvers {
+    positions: Vec<usize>,
+    next: usize,
+}
+
+impl WidthResolvers {
+    /// Creates a new empty set of resolvers.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the position of a dependency.
+    #[inline]
+    pub fn push(&mut self, pos: usize) {
+        self.positions.push(pos);
+    }
+
+    /// Takes the next position that was pushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every position has already been taken.
+    #[inline]
+    pub fn take(&mut self) -> usize {
+        let result = self.positions[self.next];
+        self.next += 1;
+        result
+    }
+}
+
+/// The maximum depth that values may be nested to by default.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// A context for re-laying out an archive for a different pointer width.
+///
+/// The transcoder reads from the source archive and writes to a new archive. Positions passed to
+/// the read functions are positions in the source archive, and positions passed to the write
+/// functions are positions in the new archive.
+///
+/// The source archive does not need to be valid. Every value that is transcoded out of line must
+/// not overlap any other, which rules out cycles and repeated subtrees, and values may only be
+/// nested up to a maximum depth.
+pub struct WidthTranscoder<'a> {
+    bytes: &'a [u8],
+    from: PointerWidth,
+    to: PointerWidth,
+    out: AlignedVec,
+    shared: HashMap<usize, usize>,
+    claimed: BTreeMap<usize, usize>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> WidthTranscoder<'a> {
+    /// Creates a new transcoder that converts the given bytes from one pointer width to another.
+    #[inline]
+    pub fn new(bytes: &'a [u8], from: PointerWidth, to: PointerWidth) -> Self {
+        Self::with_max_depth(bytes, from, to, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Creates a new transcoder that converts the given bytes from one pointer width to another
+    /// and nests values up to the given depth.
+    #[inline]
+    pub fn with_max_depth(
+        bytes: &'a [u8],
+        from: PointerWidth,
+        to: PointerWidth,
+        max_depth: usize,
+    ) -> Self {
+        Self {
+            bytes,
+            from,
+            to,
+            out: AlignedVec::with_capacity(bytes.len()),
+            shared: HashMap::new(),
+            claimed: BTreeMap::new(),
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// Returns the pointer width of the source archive.
+    #[inline]
+    pub fn from(&self) -> PointerWidth {
+        self.from
+    }
+
+    /// Returns the pointer width of the new archive.
+    #[inline]
+    pub fn to(&self) -> PointerWidth {
+        self.to
+    }
+
+    /// Returns the length of the new archive so far.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.out.len()
+    }
+
+    /// Consumes the transcoder and returns the new archive.
+    #[inline]
+    pub fn into_inner(self) -> AlignedVec {
+        self.out
+    }
+
+    /// Returns `len` bytes of the source archive starting at `pos`.
+    #[inline]
+    pub fn read_bytes(&self, pos: usize, len: usize) -> Result<&'a [u8], TranscodeError> {
+        if pos > self.bytes.len() || self.bytes.len() - pos < len {
+            Err(TranscodeError::Overrun {
+                pos,
+                size: len,
+                archive_len: self.bytes.len(),
+            })
+        } else {
+            Ok(&self.bytes[pos..pos + len])
+        }
+    }
+
+    fn read_uint(&self, pos: usize, size: usize) -> Result<u64, TranscodeError> {
+        let bytes = self.read_bytes(pos, size)?;
+        let fold = |value: u64, byte: &u8| value << 8 | *byte as u64;
+        Ok(match Endianness::ARCHIVED {
+            Endianness::Little => bytes.iter().rev().fold(0, fold),
+            Endianness::Big => bytes.iter().fold(0, fold),
+        })
+    }
+
+    /// Reads an archived `usize` from the source archive.
+    #[inline]
+    pub fn read_usize(&self, pos: usize) -> Result<u64, TranscodeError> {
+        self.read_uint(pos, self.from.bytes())
+    }
+
+    /// Reads an archived `isize` from the source archive.
+    #[inline]
+    pub fn read_isize(&self, pos: usize) -> Result<i64, TranscodeError> {
+        let shift = 64 - 8 * self.from.bytes();
+        Ok((self.read_usize(pos)? << shift) as i64 >> shift)
+    }
+
+    /// Reads the relative pointer at `pos` in the source archive and returns the position it
+    /// points to.
+    #[inline]
+    pub fn read_rel_ptr(&self, pos: usize) -> Result<usize, TranscodeError> {
+        let offset = self.read_isize(pos)? as isize;
+        let target = pos as isize + offset;
+        if target < 0 || target as usize > self.bytes.len() {
+            Err(TranscodeError::OutOfBounds { pos, offset })
+        } else {
+            Ok(target as usize)
+        }
+    }
+
+    /// Reserves space for a value with the given layout at the end of the new archive and returns
+    /// its position.
+    ///
+    /// The reserved space is zeroed.
+    #[inline]
+    pub fn reserve(&mut self, layout: Layout) -> usize {
+        let padding = (layout.align() - self.out.len() % layout.align()) % layout.align();
+        let pos = self.out.len() + padding;
+        self.out.resize(pos + layout.size(), 0);
+        pos
+    }
+
+    /// Writes bytes to the new archive at `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes were not reserved.
+    #[inline]
+    pub fn write_bytes(&mut self, out: usize, bytes: &[u8]) {
+        self.out[out..out + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn write_uint(&mut self, out: usize, value: u64) {
+        let size = self.to.bytes();
+        let bytes = match Endianness::ARCHIVED {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => (value << (64 - 8 * size)).to_be_bytes(),
+        };
+        self.write_bytes(out, &bytes[..size]);
+    }
+
+    /// Writes an archived `usize` to the new archive at `out`.
+    ///
+    /// `pos` is the position of the value in the source archive, which is reported if the value
+    /// is too large for the new pointer width.
+    #[inline]
+    pub fn write_usize(
+        &mut self,
+        pos: usize,
+        out: usize,
+        value: u64,
+    ) -> Result<(), TranscodeError> {
+        let bits = 8 * self.to.bytes();
+        if bits < 64 && value >> bits != 0 {
+            return Err(TranscodeError::Overflow {
+                pos,
+                value: value as i128,
+                width: self.to,
+            });
+        }
+        self.write_uint(out, value);
+        Ok(())
+    }
+
+    /// Writes an archived `isize` to the new archive at `out`.
+    ///
+    /// `pos` is the position of the value in the source archive, which is reported if the value
+    /// is too large for the new pointer width.
+    #[inline]
+    pub fn write_isize(
+        &mut self,
+        pos: usize,
+        out: usize,
+        value: i64,
+    ) -> Result<(), TranscodeError> {
+        let bits = 8 * self.to.bytes();
+        if bits < 64 && (value << (64 - bits)) >> (64 - bits) != value {
+            return Err(TranscodeError::Overflow {
+                pos,
+                value: value as i128,
+                width: self.to,
+            });
+        }
+        self.write_uint(out, value as u64);
+        Ok(())
+    }
+
+    /// Writes a relative pointer to the new archive at `out` that points to `target`.
+    ///
+    /// `pos` is the position of the relative pointer in the source archive, which is reported if
+    /// the offset is too large for the new pointer width.
+    #[inline]
+    pub fn write_rel_ptr(
+        &mut self,
+        pos: usize,
+        out: usize,
+        target: usize,
+    ) -> Result<(), TranscodeError> {
+        self.write_isize(pos, out, target as i64 - out as i64)
+    }
+
+    /// Marks `size` bytes at `pos` in the source archive as belonging to a value that is being
+    /// transcoded, and checks that they don't overlap any other value.
+    fn claim(&mut self, pos: usize, size: usize) -> Result<(), TranscodeError> {
+        self.read_bytes(pos, size)?;
+        if size == 0 {
+            return Ok(());
+        }
+        let end = pos + size;
+        if let Some((_, prev_end)) = self.claimed.range(..end).next_back() {
+            if *prev_end > pos {
+                return Err(TranscodeError::Overlap { pos, size });
+            }
+        }
+        self.claimed.insert(pos, end);
+        Ok(())
+    }
+
+    /// Runs `f` one level deeper, failing if that is deeper than the maximum depth.
+    fn nested<R>(
+        &mut self,
+        pos: usize,
+        f: impl FnOnce(&mut Self) -> Result<R, TranscodeError>,
+    ) -> Result<R, TranscodeError> {
+        if self.depth >= self.max_depth {
+            return Err(TranscodeError::ExceededMaximumDepth {
+                pos,
+                max_depth: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Transcodes the value at `pos` in the source archive and its dependencies, then returns the
+    /// position of the value in the new archive.
+    #[inline]
+    pub fn transcode_out_of_line<T: TranscodeWidth + ?Sized>(
+        &mut self,
+        pos: usize,
+    ) -> Result<usize, TranscodeError> {
+        self.claim(pos, T::archived_layout(self.from).size())?;
+        self.nested(pos, |transcoder| {
+            let mut resolvers = WidthResolvers::new();
+            T::transcode_dependencies(pos, transcoder, &mut resolvers)?;
+            let out = transcoder.reserve(T::archived_layout(transcoder.to));
+            T::transcode_value(pos, out, transcoder, &mut resolvers)?;
+            Ok(out)
+        })
+    }
+
+    /// Transcodes `len` consecutive values starting at `pos` in the source archive and their
+    /// dependencies, then returns the position of the first value in the new archive.
+    #[inline]
+    pub fn transcode_slice<T: TranscodeWidth>(
+        &mut self,
+        pos: usize,
+        len: usize,
+    ) -> Result<usize, TranscodeError> {
+        let from_stride = T::archived_layout(self.from).pad_to_align().size();
+        let to_stride = T::archived_layout(self.to).pad_to_align().size();
+        let size = if from_stride == 0 {
+            // Zero-sized elements take up no bytes, so allow at most one per remaining byte to
+            // keep malformed lengths from looping for too long
+            if len > self.bytes.len().saturating_sub(pos) {
+                return Err(TranscodeError::Overrun {
+                    pos,
+                    size: len,
+                    archive_len: self.bytes.len(),
+                });
+            }
+            0
+        } else {
+            from_stride.saturating_mul(len)
+        };
+        self.claim(pos, size)?;
+
+        self.nested(pos, |transcoder| {
+            let mut resolvers = WidthResolvers::new();
+            for i in 0..len {
+                T::transcode_dependencies(pos + i * from_stride, transcoder, &mut resolvers)?;
+            }
+            let out = transcoder.reserve(array_layout(T::archived_layout(transcoder.to), len));
+            for i in 0..len {
+                T::transcode_value(
+                    pos + i * from_stride,
+                    out + i * to_stride,
+                    transcoder,
+                    &mut resolvers,
+                )?;
+            }
+            Ok(out)
+        })
+    }
+
+    /// Copies `len` bytes starting at `pos` in the source archive to the end of the new archive,
+    /// then returns their position in the new archive.
+    #[inline]
+    pub fn transcode_bytes(&mut self, pos: usize, len: usize) -> Result<usize, TranscodeError> {
+        let bytes = self.read_bytes(pos, len)?;
+        let out = self.reserve(Layout::from_size_align(len, 1).unwrap());
+        self.write_bytes(out, bytes);
+        Ok(out)
+    }
+
+    /// Transcodes a shared value at `pos` in the source archive with the given function, unless it
+    /// has already been transcoded. Returns the position of the value in the new archive.
+    #[inline]
+    pub fn transcode_shared(
+        &mut self,
+        pos: usize,
+        f: impl FnOnce(&mut Self) -> Result<usize, TranscodeError>,
+    ) -> Result<usize, TranscodeError> {
+        if let Some(out) = self.shared.get(&pos) {
+            Ok(*out)
+        } else {
+            let out = f(self)?;
+            self.shared.insert(pos, out);
+            Ok(out)
+        }
+    }
+}
+
+/// A type whose archived form can be re-laid out for a different pointer width.
+///
+/// Transcoding happens in two passes for each value, like serializing and resolving. First,
+/// [`transcode_dependencies`](TranscodeWidth::transcode_dependencies) writes any values that the
+/// archived value points to and records their new positions. Then,
+/// [`transcode_value`](TranscodeWidth::transcode_value) writes the archived value itself using
+/// those positions.
+///
+/// This trait can be derived for types with `#[archive(transcode_width)]`.
+pub trait TranscodeWidth {
+    /// Returns the layout of the archived type when archived with the given pointer width.
+    fn archived_layout(width: PointerWidth) -> Layout;
+
+    /// Writes the values that the archived value at `pos` in the source archive points to, and
+    /// pushes their positions in the new archive to `resolvers`.
+    fn transcode_dependencies(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError>;
+
+    /// Writes the archived value at `pos` in the source archive to `out` in the new archive.
+    ///
+    /// `out` has already been reserved with the layout of the archived type for the new pointer
+    /// width.
+    fn transcode_value(
+        pos: usize,
+        out: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+        resolvers: &mut WidthResolvers,
+    ) -> Result<(), TranscodeError>;
+}
+
+/// A type that can be pointed to by a relative pointer that is being re-laid out for a different
+/// pointer width.
+///
+/// This is implemented for all sized types that implement [`TranscodeWidth`], as well as for
+/// slices and `str`.
+pub trait TranscodeWidthUnsized {
+    /// Returns the layout of an archived relative pointer to the type when archived with the given
+    /// pointer width.
+    fn archived_ptr_layout(width: PointerWidth) -> Layout;
+
+    /// Transcodes the value that the relative pointer at `pos` in the source archive points to,
+    /// then returns the position of the value in the new archive.
+    fn transcode_pointee(
+        pos: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<usize, TranscodeError>;
+
+    /// Writes a relative pointer to `out` in the new archive that points to `target` and has the
+    /// same metadata as the relative pointer at `pos` in the source archive.
+    fn transcode_ptr(
+        pos: usize,
+        out: usize,
+        target: usize,
+        transcoder: &mut WidthTranscoder<'_>,
+    ) -> Result<(), TranscodeError>;
+}
+
+/// Re-lays out the archived root of the given bytes from one pointer width to another.
+///
+/// The root of the new archive is located at the end of the returned bytes, so it can be accessed
+/// with [`archived_root`](crate::archived_root) by a program built with the new pointer width.
+/// Values may be nested up to [`DEFAULT_MAX_DEPTH`] deep.
+#[inline]
+pub fn transcode_width_root<T: TranscodeWidth + ?Sized>(
+    bytes: &[u8],
+    from: PointerWidth,
+    to: PointerWidth,
+) -> Result<AlignedVec, TranscodeError> {
+    transcode_width_root_with_max_depth::<T>(bytes, from, to, DEFAULT_MAX_DEPTH)
+}
+
+/// Re-lays out the archived root of the given bytes from one pointer width to another, nesting
+/// values up to the given depth.
+#[inline]
+pub fn transcode_width_root_with_max_depth<T: TranscodeWidth + ?Sized>(
+    bytes: &[u8],
+    from: PointerWidth,
+    to: PointerWidth,
+    max_depth: usize,
+) -> Result<AlignedVec, TranscodeError> {
+    let size = T::archived_layout(from).size();
+    if bytes.len() < size {
+        return Err(TranscodeError::Overrun {
+            pos: 0,
+            size,
+            archive_len: bytes.len(),
+        });
+    }
+
+    let mut transcoder = WidthTranscoder::with_max_depth(bytes, from, to, max_depth);
+    transcoder.transcode_out_of_line::<T>(bytes.len() - size)?;
+    Ok(transcoder.into_inner())
+}
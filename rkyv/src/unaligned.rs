@@ -0,0 +1,379 @@
+//! Archived primitives with an alignment of 1.
+//!
+//! With the `unaligned` feature, multibyte primitives are archived as [`Unaligned`] values instead
+//! of their aligned counterparts. Every archived type then has an alignment of 1, so archives can
+//! be accessed directly from any byte slice without copying them into an aligned buffer first.
+//!
+//! Unaligned primitives are stored in the endianness selected by the `archive_le` and `archive_be`
+//! features, or in the native endianness if neither is enabled. Types from `rend` are still archived
+//! as themselves and keep their alignment, and multibyte atomics and enum discriminants are not
+//! supported.
+
+use core::{
+    cmp, fmt, hash,
+    iter::{Product, Sum},
+    marker::PhantomData,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroU128, NonZeroU16, NonZeroU32,
+        NonZeroU64,
+    },
+};
+
+/// A primitive that can be stored in an [`Unaligned`].
+pub trait UnalignedPrimitive: Copy {
+    /// The archived bytes of the primitive.
+    type Bytes: Copy;
+
+    /// Converts the primitive to its archived bytes.
+    fn to_archived_bytes(self) -> Self::Bytes;
+
+    /// Converts some archived bytes back to the primitive.
+    ///
+    /// Primitives with invalid bit patterns (such as `char` and the `NonZero` integers) do not
+    /// check their bytes, so the bytes must be valid for the primitive.
+    fn from_archived_bytes(bytes: Self::Bytes) -> Self;
+}
+
+macro_rules! to_bytes {
+    ($value:expr) => {{
+        #[cfg(feature = "archive_le")]
+        {
+            $value.to_le_bytes()
+        }
+        #[cfg(feature = "archive_be")]
+        {
+            $value.to_be_bytes()
+        }
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        {
+            $value.to_ne_bytes()
+        }
+    }};
+}
+
+macro_rules! from_bytes {
+    ($ty:ty, $bytes:expr) => {{
+        #[cfg(feature = "archive_le")]
+        {
+            <$ty>::from_le_bytes($bytes)
+        }
+        #[cfg(feature = "archive_be")]
+        {
+            <$ty>::from_be_bytes($bytes)
+        }
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        {
+            <$ty>::from_ne_bytes($bytes)
+        }
+    }};
+}
+
+macro_rules! impl_unaligned_primitive {
+    (@common [$($qualifier:tt)*] $ty:ty, |$value:ident| $to:expr, |$bytes:ident| $from:expr) => {
+        impl Unaligned<$ty> {
+            /// Creates a new unaligned primitive from the given value.
+            #[inline]
+            pub $($qualifier)* fn new($value: $ty) -> Self {
+                Self {
+                    bytes: $to,
+                    _phantom: PhantomData,
+                }
+            }
+
+            /// Returns the value of the unaligned primitive.
+            #[inline]
+            pub $($qualifier)* fn value(&self) -> $ty {
+                let $bytes = self.bytes;
+                $from
+            }
+        }
+
+        impl UnalignedPrimitive for $ty {
+            type Bytes = [u8; core::mem::size_of::<$ty>()];
+
+            #[inline]
+            fn to_archived_bytes(self) -> Self::Bytes {
+                let $value = self;
+                $to
+            }
+
+            #[inline]
+            fn from_archived_bytes($bytes: Self::Bytes) -> Self {
+                $from
+            }
+        }
+
+        impl From<Unaligned<$ty>> for $ty {
+            #[inline]
+            fn from(value: Unaligned<$ty>) -> Self {
+                value.value()
+            }
+        }
+
+        impl<'a> From<&'a Unaligned<$ty>> for $ty {
+            #[inline]
+            fn from(value: &'a Unaligned<$ty>) -> Self {
+                value.value()
+            }
+        }
+
+        impl PartialEq<Unaligned<$ty>> for $ty {
+            #[inline]
+            fn eq(&self, other: &Unaligned<$ty>) -> bool {
+                self.eq(&other.value())
+            }
+        }
+    };
+    // Converting floats and chars in const functions needs a newer compiler than the rest of the
+    // crate, so only the integers get const constructors and accessors
+    (@float $($ty:ty),* $(,)?) => {
+        $(
+            impl_unaligned_primitive!(
+                @common [] $ty,
+                |value| to_bytes!(value),
+                |bytes| from_bytes!($ty, bytes)
+            );
+        )*
+    };
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl_unaligned_primitive!(
+                @common [const] $ty,
+                |value| to_bytes!(value),
+                |bytes| from_bytes!($ty, bytes)
+            );
+        )*
+    };
+    (@char) => {
+        impl_unaligned_primitive!(
+            @common [] char,
+            |value| to_bytes!(value as u32),
+            |bytes| unsafe { char::from_u32_unchecked(from_bytes!(u32, bytes)) }
+        );
+    };
+    (@nonzero $($nz:ty = $prim:ty),* $(,)?) => {
+        $(
+            impl_unaligned_primitive!(
+                @common [const] $nz,
+                |value| to_bytes!(value.get()),
+                |bytes| unsafe { <$nz>::new_unchecked(from_bytes!($prim, bytes)) }
+            );
+        )*
+    };
+}
+
+impl_unaligned_primitive!(i16, i32, i64, i128, u16, u32, u64, u128);
+impl_unaligned_primitive!(@float f32, f64);
+impl_unaligned_primitive!(@char);
+impl_unaligned_primitive!(
+    @nonzero
+    NonZeroI16 = i16,
+    NonZeroI32 = i32,
+    NonZeroI64 = i64,
+    NonZeroI128 = i128,
+    NonZeroU16 = u16,
+    NonZeroU32 = u32,
+    NonZeroU64 = u64,
+    NonZeroU128 = u128,
+);
+
+/// An archived primitive with an alignment of 1.
+///
+/// The primitive is stored as bytes in the archived endianness and is read and written through
+/// [`value`](Unaligned::value) and [`new`](Unaligned::new).
+#[repr(transparent)]
+pub struct Unaligned<T: UnalignedPrimitive> {
+    bytes: T::Bytes,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: UnalignedPrimitive> Unaligned<T> {
+    /// Creates a new unaligned primitive from the given value.
+    ///
+    /// This is the generic counterpart of `new`, which is only available for concrete primitives.
+    #[inline]
+    pub fn from_native(value: T) -> Self {
+        Self {
+            bytes: value.to_archived_bytes(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the value of the unaligned primitive.
+    ///
+    /// This is the generic counterpart of `value`, which is only available for concrete primitives.
+    #[inline]
+    pub fn to_native(&self) -> T {
+        T::from_archived_bytes(self.bytes)
+    }
+
+    /// Sets the value of the unaligned primitive.
+    #[inline]
+    pub fn set(&mut self, value: T) {
+        self.bytes = value.to_archived_bytes();
+    }
+}
+
+impl<T: UnalignedPrimitive> Clone for Unaligned<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: UnalignedPrimitive> Copy for Unaligned<T> {}
+
+impl<T: UnalignedPrimitive + fmt::Debug> fmt::Debug for Unaligned<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_native().fmt(f)
+    }
+}
+
+impl<T: UnalignedPrimitive + fmt::Display> fmt::Display for Unaligned<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_native().fmt(f)
+    }
+}
+
+impl<T: UnalignedPrimitive + Default> Default for Unaligned<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::from_native(T::default())
+    }
+}
+
+impl<T: UnalignedPrimitive> From<T> for Unaligned<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::from_native(value)
+    }
+}
+
+impl<T: UnalignedPrimitive + Eq> Eq for Unaligned<T> {}
+
+impl<T: UnalignedPrimitive + hash::Hash> hash::Hash for Unaligned<T> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.to_native().hash(state);
+    }
+}
+
+impl<T: UnalignedPrimitive + Ord> Ord for Unaligned<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.to_native().cmp(&other.to_native())
+    }
+}
+
+impl<T: UnalignedPrimitive + PartialEq> PartialEq for Unaligned<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.to_native().eq(&other.to_native())
+    }
+}
+
+impl<T: UnalignedPrimitive + PartialEq> PartialEq<T> for Unaligned<T> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.to_native().eq(other)
+    }
+}
+
+impl<T: UnalignedPrimitive + PartialOrd> PartialOrd for Unaligned<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.to_native().partial_cmp(&other.to_native())
+    }
+}
+
+impl<T: UnalignedPrimitive + PartialOrd> PartialOrd<T> for Unaligned<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &T) -> Option<cmp::Ordering> {
+        self.to_native().partial_cmp(other)
+    }
+}
+
+impl<T: UnalignedPrimitive + Sum> Sum for Unaligned<T> {
+    #[inline]
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::from_native(iter.map(|x| x.to_native()).sum())
+    }
+}
+
+impl<T: UnalignedPrimitive + Product> Product for Unaligned<T> {
+    #[inline]
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::from_native(iter.map(|x| x.to_native()).product())
+    }
+}
+
+#[cfg(feature = "validation")]
+const _: () = {
+    use bytecheck::{CharCheckError, CheckBytes, NonZeroCheckError};
+    use core::convert::Infallible;
+
+    macro_rules! impl_check_bytes {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                impl<C: ?Sized> CheckBytes<C> for Unaligned<$ty> {
+                    type Error = Infallible;
+
+                    #[inline]
+                    unsafe fn check_bytes<'a>(
+                        value: *const Self,
+                        _: &mut C,
+                    ) -> Result<&'a Self, Self::Error> {
+                        Ok(&*value)
+                    }
+                }
+            )*
+        };
+        (@nonzero $($nz:ty = $prim:ty),* $(,)?) => {
+            $(
+                impl<C: ?Sized> CheckBytes<C> for Unaligned<$nz> {
+                    type Error = NonZeroCheckError;
+
+                    #[inline]
+                    unsafe fn check_bytes<'a>(
+                        value: *const Self,
+                        _: &mut C,
+                    ) -> Result<&'a Self, Self::Error> {
+                        if <$prim>::from_archived_bytes((*value).bytes) == 0 {
+                            Err(NonZeroCheckError::IsZero)
+                        } else {
+                            Ok(&*value)
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_check_bytes!(i16, i32, i64, i128, u16, u32, u64, u128, f32, f64);
+    impl_check_bytes!(
+        @nonzero
+        NonZeroI16 = i16,
+        NonZeroI32 = i32,
+        NonZeroI64 = i64,
+        NonZeroI128 = i128,
+        NonZeroU16 = u16,
+        NonZeroU32 = u32,
+        NonZeroU64 = u64,
+        NonZeroU128 = u128,
+    );
+
+    impl<C: ?Sized> CheckBytes<C> for Unaligned<char> {
+        type Error = CharCheckError;
+
+        #[inline]
+        unsafe fn check_bytes<'a>(value: *const Self, _: &mut C) -> Result<&'a Self, Self::Error> {
+            let code = u32::from_archived_bytes((*value).bytes);
+            char::from_u32(code).ok_or(CharCheckError {
+                invalid_value: code,
+            })?;
+            Ok(&*value)
+        }
+    }
+};
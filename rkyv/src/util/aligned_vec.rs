@@ -17,7 +17,11 @@ use core::{
 #[cfg(feature = "std")]
 use std::{alloc, io};
 
-/// A vector of bytes that aligns its memory to 16 bytes.
+/// A vector of bytes that aligns its memory to `ALIGN` bytes.
+///
+/// The alignment defaults to 16 bytes and must be a power of two. Archives containing types with a
+/// higher alignment (i.e. from `#[archive(align = 64)]`) should be written to an `AlignedVec` with
+/// at least that alignment.
 ///
 /// The alignment also applies to `ArchivedAlignedVec`, which is useful for aligning opaque bytes inside of an archived data
 /// type.
@@ -48,13 +52,13 @@ use std::{alloc, io};
 /// assert_eq!(archived.bytes.as_slice(), &[1, 2, 3]);
 /// assert_eq!(archived.bytes.as_ptr().align_offset(16), 0);
 /// ```
-pub struct AlignedVec {
+pub struct AlignedVec<const ALIGN: usize = 16> {
     ptr: NonNull<u8>,
     cap: usize,
     len: usize,
 }
 
-impl Drop for AlignedVec {
+impl<const ALIGN: usize> Drop for AlignedVec<ALIGN> {
     #[inline]
     fn drop(&mut self) {
         if self.cap != 0 {
@@ -66,17 +70,7 @@ impl Drop for AlignedVec {
 }
 
 impl AlignedVec {
-    /// The alignment of the vector
-    pub const ALIGNMENT: usize = 16;
-
-    /// Maximum capacity of the vector.
-    /// Dictated by the requirements of
-    /// [`alloc::Layout`](https://doc.rust-lang.org/alloc/alloc/struct.Layout.html).
-    /// "`size`, when rounded up to the nearest multiple of `align`, must not overflow `isize`
-    /// (i.e. the rounded value must be less than or equal to `isize::MAX`)".
-    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
-
-    /// Constructs a new, empty `AlignedVec`.
+    /// Constructs a new, empty `AlignedVec` with the default alignment of 16.
     ///
     /// The vector will not allocate until elements are pushed into it.
     ///
@@ -88,14 +82,11 @@ impl AlignedVec {
     /// ```
     #[inline]
     pub fn new() -> Self {
-        AlignedVec {
-            ptr: NonNull::dangling(),
-            cap: 0,
-            len: 0,
-        }
+        Self::new_aligned()
     }
 
-    /// Constructs a new, empty `AlignedVec` with the specified capacity.
+    /// Constructs a new, empty `AlignedVec` with the default alignment of 16 and the specified
+    /// capacity.
     ///
     /// The vector will be able to hold exactly `capacity` bytes without reallocating. If
     /// `capacity` is 0, the vector will not allocate.
@@ -124,12 +115,70 @@ impl AlignedVec {
     /// ```
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_aligned(capacity)
+    }
+}
+
+impl<const ALIGN: usize> AlignedVec<ALIGN> {
+    /// The alignment of the vector
+    pub const ALIGNMENT: usize = {
+        assert!(
+            ALIGN.is_power_of_two(),
+            "the alignment must be a power of two"
+        );
+        ALIGN
+    };
+
+    /// Maximum capacity of the vector.
+    /// Dictated by the requirements of
+    /// [`alloc::Layout`](https://doc.rust-lang.org/alloc/alloc/struct.Layout.html).
+    /// "`size`, when rounded up to the nearest multiple of `align`, must not overflow `isize`
+    /// (i.e. the rounded value must be less than or equal to `isize::MAX`)".
+    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
+
+    /// Constructs a new, empty `AlignedVec` with an alignment of `ALIGN`.
+    ///
+    /// The vector will not allocate until elements are pushed into it.
+    ///
+    /// # Examples
+    /// ```
+    /// use rkyv::AlignedVec;
+    ///
+    /// let mut vec = AlignedVec::<64>::new_aligned();
+    /// vec.push(1);
+    /// assert_eq!(vec.as_ptr() as usize % 64, 0);
+    /// ```
+    #[inline]
+    pub fn new_aligned() -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            cap: 0,
+            len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `AlignedVec` with an alignment of `ALIGN` and the specified
+    /// capacity.
+    ///
+    /// The vector will be able to hold exactly `capacity` bytes without reallocating. If
+    /// `capacity` is 0, the vector will not allocate.
+    ///
+    /// # Examples
+    /// ```
+    /// use rkyv::AlignedVec;
+    ///
+    /// let vec = AlignedVec::<64>::with_capacity_aligned(10);
+    /// assert_eq!(vec.capacity(), 10);
+    /// assert_eq!(vec.as_ptr() as usize % 64, 0);
+    /// ```
+    #[inline]
+    pub fn with_capacity_aligned(capacity: usize) -> Self {
         if capacity == 0 {
-            Self::new()
+            Self::new_aligned()
         } else {
             assert!(
                 capacity <= Self::MAX_CAPACITY,
-                "`capacity` cannot exceed isize::MAX - 15"
+                "`capacity` cannot exceed isize::MAX - (ALIGN - 1)"
             );
             let ptr = unsafe {
                 let layout = alloc::Layout::from_size_align_unchecked(capacity, Self::ALIGNMENT);
@@ -369,7 +418,7 @@ impl AlignedVec {
     ///
     /// # Panics
     ///
-    /// Panics if the new capacity exceeds `isize::MAX - 15` bytes.
+    /// Panics if the new capacity exceeds `isize::MAX - (ALIGN - 1)` bytes.
     ///
     /// # Examples
     /// ```
@@ -419,11 +468,11 @@ impl AlignedVec {
     /// code where where the calculation of capacity required has already been
     /// performed, and you want to avoid doing it again.
     ///
-    /// Maximum capacity is `isize::MAX - 15` bytes.
+    /// Maximum capacity is `isize::MAX - (ALIGN - 1)` bytes.
     ///
     /// # Panics
     ///
-    /// Panics if `new_cap` exceeds `isize::MAX - 15` bytes.
+    /// Panics if `new_cap` exceeds `isize::MAX - (ALIGN - 1)` bytes.
     ///
     /// # Safety
     ///
@@ -464,7 +513,7 @@ impl AlignedVec {
     ///
     /// # Panics
     ///
-    /// Panics if the new length exceeds `isize::MAX - 15` bytes.
+    /// Panics if the new length exceeds `isize::MAX - (ALIGN - 1)` bytes.
     ///
     /// # Examples
     /// ```
@@ -580,7 +629,7 @@ impl AlignedVec {
     ///
     /// # Panics
     ///
-    /// Panics if the new capacity exceeds `isize::MAX - 15` bytes.
+    /// Panics if the new capacity exceeds `isize::MAX - (ALIGN - 1)` bytes.
     ///
     /// # Examples
     /// ```
@@ -627,7 +676,7 @@ impl AlignedVec {
     ///
     /// # Panics
     ///
-    /// Panics if the new capacity overflows `isize::MAX - 15`.
+    /// Panics if the new capacity overflows `isize::MAX - (ALIGN - 1)`.
     ///
     /// # Examples
     /// ```
@@ -742,7 +791,7 @@ impl AlignedVec {
 const _: () = {
     use std::io::{ErrorKind, Read};
 
-    impl AlignedVec {
+    impl<const ALIGN: usize> AlignedVec<ALIGN> {
         /// Reads all bytes until EOF from `r` and appends them to this `AlignedVec`.
         ///
         /// If successful, this function will return the total number of bytes read.
@@ -835,14 +884,14 @@ const _: () = {
     }
 };
 
-impl From<AlignedVec> for Vec<u8> {
+impl<const ALIGN: usize> From<AlignedVec<ALIGN>> for Vec<u8> {
     #[inline]
-    fn from(aligned: AlignedVec) -> Self {
+    fn from(aligned: AlignedVec<ALIGN>) -> Self {
         aligned.to_vec()
     }
 }
 
-impl Archive for AlignedVec {
+impl<const ALIGN: usize> Archive for AlignedVec<ALIGN> {
     type Archived = ArchivedVec<u8>;
     type Resolver = VecResolver;
 
@@ -852,39 +901,39 @@ impl Archive for AlignedVec {
     }
 }
 
-impl AsMut<[u8]> for AlignedVec {
+impl<const ALIGN: usize> AsMut<[u8]> for AlignedVec<ALIGN> {
     #[inline]
     fn as_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl AsRef<[u8]> for AlignedVec {
+impl<const ALIGN: usize> AsRef<[u8]> for AlignedVec<ALIGN> {
     #[inline]
     fn as_ref(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl Borrow<[u8]> for AlignedVec {
+impl<const ALIGN: usize> Borrow<[u8]> for AlignedVec<ALIGN> {
     #[inline]
     fn borrow(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl BorrowMut<[u8]> for AlignedVec {
+impl<const ALIGN: usize> BorrowMut<[u8]> for AlignedVec<ALIGN> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl Clone for AlignedVec {
+impl<const ALIGN: usize> Clone for AlignedVec<ALIGN> {
     #[inline]
     fn clone(&self) -> Self {
         unsafe {
-            let mut result = AlignedVec::with_capacity(self.len);
+            let mut result = Self::with_capacity_aligned(self.len);
             result.len = self.len;
             core::ptr::copy_nonoverlapping(self.as_ptr(), result.as_mut_ptr(), self.len);
             result
@@ -892,21 +941,21 @@ impl Clone for AlignedVec {
     }
 }
 
-impl fmt::Debug for AlignedVec {
+impl<const ALIGN: usize> fmt::Debug for AlignedVec<ALIGN> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.as_slice().fmt(f)
     }
 }
 
-impl Default for AlignedVec {
+impl<const ALIGN: usize> Default for AlignedVec<ALIGN> {
     #[inline]
     fn default() -> Self {
-        Self::new()
+        Self::new_aligned()
     }
 }
 
-impl Deref for AlignedVec {
+impl<const ALIGN: usize> Deref for AlignedVec<ALIGN> {
     type Target = [u8];
 
     #[inline]
@@ -915,14 +964,14 @@ impl Deref for AlignedVec {
     }
 }
 
-impl DerefMut for AlignedVec {
+impl<const ALIGN: usize> DerefMut for AlignedVec<ALIGN> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.as_mut_slice()
     }
 }
 
-impl<I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec {
+impl<const ALIGN: usize, I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec<ALIGN> {
     type Output = <I as slice::SliceIndex<[u8]>>::Output;
 
     #[inline]
@@ -931,7 +980,7 @@ impl<I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec {
     }
 }
 
-impl<I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec {
+impl<const ALIGN: usize, I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec<ALIGN> {
     #[inline]
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
         &mut self.as_mut_slice()[index]
@@ -939,7 +988,7 @@ impl<I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec {
 }
 
 #[cfg(feature = "std")]
-impl io::Write for AlignedVec {
+impl<const ALIGN: usize> io::Write for AlignedVec<ALIGN> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.extend_from_slice(buf);
@@ -968,9 +1017,9 @@ impl io::Write for AlignedVec {
 }
 
 // SAFETY: AlignedVec is safe to send to another thread
-unsafe impl Send for AlignedVec {}
+unsafe impl<const ALIGN: usize> Send for AlignedVec<ALIGN> {}
 
-impl<S: ScratchSpace + Serializer + ?Sized> Serialize<S> for AlignedVec {
+impl<S: ScratchSpace + Serializer + ?Sized, const ALIGN: usize> Serialize<S> for AlignedVec<ALIGN> {
     #[inline]
     fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
         serializer.align(Self::ALIGNMENT)?;
@@ -979,6 +1028,6 @@ impl<S: ScratchSpace + Serializer + ?Sized> Serialize<S> for AlignedVec {
 }
 
 // SAFETY: AlignedVec is safe to share between threads
-unsafe impl Sync for AlignedVec {}
+unsafe impl<const ALIGN: usize> Sync for AlignedVec<ALIGN> {}
 
-impl Unpin for AlignedVec {}
+impl<const ALIGN: usize> Unpin for AlignedVec<ALIGN> {}
@@ -12,6 +12,7 @@
 #[cfg(feature = "alloc")]
 mod aligned_vec;
 mod scratch_vec;
+mod spill_vec;
 
 #[cfg(feature = "alloc")]
 use crate::{
@@ -31,6 +32,8 @@ use core::{
 pub use self::aligned_vec::*;
 #[doc(inline)]
 pub use self::scratch_vec::*;
+#[doc(inline)]
+pub use self::spill_vec::*;
 
 #[cfg(debug_assertions)]
 #[inline]
@@ -219,6 +222,8 @@ pub unsafe fn archived_unsized_root_mut<T: ArchiveUnsized + ?Sized>(
 /// ```
 #[derive(Archive, Clone, Copy, Debug, Deserialize, Serialize)]
 #[archive(crate = "crate")]
+#[cfg_attr(feature = "compact", archive(compact))]
+#[cfg_attr(feature = "transcode", archive(transcode))]
 #[repr(C, align(16))]
 pub struct AlignedBytes<const N: usize>(pub [u8; N]);
 
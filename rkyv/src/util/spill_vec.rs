@@ -0,0 +1,190 @@
+use crate::ser::SpillSpace;
+use core::{marker::PhantomData, mem, slice};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A plain-old-data type that can be moved through spill space.
+///
+/// Spill spaces may write spilled data to external storage like a file, so only types with no
+/// padding bytes and no invalid bit patterns can be spilled. Values that own memory or contain
+/// pointers are kept in memory by the builders that use them. This trait is sealed and is only
+/// implemented for the unsigned integer types with a fixed size.
+pub trait SpillRecord: Copy + sealed::Sealed {}
+
+macro_rules! impl_spill_record {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl SpillRecord for $ty {}
+        )*
+    };
+}
+
+impl_spill_record!(u8, u16, u32, u64);
+
+/// A vector view into serializer spill space.
+///
+/// Elements are moved into and out of spill space bytewise, so they must be
+/// [`SpillRecord`]s.
+pub struct SpillVec<T: SpillRecord> {
+    start: usize,
+    len: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: SpillRecord> SpillVec<T> {
+    /// Constructs a new, empty `SpillVec` at the end of the spilled data.
+    ///
+    /// # Safety
+    ///
+    /// - The vector must not outlive the given spill space.
+    /// - Nothing else may be spilled while elements are pushed to the vector.
+    /// - Vectors must be freed in the reverse order they are constructed.
+    #[inline]
+    pub unsafe fn new<S: SpillSpace + ?Sized>(spill_space: &mut S) -> Self {
+        Self {
+            start: spill_space.spill_pos(),
+            len: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Constructs a new `SpillVec` at the end of the spilled data that holds `len` copies of the
+    /// given value.
+    ///
+    /// # Safety
+    ///
+    /// See [`new`](SpillVec::new).
+    pub unsafe fn from_elem<S: SpillSpace + ?Sized>(
+        spill_space: &mut S,
+        value: T,
+        len: usize,
+    ) -> Result<Self, S::Error> {
+        // Spill in chunks so that file-backed spill spaces are not written one element at a time
+        const CHUNK_LEN: usize = 64;
+
+        let mut result = Self::new(spill_space);
+        let chunk = [value; CHUNK_LEN];
+        while result.len < len {
+            let count = usize::min(len - result.len, CHUNK_LEN);
+            spill_space.spill(Self::bytes_of(&chunk[..count]))?;
+            result.len += count;
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    fn bytes_of(values: &[T]) -> &[u8] {
+        // Spill records have no padding bytes
+        unsafe { slice::from_raw_parts(values.as_ptr().cast::<u8>(), mem::size_of_val(values)) }
+    }
+
+    #[inline]
+    fn bytes_of_mut(values: &mut [T]) -> &mut [u8] {
+        // Spill records are valid for any bit pattern
+        unsafe {
+            slice::from_raw_parts_mut(values.as_mut_ptr().cast::<u8>(), mem::size_of_val(values))
+        }
+    }
+
+    #[inline]
+    fn pos_of(&self, index: usize) -> usize {
+        self.start + index * mem::size_of::<T>()
+    }
+
+    /// Returns the number of elements in the vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the vector has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Spills an element at the end of the vector.
+    ///
+    /// # Safety
+    ///
+    /// Nothing else may have been spilled since the vector was constructed.
+    #[inline]
+    pub unsafe fn push<S: SpillSpace + ?Sized>(
+        &mut self,
+        spill_space: &mut S,
+        value: T,
+    ) -> Result<(), S::Error> {
+        debug_assert_eq!(spill_space.spill_pos(), self.pos_of(self.len));
+        spill_space.spill(Self::bytes_of(slice::from_ref(&value)))?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Reads the element at the given index.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the vector.
+    #[inline]
+    pub unsafe fn get<S: SpillSpace + ?Sized>(
+        &self,
+        spill_space: &mut S,
+        index: usize,
+    ) -> Result<T, S::Error> {
+        debug_assert!(index < self.len);
+        let mut result = mem::MaybeUninit::<T>::zeroed();
+        let bytes =
+            slice::from_raw_parts_mut(result.as_mut_ptr().cast::<u8>(), mem::size_of::<T>());
+        spill_space.unspill(self.pos_of(index), bytes)?;
+        Ok(result.assume_init())
+    }
+
+    /// Overwrites the element at the given index.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the vector.
+    #[inline]
+    pub unsafe fn set<S: SpillSpace + ?Sized>(
+        &mut self,
+        spill_space: &mut S,
+        index: usize,
+        value: T,
+    ) -> Result<(), S::Error> {
+        debug_assert!(index < self.len);
+        spill_space.respill(self.pos_of(index), Self::bytes_of(slice::from_ref(&value)))
+    }
+
+    /// Reads the elements starting at the given index into `out`.
+    ///
+    /// # Safety
+    ///
+    /// `index + out.len()` must be less than or equal to the length of the vector.
+    #[inline]
+    pub unsafe fn read<S: SpillSpace + ?Sized>(
+        &self,
+        spill_space: &mut S,
+        index: usize,
+        out: &mut [T],
+    ) -> Result<(), S::Error> {
+        debug_assert!(index + out.len() <= self.len);
+        spill_space.unspill(self.pos_of(index), Self::bytes_of_mut(out))
+    }
+
+    /// Frees the spilled data of the vector and truncates the spill space back to where the vector
+    /// started.
+    ///
+    /// Builders must free their spill vecs or truncate the spill space back to where they started
+    /// on every exit path, including when serialization fails.
+    ///
+    /// # Safety
+    ///
+    /// The given spill space must be the same one used to construct the spill vec.
+    #[inline]
+    pub unsafe fn free<S: SpillSpace + ?Sized>(self, spill_space: &mut S) -> Result<(), S::Error> {
+        spill_space.truncate_spill(self.start)
+    }
+}
@@ -208,6 +208,7 @@ unsafe impl Sync for SuffixRange {}
 #[derive(Debug)]
 pub struct ArchiveValidator<'a> {
     bytes: &'a [u8],
+    alignment: usize,
     subtree_range: Range<*const u8>,
     subtree_depth: usize,
     max_subtree_depth: usize,
@@ -233,25 +234,50 @@ impl<'a> ArchiveValidator<'a> {
     pub fn with_max_depth(bytes: &'a [u8], max_subtree_depth: usize) -> Self {
         Self {
             bytes,
+            alignment: 1 << (bytes.as_ptr() as usize).trailing_zeros(),
             subtree_range: bytes.as_ptr_range(),
             subtree_depth: 0,
             max_subtree_depth,
         }
     }
 
+    /// Creates a new bounds validator for bytes that were serialized with the given alignment.
+    ///
+    /// The alignment is usually the `ALIGN` of the [`AlignedVec`](crate::AlignedVec) that the
+    /// archive was serialized into. Types aligned above it are rejected even if the bytes happen
+    /// to be more aligned, so the same archive validates the same way wherever it is placed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is not a power of two.
+    #[inline]
+    pub fn with_alignment(bytes: &'a [u8], alignment: usize) -> Self {
+        assert!(
+            alignment.is_power_of_two(),
+            "the alignment must be a power of two"
+        );
+        let mut result = Self::new(bytes);
+        result.alignment = usize::min(result.alignment, alignment);
+        result
+    }
+
     /// Returns the log base 2 of the alignment of the archive.
     ///
     /// An archive that is 2-aligned will return 1, 4-aligned will return 2, 8-aligned will return 3
     /// and so on.
     #[inline]
     pub fn log_alignment(&self) -> usize {
-        (self.bytes.as_ptr() as usize).trailing_zeros() as usize
+        self.alignment.trailing_zeros() as usize
     }
 
     /// Returns the alignment of the archive.
+    ///
+    /// This is the largest power of two that the start of the archive is aligned to, limited to
+    /// the alignment passed to [`with_alignment`](ArchiveValidator::with_alignment). Types aligned
+    /// above it are rejected as underaligned.
     #[inline]
     pub fn alignment(&self) -> usize {
-        1 << self.log_alignment()
+        self.alignment
     }
 }
 
@@ -69,6 +69,17 @@ impl<'a> DefaultValidator<'a> {
             shared: SharedValidator::new(),
         }
     }
+
+    /// Creates a new validator from a byte range that was serialized with the given alignment.
+    ///
+    /// See [`ArchiveValidator::with_alignment`] for more details.
+    #[inline]
+    pub fn with_alignment(bytes: &'a [u8], alignment: usize) -> Self {
+        Self {
+            archive: ArchiveValidator::with_alignment(bytes, alignment),
+            shared: SharedValidator::new(),
+        }
+    }
 }
 
 impl<'a> Fallible for DefaultValidator<'a> {
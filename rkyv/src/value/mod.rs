@@ -0,0 +1,323 @@
+//! A schemaless, dynamically-typed value and its archived counterpart.
+//!
+//! [`Value`] can hold any tree of nulls, booleans, numbers, strings, arrays, and string-keyed
+//! objects. Its archived form, [`ArchivedValue`], can be inspected in place and indexed by key or
+//! position. With the `serde` feature enabled, any type that implements serde's `Serialize` can be
+//! converted to a value with [`to_value`] or archived as one with [`to_bytes`], without deriving
+//! [`Archive`].
+//!
+//! [`Archive`]: crate::Archive
+
+#[cfg(feature = "serde")]
+mod serde;
+
+#[cfg(feature = "serde")]
+pub use self::serde::*;
+
+use crate::{
+    collections::btree_map::ArchivedBTreeMap, string::ArchivedString, vec::ArchivedVec, Archive,
+    Deserialize, Serialize,
+};
+#[cfg(feature = "validation")]
+use crate::{validation::ArchiveContext, Fallible};
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::ops::Index;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// A dynamically-typed value.
+///
+/// Objects are stored in a [`BTreeMap`] so that their archived form can be searched by key.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[archive(crate = "crate")]
+#[archive(bound(serialize = "__S: crate::ser::ScratchSpace + crate::ser::Serializer"))]
+#[cfg_attr(feature = "validation", archive(check_bytes))]
+#[cfg_attr(
+    feature = "validation",
+    archive_attr(check_bytes(
+        bound = "__C: ArchiveContext, <__C as Fallible>::Error: bytecheck::Error"
+    ))
+)]
+#[archive_attr(derive(Debug))]
+pub enum Value {
+    /// A null value
+    Null,
+    /// A boolean
+    Bool(bool),
+    /// A number
+    Number(Number),
+    /// A string
+    String(String),
+    /// An ordered sequence of values
+    Array(
+        #[omit_bounds]
+        #[cfg_attr(feature = "validation", archive_attr(omit_bounds))]
+        Vec<Value>,
+    ),
+    /// A map from strings to values
+    Object(
+        #[omit_bounds]
+        #[cfg_attr(feature = "validation", archive_attr(omit_bounds))]
+        BTreeMap<String, Value>,
+    ),
+}
+
+/// A number held by a [`Value`].
+#[derive(Archive, Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[archive(crate = "crate")]
+#[cfg_attr(feature = "validation", archive(check_bytes))]
+#[archive_attr(derive(Clone, Copy, Debug, PartialEq))]
+pub enum Number {
+    /// A non-negative integer
+    PosInt(u64),
+    /// A negative integer
+    NegInt(i64),
+    /// A floating-point number
+    Float(f64),
+}
+
+impl Number {
+    /// Returns the number as a `u64` if it is a non-negative integer.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Number::PosInt(n) => Some(n),
+            Number::NegInt(_) | Number::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as an `i64` if it is an integer that fits.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Number::PosInt(n) => i64::try_from(n).ok(),
+            Number::NegInt(n) => Some(n),
+            Number::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, which may lose precision for large integers.
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::PosInt(n) => n as f64,
+            Number::NegInt(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+}
+
+impl ArchivedNumber {
+    /// Returns the number as a `u64` if it is a non-negative integer.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.to_number().as_u64()
+    }
+
+    /// Returns the number as an `i64` if it is an integer that fits.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.to_number().as_i64()
+    }
+
+    /// Returns the number as an `f64`, which may lose precision for large integers.
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        self.to_number().as_f64()
+    }
+
+    /// Converts the archived number back to a [`Number`].
+    #[inline]
+    pub fn to_number(&self) -> Number {
+        match self {
+            ArchivedNumber::PosInt(n) => Number::PosInt(from_archived!(*n)),
+            ArchivedNumber::NegInt(n) => Number::NegInt(from_archived!(*n)),
+            ArchivedNumber::Float(n) => Number::Float(from_archived!(*n)),
+        }
+    }
+}
+
+impl PartialEq<Number> for ArchivedNumber {
+    #[inline]
+    fn eq(&self, other: &Number) -> bool {
+        self.to_number() == *other
+    }
+}
+
+impl PartialEq<ArchivedNumber> for Number {
+    #[inline]
+    fn eq(&self, other: &ArchivedNumber) -> bool {
+        other.eq(self)
+    }
+}
+
+impl ArchivedValue {
+    /// Returns `true` if the value is null.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, ArchivedValue::Null)
+    }
+
+    /// Returns the boolean if the value is a boolean.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ArchivedValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if the value is a number.
+    #[inline]
+    pub fn as_number(&self) -> Option<&ArchivedNumber> {
+        match self {
+            ArchivedValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a `u64` if it is a non-negative integer.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(ArchivedNumber::as_u64)
+    }
+
+    /// Returns the value as an `i64` if it is an integer that fits.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(ArchivedNumber::as_i64)
+    }
+
+    /// Returns the value as an `f64` if it is a number.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(ArchivedNumber::as_f64)
+    }
+
+    /// Returns the string if the value is a string.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ArchivedValue::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if the value is an array.
+    #[inline]
+    pub fn as_array(&self) -> Option<&ArchivedVec<ArchivedValue>> {
+        match self {
+            ArchivedValue::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is an object.
+    #[inline]
+    pub fn as_object(&self) -> Option<&ArchivedBTreeMap<ArchivedString, ArchivedValue>> {
+        match self {
+            ArchivedValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Looks up an element of an array by position or an entry of an object by key.
+    ///
+    /// Returns `None` if the value is not the right kind of container or if the element or entry
+    /// does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use rkyv::{
+    ///     archived_root,
+    ///     value::{Number, Value},
+    /// };
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut object = BTreeMap::new();
+    /// object.insert("tags".to_string(), Value::Array(vec![Value::String("crab".to_string())]));
+    /// object.insert("age".to_string(), Value::Number(Number::PosInt(10)));
+    ///
+    /// let bytes = rkyv::to_bytes::<_, 256>(&Value::Object(object)).unwrap();
+    /// let archived = unsafe { archived_root::<Value>(&bytes) };
+    ///
+    /// assert_eq!(archived.get("age").and_then(|v| v.as_u64()), Some(10));
+    /// assert_eq!(archived["tags"][0].as_str(), Some("crab"));
+    /// assert!(archived.get("name").is_none());
+    /// assert!(archived["tags"][1].is_null());
+    /// ```
+    #[inline]
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&ArchivedValue> {
+        index.index_into(self)
+    }
+}
+
+/// A type that can be used to index into an [`ArchivedValue`].
+///
+/// Positions (`usize`) index into arrays and strings index into objects.
+pub trait ValueIndex {
+    /// Returns the element of `value` at this index, if any.
+    fn index_into<'v>(&self, value: &'v ArchivedValue) -> Option<&'v ArchivedValue>;
+}
+
+impl ValueIndex for usize {
+    #[inline]
+    fn index_into<'v>(&self, value: &'v ArchivedValue) -> Option<&'v ArchivedValue> {
+        value.as_array().and_then(|a| a.get(*self))
+    }
+}
+
+impl ValueIndex for str {
+    #[inline]
+    fn index_into<'v>(&self, value: &'v ArchivedValue) -> Option<&'v ArchivedValue> {
+        value.as_object().and_then(|o| o.get(self))
+    }
+}
+
+impl ValueIndex for String {
+    #[inline]
+    fn index_into<'v>(&self, value: &'v ArchivedValue) -> Option<&'v ArchivedValue> {
+        self.as_str().index_into(value)
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    #[inline]
+    fn index_into<'v>(&self, value: &'v ArchivedValue) -> Option<&'v ArchivedValue> {
+        (**self).index_into(value)
+    }
+}
+
+static NULL: ArchivedValue = ArchivedValue::Null;
+
+/// Indexing an archived value returns a null value instead of panicking when the index is missing.
+impl<I: ValueIndex> Index<I> for ArchivedValue {
+    type Output = ArchivedValue;
+
+    #[inline]
+    fn index(&self, index: I) -> &Self::Output {
+        self.get(index).unwrap_or(&NULL)
+    }
+}
+
+impl PartialEq<Value> for ArchivedValue {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (ArchivedValue::Null, Value::Null) => true,
+            (ArchivedValue::Bool(a), Value::Bool(b)) => a == b,
+            (ArchivedValue::Number(a), Value::Number(b)) => a == b,
+            (ArchivedValue::String(a), Value::String(b)) => a == b,
+            (ArchivedValue::Array(a), Value::Array(b)) => a.as_slice() == b.as_slice(),
+            (ArchivedValue::Object(a), Value::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<ArchivedValue> for Value {
+    #[inline]
+    fn eq(&self, other: &ArchivedValue) -> bool {
+        other.eq(self)
+    }
+}
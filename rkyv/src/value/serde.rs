@@ -0,0 +1,1566 @@
+use super::{ArchivedNumber, ArchivedValue, Number, Value};
+use crate::{
+    collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+    ser::{serializers::AllocSerializer, ScratchSpace, Serializer},
+    string::repr::{ArchivedStringRepr, INLINE_CAPACITY},
+    string::ArchivedString,
+    vec::{ArchivedVec, VecResolver},
+    AlignedVec, Archive, Fallible, ScratchVec, Serialize, SerializeUnsized,
+};
+use ::serde::ser::{self, SerializeMap as _, SerializeSeq as _};
+#[cfg(not(feature = "std"))]
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{alloc::Layout, convert::Infallible, fmt, iter, mem, ptr::NonNull, str};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// An error that occurred while serializing a serde type as a [`Value`].
+#[derive(Debug)]
+pub enum ValueSerializeError<E = Infallible> {
+    /// The rkyv serializer failed while archiving the value
+    Serialize(E),
+    /// The type being serialized returned an error
+    Custom(String),
+    /// A map key was not a string, character, boolean, or integer
+    KeyMustBeString,
+}
+
+impl ValueSerializeError {
+    #[inline]
+    fn widen<E>(self) -> ValueSerializeError<E> {
+        match self {
+            ValueSerializeError::Serialize(e) => match e {},
+            ValueSerializeError::Custom(msg) => ValueSerializeError::Custom(msg),
+            ValueSerializeError::KeyMustBeString => ValueSerializeError::KeyMustBeString,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ValueSerializeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSerializeError::Serialize(e) => write!(f, "serializer error: {}", e),
+            ValueSerializeError::Custom(msg) => write!(f, "{}", msg),
+            ValueSerializeError::KeyMustBeString => write!(f, "map key must be a string"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> ser::StdError for ValueSerializeError<E> {}
+
+impl<E: fmt::Debug + fmt::Display> ser::Error for ValueSerializeError<E> {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ValueSerializeError::Custom(msg.to_string())
+    }
+}
+
+/// Converts a serde type to a [`Value`].
+///
+/// Structs and maps become objects, sequences and tuples become arrays, and enum variants with data
+/// become single-entry objects keyed by the variant name. Map keys must serialize as strings,
+/// characters, booleans, or integers.
+#[inline]
+pub fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value, ValueSerializeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Serializes a serde type as an archived [`Value`] and returns the position of the root.
+///
+/// The type is archived while it is being visited instead of being converted to a [`Value`]
+/// first. Arrays are written directly to the serializer, and the elements of arrays with a known
+/// length are kept in scratch space. The entries of an object must be written in sorted order, so
+/// the archived values of the objects that are still being serialized are buffered until they can
+/// be written. If an object has more than one entry with the same key, the last entry is kept.
+#[inline]
+pub fn serialize<T, S>(
+    value: &T,
+    serializer: &mut S,
+) -> Result<usize, ValueSerializeError<S::Error>>
+where
+    T: ser::Serialize + ?Sized,
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    let mut sink = ArchiveSink {
+        serializer: &mut *serializer,
+        buffers: Vec::new(),
+    };
+    let node = value.serialize(ArchiveSerializer { sink: &mut sink })?;
+    serializer
+        .serialize_value(&node)
+        .map_err(ValueSerializeError::Serialize)
+}
+
+/// Serializes a serde type as an archived [`Value`] using an
+/// [`AllocSerializer`](crate::ser::serializers::AllocSerializer) with a scratch space of `N` bytes.
+///
+/// The result can be accessed as an [`ArchivedValue`] without the type deriving
+/// [`Archive`](crate::Archive).
+///
+/// # Example
+/// ```
+/// use rkyv::archived_root;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Crab {
+///     name: &'static str,
+///     legs: Vec<u32>,
+/// }
+///
+/// let crab = Crab {
+///     name: "ferris",
+///     legs: vec![1, 2, 3, 4, 5, 6, 7, 8],
+/// };
+///
+/// let bytes = rkyv::value::to_bytes::<_, 256>(&crab).unwrap();
+/// let archived = unsafe { archived_root::<rkyv::value::Value>(&bytes) };
+///
+/// assert_eq!(archived["name"].as_str(), Some("ferris"));
+/// assert_eq!(archived["legs"][7].as_u64(), Some(8));
+/// ```
+#[inline]
+pub fn to_bytes<T, const N: usize>(
+    value: &T,
+) -> Result<AlignedVec, ValueSerializeError<<AllocSerializer<N> as Fallible>::Error>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut serializer = AllocSerializer::<N>::default();
+    serialize(value, &mut serializer)?;
+    Ok(serializer.into_serializer().into_inner())
+}
+
+/// A serde serializer that converts types to [`Value`]s.
+pub struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Value, Self::Error> {
+        Ok(Value::Bool(v))
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Value, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Value, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Value, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Value, Self::Error> {
+        if v < 0 {
+            Ok(Value::Number(Number::NegInt(v)))
+        } else {
+            Ok(Value::Number(Number::PosInt(v as u64)))
+        }
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Value, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Value, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Value, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Value, Self::Error> {
+        Ok(Value::Number(Number::PosInt(v)))
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Value, Self::Error> {
+        self.serialize_f64(v.into())
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Value, Self::Error> {
+        Ok(Value::Number(Number::Float(v)))
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Value, Self::Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Value, Self::Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Self::Error> {
+        Ok(Value::Array(
+            v.iter()
+                .map(|&b| Value::Number(Number::PosInt(b.into())))
+                .collect(),
+        ))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        Ok(single_entry(variant, value.serialize(self)?))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeArray {
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeObject {
+            entries: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+#[inline]
+fn single_entry(key: &str, value: Value) -> Value {
+    let mut entries = BTreeMap::new();
+    entries.insert(key.to_string(), value);
+    Value::Object(entries)
+}
+
+/// Serializes the elements of an array for a [`ValueSerializer`].
+pub struct SerializeArray {
+    elements: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.elements.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.elements))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Serializes the entries of an object for a [`ValueSerializer`].
+///
+/// If a key appears more than once, the last entry with that key is kept.
+pub struct SerializeObject {
+    entries: BTreeMap<String, Value>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.entries.insert(key, value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Serializes the contents of an enum variant as a single-entry object keyed by the variant name.
+pub struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.inner.serialize_element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(single_entry(self.variant, self.inner.end()?))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = Value;
+    type Error = ValueSerializeError;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.inner.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(single_entry(self.variant, self.inner.end()?))
+    }
+}
+
+// A value that has already had its out-of-line data serialized. Its archived form is the same as
+// the archived form of the corresponding `Value`, so the variants must stay in the same order.
+#[derive(Archive, Clone, Copy, Serialize)]
+#[archive(crate = "crate", as = "ArchivedValue")]
+enum ValueNode {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(StrNode),
+    Array(ArrayNode),
+    Object(ObjectNode),
+}
+
+impl ValueNode {
+    // Moves the positions of the node's out-of-line data by `base`
+    #[inline]
+    fn relocate(self, base: usize) -> Self {
+        match self {
+            ValueNode::String(StrNode::OutOfLine { len, pos }) => {
+                ValueNode::String(StrNode::OutOfLine {
+                    len,
+                    pos: base + pos,
+                })
+            }
+            ValueNode::Array(ArrayNode { len, pos }) => ValueNode::Array(ArrayNode {
+                len,
+                pos: base + pos,
+            }),
+            ValueNode::Object(ObjectNode { len, root_pos }) if len > 0 => {
+                ValueNode::Object(ObjectNode {
+                    len,
+                    root_pos: base + root_pos,
+                })
+            }
+            node => node,
+        }
+    }
+}
+
+// A string that is either short enough to be stored inline or has already been written out
+#[derive(Clone, Copy)]
+enum StrNode {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    OutOfLine {
+        len: usize,
+        pos: usize,
+    },
+}
+
+impl StrNode {
+    #[inline]
+    fn serialize<S: Serializer + ?Sized>(
+        value: &str,
+        serializer: &mut S,
+    ) -> Result<Self, S::Error> {
+        if value.len() <= INLINE_CAPACITY {
+            let mut bytes = [0; INLINE_CAPACITY];
+            bytes[..value.len()].copy_from_slice(value.as_bytes());
+            Ok(StrNode::Inline {
+                len: value.len() as u8,
+                bytes,
+            })
+        } else {
+            Ok(StrNode::OutOfLine {
+                len: value.len(),
+                pos: value.serialize_unsized(serializer)?,
+            })
+        }
+    }
+}
+
+impl Archive for StrNode {
+    type Archived = ArchivedString;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, _: Self::Resolver, out: *mut Self::Archived) {
+        match *self {
+            StrNode::Inline { len, ref bytes } => ArchivedStringRepr::emplace_inline(
+                str::from_utf8_unchecked(&bytes[..len as usize]),
+                out.cast(),
+            ),
+            StrNode::OutOfLine { len, pos: target } => {
+                ArchivedStringRepr::emplace_out_of_line_from_len(len, pos, target, out.cast())
+            }
+        }
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for StrNode {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+// An array whose elements have already been written out
+#[derive(Clone, Copy)]
+struct ArrayNode {
+    len: usize,
+    pos: usize,
+}
+
+impl Archive for ArrayNode {
+    type Archived = ArchivedVec<ArchivedValue>;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, _: Self::Resolver, out: *mut Self::Archived) {
+        ArchivedVec::resolve_from_len(self.len, pos, VecResolver { pos: self.pos }, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for ArrayNode {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+// An object whose entries have already been written out
+#[derive(Clone, Copy)]
+struct ObjectNode {
+    len: usize,
+    root_pos: usize,
+}
+
+impl Archive for ObjectNode {
+    type Archived = ArchivedBTreeMap<ArchivedString, ArchivedValue>;
+    type Resolver = ();
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, _: Self::Resolver, out: *mut Self::Archived) {
+        let resolver = BTreeMapResolver {
+            root_pos: self.root_pos,
+        };
+        ArchivedBTreeMap::resolve_from_len(self.len, pos, resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for ObjectNode {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+// A value that was archived to a separate buffer. B-tree nodes must contain the out-of-line data
+// of their entries in sorted order, so the values of objects are buffered until all of the keys
+// are known and then copied into place.
+struct BufferedValue {
+    bytes: Vec<u8>,
+    align: usize,
+    node: ValueNode,
+}
+
+impl Archive for BufferedValue {
+    type Archived = ArchivedValue;
+    type Resolver = (usize, ValueNodeResolver);
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        let (base, resolver) = resolver;
+        self.node.relocate(base).resolve(pos, resolver, out);
+    }
+}
+
+impl<S: Serializer + ?Sized> Serialize<S> for BufferedValue {
+    #[inline]
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        // Relative pointers within the buffer stay valid as long as the buffer is aligned to at
+        // least the largest alignment that was used in it
+        let base = serializer.align(self.align)?;
+        serializer.write(&self.bytes)?;
+        let resolver = self.node.relocate(base).serialize(serializer)?;
+        Ok((base, resolver))
+    }
+}
+
+// Writes to the most recently started buffer, or to the underlying serializer if there are no
+// buffers. Scratch space always comes from the underlying serializer.
+struct ArchiveSink<'a, S: ?Sized> {
+    serializer: &'a mut S,
+    buffers: Vec<Buffer>,
+}
+
+struct Buffer {
+    bytes: Vec<u8>,
+    align: usize,
+}
+
+impl<'a, S> ArchiveSink<'a, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    #[inline]
+    fn start_buffer(&mut self) {
+        self.buffers.push(Buffer {
+            bytes: Vec::new(),
+            align: 1,
+        });
+    }
+
+    #[inline]
+    fn finish_buffer(&mut self, node: ValueNode) -> BufferedValue {
+        let buffer = self
+            .buffers
+            .pop()
+            .expect("finish_buffer called without a buffer");
+        BufferedValue {
+            bytes: buffer.bytes,
+            align: buffer.align,
+            node,
+        }
+    }
+
+    #[inline]
+    fn buffer<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<BufferedValue, ValueSerializeError<S::Error>> {
+        self.start_buffer();
+        let node = value.serialize(ArchiveSerializer { sink: &mut *self })?;
+        Ok(self.finish_buffer(node))
+    }
+
+    // Writes the entries of an object. The entries must be in sorted order.
+    #[inline]
+    fn archive_object<'e, I>(
+        &mut self,
+        entries: I,
+    ) -> Result<ValueNode, ValueSerializeError<S::Error>>
+    where
+        I: DoubleEndedIterator<Item = (&'e String, &'e BufferedValue)> + ExactSizeIterator,
+    {
+        let len = entries.len();
+        let resolver =
+            unsafe { ArchivedBTreeMap::serialize_from_reverse_iter(entries.rev(), self) }
+                .map_err(ValueSerializeError::Serialize)?;
+        Ok(ValueNode::Object(ObjectNode {
+            len,
+            root_pos: resolver.root_pos,
+        }))
+    }
+}
+
+impl<S: Fallible + ?Sized> Fallible for ArchiveSink<'_, S> {
+    type Error = S::Error;
+}
+
+impl<S: Serializer + ?Sized> Serializer for ArchiveSink<'_, S> {
+    #[inline]
+    fn pos(&self) -> usize {
+        match self.buffers.last() {
+            Some(buffer) => buffer.bytes.len(),
+            None => self.serializer.pos(),
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        match self.buffers.last_mut() {
+            Some(buffer) => {
+                buffer.bytes.extend_from_slice(bytes);
+                Ok(())
+            }
+            None => self.serializer.write(bytes),
+        }
+    }
+
+    #[inline]
+    fn align(&mut self, align: usize) -> Result<usize, Self::Error> {
+        if let Some(buffer) = self.buffers.last_mut() {
+            buffer.align = usize::max(buffer.align, align);
+        }
+
+        let mask = align - 1;
+        debug_assert_eq!(align & mask, 0);
+
+        self.pad((align - (self.pos() & mask)) & mask)?;
+        Ok(self.pos())
+    }
+}
+
+impl<S: ScratchSpace + ?Sized> ScratchSpace for ArchiveSink<'_, S> {
+    #[inline]
+    unsafe fn push_scratch(&mut self, layout: Layout) -> Result<NonNull<[u8]>, Self::Error> {
+        self.serializer.push_scratch(layout)
+    }
+
+    #[inline]
+    unsafe fn pop_scratch(&mut self, ptr: NonNull<u8>, layout: Layout) -> Result<(), Self::Error> {
+        self.serializer.pop_scratch(ptr, layout)
+    }
+}
+
+// Archives serde types as archived values while visiting them
+struct ArchiveSerializer<'a, 'b, S: ?Sized> {
+    sink: &'a mut ArchiveSink<'b, S>,
+}
+
+impl<'a, 'b, S> ser::Serializer for ArchiveSerializer<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    type SerializeSeq = ArchiveArray<'a, 'b, S>;
+    type SerializeTuple = ArchiveArray<'a, 'b, S>;
+    type SerializeTupleStruct = ArchiveArray<'a, 'b, S>;
+    type SerializeTupleVariant = SerializeVariant<ArchiveArray<'a, 'b, S>>;
+    type SerializeMap = ArchiveObject<'a, 'b, S>;
+    type SerializeStruct = ArchiveObject<'a, 'b, S>;
+    type SerializeStructVariant = SerializeVariant<ArchiveObject<'a, 'b, S>>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Bool(v))
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<ValueNode, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<ValueNode, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<ValueNode, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<ValueNode, Self::Error> {
+        if v < 0 {
+            Ok(ValueNode::Number(Number::NegInt(v)))
+        } else {
+            Ok(ValueNode::Number(Number::PosInt(v as u64)))
+        }
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<ValueNode, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<ValueNode, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<ValueNode, Self::Error> {
+        self.serialize_u64(v.into())
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Number(Number::PosInt(v)))
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<ValueNode, Self::Error> {
+        self.serialize_f64(v.into())
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Number(Number::Float(v)))
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<ValueNode, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<ValueNode, Self::Error> {
+        StrNode::serialize(v, self.sink)
+            .map(ValueNode::String)
+            .map_err(ValueSerializeError::Serialize)
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<ValueNode, Self::Error> {
+        let elements = v
+            .iter()
+            .map(|&b| ValueNode::Number(Number::PosInt(b.into())));
+        let resolver = ArchivedVec::serialize_from_iter::<ValueNode, _, _, _>(elements, self.sink)
+            .map_err(ValueSerializeError::Serialize)?;
+        Ok(ValueNode::Array(ArrayNode {
+            len: v.len(),
+            pos: resolver.pos,
+        }))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Null)
+    }
+
+    #[inline]
+    fn serialize_some<T: ser::Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<ValueNode, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<ValueNode, Self::Error> {
+        Ok(ValueNode::Null)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<ValueNode, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<ValueNode, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<ValueNode, Self::Error> {
+        let value = self.sink.buffer(value)?;
+        self.sink
+            .archive_object(iter::once((&variant.to_string(), &value)))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let elements = match len {
+            Some(len) => Elements::Scratch(
+                unsafe { ScratchVec::new(self.sink, len) }
+                    .map_err(ValueSerializeError::Serialize)?,
+            ),
+            None => Elements::Heap(Vec::new()),
+        };
+        Ok(ArchiveArray {
+            sink: self.sink,
+            elements,
+        })
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.sink.start_buffer();
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(ArchiveObject {
+            sink: self.sink,
+            entries: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.sink.start_buffer();
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+// Arrays with a known length keep their elements in scratch space. If the length is unknown or
+// turns out to be wrong, the elements are moved to the heap instead.
+enum Elements {
+    Scratch(ScratchVec<ValueNode>),
+    Heap(Vec<ValueNode>),
+}
+
+// Archives the elements of an array for an `ArchiveSerializer`
+struct ArchiveArray<'a, 'b, S: ?Sized> {
+    sink: &'a mut ArchiveSink<'b, S>,
+    elements: Elements,
+}
+
+impl<'a, 'b, S> ArchiveArray<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    #[inline]
+    fn push(&mut self, node: ValueNode) -> Result<(), ValueSerializeError<S::Error>> {
+        if let Elements::Scratch(ref scratch) = self.elements {
+            if scratch.len() == scratch.capacity() {
+                let mut scratch = match mem::replace(&mut self.elements, Elements::Heap(Vec::new()))
+                {
+                    Elements::Scratch(scratch) => scratch,
+                    Elements::Heap(_) => unreachable!(),
+                };
+                let mut heap = Vec::with_capacity(2 * scratch.len() + 1);
+                heap.extend(scratch.drain(..));
+                // Elements free their own scratch space before they are pushed, so this is the
+                // most recent allocation
+                unsafe { scratch.free(self.sink) }.map_err(ValueSerializeError::Serialize)?;
+                self.elements = Elements::Heap(heap);
+            }
+        }
+
+        match self.elements {
+            Elements::Scratch(ref mut scratch) => scratch.push(node),
+            Elements::Heap(ref mut heap) => heap.push(node),
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn finish(
+        self,
+    ) -> Result<(&'a mut ArchiveSink<'b, S>, ValueNode), ValueSerializeError<S::Error>> {
+        let sink = self.sink;
+        let (len, resolver) = match self.elements {
+            Elements::Scratch(scratch) => {
+                let resolver = ArchivedVec::serialize_from_slice(scratch.as_slice(), sink)
+                    .map_err(ValueSerializeError::Serialize)?;
+                let len = scratch.len();
+                unsafe { scratch.free(sink) }.map_err(ValueSerializeError::Serialize)?;
+                (len, resolver)
+            }
+            Elements::Heap(heap) => {
+                let resolver = ArchivedVec::serialize_from_slice(heap.as_slice(), sink)
+                    .map_err(ValueSerializeError::Serialize)?;
+                (heap.len(), resolver)
+            }
+        };
+        let node = ValueNode::Array(ArrayNode {
+            len,
+            pos: resolver.pos,
+        });
+        Ok((sink, node))
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeSeq for ArchiveArray<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let node = value.serialize(ArchiveSerializer {
+            sink: &mut *self.sink,
+        })?;
+        self.push(node)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        self.finish().map(|(_, node)| node)
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeTuple for ArchiveArray<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_element<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeTupleStruct for ArchiveArray<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+// Archives the entries of an object for an `ArchiveSerializer`
+struct ArchiveObject<'a, 'b, S: ?Sized> {
+    sink: &'a mut ArchiveSink<'b, S>,
+    entries: BTreeMap<String, BufferedValue>,
+    next_key: Option<String>,
+}
+
+impl<'a, 'b, S> ArchiveObject<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    #[inline]
+    fn finish(
+        self,
+    ) -> Result<(&'a mut ArchiveSink<'b, S>, ValueNode), ValueSerializeError<S::Error>> {
+        let node = self.sink.archive_object(self.entries.iter())?;
+        Ok((self.sink, node))
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeMap for ArchiveObject<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.next_key = Some(
+            key.serialize(KeySerializer)
+                .map_err(ValueSerializeError::widen)?,
+        );
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let value = self.sink.buffer(value)?;
+        self.entries.insert(key, value);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        self.finish().map(|(_, node)| node)
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeStruct for ArchiveObject<'a, 'b, S>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeTupleVariant for SerializeVariant<ArchiveArray<'a, 'b, S>>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.inner.serialize_element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        let (sink, node) = self.inner.finish()?;
+        let value = sink.finish_buffer(node);
+        sink.archive_object(iter::once((&self.variant.to_string(), &value)))
+    }
+}
+
+impl<'a, 'b, S> ser::SerializeStructVariant for SerializeVariant<ArchiveObject<'a, 'b, S>>
+where
+    S: ScratchSpace + Serializer + ?Sized,
+    S::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ValueNode;
+    type Error = ValueSerializeError<S::Error>;
+
+    #[inline]
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.inner.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<ValueNode, Self::Error> {
+        let (sink, node) = self.inner.finish()?;
+        let value = sink.finish_buffer(node);
+        sink.archive_object(iter::once((&self.variant.to_string(), &value)))
+    }
+}
+
+// Converts map keys to strings. Only strings and types with an obvious string form are accepted.
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = ValueSerializeError;
+
+    type SerializeSeq = ser::Impossible<String, Self::Error>;
+    type SerializeTuple = ser::Impossible<String, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<String, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<String, Self::Error>;
+    type SerializeMap = ser::Impossible<String, Self::Error>;
+    type SerializeStruct = ser::Impossible<String, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<String, Self::Error>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_f32(self, _: f32) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_f64(self, _: f64) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, _: &[u8]) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, _: &T) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<String, Self::Error> {
+        Ok(variant.to_string())
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<String, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ValueSerializeError::KeyMustBeString)
+    }
+}
+
+impl ser::Serialize for Number {
+    #[inline]
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Number::PosInt(n) => serializer.serialize_u64(n),
+            Number::NegInt(n) => serializer.serialize_i64(n),
+            Number::Float(n) => serializer.serialize_f64(n),
+        }
+    }
+}
+
+impl ser::Serialize for ArchivedNumber {
+    #[inline]
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ser::Serialize::serialize(&self.to_number(), serializer)
+    }
+}
+
+impl ser::Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Number(n) => ser::Serialize::serialize(n, serializer),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Array(a) => serializer.collect_seq(a),
+            Value::Object(o) => serializer.collect_map(o),
+        }
+    }
+}
+
+impl ser::Serialize for ArchivedValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ArchivedValue::Null => serializer.serialize_unit(),
+            ArchivedValue::Bool(b) => serializer.serialize_bool(*b),
+            ArchivedValue::Number(n) => ser::Serialize::serialize(n, serializer),
+            ArchivedValue::String(s) => serializer.serialize_str(s),
+            ArchivedValue::Array(a) => {
+                let mut seq = serializer.serialize_seq(Some(a.len()))?;
+                for element in a.iter() {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            ArchivedValue::Object(o) => {
+                let mut map = serializer.serialize_map(Some(o.len()))?;
+                for (key, value) in o.iter() {
+                    map.serialize_entry(key.as_str(), value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
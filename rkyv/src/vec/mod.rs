@@ -178,6 +178,45 @@ impl<T> ArchivedVec<T> {
             Ok(VecResolver { pos })
         }
     }
+
+    /// Serializes an archived `Vec` from an iterator of unknown length.
+    ///
+    /// Items are serialized as they arrive. Resolving an item still requires the item, so items
+    /// are kept in memory along with their resolvers until the elements are written. Items that
+    /// have not been written yet are dropped if serialization fails. The result is the same as
+    /// [`serialize_from_iter`](ArchivedVec::serialize_from_iter).
+    ///
+    /// Returns the number of items serialized along with the resolver.
+    #[cfg(feature = "alloc")]
+    pub fn serialize_from_stream<U, B, I, S>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<(usize, VecResolver), S::Error>
+    where
+        U: Serialize<S, Archived = T>,
+        B: Borrow<U>,
+        I: Iterator<Item = B>,
+        S: Serializer + ?Sized,
+    {
+        #[cfg(not(feature = "std"))]
+        use alloc::vec::Vec;
+
+        let mut resolvers = Vec::new();
+        for value in iter {
+            let resolver = value.borrow().serialize(serializer)?;
+            resolvers.push((value, resolver));
+        }
+        let len = resolvers.len();
+
+        unsafe {
+            let pos = serializer.align_for::<T>()?;
+            for (value, resolver) in resolvers {
+                serializer.resolve_aligned(value.borrow(), resolver)?;
+            }
+
+            Ok((len, VecResolver { pos }))
+        }
+    }
 }
 
 impl<T> AsRef<[T]> for ArchivedVec<T> {
@@ -292,7 +331,16 @@ impl<T: PartialOrd> PartialOrd<ArchivedVec<T>> for [T] {
 
 /// The resolver for [`ArchivedVec`].
 pub struct VecResolver {
-    pos: usize,
+    pub(crate) pos: usize,
+}
+
+impl VecResolver {
+    /// Creates a resolver for an archived `Vec` whose elements were written at the given position.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    #[inline]
+    pub(crate) fn from_pos(pos: usize) -> Self {
+        Self { pos }
+    }
 }
 
 #[cfg(feature = "validation")]
@@ -304,6 +352,12 @@ const _: () = {
     use bytecheck::{CheckBytes, Error};
 
     impl<T> ArchivedVec<T> {
+        /// Returns the relative pointer to the elements of the archived vec.
+        #[inline]
+        pub(crate) fn rel_ptr(&self) -> &RelPtr<T> {
+            &self.ptr
+        }
+
         /// Checks the bytes of the `ArchivedVec` with the given element checking function.
         ///
         /// # Safety
@@ -356,3 +410,45 @@ const _: () = {
         }
     }
 };
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<T: Compact> Compact for ArchivedVec<T> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            resolvers.push(compactor.compact_out_of_line(self.as_slice()));
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.ptr
+                .compact_ptr(field_pos(self, out, &self.ptr), resolvers.take(), compactor);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<T: Transcode> Transcode for ArchivedVec<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            RelPtr::<[T]>::transcode(value.cast(), transcoder)
+        }
+    }
+};
@@ -0,0 +1,355 @@
+//! Archived owned pointers with 64-bit offsets and lengths.
+//!
+//! The relative pointers and lengths used by most archived types are sized by the `size_16`,
+//! `size_32`, and `size_64` features. With `size_32`, an archive can't be larger than about 2 GiB.
+//! [`ArchivedWideBox`] always uses a 64-bit relative pointer and a 64-bit length, so only the
+//! fields that actually hold huge values pay for the larger pointers. Use the
+//! [`Wide`](crate::with::Wide) wrapper to archive a `Box`, `Vec`, or `String` field this way.
+
+use crate::{rel_ptr::RawRelPtr, ArchivePointee, ArchiveUnsized, Archived, SerializeUnsized};
+use core::{borrow::Borrow, cmp, fmt, hash, marker::PhantomData, ops::Deref, pin::Pin};
+use ptr_meta::Pointee;
+
+/// A pointee that can be archived behind a wide pointer.
+///
+/// The pointer metadata of wide pointees is archived as a single 64-bit integer.
+pub trait WidePointee: ArchivePointee {
+    /// Converts some pointer metadata to its wide archived form.
+    fn to_wide_metadata(metadata: <Self as Pointee>::Metadata) -> u64;
+
+    /// Converts some wide archived metadata back to pointer metadata.
+    fn from_wide_metadata(metadata: u64) -> <Self as Pointee>::Metadata;
+}
+
+impl<T> WidePointee for T {
+    #[inline]
+    fn to_wide_metadata(_: <Self as Pointee>::Metadata) -> u64 {
+        0
+    }
+
+    #[inline]
+    fn from_wide_metadata(_: u64) -> <Self as Pointee>::Metadata {}
+}
+
+impl<T> WidePointee for [T] {
+    #[inline]
+    fn to_wide_metadata(metadata: <Self as Pointee>::Metadata) -> u64 {
+        metadata as u64
+    }
+
+    #[inline]
+    fn from_wide_metadata(metadata: u64) -> <Self as Pointee>::Metadata {
+        metadata as usize
+    }
+}
+
+impl WidePointee for str {
+    #[inline]
+    fn to_wide_metadata(metadata: <Self as Pointee>::Metadata) -> u64 {
+        metadata as u64
+    }
+
+    #[inline]
+    fn from_wide_metadata(metadata: u64) -> <Self as Pointee>::Metadata {
+        metadata as usize
+    }
+}
+
+/// An archived `Box` with a 64-bit relative pointer and length.
+///
+/// Unlike [`ArchivedBox`](crate::boxed::ArchivedBox), the size of this type does not depend on the
+/// `size_*` features.
+#[cfg_attr(feature = "strict", repr(C))]
+pub struct ArchivedWideBox<T: WidePointee + ?Sized> {
+    ptr: RawRelPtr<Archived<i64>>,
+    metadata: Archived<u64>,
+    _phantom: PhantomData<T>,
+}
+
+/// An archived `Vec` with a 64-bit relative pointer and length.
+pub type ArchivedWideVec<T> = ArchivedWideBox<[T]>;
+
+/// An archived `String` with a 64-bit relative pointer and length.
+pub type ArchivedWideString = ArchivedWideBox<str>;
+
+impl<T: WidePointee + ?Sized> ArchivedWideBox<T> {
+    /// Returns the archived pointer metadata of this wide box.
+    #[inline]
+    fn pointer_metadata(&self) -> <T as Pointee>::Metadata {
+        T::from_wide_metadata(from_archived!(self.metadata))
+    }
+
+    /// Returns a reference to the value of this archived wide box.
+    #[inline]
+    pub fn get(&self) -> &T {
+        unsafe { &*ptr_meta::from_raw_parts(self.ptr.as_ptr(), self.pointer_metadata()) }
+    }
+
+    /// Returns a pinned mutable reference to the value of this archived wide box.
+    #[inline]
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        unsafe {
+            self.map_unchecked_mut(|s| {
+                let metadata = s.pointer_metadata();
+                &mut *ptr_meta::from_raw_parts_mut(s.ptr.as_mut_ptr(), metadata)
+            })
+        }
+    }
+
+    /// Resolves an archived wide box from the given value and parameters.
+    ///
+    /// # Safety
+    ///
+    /// - `pos` must be the position of `out` within the archive
+    /// - `resolver` must be the result of serializing `value`
+    #[inline]
+    pub unsafe fn resolve_from_ref<U>(
+        value: &U,
+        pos: usize,
+        resolver: WideBoxResolver,
+        out: *mut Self,
+    ) where
+        U: ArchiveUnsized<Archived = T> + Pointee<Metadata = <T as Pointee>::Metadata> + ?Sized,
+    {
+        let (fp, fo) = out_field!(out.ptr);
+        RawRelPtr::emplace(pos + fp, resolver.pos, fo);
+        let (_, fo) = out_field!(out.metadata);
+        let metadata = T::to_wide_metadata(ptr_meta::metadata(value));
+        fo.write(to_archived!(metadata));
+    }
+
+    /// Serializes an archived wide box from the given value and serializer.
+    #[inline]
+    pub fn serialize_from_ref<U, S>(
+        value: &U,
+        serializer: &mut S,
+    ) -> Result<WideBoxResolver, S::Error>
+    where
+        U: SerializeUnsized<S, Archived = T> + ?Sized,
+        S: crate::Fallible + ?Sized,
+    {
+        Ok(WideBoxResolver {
+            pos: value.serialize_unsized(serializer)?,
+        })
+    }
+}
+
+impl<T: WidePointee + ?Sized> AsRef<T> for ArchivedWideBox<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: WidePointee + ?Sized> Borrow<T> for ArchivedWideBox<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: WidePointee + fmt::Debug + ?Sized> fmt::Debug for ArchivedWideBox<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: WidePointee + ?Sized> Deref for ArchivedWideBox<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T: WidePointee + fmt::Display + ?Sized> fmt::Display for ArchivedWideBox<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: WidePointee + Eq + ?Sized> Eq for ArchivedWideBox<T> {}
+
+impl<T: WidePointee + hash::Hash + ?Sized> hash::Hash for ArchivedWideBox<T> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.get().hash(state);
+    }
+}
+
+impl<T: WidePointee + Ord + ?Sized> Ord for ArchivedWideBox<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl<T: WidePointee + PartialEq<U> + ?Sized, U: WidePointee + ?Sized> PartialEq<ArchivedWideBox<U>>
+    for ArchivedWideBox<T>
+{
+    #[inline]
+    fn eq(&self, other: &ArchivedWideBox<U>) -> bool {
+        self.get().eq(other.get())
+    }
+}
+
+impl<T: WidePointee + PartialOrd + ?Sized> PartialOrd for ArchivedWideBox<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.get().partial_cmp(other.get())
+    }
+}
+
+impl<T: WidePointee + ?Sized> fmt::Pointer for ArchivedWideBox<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ptr = self.get() as *const T;
+        fmt::Pointer::fmt(&ptr, f)
+    }
+}
+
+/// The resolver for [`ArchivedWideBox`].
+pub struct WideBoxResolver {
+    pos: usize,
+}
+
+#[cfg(feature = "alloc")]
+const _: () = {
+    #[cfg(not(feature = "std"))]
+    use alloc::{boxed::Box, string::String, vec::Vec};
+
+    impl<T, U> PartialEq<Box<U>> for ArchivedWideBox<T>
+    where
+        T: WidePointee + PartialEq<U> + ?Sized,
+        U: ?Sized,
+    {
+        #[inline]
+        fn eq(&self, other: &Box<U>) -> bool {
+            self.get().eq(other.as_ref())
+        }
+    }
+
+    impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for ArchivedWideVec<T> {
+        #[inline]
+        fn eq(&self, other: &Vec<U>) -> bool {
+            self.get().eq(other.as_slice())
+        }
+    }
+
+    impl PartialEq<String> for ArchivedWideString {
+        #[inline]
+        fn eq(&self, other: &String) -> bool {
+            self.get().eq(other.as_str())
+        }
+    }
+};
+
+#[cfg(feature = "validation")]
+const _: () = {
+    use crate::validation::{owned::OwnedPointerError, ArchiveContext, LayoutRaw};
+    use bytecheck::{CheckBytes, Error};
+    use core::{convert::Infallible, ptr};
+
+    impl<T, C> CheckBytes<C> for ArchivedWideBox<T>
+    where
+        T: WidePointee + CheckBytes<C> + LayoutRaw + ?Sized,
+        C: ArchiveContext + ?Sized,
+        C::Error: Error,
+    {
+        type Error = OwnedPointerError<Infallible, T::Error, C::Error>;
+
+        #[inline]
+        unsafe fn check_bytes<'a>(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<&'a Self, Self::Error> {
+            let raw_ptr = RawRelPtr::manual_check_bytes(ptr::addr_of!((*value).ptr), context)
+                .map_err(OwnedPointerError::PointerCheckBytesError)?;
+            let metadata = Archived::<u64>::check_bytes(ptr::addr_of!((*value).metadata), context)
+                .map_err(OwnedPointerError::PointerCheckBytesError)?;
+
+            let ptr = context
+                .check_subtree_ptr::<T>(
+                    raw_ptr.base(),
+                    raw_ptr.offset(),
+                    T::from_wide_metadata(from_archived!(*metadata)),
+                )
+                .map_err(OwnedPointerError::ContextError)?;
+
+            let range = context
+                .push_prefix_subtree(ptr)
+                .map_err(OwnedPointerError::ContextError)?;
+            T::check_bytes(ptr, context).map_err(OwnedPointerError::ValueCheckBytesError)?;
+            context
+                .pop_prefix_range(range)
+                .map_err(OwnedPointerError::ContextError)?;
+
+            Ok(&*value)
+        }
+    }
+};
+
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<T: WidePointee + Compact + ?Sized> Compact for ArchivedWideBox<T> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            resolvers.push(compactor.compact_out_of_line(self.get()));
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            compactor
+                .write_offset::<Archived<i64>>(field_pos(self, out, &self.ptr), resolvers.take());
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::{
+        rel_ptr::Offset,
+        transcode::{Transcode, TranscodeError, Transcoder},
+    };
+    use core::ptr;
+
+    impl<T: WidePointee + Transcode + ?Sized> Transcode for ArchivedWideBox<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            let ptr_ptr = ptr::addr_of_mut!((*value).ptr).cast::<Archived<i64>>();
+            let metadata_ptr = ptr::addr_of_mut!((*value).metadata);
+
+            let offset = transcoder.read(ptr_ptr).to_isize();
+            let metadata = transcoder.read(metadata_ptr);
+
+            Archived::<i64>::transcode(ptr_ptr, transcoder)?;
+            Archived::<u64>::transcode(metadata_ptr, transcoder)?;
+
+            let target = transcoder.bounds_check_ptr(ptr_ptr.cast(), offset)?;
+            T::transcode(
+                ptr_meta::from_raw_parts_mut(
+                    target.cast(),
+                    T::from_wide_metadata(from_archived!(metadata)),
+                ),
+                transcoder,
+            )
+        }
+    }
+};
@@ -1,13 +1,24 @@
 use crate::{
     boxed::{ArchivedBox, BoxResolver},
-    collections::util::Entry,
+    collections::{
+        sorted_map::{ArchivedSortedMap, SortedMapResolver},
+        sorted_set::{ArchivedSortedSet, SortedSetResolver},
+        trie_map::{ArchivedTrieMap, TrieMapResolver},
+        util::Entry,
+    },
+    columnar::{ArchiveColumns, DeserializeColumns, SerializeColumns},
     niche::option_box::{ArchivedOptionBox, OptionBoxResolver},
-    ser::{ScratchSpace, Serializer},
-    string::{ArchivedString, StringResolver},
+    packed::{
+        bit_packed::BitPackedResolver, delta_packed::DeltaPackedResolver, ArchivedBitPacked,
+        ArchivedDeltaPacked, PackedInt,
+    },
+    rc::RcResolver,
+    ser::{InternRegistry, ScratchSpace, Serializer},
+    string::{ArchivedInternedString, ArchivedString, StringResolver},
     vec::{ArchivedVec, RawArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsOwned, AsVec, CopyOptimize, DeserializeWith, Map, Niche, Raw, RefAsBox,
-        SerializeWith, With,
+        ArchiveWith, AsOwned, AsSortedVec, AsTrie, AsVec, BitPacked, Columnar, CopyOptimize,
+        DeltaPacked, DeserializeWith, Interned, Map, Niche, Raw, RefAsBox, SerializeWith, With,
     },
     Archive, ArchiveUnsized, ArchivedMetadata, Deserialize, DeserializeUnsized, Fallible,
     MetadataResolver, Serialize, SerializeUnsized,
@@ -18,6 +29,7 @@ use alloc::{
     borrow::Cow,
     boxed::Box,
     collections::{BTreeMap, BTreeSet},
+    string::String,
     vec::Vec,
 };
 #[cfg(feature = "std")]
@@ -355,6 +367,629 @@ where
     }
 }
 
+// AsSortedVec
+
+impl<K, V> ArchiveWith<BTreeMap<K, V>> for AsSortedVec
+where
+    K: Archive,
+    V: Archive,
+{
+    type Archived = ArchivedSortedMap<K::Archived, V::Archived>;
+    type Resolver = SortedMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &BTreeMap<K, V>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedSortedMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<K, V, S> SerializeWith<BTreeMap<K, V>, S> for AsSortedVec
+where
+    K: Serialize<S> + Ord,
+    V: Serialize<S>,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &BTreeMap<K, V>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedSortedMap::<K::Archived, V::Archived>::serialize_from_iter(
+                field.iter(),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<K, V, D> DeserializeWith<ArchivedSortedMap<K::Archived, V::Archived>, BTreeMap<K, V>, D>
+    for AsSortedVec
+where
+    K: Archive + Ord,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeMap<K, V>, D::Error> {
+        let mut result = BTreeMap::new();
+        for (key, value) in field.iter() {
+            result.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+impl<T> ArchiveWith<BTreeSet<T>> for AsSortedVec
+where
+    T: Archive,
+{
+    type Archived = ArchivedSortedSet<T::Archived>;
+    type Resolver = SortedSetResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &BTreeSet<T>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedSortedSet::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<BTreeSet<T>, S> for AsSortedVec
+where
+    T: Serialize<S> + Ord,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(field: &BTreeSet<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        unsafe { ArchivedSortedSet::<T::Archived>::serialize_from_iter(field.iter(), serializer) }
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedSortedSet<T::Archived>, BTreeSet<T>, D> for AsSortedVec
+where
+    T: Archive + Ord,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedSet<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeSet<T>, D::Error> {
+        let mut result = BTreeSet::new();
+        for key in field.iter() {
+            result.insert(key.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// AsTrie
+
+impl<V> ArchiveWith<BTreeMap<String, V>> for AsTrie
+where
+    V: Archive,
+{
+    type Archived = ArchivedTrieMap<V::Archived>;
+    type Resolver = TrieMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &BTreeMap<String, V>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedTrieMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<V, S> SerializeWith<BTreeMap<String, V>, S> for AsTrie
+where
+    V: Serialize<S>,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &BTreeMap<String, V>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedTrieMap::<V::Archived>::serialize_from_iter(
+                field.iter().map(|(key, value)| (key.as_str(), value)),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<V, D> DeserializeWith<ArchivedTrieMap<V::Archived>, BTreeMap<String, V>, D> for AsTrie
+where
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedTrieMap<V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeMap<String, V>, D::Error> {
+        let mut result = BTreeMap::new();
+        for (key, value) in field.iter() {
+            result.insert(key, value.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// Columnar
+
+impl<T: ArchiveColumns> ArchiveWith<Vec<T>> for Columnar {
+    type Archived = T::Columns;
+    type Resolver = T::ColumnsResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &Vec<T>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        T::resolve_columns(field.len(), pos, resolver, out);
+    }
+}
+
+impl<T: SerializeColumns<S>, S: Fallible + ?Sized> SerializeWith<Vec<T>, S> for Columnar {
+    #[inline]
+    fn serialize_with(field: &Vec<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        T::serialize_columns(field, serializer)
+    }
+}
+
+impl<T: DeserializeColumns<D>, D: Fallible + ?Sized> DeserializeWith<T::Columns, Vec<T>, D>
+    for Columnar
+{
+    #[inline]
+    fn deserialize_with(field: &T::Columns, deserializer: &mut D) -> Result<Vec<T>, D::Error> {
+        let len = T::columns_len(field);
+        let mut result = Vec::with_capacity(len);
+        for index in 0..len {
+            result.push(T::deserialize_row(field, index, deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// BitPacked
+
+impl<T: PackedInt> ArchiveWith<Vec<T>> for BitPacked {
+    type Archived = ArchivedBitPacked<T>;
+    type Resolver = BitPackedResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &Vec<T>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedBitPacked::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<T>, S> for BitPacked
+where
+    T: PackedInt,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(field: &Vec<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedBitPacked::serialize_from_slice(field.as_slice(), serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedBitPacked<T>, Vec<T>, D> for BitPacked
+where
+    T: PackedInt,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(field: &ArchivedBitPacked<T>, _: &mut D) -> Result<Vec<T>, D::Error> {
+        Ok(field.iter().collect())
+    }
+}
+
+// DeltaPacked
+
+impl<T: PackedInt> ArchiveWith<Vec<T>> for DeltaPacked {
+    type Archived = ArchivedDeltaPacked<T>;
+    type Resolver = DeltaPackedResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &Vec<T>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedDeltaPacked::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<T>, S> for DeltaPacked
+where
+    T: PackedInt,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(field: &Vec<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedDeltaPacked::serialize_from_slice(field.as_slice(), serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedDeltaPacked<T>, Vec<T>, D> for DeltaPacked
+where
+    T: PackedInt,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(field: &ArchivedDeltaPacked<T>, _: &mut D) -> Result<Vec<T>, D::Error> {
+        Ok(field.iter().collect())
+    }
+}
+
+// EliasFano
+
+#[cfg(feature = "bitvec")]
+const _: () = {
+    use crate::{
+        packed::elias_fano::{ArchivedEliasFano, EliasFanoResolver},
+        with::{EliasFano, UnsortedError},
+    };
+
+    impl ArchiveWith<Vec<u64>> for EliasFano {
+        type Archived = ArchivedEliasFano;
+        type Resolver = EliasFanoResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &Vec<u64>,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedEliasFano::resolve_from_len(field.len(), pos, resolver, out);
+        }
+    }
+
+    impl<S> SerializeWith<Vec<u64>, S> for EliasFano
+    where
+        S: ScratchSpace + Serializer + ?Sized,
+        S::Error: From<UnsortedError>,
+    {
+        #[inline]
+        fn serialize_with(
+            field: &Vec<u64>,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            if field.windows(2).any(|pair| pair[0] > pair[1]) {
+                return Err(UnsortedError::Unsorted.into());
+            }
+            ArchivedEliasFano::serialize_from_iter(field.iter().copied(), serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> DeserializeWith<ArchivedEliasFano, Vec<u64>, D> for EliasFano {
+        #[inline]
+        fn deserialize_with(field: &ArchivedEliasFano, _: &mut D) -> Result<Vec<u64>, D::Error> {
+            Ok(field.iter().collect())
+        }
+    }
+
+    impl ArchiveWith<BTreeSet<u64>> for EliasFano {
+        type Archived = ArchivedEliasFano;
+        type Resolver = EliasFanoResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &BTreeSet<u64>,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedEliasFano::resolve_from_len(field.len(), pos, resolver, out);
+        }
+    }
+
+    impl<S> SerializeWith<BTreeSet<u64>, S> for EliasFano
+    where
+        S: ScratchSpace + Serializer + ?Sized,
+    {
+        #[inline]
+        fn serialize_with(
+            field: &BTreeSet<u64>,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedEliasFano::serialize_from_iter(field.iter().copied(), serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> DeserializeWith<ArchivedEliasFano, BTreeSet<u64>, D> for EliasFano {
+        #[inline]
+        fn deserialize_with(
+            field: &ArchivedEliasFano,
+            _: &mut D,
+        ) -> Result<BTreeSet<u64>, D::Error> {
+            Ok(field.iter().collect())
+        }
+    }
+};
+
+// RankSelect
+
+#[cfg(feature = "bitvec")]
+const _: () = {
+    use crate::{
+        bitvec::{rank_select::RankSelectResolver, ArchivedRankSelect},
+        with::RankSelect,
+        Archived,
+    };
+    use bitvec::{order::BitOrder, store::BitStore, vec::BitVec};
+
+    impl<T, O> ArchiveWith<BitVec<T, O>> for RankSelect
+    where
+        T: BitStore + Archive,
+        O: BitOrder,
+    {
+        type Archived = ArchivedRankSelect<Archived<T>, O>;
+        type Resolver = RankSelectResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &BitVec<T, O>,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedRankSelect::resolve_from_len(field.len(), pos, resolver, out);
+        }
+    }
+
+    impl<T, O, S> SerializeWith<BitVec<T, O>, S> for RankSelect
+    where
+        T: BitStore + Archive,
+        O: BitOrder,
+        Archived<T>: BitStore + Serialize<S, Archived = Archived<T>>,
+        S: ScratchSpace + Serializer + ?Sized,
+    {
+        #[inline]
+        fn serialize_with(
+            field: &BitVec<T, O>,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedRankSelect::<Archived<T>, O>::serialize_from_bitslice(field, serializer)
+        }
+    }
+
+    impl<T, O, D> DeserializeWith<ArchivedRankSelect<Archived<T>, O>, BitVec<T, O>, D> for RankSelect
+    where
+        T: BitStore + Archive,
+        O: BitOrder,
+        Archived<T>: BitStore,
+        D: Fallible + ?Sized,
+    {
+        #[inline]
+        fn deserialize_with(
+            field: &ArchivedRankSelect<Archived<T>, O>,
+            _: &mut D,
+        ) -> Result<BitVec<T, O>, D::Error> {
+            let mut result = BitVec::with_capacity(field.len());
+            result.extend_from_bitslice(field.as_bitslice());
+            Ok(result)
+        }
+    }
+};
+
+// Interned
+
+impl ArchiveWith<String> for Interned {
+    type Archived = ArchivedInternedString;
+    type Resolver = RcResolver<()>;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &String,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedInternedString::resolve_from_ref(field.as_str(), pos, resolver, out);
+    }
+}
+
+impl<S> SerializeWith<String, S> for Interned
+where
+    S: InternRegistry + Serializer + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    #[inline]
+    fn serialize_with(field: &String, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedInternedString::serialize_interned_from_str(field.as_str(), serializer)
+    }
+}
+
+impl<D: Fallible + ?Sized> DeserializeWith<ArchivedInternedString, String, D> for Interned {
+    #[inline]
+    fn deserialize_with(field: &ArchivedInternedString, _: &mut D) -> Result<String, D::Error> {
+        Ok(String::from(field.get()))
+    }
+}
+
+// Wide
+
+#[cfg(target_pointer_width = "64")]
+const _: () = {
+    use crate::{
+        wide::{
+            ArchivedWideBox, ArchivedWideString, ArchivedWideVec, WideBoxResolver, WidePointee,
+        },
+        with::Wide,
+    };
+    #[cfg(not(feature = "std"))]
+    use ::alloc::alloc;
+    use ptr_meta::Pointee;
+    #[cfg(feature = "std")]
+    use std::alloc;
+
+    impl<T> ArchiveWith<Box<T>> for Wide
+    where
+        T: ArchiveUnsized + Pointee<Metadata = <T::Archived as Pointee>::Metadata> + ?Sized,
+        T::Archived: WidePointee,
+    {
+        type Archived = ArchivedWideBox<T::Archived>;
+        type Resolver = WideBoxResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &Box<T>,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedWideBox::resolve_from_ref(field.as_ref(), pos, resolver, out);
+        }
+    }
+
+    impl<T, S> SerializeWith<Box<T>, S> for Wide
+    where
+        T: SerializeUnsized<S> + Pointee<Metadata = <T::Archived as Pointee>::Metadata> + ?Sized,
+        T::Archived: WidePointee,
+        S: Fallible + ?Sized,
+    {
+        #[inline]
+        fn serialize_with(field: &Box<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedWideBox::serialize_from_ref(field.as_ref(), serializer)
+        }
+    }
+
+    impl<T, D> DeserializeWith<ArchivedWideBox<T::Archived>, Box<T>, D> for Wide
+    where
+        T: ArchiveUnsized + ?Sized,
+        T::Archived: WidePointee + DeserializeUnsized<T, D>,
+        D: Fallible + ?Sized,
+    {
+        #[inline]
+        fn deserialize_with(
+            field: &ArchivedWideBox<T::Archived>,
+            deserializer: &mut D,
+        ) -> Result<Box<T>, D::Error> {
+            unsafe {
+                let data_address = field
+                    .get()
+                    .deserialize_unsized(deserializer, |layout| alloc::alloc(layout))?;
+                let metadata = field.get().deserialize_metadata(deserializer)?;
+                let ptr = ptr_meta::from_raw_parts_mut(data_address, metadata);
+                Ok(Box::from_raw(ptr))
+            }
+        }
+    }
+
+    impl<T: Archive> ArchiveWith<Vec<T>> for Wide {
+        type Archived = ArchivedWideVec<T::Archived>;
+        type Resolver = WideBoxResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &Vec<T>,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedWideVec::resolve_from_ref(field.as_slice(), pos, resolver, out);
+        }
+    }
+
+    impl<T, S> SerializeWith<Vec<T>, S> for Wide
+    where
+        T: Archive,
+        [T]: SerializeUnsized<S, Archived = [T::Archived]>,
+        S: Fallible + ?Sized,
+    {
+        #[inline]
+        fn serialize_with(field: &Vec<T>, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedWideVec::serialize_from_ref(field.as_slice(), serializer)
+        }
+    }
+
+    impl<T, D> DeserializeWith<ArchivedWideVec<T::Archived>, Vec<T>, D> for Wide
+    where
+        T: Archive,
+        [T::Archived]: DeserializeUnsized<[T], D>,
+        D: Fallible + ?Sized,
+    {
+        #[inline]
+        fn deserialize_with(
+            field: &ArchivedWideVec<T::Archived>,
+            deserializer: &mut D,
+        ) -> Result<Vec<T>, D::Error> {
+            unsafe {
+                let data_address = field
+                    .get()
+                    .deserialize_unsized(deserializer, |layout| alloc::alloc(layout))?;
+                let metadata = field.get().deserialize_metadata(deserializer)?;
+                let ptr = ptr_meta::from_raw_parts_mut(data_address, metadata);
+                Ok(Box::<[T]>::from_raw(ptr).into())
+            }
+        }
+    }
+
+    impl ArchiveWith<String> for Wide {
+        type Archived = ArchivedWideString;
+        type Resolver = WideBoxResolver;
+
+        #[inline]
+        unsafe fn resolve_with(
+            field: &String,
+            pos: usize,
+            resolver: Self::Resolver,
+            out: *mut Self::Archived,
+        ) {
+            ArchivedWideString::resolve_from_ref(field.as_str(), pos, resolver, out);
+        }
+    }
+
+    impl<S: Fallible + ?Sized> SerializeWith<String, S> for Wide
+    where
+        str: SerializeUnsized<S>,
+    {
+        #[inline]
+        fn serialize_with(field: &String, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedWideString::serialize_from_ref(field.as_str(), serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> DeserializeWith<ArchivedWideString, String, D> for Wide {
+        #[inline]
+        fn deserialize_with(field: &ArchivedWideString, _: &mut D) -> Result<String, D::Error> {
+            Ok(String::from(field.get()))
+        }
+    }
+};
+
 // Niche
 
 impl<T: ArchiveUnsized + ?Sized> ArchiveWith<Option<Box<T>>> for Niche
@@ -449,9 +1084,14 @@ where
         // Basic debug assert that T and T::Archived are at least the same size
         debug_assert_eq!(size_of::<T>(), size_of::<T::Archived>());
 
-        let mut result = Vec::with_capacity(field.len());
+        let mut result = Vec::<T>::with_capacity(field.len());
         unsafe {
-            copy_nonoverlapping(field.as_ptr().cast(), result.as_mut_ptr(), field.len());
+            // Copy bytes since the archived values may be less aligned than the native values
+            copy_nonoverlapping(
+                field.as_ptr().cast::<u8>(),
+                result.as_mut_ptr().cast::<u8>(),
+                field.len() * size_of::<T>(),
+            );
             result.set_len(field.len());
         }
 
@@ -503,9 +1143,14 @@ where
         // Basic debug assert that T and T::Archived are at least the same size
         debug_assert_eq!(size_of::<T>(), size_of::<T::Archived>());
 
-        let mut result = Vec::with_capacity(field.len());
+        let mut result = Vec::<T>::with_capacity(field.len());
         unsafe {
-            copy_nonoverlapping(field.as_ptr().cast(), result.as_mut_ptr(), field.len());
+            // Copy bytes since the archived values may be less aligned than the native values
+            copy_nonoverlapping(
+                field.as_ptr().cast::<u8>(),
+                result.as_mut_ptr().cast::<u8>(),
+                field.len() * size_of::<T>(),
+            );
             result.set_len(field.len());
         }
 
@@ -593,9 +1238,14 @@ where
         // Basic debug assert that T and T::Archived are at least the same size
         debug_assert_eq!(size_of::<T>(), size_of::<T::Archived>());
 
-        let mut result = Vec::with_capacity(field.len());
+        let mut result = Vec::<T>::with_capacity(field.len());
         unsafe {
-            copy_nonoverlapping(field.as_ptr().cast(), result.as_mut_ptr(), field.len());
+            // Copy bytes since the archived values may be less aligned than the native values
+            copy_nonoverlapping(
+                field.as_ptr().cast::<u8>(),
+                result.as_mut_ptr().cast::<u8>(),
+                field.len() * size_of::<T>(),
+            );
             result.set_len(field.len());
         }
 
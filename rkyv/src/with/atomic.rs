@@ -69,20 +69,28 @@ impl_atomic!(AtomicBool);
 impl_atomic!(AtomicI8);
 impl_atomic!(AtomicU8);
 
+// Multibyte atomics must be aligned, so they can't be archived with the unaligned feature
+#[cfg(not(feature = "unaligned"))]
 impl_atomic!(@multibyte AtomicI16);
+#[cfg(not(feature = "unaligned"))]
 impl_atomic!(@multibyte AtomicI32);
-#[cfg(has_atomics_64)]
+#[cfg(all(has_atomics_64, not(feature = "unaligned")))]
 impl_atomic!(@multibyte AtomicI64);
+#[cfg(not(feature = "unaligned"))]
 impl_atomic!(@multibyte AtomicU16);
+#[cfg(not(feature = "unaligned"))]
 impl_atomic!(@multibyte AtomicU32);
-#[cfg(has_atomics_64)]
+#[cfg(all(has_atomics_64, not(feature = "unaligned")))]
 impl_atomic!(@multibyte AtomicU64);
 
 // AtomicUsize
 
 // We can't implement Archive for AtomicUsize if the platform does not have 64-bit atomics but the
 // size type is 64-bit
-#[cfg(any(has_atomics_64, not(feature = "size_64")))]
+#[cfg(all(
+    any(has_atomics_64, not(feature = "size_64")),
+    not(feature = "unaligned")
+))]
 const _: () = {
     use crate::FixedUsize;
     use core::sync::atomic::AtomicUsize;
@@ -135,7 +143,10 @@ const _: () = {
 
 // We can't implement Archive for AtomicIsize if the platform does not have 64-bit atomics but the
 // size type is 64-bit
-#[cfg(any(has_atomics_64, not(feature = "size_64")))]
+#[cfg(all(
+    any(has_atomics_64, not(feature = "size_64")),
+    not(feature = "unaligned")
+))]
 const _: () = {
     use crate::FixedIsize;
     use core::sync::atomic::AtomicIsize;
@@ -17,7 +17,7 @@ pub use self::alloc::*;
 pub use self::std::*;
 
 use crate::{Archive, Deserialize, Fallible, Serialize};
-use ::core::{fmt, marker::PhantomData, mem::transmute, ops::Deref};
+use ::core::{fmt, hash, marker::PhantomData, mem::transmute, ops::Deref};
 
 /// A transparent wrapper for archived fields.
 ///
@@ -78,6 +78,22 @@ impl<F: ?Sized, W> AsRef<F> for With<F, W> {
     }
 }
 
+impl<F: hash::Hash + ?Sized, W> hash::Hash for With<F, W> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.field.hash(state)
+    }
+}
+
+impl<F: PartialEq + ?Sized, W> PartialEq for With<F, W> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.field == other.field
+    }
+}
+
+impl<F: Eq + ?Sized, W> Eq for With<F, W> {}
+
 /// A variant of [`Archive`] that works with [`With`] wrappers.
 ///
 /// Creating a wrapper allows users to customize how fields are archived easily without changing the
@@ -274,6 +290,48 @@ const _: () = {
     }
 };
 
+#[cfg(feature = "compact")]
+const _: () = {
+    use crate::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+    impl<T: Compact + ?Sized> Compact for Immutable<T> {
+        #[inline]
+        fn compact_dependencies(
+            &self,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0.compact_dependencies(compactor, resolvers);
+        }
+
+        #[inline]
+        fn compact_pointers(
+            &self,
+            out: usize,
+            compactor: &mut Compactor,
+            resolvers: &mut CompactResolvers,
+        ) {
+            self.0
+                .compact_pointers(field_pos(self, out, &self.0), compactor, resolvers);
+        }
+    }
+};
+
+#[cfg(feature = "transcode")]
+const _: () = {
+    use crate::transcode::{Transcode, TranscodeError, Transcoder};
+
+    impl<T: Transcode + ?Sized> Transcode for Immutable<T> {
+        #[inline]
+        unsafe fn transcode(
+            value: *mut Self,
+            transcoder: &mut Transcoder<'_>,
+        ) -> Result<(), TranscodeError> {
+            T::transcode(::core::ptr::addr_of_mut!((*value).0), transcoder)
+        }
+    }
+};
+
 /// A generic wrapper that allows wrapping an `Option<T>`.
 ///
 /// # Example
@@ -296,7 +354,9 @@ pub struct Map<Archivable> {
 
 /// A wrapper that archives an atomic with an underlying atomic.
 ///
-/// By default, atomics are archived with an underlying integer.
+/// By default, atomics are archived with an underlying integer. Multibyte atomics require
+/// alignment, so only `AtomicBool`, `AtomicI8`, and `AtomicU8` can be archived with this wrapper
+/// when the `unaligned` feature is enabled.
 ///
 /// # Safety
 ///
@@ -402,6 +462,54 @@ pub struct AsBox;
 #[derive(Debug)]
 pub struct RefAsBox;
 
+/// A wrapper that interns strings so that each distinct string is only serialized once.
+///
+/// Interned strings become [`ArchivedInternedString`](crate::string::ArchivedInternedString)s,
+/// which are relative pointers to a string that is shared by every interned string with the same
+/// contents. This can be applied to `String`s and to the keys of `HashMap`s. The serializer must
+/// implement [`InternRegistry`](crate::ser::InternRegistry), which
+/// [`AllocSerializer`](crate::ser::serializers::AllocSerializer) does.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rkyv::{Archive, with::Interned};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(Interned)]
+///     name: String,
+///     #[with(Interned)]
+///     properties: HashMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Interned;
+
+/// A wrapper that archives a `Box`, `Vec`, or `String` with a 64-bit relative pointer and length.
+///
+/// Fields are archived as [`ArchivedWideBox`](crate::wide::ArchivedWideBox)es, which don't depend
+/// on the `size_*` features. This lets a few huge fields point past the range of the default
+/// relative pointers without switching the whole archive to `size_64`. Only available on 64-bit
+/// targets.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{Archive, with::Wide};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     name: String,
+///     #[with(Wide)]
+///     blob: Vec<u8>,
+/// }
+/// ```
+#[cfg(target_pointer_width = "64")]
+#[derive(Debug)]
+pub struct Wide;
+
 /// A wrapper that attempts to convert a type to and from UTF-8.
 ///
 /// Types like `OsString` and `PathBuf` aren't guaranteed to be encoded as UTF-8, but they usually
@@ -532,6 +640,239 @@ pub struct AsOwned;
 #[derive(Debug)]
 pub struct AsVec;
 
+/// A wrapper that archives maps and sets as vectors sorted by key.
+///
+/// Maps are archived as [`ArchivedSortedMap`](crate::collections::ArchivedSortedMap)s and sets as
+/// [`ArchivedSortedSet`](crate::collections::ArchivedSortedSet)s. Unlike [`AsVec`], the archived
+/// containers keep their map semantics and can be looked up with a binary search. This is the most
+/// compact archived layout for maps and sets.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rkyv::{Archive, with::AsSortedVec};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsSortedVec)]
+///     values: HashMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsSortedVec;
+
+/// A wrapper that archives maps with string keys as tries.
+///
+/// Maps are archived as [`ArchivedTrieMap`](crate::collections::ArchivedTrieMap)s, which store the
+/// shared prefixes of their keys once and support ordered, prefix, and fuzzy iteration.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use rkyv::{Archive, with::AsTrie};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsTrie)]
+///     values: BTreeMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsTrie;
+
+/// A wrapper that archives a `Vec` of structs as one column per field.
+///
+/// The struct must use `#[archive(columnar)]`, and the vector is archived as its generated columns
+/// type. Each column is a contiguous slice of one archived field, so scans over a single field
+/// don't have to touch the other fields. See [`columnar`](crate::columnar) for more details.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{Archive, with::Columnar};
+///
+/// #[derive(Archive)]
+/// #[archive(columnar)]
+/// struct Record {
+///     id: u32,
+///     price: f64,
+/// }
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(Columnar)]
+///     records: Vec<Record>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Columnar;
+
+/// A wrapper that archives vectors of unsigned integers with bit packing.
+///
+/// Vectors are archived as [`ArchivedBitPacked`](crate::packed::ArchivedBitPacked)s, which store
+/// every value in the number of bits needed for the range of the values. The bit width is chosen
+/// when the vector is serialized. Values can still be accessed by index, iterated, and binary
+/// searched without unpacking the whole vector.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{Archive, with::BitPacked};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(BitPacked)]
+///     values: Vec<u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BitPacked;
+
+/// A wrapper that archives vectors of unsigned integers as blocks of packed deltas.
+///
+/// Vectors are archived as [`ArchivedDeltaPacked`](crate::packed::ArchivedDeltaPacked)s, which
+/// store the differences between consecutive values with bit packing. Each block of values starts
+/// with its full first value so that values can still be accessed by index, iterated, and binary
+/// searched without unpacking the whole vector. This works best for sorted values with small gaps.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{Archive, with::DeltaPacked};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(DeltaPacked)]
+///     timestamps: Vec<u64>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DeltaPacked;
+
+/// A wrapper that archives sorted sequences of `u64`s in Elias-Fano encoding.
+///
+/// Sorted `Vec<u64>`s and `BTreeSet<u64>`s are archived as
+/// [`ArchivedEliasFano`](crate::packed::ArchivedEliasFano)s, which support lookups by index,
+/// successor and predecessor queries, and iteration without decoding the whole sequence. This is a
+/// compact layout for posting lists and offset tables.
+///
+/// Sets are always sorted, but vectors might not be. Regular serializers don't support the custom
+/// error handling needed to serialize vectors by default. To serialize a `Vec<u64>` with this
+/// wrapper, a custom serializer with an error type satisfying
+/// `<S as Fallible>::Error: From<UnsortedError>` must be provided.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{Archive, with::EliasFano};
+/// use std::collections::BTreeSet;
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(EliasFano)]
+///     offsets: Vec<u64>,
+///     #[with(EliasFano)]
+///     ids: BTreeSet<u64>,
+/// }
+/// ```
+#[cfg(feature = "bitvec")]
+#[derive(Debug)]
+pub struct EliasFano;
+
+/// Errors that can occur when serializing a vector with an [`EliasFano`] wrapper.
+#[cfg(feature = "bitvec")]
+#[derive(Debug)]
+pub enum UnsortedError {
+    /// The values were not in non-decreasing order
+    Unsorted,
+}
+
+#[cfg(feature = "bitvec")]
+impl fmt::Display for UnsortedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "values serialized as Elias-Fano must be in non-decreasing order"
+        )
+    }
+}
+
+#[cfg(all(feature = "bitvec", feature = "std"))]
+impl ::std::error::Error for UnsortedError {}
+
+/// A wrapper that archives bit vectors with an index for rank and select queries.
+///
+/// Bit vectors archived with this wrapper become
+/// [`ArchivedRankSelect`](crate::bitvec::ArchivedRankSelect)s, which answer `rank1`, `rank0`,
+/// `select1` and `select0` queries without scanning the whole bit vector. The index is built
+/// while serializing and takes up about a quarter of the space of the bits.
+///
+/// # Example
+///
+/// ```
+/// use bitvec::vec::BitVec;
+/// use rkyv::{Archive, with::RankSelect};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(RankSelect)]
+///     bits: BitVec,
+/// }
+/// ```
+#[cfg(feature = "bitvec")]
+#[derive(Debug)]
+pub struct RankSelect;
+
+/// A wrapper that archives hash maps and sets with a different hasher.
+///
+/// By default, archived hash maps and sets use
+/// [`HashBuilder`](crate::collections::hash_index::HashBuilder). This wrapper can use any
+/// [`ArchiveHasher`](crate::collections::hash_index::ArchiveHasher) instead, for example a faster
+/// hasher for integer keys.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rkyv::{collections::hash_index::FxHasher, Archive, with::Hashed};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(Hashed<FxHasher>)]
+///     values: HashMap<u32, String>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Hashed<H> {
+    _hasher: PhantomData<H>,
+}
+
+/// A wrapper that archives hash maps as [`ArchivedSwissMap`](crate::collections::ArchivedSwissMap)s.
+///
+/// Swiss maps are open-addressing hash tables that are faster to look up than the default archived
+/// hash maps, but take up more space. The hasher can be any
+/// [`ArchiveHasher`](crate::collections::hash_index::ArchiveHasher) and defaults to
+/// [`HashBuilder`](crate::collections::hash_index::HashBuilder).
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rkyv::{Archive, with::AsSwissMap};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsSwissMap)]
+///     values: HashMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsSwissMap<H = crate::collections::hash_index::HashBuilder> {
+    _hasher: PhantomData<H>,
+}
+
 /// A wrapper that niches some type combinations.
 ///
 /// A common type combination is `Option<Box<T>>`. By using a null pointer, the archived version can
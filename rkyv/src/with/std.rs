@@ -1,16 +1,29 @@
 use crate::{
-    collections::util::Entry,
-    ser::{ScratchSpace, Serializer},
-    string::{ArchivedString, StringResolver},
+    collections::{
+        hash_index::ArchiveHasher,
+        hash_map::{ArchivedHashMap, HashMapResolver},
+        hash_set::{ArchivedHashSet, HashSetResolver},
+        sorted_map::{ArchivedSortedMap, SortedMapResolver},
+        sorted_set::{ArchivedSortedSet, SortedSetResolver},
+        swiss_map::{ArchivedSwissMap, SwissMapResolver},
+        trie_map::{ArchivedTrieMap, TrieMapResolver},
+        util::Entry,
+    },
+    ser::{InternRegistry, ScratchSpace, Serializer},
+    string::{ArchivedInternedString, ArchivedString, StringResolver},
     time::ArchivedDuration,
     vec::{ArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsString, AsStringError, AsVec, DeserializeWith, Immutable, Lock, LockError,
-        SerializeWith, UnixTimestamp, UnixTimestampError,
+        ArchiveWith, AsSortedVec, AsString, AsStringError, AsSwissMap, AsTrie, AsVec,
+        DeserializeWith, Hashed, Immutable, Interned, Lock, LockError, SerializeWith,
+        UnixTimestamp, UnixTimestampError, With,
     },
     Archive, Deserialize, Fallible, Serialize, SerializeUnsized,
 };
-use core::{hash::Hash, str::FromStr};
+use core::{
+    hash::{BuildHasher, Hash},
+    str::FromStr,
+};
 use std::{
     collections::{HashMap, HashSet},
     ffi::OsString,
@@ -290,6 +303,436 @@ where
     }
 }
 
+// Hashed
+
+impl<K, V, RS, H> ArchiveWith<HashMap<K, V, RS>> for Hashed<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Archive,
+    H: ArchiveHasher,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived, H>;
+    type Resolver = HashMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedHashMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<K, V, RS, H, S> SerializeWith<HashMap<K, V, RS>, S> for Hashed<H>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Serialize<S>,
+    H: ArchiveHasher,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedHashMap::<K::Archived, V::Archived, H>::serialize_from_iter(
+                field.iter(),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<K, V, RS, H, D>
+    DeserializeWith<ArchivedHashMap<K::Archived, V::Archived, H>, HashMap<K, V, RS>, D>
+    for Hashed<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D> + Hash + Eq,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    H: ArchiveHasher,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived, H>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+impl<K, RS, H> ArchiveWith<HashSet<K, RS>> for Hashed<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Hash + Eq,
+    H: ArchiveHasher,
+{
+    type Archived = ArchivedHashSet<K::Archived, H>;
+    type Resolver = HashSetResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashSet<K, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedHashSet::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<K, RS, H, S> SerializeWith<HashSet<K, RS>, S> for Hashed<H>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    H: ArchiveHasher,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashSet<K, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe { ArchivedHashSet::<K::Archived, H>::serialize_from_iter(field.iter(), serializer) }
+    }
+}
+
+impl<K, RS, H, D> DeserializeWith<ArchivedHashSet<K::Archived, H>, HashSet<K, RS>, D> for Hashed<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D> + Hash + Eq,
+    RS: Default + BuildHasher,
+    H: ArchiveHasher,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedHashSet<K::Archived, H>,
+        deserializer: &mut D,
+    ) -> Result<HashSet<K, RS>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+// Interned
+
+impl<V, RS> ArchiveWith<HashMap<String, V, RS>> for Interned
+where
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<ArchivedInternedString, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashMap<String, V, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedHashMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<V, RS, S> SerializeWith<HashMap<String, V, RS>, S> for Interned
+where
+    V: Serialize<S>,
+    S: InternRegistry + ScratchSpace + Serializer + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<String, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        // Keys in a `HashMap` are unique, so the interned keys are unique as well
+        unsafe {
+            ArchivedHashMap::<ArchivedInternedString, V::Archived>::serialize_from_iter(
+                field
+                    .iter()
+                    .map(|(key, value)| (With::<String, Interned>::cast(key), value)),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<V, RS, D>
+    DeserializeWith<ArchivedHashMap<ArchivedInternedString, V::Archived>, HashMap<String, V, RS>, D>
+    for Interned
+where
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedHashMap<ArchivedInternedString, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<String, V, RS>, D::Error> {
+        let mut result = HashMap::with_capacity_and_hasher(field.len(), RS::default());
+        for (key, value) in field.iter() {
+            result.insert(String::from(key.get()), value.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+// AsSortedVec
+
+impl<K, V, RS> ArchiveWith<HashMap<K, V, RS>> for AsSortedVec
+where
+    K: Archive,
+    V: Archive,
+{
+    type Archived = ArchivedSortedMap<K::Archived, V::Archived>;
+    type Resolver = SortedMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedSortedMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<K, V, RS, S> SerializeWith<HashMap<K, V, RS>, S> for AsSortedVec
+where
+    K: Serialize<S> + Ord,
+    V: Serialize<S>,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedSortedMap::<K::Archived, V::Archived>::serialize_from_iter(
+                field.iter(),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<K, V, RS, D> DeserializeWith<ArchivedSortedMap<K::Archived, V::Archived>, HashMap<K, V, RS>, D>
+    for AsSortedVec
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        let mut result = HashMap::with_capacity_and_hasher(field.len(), RS::default());
+        for (key, value) in field.iter() {
+            result.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+impl<T, RS> ArchiveWith<HashSet<T, RS>> for AsSortedVec
+where
+    T: Archive,
+{
+    type Archived = ArchivedSortedSet<T::Archived>;
+    type Resolver = SortedSetResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashSet<T, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedSortedSet::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<T, RS, S> SerializeWith<HashSet<T, RS>, S> for AsSortedVec
+where
+    T: Serialize<S> + Ord,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashSet<T, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe { ArchivedSortedSet::<T::Archived>::serialize_from_iter(field.iter(), serializer) }
+    }
+}
+
+impl<T, RS, D> DeserializeWith<ArchivedSortedSet<T::Archived>, HashSet<T, RS>, D> for AsSortedVec
+where
+    T: Archive + Hash + Eq,
+    T::Archived: Deserialize<T, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedSet<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashSet<T, RS>, D::Error> {
+        let mut result = HashSet::with_capacity_and_hasher(field.len(), RS::default());
+        for key in field.iter() {
+            result.insert(key.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// AsTrie
+
+impl<V, RS> ArchiveWith<HashMap<String, V, RS>> for AsTrie
+where
+    V: Archive,
+{
+    type Archived = ArchivedTrieMap<V::Archived>;
+    type Resolver = TrieMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashMap<String, V, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedTrieMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<V, RS, S> SerializeWith<HashMap<String, V, RS>, S> for AsTrie
+where
+    V: Serialize<S>,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<String, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedTrieMap::<V::Archived>::serialize_from_iter(
+                field.iter().map(|(key, value)| (key.as_str(), value)),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<V, RS, D> DeserializeWith<ArchivedTrieMap<V::Archived>, HashMap<String, V, RS>, D> for AsTrie
+where
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedTrieMap<V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<String, V, RS>, D::Error> {
+        let mut result = HashMap::with_capacity_and_hasher(field.len(), RS::default());
+        for (key, value) in field.iter() {
+            result.insert(key, value.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// AsSwissMap
+
+impl<K, V, RS, H> ArchiveWith<HashMap<K, V, RS>> for AsSwissMap<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Archive,
+    H: ArchiveHasher,
+{
+    type Archived = ArchivedSwissMap<K::Archived, V::Archived, H>;
+    type Resolver = SwissMapResolver;
+
+    #[inline]
+    unsafe fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        pos: usize,
+        resolver: Self::Resolver,
+        out: *mut Self::Archived,
+    ) {
+        ArchivedSwissMap::resolve_from_len(field.len(), pos, resolver, out);
+    }
+}
+
+impl<K, V, RS, H, S> SerializeWith<HashMap<K, V, RS>, S> for AsSwissMap<H>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Serialize<S>,
+    H: ArchiveHasher,
+    S: ScratchSpace + Serializer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        unsafe {
+            ArchivedSwissMap::<K::Archived, V::Archived, H>::serialize_from_iter(
+                field.iter(),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<K, V, RS, H, D>
+    DeserializeWith<ArchivedSwissMap<K::Archived, V::Archived, H>, HashMap<K, V, RS>, D>
+    for AsSwissMap<H>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D> + Hash + Eq,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    H: ArchiveHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSwissMap<K::Archived, V::Archived, H>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        let mut result = HashMap::with_capacity_and_hasher(field.len(), RS::default());
+        for (key, value) in field.iter() {
+            result.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
 // UnixTimestamp
 
 impl ArchiveWith<SystemTime> for UnixTimestamp {
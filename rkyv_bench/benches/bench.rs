@@ -7,6 +7,7 @@ use rkyv::{
         serializers::{AlignedSerializer, BufferScratch, CompositeSerializer},
         Serializer,
     },
+    with::AsSwissMap,
     AlignedVec, Archive, Deserialize, Infallible, Serialize,
 };
 use std::collections::HashMap;
@@ -451,5 +452,82 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, criterion_benchmark);
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+pub struct HashMapTable {
+    values: HashMap<String, u32>,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+pub struct SwissMapTable {
+    #[with(AsSwissMap)]
+    values: HashMap<String, u32>,
+}
+
+pub fn hash_map_benchmark(c: &mut Criterion) {
+    const ENTRIES: usize = 100_000;
+    const LOOKUPS: usize = 1_000;
+    const STATE: u64 = 2718281828;
+    const STREAM: u64 = 4590452353;
+
+    let mut values = HashMap::with_capacity(ENTRIES);
+    let mut rng = Lcg64Xsh32::new(STATE, STREAM);
+    while values.len() < ENTRIES {
+        values.insert(generate_player_name(&mut rng), rng.gen());
+    }
+
+    // Half of the lookups are for keys that are not in the map
+    let mut keys = values.keys().take(LOOKUPS / 2).cloned().collect::<Vec<_>>();
+    while keys.len() < LOOKUPS {
+        let key = generate_player_name(&mut rng);
+        if !values.contains_key(&key) {
+            keys.push(key);
+        }
+    }
+
+    let hash_map_buffer = rkyv::to_bytes::<_, 4096>(&HashMapTable {
+        values: values.clone(),
+    })
+    .unwrap();
+    let swiss_map_buffer = rkyv::to_bytes::<_, 4096>(&SwissMapTable { values }).unwrap();
+
+    let mut group = c.benchmark_group("hash_map");
+    {
+        let table = unsafe { archived_root::<HashMapTable>(hash_map_buffer.as_ref()) };
+        group.bench_function("lookup", |b| {
+            b.iter(|| {
+                for key in keys.iter() {
+                    black_box(table.values.get(black_box(key.as_str())));
+                }
+            })
+        });
+        group.bench_function("validate", |b| {
+            b.iter(|| {
+                check_archived_root::<HashMapTable>(black_box(hash_map_buffer.as_ref())).unwrap();
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("swiss_map");
+    {
+        let table = unsafe { archived_root::<SwissMapTable>(swiss_map_buffer.as_ref()) };
+        group.bench_function("lookup", |b| {
+            b.iter(|| {
+                for key in keys.iter() {
+                    black_box(table.values.get(black_box(key.as_str())));
+                }
+            })
+        });
+        group.bench_function("validate", |b| {
+            b.iter(|| {
+                check_archived_root::<SwissMapTable>(black_box(swiss_map_buffer.as_ref())).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, hash_map_benchmark);
 criterion_main!(benches);
@@ -1,14 +1,16 @@
 use crate::{
     attributes::{parse_attributes, Attributes},
+    columnar::derive_columnar_struct,
     repr::{BaseRepr, IntRepr, Repr},
+    transcode::{derive_transcode_width_enum, derive_transcode_width_struct},
     util::{add_bounds, strip_raw},
     with::{make_with_cast, make_with_ty},
 };
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{
-    parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Field, Fields, Ident,
-    Index, LitStr, Meta, NestedMeta, Type,
+    parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Field, Fields,
+    GenericArgument, Ident, Index, LitStr, Meta, NestedMeta, PathArguments, Type,
 };
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -34,6 +36,60 @@ fn field_archive_attrs(field: &Field) -> impl '_ + Iterator<Item = NestedMeta> {
         .flatten()
 }
 
+// Whether the archived type is made of archived primitives, which can't implement `Serialize` when
+// they're `rend` types
+fn serializes_as_primitive(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "f32",
+        "f64",
+        "char",
+        "NonZeroI16",
+        "NonZeroI32",
+        "NonZeroI64",
+        "NonZeroI128",
+        "NonZeroIsize",
+        "NonZeroU16",
+        "NonZeroU32",
+        "NonZeroU64",
+        "NonZeroU128",
+        "NonZeroUsize",
+    ];
+
+    match ty {
+        Type::Array(array) => serializes_as_primitive(&array.elem),
+        Type::Slice(slice) => serializes_as_primitive(&slice.elem),
+        Type::Group(group) => serializes_as_primitive(&group.elem),
+        Type::Paren(paren) => serializes_as_primitive(&paren.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last().unwrap();
+            match segment.arguments {
+                PathArguments::None => PRIMITIVES.iter().any(|p| segment.ident == p),
+                PathArguments::AngleBracketed(ref args)
+                    if ["Box", "Option", "Vec"].iter().any(|c| segment.ident == c)
+                        && args.args.len() == 1 =>
+                {
+                    matches!(
+                        args.args.first(),
+                        Some(GenericArgument::Type(ty)) if serializes_as_primitive(ty)
+                    )
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
 fn derive_archive_impl(
     mut input: DeriveInput,
     attributes: &Attributes,
@@ -69,7 +125,98 @@ fn derive_archive_impl(
         Vec::new()
     };
 
-    let archive_attrs = derive_check_bytes.into_iter().chain(
+    let derive_transcode = if attributes.transcode.is_some() {
+        let rkyv_path_str = attributes
+            .rkyv_path_str
+            .clone()
+            .unwrap_or_else(|| parse_quote!("::rkyv"));
+        vec![
+            parse_quote! { #[derive(#rkyv_path::transcode::Transcode)] },
+            parse_quote! { #[transcode(crate = #rkyv_path_str)] },
+        ]
+    } else {
+        Vec::new()
+    };
+
+    // Types that can be validated can also be extracted, which requires them to be compacted
+    let derive_compact = if attributes.compact.is_some()
+        || (cfg!(feature = "compact") && attributes.check_bytes.is_some())
+    {
+        let rkyv_path_str = attributes
+            .rkyv_path_str
+            .clone()
+            .unwrap_or_else(|| parse_quote!("::rkyv"));
+        vec![
+            parse_quote! { #[derive(#rkyv_path::compact::Compact)] },
+            parse_quote! { #[compact(crate = #rkyv_path_str)] },
+        ]
+    } else {
+        Vec::new()
+    };
+
+    // Archived primitives from rend can't implement `Serialize`, so fields made of them are
+    // serialized through their native values instead
+    let serialize_primitive_path_str = attributes
+        .rkyv_path_str
+        .as_ref()
+        .map(|x| LitStr::new(&format!("{}::serialize_primitive", x.value()), x.span()))
+        .unwrap_or_else(|| parse_quote!("::rkyv::serialize_primitive"));
+    let serializes_with_primitive = |field: &Field| {
+        attributes.serde.is_some()
+            && cfg!(any(feature = "archive_le", feature = "archive_be"))
+            && !field.attrs.iter().any(|a| a.path.is_ident("with"))
+            && serializes_as_primitive(&field.ty)
+    };
+    let field_serde_attrs = |field: &Field| -> Option<Attribute> {
+        serializes_with_primitive(field).then(|| {
+            parse_quote! { #[serde(serialize_with = #serialize_primitive_path_str)] }
+        })
+    };
+
+    let derive_serde = if attributes.serde.is_some() {
+        let serde_path_str = attributes
+            .rkyv_path_str
+            .as_ref()
+            .map(|x| LitStr::new(&format!("{}::serde", x.value()), x.span()))
+            .unwrap_or_else(|| parse_quote!("::rkyv::serde"));
+
+        // serde would bound each type parameter by `Serialize`, but the fields of the archived type
+        // are archived so they need to be bounded instead
+        let fields: Vec<&Field> = match input.data {
+            Data::Struct(ref data) => data.fields.iter().collect(),
+            Data::Enum(ref data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+            Data::Union(_) => Vec::new(),
+        };
+        let mut serialize_bounds = Vec::new();
+        for field in fields.into_iter().filter(|f| {
+            !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds")) && !serializes_with_primitive(f)
+        }) {
+            let ty = with_ty(field)?;
+            serialize_bounds.push(
+                quote! { #rkyv_path::Archived<#ty>: #rkyv_path::serde::Serialize }.to_string(),
+            );
+        }
+        let serialize_bound = LitStr::new(&serialize_bounds.join(", "), Span::call_site());
+
+        vec![
+            parse_quote! { #[derive(#rkyv_path::serde::Serialize)] },
+            parse_quote! {
+                #[serde(crate = #serde_path_str, bound(serialize = #serialize_bound))]
+            },
+        ]
+    } else {
+        Vec::new()
+    };
+
+    let derive_attrs = derive_check_bytes
+        .iter()
+        .cloned()
+        .chain(derive_compact)
+        .chain(derive_serde)
+        .chain(derive_transcode)
+        .collect::<Vec<Attribute>>();
+
+    let archive_attrs = derive_attrs.iter().cloned().chain(
         attributes
             .attrs
             .iter()
@@ -131,6 +278,30 @@ fn derive_archive_impl(
     );
     let resolver_doc = format!("The resolver for an archived [`{}`]", name);
 
+    if let (Some(_), Some(path)) = (&attributes.archive_as, &attributes.transcode_width) {
+        return Err(Error::new_spanned(
+            path,
+            "transcode_width may not be used with as = \"...\"",
+        ));
+    }
+
+    if let Some(ref path) = attributes.columnar {
+        if !matches!(
+            input.data,
+            Data::Struct(syn::DataStruct {
+                fields: Fields::Named(_),
+                ..
+            })
+        ) {
+            return Err(Error::new_spanned(
+                path,
+                "columnar may only be used on structs with named fields",
+            ));
+        }
+    }
+
+    let mut transcode_width_impl = None;
+
     let (archive_types, archive_impls) = match input.data {
         Data::Struct(ref data) => {
             let base_repr = if cfg!(feature = "strict") {
@@ -153,6 +324,17 @@ fn derive_archive_impl(
                 modifier: attributes.archived_repr.modifier.clone(),
             };
 
+            if attributes.transcode_width.is_some() {
+                transcode_width_impl = Some(derive_transcode_width_struct(
+                    name,
+                    generics,
+                    where_clause,
+                    &data.fields,
+                    &repr,
+                    rkyv_path,
+                )?);
+            }
+
             match data.fields {
                 Fields::Named(ref fields) => {
                     let mut archive_where = where_clause.clone();
@@ -184,9 +366,11 @@ fn derive_archive_impl(
                                 field_name.unwrap()
                             );
                             let archive_attrs = field_archive_attrs(f);
+                            let serde_attrs = field_serde_attrs(f);
                             quote! {
                                 #[doc = #field_doc]
                                 #(#[#archive_attrs])*
+                                #serde_attrs
                                 #vis #field_name: #rkyv_path::Archived<#ty>
                             }
                         });
@@ -311,6 +495,22 @@ fn derive_archive_impl(
                         None
                     };
 
+                    let (columnar_types, columnar_impls) = if attributes.columnar.is_some() {
+                        let (types, impls) = derive_columnar_struct(
+                            &input,
+                            &archive_where,
+                            fields,
+                            // The columns implement CheckBytes themselves so they can also check
+                            // that every column has the same length
+                            &derive_attrs[derive_check_bytes.len()..],
+                            attributes.check_bytes.is_some(),
+                            rkyv_path,
+                        )?;
+                        (Some(types), Some(impls))
+                    } else {
+                        (None, None)
+                    };
+
                     (
                         quote! {
                             #archived_def
@@ -320,6 +520,8 @@ fn derive_archive_impl(
                             #vis struct #resolver #generics #archive_where {
                                 #(#resolver_fields,)*
                             }
+
+                            #columnar_types
                         },
                         quote! {
                             impl #impl_generics Archive for #name #ty_generics #archive_where {
@@ -337,6 +539,7 @@ fn derive_archive_impl(
                             #partial_eq_impl
                             #partial_ord_impl
                             #copy_safe_impl
+                            #columnar_impls
                         },
                     )
                 }
@@ -365,9 +568,11 @@ fn derive_archive_impl(
                             let field_doc =
                                 format!("The archived counterpart of [`{}::{}`]", name, i);
                             let archive_attrs = field_archive_attrs(f);
+                            let serde_attrs = field_serde_attrs(f);
                             quote! {
                                 #[doc = #field_doc]
                                 #(#[#archive_attrs])*
+                                #serde_attrs
                                 #vis #rkyv_path::Archived<#ty>
                             }
                         });
@@ -809,6 +1014,17 @@ fn derive_archive_impl(
                 modifier: attributes.archived_repr.modifier.clone(),
             };
 
+            if attributes.transcode_width.is_some() {
+                transcode_width_impl = Some(derive_transcode_width_enum(
+                    name,
+                    generics,
+                    where_clause,
+                    data,
+                    &int_repr,
+                    rkyv_path,
+                )?);
+            }
+
             let is_fieldless = data
                 .variants
                 .iter()
@@ -827,6 +1043,14 @@ fn derive_archive_impl(
                 ));
             }
 
+            #[cfg(feature = "unaligned")]
+            if !matches!(int_repr, IntRepr::U8 | IntRepr::I8) {
+                return Err(Error::new_spanned(
+                    name,
+                    "enums cannot have multibyte discriminants when using the `unaligned` feature",
+                ));
+            }
+
             let archived_def = if attributes.archive_as.is_none() {
                 let archived_variants = data.variants.iter().enumerate().map(|(i, v)| {
                     let variant = &v.ident;
@@ -849,9 +1073,11 @@ fn derive_archive_impl(
                                     field_name.unwrap(),
                                 );
                                 let archive_attrs = field_archive_attrs(f);
+                                let serde_attrs = field_serde_attrs(f);
                                 quote! {
                                     #[doc = #field_doc]
                                     #(#[#archive_attrs])*
+                                    #serde_attrs
                                     #vis #field_name: #rkyv_path::Archived<#ty>
                                 }
                             });
@@ -874,9 +1100,11 @@ fn derive_archive_impl(
                                     name, variant, i,
                                 );
                                 let archive_attrs = field_archive_attrs(f);
+                                let serde_attrs = field_serde_attrs(f);
                                 quote! {
                                     #[doc = #field_doc]
                                     #(#[#archive_attrs])*
+                                    #serde_attrs
                                     #vis #rkyv_path::Archived<#ty>
                                 }
                             });
@@ -1305,6 +1533,8 @@ fn derive_archive_impl(
             use #rkyv_path::{out_field, Archive, Archived};
 
             #archive_impls
+
+            #transcode_width_impl
         };
     })
 }
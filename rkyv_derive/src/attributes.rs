@@ -14,12 +14,17 @@ pub struct Attributes {
     pub serialize_bound: Option<LitStr>,
     pub deserialize_bound: Option<LitStr>,
     pub check_bytes: Option<Path>,
+    pub columnar: Option<Path>,
+    pub compact: Option<Path>,
+    pub serde: Option<Path>,
+    pub transcode: Option<Path>,
+    pub transcode_width: Option<Path>,
     pub copy_safe: Option<Path>,
     pub rkyv_path: Option<Path>,
     pub rkyv_path_str: Option<LitStr>,
 }
 
-fn try_set_attribute<T: ToTokens>(
+pub fn try_set_attribute<T: ToTokens>(
     attribute: &mut Option<T>,
     value: T,
     name: &'static str,
@@ -40,6 +45,20 @@ fn parse_archive_attributes(attributes: &mut Attributes, meta: &Meta) -> Result<
         Meta::Path(path) => {
             if path.is_ident("check_bytes") {
                 try_set_attribute(&mut attributes.check_bytes, path.clone(), "check_bytes")
+            } else if path.is_ident("columnar") {
+                try_set_attribute(&mut attributes.columnar, path.clone(), "columnar")
+            } else if path.is_ident("compact") {
+                try_set_attribute(&mut attributes.compact, path.clone(), "compact")
+            } else if path.is_ident("serde") {
+                try_set_attribute(&mut attributes.serde, path.clone(), "serde")
+            } else if path.is_ident("transcode") {
+                try_set_attribute(&mut attributes.transcode, path.clone(), "transcode")
+            } else if path.is_ident("transcode_width") {
+                try_set_attribute(
+                    &mut attributes.transcode_width,
+                    path.clone(),
+                    "transcode_width",
+                )
             } else if path.is_ident("copy_safe") {
                 try_set_attribute(&mut attributes.copy_safe, path.clone(), "copy_safe")
             } else {
@@ -144,6 +163,12 @@ fn parse_archive_attributes(attributes: &mut Attributes, meta: &Meta) -> Result<
                 } else {
                     Err(Error::new_spanned(meta, "archive as must be a string"))
                 }
+            } else if meta.path.is_ident("align") {
+                if let Lit::Int(ref lit_int) = meta.lit {
+                    attributes.archived_repr.try_set_align(lit_int)
+                } else {
+                    Err(Error::new_spanned(meta, "align must be an integer"))
+                }
             } else if meta.path.is_ident("crate") {
                 if let Lit::Str(ref lit_str) = meta.lit {
                     let stream = syn::parse_str(&lit_str.value())?;
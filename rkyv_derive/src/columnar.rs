@@ -0,0 +1,379 @@
+use crate::{
+    util::strip_raw,
+    with::{make_with_cast, make_with_ty, with_inner},
+};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse_quote, punctuated::Punctuated, Attribute, DeriveInput, Error, FieldsNamed, Generics,
+    Ident, Lifetime, Path, WhereClause,
+};
+
+// These names are used by the methods of the generated columns type
+const RESERVED_NAMES: [&str; 4] = ["len", "is_empty", "get", "iter"];
+
+fn prepend_param(generics: &Generics, param: syn::GenericParam) -> Generics {
+    let mut params = Punctuated::default();
+    params.push(param);
+    for param in generics.params.iter() {
+        params.push(param.clone());
+    }
+    Generics {
+        lt_token: Some(Default::default()),
+        params,
+        gt_token: Some(Default::default()),
+        where_clause: generics.where_clause.clone(),
+    }
+}
+
+pub fn derive_columnar_struct(
+    input: &DeriveInput,
+    archive_where: &WhereClause,
+    fields: &FieldsNamed,
+    derive_attrs: &[Attribute],
+    check_bytes: bool,
+    rkyv_path: &Path,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let generics = &input.generics;
+
+    let with_ty = make_with_ty(rkyv_path);
+    let with_cast = make_with_cast(rkyv_path);
+
+    let first_field = fields.named.first().ok_or_else(|| {
+        Error::new_spanned(
+            name,
+            "columnar may only be used on structs with at least one field",
+        )
+    })?;
+    let first_name = &first_field.ident;
+    for field in fields.named.iter() {
+        let field_name = field.ident.as_ref().unwrap();
+        if RESERVED_NAMES.contains(&strip_raw(field_name).as_str()) {
+            return Err(Error::new_spanned(
+                field_name,
+                format!(
+                    "columnar structs may not have fields named {}",
+                    RESERVED_NAMES.join(", "),
+                ),
+            ));
+        }
+    }
+
+    let columns_name = Ident::new(&format!("Archived{}Columns", strip_raw(name)), name.span());
+    let columns_doc = format!("The archived columns of a slice of [`{}`]", name);
+    let row_name = Ident::new(&format!("Archived{}Row", strip_raw(name)), name.span());
+    let row_doc = format!("A row of archived [`{}`] columns", name);
+    let resolver_name = Ident::new(&format!("{}ColumnsResolver", strip_raw(name)), name.span());
+    let resolver_doc = format!("The resolver for the archived columns of [`{}`]", name);
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let row_lifetime = Lifetime::new("'__a", Span::call_site());
+    let row_generics = prepend_param(generics, parse_quote! { #row_lifetime });
+    let (_, row_ty_generics, _) = row_generics.split_for_impl();
+
+    let repr = if cfg!(feature = "strict") {
+        Some(quote! { #[repr(C)] })
+    } else {
+        None
+    };
+
+    let column_fields = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        let ty = with_ty(f).unwrap();
+        quote! { #field_name: #rkyv_path::vec::ArchivedVec<#rkyv_path::Archived<#ty>> }
+    });
+
+    let row_fields = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        let ty = with_ty(f).unwrap();
+        let field_vis = &f.vis;
+        let field_doc = format!(
+            "The archived counterpart of [`{}::{}`]",
+            name,
+            field_name.as_ref().unwrap()
+        );
+        quote! {
+            #[doc = #field_doc]
+            #field_vis #field_name: &#row_lifetime #rkyv_path::Archived<#ty>
+        }
+    });
+
+    let resolver_fields = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        quote! { #field_name: #rkyv_path::vec::VecResolver }
+    });
+
+    let types = quote! {
+        #[automatically_derived]
+        #[doc = #columns_doc]
+        #(#derive_attrs)*
+        #repr
+        #vis struct #columns_name #generics #archive_where {
+            #(#column_fields,)*
+        }
+
+        #[automatically_derived]
+        #[doc = #row_doc]
+        #vis struct #row_name #row_generics #archive_where {
+            #(#row_fields,)*
+        }
+
+        #[automatically_derived]
+        #[doc = #resolver_doc]
+        #vis struct #resolver_name {
+            #(#resolver_fields,)*
+        }
+    };
+
+    let column_accessors = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        let ty = with_ty(f).unwrap();
+        let accessor_doc = format!(
+            "Returns the column of [`{}::{}`]",
+            name,
+            field_name.as_ref().unwrap()
+        );
+        quote! {
+            #[doc = #accessor_doc]
+            #[inline]
+            pub fn #field_name(&self) -> &[Archived<#ty>] {
+                self.#field_name.as_slice()
+            }
+        }
+    });
+
+    let field_names = fields.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+
+    let resolve_columns = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        quote! {
+            let (fp, fo) = out_field!(out.#field_name);
+            #rkyv_path::vec::ArchivedVec::resolve_from_len(
+                len,
+                pos + fp,
+                resolver.#field_name,
+                fo,
+            );
+        }
+    });
+
+    let mut serialize_where = archive_where.clone();
+    serialize_where
+        .predicates
+        .push(parse_quote! { __S: #rkyv_path::ser::ScratchSpace + #rkyv_path::ser::Serializer });
+    let mut deserialize_where = archive_where.clone();
+    for field in fields
+        .named
+        .iter()
+        .filter(|f| !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds")))
+    {
+        let ty = with_ty(field)?;
+        serialize_where
+            .predicates
+            .push(parse_quote! { #ty: #rkyv_path::Serialize<__S> });
+        deserialize_where
+            .predicates
+            .push(parse_quote! { Archived<#ty>: #rkyv_path::Deserialize<#ty, __D> });
+    }
+
+    let serialize_generics = prepend_param(
+        generics,
+        parse_quote! { __S: #rkyv_path::Fallible + ?Sized },
+    );
+    let (serialize_impl_generics, _, _) = serialize_generics.split_for_impl();
+    let deserialize_generics = prepend_param(
+        generics,
+        parse_quote! { __D: #rkyv_path::Fallible + ?Sized },
+    );
+    let (deserialize_impl_generics, _, _) = deserialize_generics.split_for_impl();
+
+    let serialize_columns = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        let ty = with_ty(f).unwrap();
+        let field = with_cast(f, parse_quote! { &value.#field_name }).unwrap();
+        quote! {
+            #field_name: #rkyv_path::vec::ArchivedVec::<Archived<#ty>>::serialize_from_iter::<
+                #ty,
+                _,
+                _,
+                _,
+            >(values.iter().map(|value| #field), serializer)?
+        }
+    });
+
+    let deserialize_fields = fields.named.iter().map(|f| {
+        let field_name = &f.ident;
+        let ty = with_ty(f).unwrap();
+        let value = with_inner(
+            f,
+            parse_quote! {
+                #rkyv_path::Deserialize::<#ty, __D>::deserialize(row.#field_name, deserializer)?
+            },
+        )
+        .unwrap();
+        quote! { #field_name: #value }
+    });
+
+    let check_bytes_impl = if check_bytes {
+        let mut check_where = archive_where.clone();
+        for field in fields
+            .named
+            .iter()
+            .filter(|f| !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds")))
+        {
+            let ty = with_ty(field)?;
+            check_where.predicates.push(parse_quote! {
+                #rkyv_path::vec::ArchivedVec<#rkyv_path::Archived<#ty>>: CheckBytes<__C>
+            });
+        }
+
+        let check_generics = prepend_param(generics, parse_quote! { __C: ?Sized });
+        let (check_impl_generics, _, _) = check_generics.split_for_impl();
+
+        let check_columns = fields.named.iter().map(|f| {
+            let field_name = &f.ident;
+            let ty = with_ty(f).unwrap();
+            quote! {
+                <#rkyv_path::vec::ArchivedVec<#rkyv_path::Archived<#ty>> as CheckBytes<__C>>::check_bytes(
+                    ::core::ptr::addr_of!((*value).#field_name),
+                    context,
+                ).map_err(|e| StructCheckError {
+                    field_name: stringify!(#field_name),
+                    inner: ErrorBox::new(e),
+                })?;
+            }
+        });
+
+        let check_lens = fields.named.iter().skip(1).map(|f| {
+            let field_name = &f.ident;
+            quote! {
+                let found = (*value).#field_name.len();
+                if found != len {
+                    return Err(StructCheckError {
+                        field_name: stringify!(#field_name),
+                        inner: ErrorBox::new(#rkyv_path::columnar::ColumnLengthError {
+                            expected: len,
+                            found,
+                        }),
+                    });
+                }
+            }
+        });
+
+        Some(quote! {
+            const _: () = {
+                use #rkyv_path::bytecheck::{CheckBytes, ErrorBox, StructCheckError};
+
+                impl #check_impl_generics CheckBytes<__C> for #columns_name #ty_generics #check_where {
+                    type Error = StructCheckError;
+
+                    unsafe fn check_bytes<'__bytecheck>(
+                        value: *const Self,
+                        context: &mut __C,
+                    ) -> ::core::result::Result<&'__bytecheck Self, StructCheckError> {
+                        #(#check_columns)*
+                        let len = (*value).#first_name.len();
+                        #(#check_lens)*
+                        Ok(&*value)
+                    }
+                }
+            };
+        })
+    } else {
+        None
+    };
+
+    let impls = quote! {
+        impl #impl_generics #columns_name #ty_generics #archive_where {
+            /// Returns the number of rows.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.#first_name.len()
+            }
+
+            /// Returns whether there are no rows.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            #(#column_accessors)*
+
+            /// Gets the row at the given index.
+            #[inline]
+            pub fn get<#row_lifetime>(
+                &#row_lifetime self,
+                index: usize,
+            ) -> ::core::option::Option<#row_name #row_ty_generics> {
+                Some(#row_name {
+                    #(#field_names: self.#field_names.get(index)?,)*
+                })
+            }
+
+            /// Gets an iterator over the rows.
+            #[inline]
+            pub fn iter<#row_lifetime>(
+                &#row_lifetime self,
+            ) -> impl Iterator<Item = #row_name #row_ty_generics> + #row_lifetime {
+                (0..self.len()).map(move |index| {
+                    self.get(index).expect("all columns must have the same length")
+                })
+            }
+        }
+
+        #check_bytes_impl
+
+        impl #impl_generics #rkyv_path::columnar::ArchiveColumns for #name #ty_generics #archive_where {
+            type Columns = #columns_name #ty_generics;
+            type ColumnsResolver = #resolver_name;
+
+            #[inline]
+            fn columns_len(columns: &Self::Columns) -> usize {
+                columns.len()
+            }
+
+            #[inline]
+            unsafe fn resolve_columns(
+                len: usize,
+                pos: usize,
+                resolver: Self::ColumnsResolver,
+                out: *mut Self::Columns,
+            ) {
+                #(#resolve_columns)*
+            }
+        }
+
+        impl #serialize_impl_generics #rkyv_path::columnar::SerializeColumns<__S> for #name #ty_generics #serialize_where {
+            #[inline]
+            fn serialize_columns(
+                values: &[Self],
+                serializer: &mut __S,
+            ) -> ::core::result::Result<Self::ColumnsResolver, __S::Error> {
+                Ok(#resolver_name {
+                    #(#serialize_columns,)*
+                })
+            }
+        }
+
+        impl #deserialize_impl_generics #rkyv_path::columnar::DeserializeColumns<__D> for #name #ty_generics #deserialize_where {
+            #[inline]
+            fn deserialize_row(
+                columns: &Self::Columns,
+                index: usize,
+                deserializer: &mut __D,
+            ) -> ::core::result::Result<Self, __D::Error> {
+                let row = columns
+                    .get(index)
+                    .expect("all columns must have the same length");
+                Ok(#name {
+                    #(#deserialize_fields,)*
+                })
+            }
+        }
+    };
+
+    Ok((types, impls))
+}
@@ -0,0 +1,240 @@
+use crate::{attributes::try_set_attribute, util::add_bounds};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, spanned::Spanned, AttrStyle, Data, DeriveInput, Error, Field, Fields, Ident,
+    Index, Lit, LitStr, Meta, NestedMeta, Path,
+};
+
+#[derive(Default)]
+struct Attributes {
+    bound: Option<LitStr>,
+    rkyv_path: Option<Path>,
+}
+
+fn parse_compact_attributes(attributes: &mut Attributes, meta: &Meta) -> Result<(), Error> {
+    if let Meta::NameValue(meta) = meta {
+        if let Lit::Str(ref lit_str) = meta.lit {
+            if meta.path.is_ident("crate") {
+                let stream = syn::parse_str(&lit_str.value())?;
+                let tokens = crate::serde::respan::respan(stream, lit_str.span());
+                let path = syn::parse2(tokens)?;
+                try_set_attribute(&mut attributes.rkyv_path, path, "crate")
+            } else if meta.path.is_ident("bound") {
+                try_set_attribute(&mut attributes.bound, lit_str.clone(), "bound")
+            } else {
+                Err(Error::new_spanned(meta, "unrecognized compact argument"))
+            }
+        } else {
+            Err(Error::new_spanned(
+                meta,
+                "compact arguments must be strings",
+            ))
+        }
+    } else {
+        Err(Error::new_spanned(meta, "unrecognized compact argument"))
+    }
+}
+
+fn parse_attributes(input: &DeriveInput) -> Result<Attributes, Error> {
+    let mut result = Attributes::default();
+    for attr in input.attrs.iter() {
+        if let AttrStyle::Outer = attr.style {
+            if attr.path.is_ident("compact") {
+                if let Meta::List(list) = attr.parse_meta()? {
+                    for nested in list.nested.iter() {
+                        if let NestedMeta::Meta(meta) = nested {
+                            parse_compact_attributes(&mut result, meta)?;
+                        } else {
+                            return Err(Error::new_spanned(
+                                nested,
+                                "compact arguments must be metas",
+                            ));
+                        }
+                    }
+                } else {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "compact may only be a structured list attribute",
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = parse_attributes(&input)?;
+    derive_compact_impl(input, &attributes)
+}
+
+/// Returns bindings for the fields of a struct or variant, along with a pattern that binds them.
+fn field_bindings(fields: &Fields) -> (Vec<Ident>, TokenStream) {
+    match fields {
+        Fields::Named(ref fields) => {
+            let names = fields
+                .named
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Ident::new(&format!("__field_{}", i), f.span()))
+                .collect::<Vec<_>>();
+            let fields = fields.named.iter().map(|f| &f.ident);
+            let pattern = quote! { { #(#fields: #names,)* } };
+            (names, pattern)
+        }
+        Fields::Unnamed(ref fields) => {
+            let names = fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Ident::new(&format!("__field_{}", i), f.span()))
+                .collect::<Vec<_>>();
+            let pattern = quote! { ( #(#names,)* ) };
+            (names, pattern)
+        }
+        Fields::Unit => (Vec::new(), quote! {}),
+    }
+}
+
+fn derive_compact_impl(
+    mut input: DeriveInput,
+    attributes: &Attributes,
+) -> Result<TokenStream, Error> {
+    let where_clause = input.generics.make_where_clause();
+    if let Some(ref bounds) = attributes.bound {
+        add_bounds(bounds, where_clause)?;
+    }
+
+    let default_rkyv_path = parse_quote! { ::rkyv };
+    let rkyv_path = attributes.rkyv_path.as_ref().unwrap_or(&default_rkyv_path);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let where_clause = where_clause.unwrap();
+
+    let add_field_bounds = |fields: &mut dyn Iterator<Item = &Field>| {
+        let mut compact_where = where_clause.clone();
+        for field in fields.filter(|f| !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds"))) {
+            let ty = &field.ty;
+            compact_where.predicates.push(parse_quote! { #ty: Compact });
+        }
+        compact_where
+    };
+
+    let compact_fields = |names: &[Ident]| {
+        let dependencies = names.iter().map(|name| {
+            quote! { Compact::compact_dependencies(#name, compactor, resolvers); }
+        });
+        let pointers = names.iter().map(|name| {
+            quote! {
+                Compact::compact_pointers(
+                    #name,
+                    field_pos(self, out, #name),
+                    compactor,
+                    resolvers,
+                );
+            }
+        });
+        (quote! { #(#dependencies)* }, quote! { #(#pointers)* })
+    };
+
+    let (compact_where, compact_dependencies, compact_pointers) = match input.data {
+        Data::Struct(ref data) => {
+            let compact_where = add_field_bounds(&mut data.fields.iter());
+            let (names, _) = field_bindings(&data.fields);
+            let fields = data.fields.iter().enumerate().map(|(i, f)| {
+                f.ident.as_ref().map_or_else(
+                    || {
+                        let index = Index::from(i);
+                        quote! { #index }
+                    },
+                    |name| quote! { #name },
+                )
+            });
+            let (dependencies, pointers) = compact_fields(&names);
+            let bindings = quote! { #(let #names = &self.#fields;)* };
+
+            (
+                compact_where,
+                quote! {
+                    #bindings
+                    #dependencies
+                },
+                quote! {
+                    #bindings
+                    #pointers
+                },
+            )
+        }
+        Data::Enum(ref data) => {
+            let compact_where =
+                add_field_bounds(&mut data.variants.iter().flat_map(|v| v.fields.iter()));
+
+            let mut dependency_arms = Vec::new();
+            let mut pointer_arms = Vec::new();
+            for v in data.variants.iter() {
+                let variant = &v.ident;
+                let (names, pattern) = field_bindings(&v.fields);
+                let (dependencies, pointers) = compact_fields(&names);
+                dependency_arms.push(quote! { Self::#variant #pattern => { #dependencies } });
+                pointer_arms.push(quote! { Self::#variant #pattern => { #pointers } });
+            }
+
+            if data.variants.is_empty() {
+                (compact_where, quote! {}, quote! {})
+            } else {
+                (
+                    compact_where,
+                    quote! {
+                        match self {
+                            #(#dependency_arms)*
+                        }
+                    },
+                    quote! {
+                        match self {
+                            #(#pointer_arms)*
+                        }
+                    },
+                )
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "Compact cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        const _: () = {
+            use #rkyv_path::compact::{field_pos, Compact, CompactResolvers, Compactor};
+
+            impl #impl_generics Compact for #name #ty_generics #compact_where {
+                #[inline]
+                #[allow(unused_variables)]
+                fn compact_dependencies(
+                    &self,
+                    compactor: &mut Compactor,
+                    resolvers: &mut CompactResolvers,
+                ) {
+                    #compact_dependencies
+                }
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn compact_pointers(
+                    &self,
+                    out: usize,
+                    compactor: &mut Compactor,
+                    resolvers: &mut CompactResolvers,
+                ) {
+                    #compact_pointers
+                }
+            }
+        };
+    })
+}
@@ -8,10 +8,13 @@
 
 mod archive;
 mod attributes;
+mod columnar;
+mod compact;
 mod deserialize;
 mod repr;
 mod serde;
 mod serialize;
+mod transcode;
 mod util;
 mod with;
 
@@ -35,6 +38,10 @@ use syn::{parse_macro_input, DeriveInput};
 /// - `repr(...)`: *Deprecated, use `#[archive_attr(repr(...))]` instead.* Sets the representation
 ///   for the archived type to the given representation. Available representation options may vary
 ///   depending on features and type layout.
+/// - `align = ...`: Raises the alignment of the archived type to the given power of two (i.e.
+///   `#[archive(align = 64)]` to make it cache-line aligned). This is equivalent to
+///   `#[archive_attr(repr(align(...)))]`. Archives containing the type must be written to and read
+///   from buffers with at least this alignment, such as an `AlignedVec<64>`.
 /// - `compare(...)`: Implements common comparison operators between the original and archived
 ///   types. Supported comparisons are `PartialEq` and `PartialOrd` (i.e.
 ///   `#[archive(compare(PartialEq, PartialOrd))]`).
@@ -47,6 +54,23 @@ use syn::{parse_macro_input, DeriveInput};
 ///   deserialization. Requires `validation` feature. Not compatible with `as = "..."`. In that
 ///   case, use `#[derive(CheckBytes)]` on the archived type, and include a `use rkyv::bytecheck`
 ///   statement.
+/// - `transcode`: Derive `Transcode` on the archived type, in order to enable converting archives
+///   between endiannesses. Requires `transcode` feature. Not compatible with `as = "..."`. In that
+///   case, use `#[derive(Transcode)]` on the archived type instead.
+/// - `transcode_width`: Implement `TranscodeWidth` for the type, in order to enable converting
+///   archives between pointer widths. Requires `transcode` feature. Archived structs must be
+///   `repr(C)` or `repr(transparent)`. Not compatible with `as = "..."` or `#[with(...)]` fields.
+/// - `compact`: Derive `Compact` on the archived type, in order to enable removing unreachable
+///   bytes from archives. Requires `compact` feature. Not compatible with `as = "..."`. In that
+///   case, use `#[derive(Compact)]` on the archived type instead.
+/// - `serde`: Derive serde's `Serialize` on the archived type, in order to write archived values to
+///   any serde format without deserializing them. Requires `serde` feature. Not compatible with
+///   `as = "..."`. Additional serde attributes can be added with `#[archive_attr(serde(...))]`.
+///   With `archive_le` or `archive_be`, fields of primitive types (and `Vec`s, `Box`es, `Option`s,
+///   slices, and arrays of them) are serialized with `rkyv::serialize_primitive`.
+/// - `columnar`: Generate an archived columns type and a row view type for the struct, in order to
+///   archive `Vec`s of it one field at a time with the `Columnar` wrapper. Only supported for
+///   structs with named fields.
 /// - `copy_safe`: States that the archived type is tightly packed with no padding bytes. This
 ///   qualifies it for copy optimizations. (requires nightly)
 /// - `as = "..."`: Instead of generating a separate archived type, this type will archive as the
@@ -113,3 +137,52 @@ pub fn derive_deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenSt
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+/// Derives `Transcode` for the labeled type.
+///
+/// This is usually applied to archived types through `#[archive(transcode)]`, but may be used
+/// directly on any type whose fields all implement `Transcode`. Enums must have an explicit integer
+/// representation (e.g. `#[repr(u8)]`).
+///
+/// # Attributes
+///
+/// `#[transcode(...)]` takes the following arguments:
+///
+/// - `bound = "..."`: Adds additional bounds to the `Transcode` implementation.
+/// - `crate = "..."`: Chooses an alternative crate path to import rkyv from.
+///
+/// Like `Archive`, this derive adds a bound `field: Transcode` for each field type. Adding the
+/// attribute `#[omit_bounds]` to a field will suppress this trait bound and allow recursive types.
+#[proc_macro_derive(Transcode, attributes(transcode, omit_bounds))]
+pub fn derive_transcode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    match transcode::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `Compact` for the labeled type.
+///
+/// This is usually applied to archived types through `#[archive(compact)]`, but may be used
+/// directly on any type whose fields all implement `Compact`.
+///
+/// # Attributes
+///
+/// `#[compact(...)]` takes the following arguments:
+///
+/// - `bound = "..."`: Adds additional bounds to the `Compact` implementation.
+/// - `crate = "..."`: Chooses an alternative crate path to import rkyv from.
+///
+/// Like `Archive`, this derive adds a bound `field: Compact` for each field type. Adding the
+/// attribute `#[omit_bounds]` to a field will suppress this trait bound and allow recursive types.
+#[proc_macro_derive(Compact, attributes(compact, omit_bounds))]
+pub fn derive_compact(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    match compact::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
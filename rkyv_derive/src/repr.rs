@@ -153,6 +153,17 @@ impl Repr {
         }
     }
 
+    pub fn try_set_align(&mut self, alignment: &LitInt) -> Result<(), Error> {
+        let value = alignment.base10_parse::<u64>()?;
+        if !value.is_power_of_two() {
+            return Err(Error::new_spanned(
+                alignment,
+                "alignment must be a power of two",
+            ));
+        }
+        self.try_set_modifier(Modifier::Align(alignment.clone()), alignment)
+    }
+
     fn try_set_base_repr<S: ToTokens>(&mut self, repr: BaseRepr, spanned: S) -> Result<(), Error> {
         if self.base_repr.is_some() {
             Err(Error::new_spanned(
@@ -217,10 +228,7 @@ impl Repr {
                             } else if let Some(NestedMeta::Lit(Lit::Int(alignment))) =
                                 list.nested.first()
                             {
-                                self.try_set_modifier(
-                                    Modifier::Align(alignment.clone()),
-                                    alignment,
-                                )?;
+                                self.try_set_align(alignment)?;
                             }
                         }
                     }
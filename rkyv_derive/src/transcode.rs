@@ -0,0 +1,604 @@
+use crate::{
+    attributes::try_set_attribute,
+    repr::{BaseRepr, IntRepr, Repr},
+    util::{add_bounds, strip_raw},
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, punctuated::Punctuated, spanned::Spanned, token::Comma, AttrStyle, Data, DataEnum,
+    DeriveInput, Error, Field, Fields, Generics, Ident, Index, Lit, LitStr, Meta, NestedMeta, Path,
+    Type, WhereClause,
+};
+
+#[derive(Default)]
+struct Attributes {
+    repr: Repr,
+    bound: Option<LitStr>,
+    rkyv_path: Option<Path>,
+}
+
+fn parse_transcode_attributes(attributes: &mut Attributes, meta: &Meta) -> Result<(), Error> {
+    if let Meta::NameValue(meta) = meta {
+        if let Lit::Str(ref lit_str) = meta.lit {
+            if meta.path.is_ident("crate") {
+                let stream = syn::parse_str(&lit_str.value())?;
+                let tokens = crate::serde::respan::respan(stream, lit_str.span());
+                let path = syn::parse2(tokens)?;
+                try_set_attribute(&mut attributes.rkyv_path, path, "crate")
+            } else if meta.path.is_ident("bound") {
+                try_set_attribute(&mut attributes.bound, lit_str.clone(), "bound")
+            } else {
+                Err(Error::new_spanned(meta, "unrecognized transcode argument"))
+            }
+        } else {
+            Err(Error::new_spanned(
+                meta,
+                "transcode arguments must be strings",
+            ))
+        }
+    } else {
+        Err(Error::new_spanned(meta, "unrecognized transcode argument"))
+    }
+}
+
+fn parse_attributes(input: &DeriveInput) -> Result<Attributes, Error> {
+    let mut result = Attributes::default();
+    for attr in input.attrs.iter() {
+        if let AttrStyle::Outer = attr.style {
+            if attr.path.is_ident("repr") || attr.path.is_ident("transcode") {
+                if let Meta::List(list) = attr.parse_meta()? {
+                    if list.path.is_ident("repr") {
+                        result.repr.parse_args(list.nested.iter())?;
+                    } else {
+                        for nested in list.nested.iter() {
+                            if let NestedMeta::Meta(meta) = nested {
+                                parse_transcode_attributes(&mut result, meta)?;
+                            } else {
+                                return Err(Error::new_spanned(
+                                    nested,
+                                    "transcode arguments must be metas",
+                                ));
+                            }
+                        }
+                    }
+                } else {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "repr and transcode may only be structured list attributes",
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = parse_attributes(&input)?;
+    derive_transcode_impl(input, &attributes)
+}
+
+fn derive_transcode_impl(
+    mut input: DeriveInput,
+    attributes: &Attributes,
+) -> Result<TokenStream, Error> {
+    let type_where_clause = input.generics.where_clause.clone();
+    let where_clause = input.generics.make_where_clause();
+    if let Some(ref bounds) = attributes.bound {
+        add_bounds(bounds, where_clause)?;
+    }
+
+    let default_rkyv_path = parse_quote! { ::rkyv };
+    let rkyv_path = attributes.rkyv_path.as_ref().unwrap_or(&default_rkyv_path);
+
+    let name = &input.ident;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let where_clause = where_clause.unwrap();
+
+    let add_field_bounds = |fields: &mut dyn Iterator<Item = &Field>| {
+        let mut transcode_where = where_clause.clone();
+        for field in fields.filter(|f| !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds"))) {
+            let ty = &field.ty;
+            transcode_where
+                .predicates
+                .push(parse_quote! { #ty: Transcode });
+        }
+        transcode_where
+    };
+
+    let transcode_impl = match input.data {
+        Data::Struct(ref data) => {
+            let transcode_where = add_field_bounds(&mut data.fields.iter());
+            let transcode_fields = data.fields.iter().enumerate().map(|(i, f)| {
+                let ty = &f.ty;
+                let field = f.ident.as_ref().map_or_else(
+                    || {
+                        let index = Index::from(i);
+                        quote! { #index }
+                    },
+                    |name| quote! { #name },
+                );
+                quote! {
+                    <#ty as Transcode>::transcode(
+                        ::core::ptr::addr_of_mut!((*value).#field),
+                        transcoder,
+                    )?;
+                }
+            });
+
+            quote! {
+                impl #impl_generics Transcode for #name #ty_generics #transcode_where {
+                    #[inline]
+                    #[allow(unused_variables)]
+                    unsafe fn transcode(
+                        value: *mut Self,
+                        transcoder: &mut Transcoder<'_>,
+                    ) -> ::core::result::Result<(), TranscodeError> {
+                        #(#transcode_fields)*
+                        Ok(())
+                    }
+                }
+            }
+        }
+        Data::Enum(ref data) => {
+            let int_repr = match attributes.repr.base_repr {
+                Some((BaseRepr::Int(int_repr), _)) => int_repr,
+                _ => {
+                    return Err(Error::new_spanned(
+                        name,
+                        "Transcode may only be derived for enums with an explicit integer repr (e.g. #[repr(u8)])",
+                    ))
+                }
+            };
+
+            let transcode_where =
+                add_field_bounds(&mut data.variants.iter().flat_map(|v| v.fields.iter()));
+
+            let tag_variants = data.variants.iter().map(|v| {
+                let variant = &v.ident;
+                let discriminant = v.discriminant.as_ref().map(|(_, expr)| quote! { = #expr });
+                quote! { #variant #discriminant }
+            });
+
+            let discriminant_consts = data.variants.iter().map(|v| {
+                let variant = &v.ident;
+                quote! { const #variant: #int_repr = Tag::#variant as #int_repr; }
+            });
+
+            let variant_structs = data.variants.iter().map(|v| {
+                let variant_name = Ident::new(
+                    &format!("TranscodeVariant{}", strip_raw(&v.ident)),
+                    v.span(),
+                );
+                match v.fields {
+                    Fields::Named(ref fields) => {
+                        let fields = fields.named.iter().map(|f| {
+                            let name = &f.ident;
+                            let ty = &f.ty;
+                            quote! { #name: #ty }
+                        });
+                        quote! {
+                            #[repr(C)]
+                            struct #variant_name #generics #type_where_clause {
+                                __tag: #int_repr,
+                                #(#fields,)*
+                                __phantom: ::core::marker::PhantomData<#name #ty_generics>,
+                            }
+                        }
+                    }
+                    Fields::Unnamed(ref fields) => {
+                        let fields = fields.unnamed.iter().map(|f| &f.ty);
+                        quote! {
+                            #[repr(C)]
+                            struct #variant_name #generics (#int_repr, #(#fields,)* ::core::marker::PhantomData<#name #ty_generics>) #type_where_clause;
+                        }
+                    }
+                    Fields::Unit => quote! {},
+                }
+            });
+
+            let transcode_arms = data.variants.iter().map(|v| {
+                let variant = &v.ident;
+                let variant_name = Ident::new(
+                    &format!("TranscodeVariant{}", strip_raw(&v.ident)),
+                    v.span(),
+                );
+                let transcode_fields = match v.fields {
+                    Fields::Named(ref fields) => fields
+                        .named
+                        .iter()
+                        .map(|f| {
+                            let name = &f.ident;
+                            let ty = &f.ty;
+                            quote! {
+                                <#ty as Transcode>::transcode(
+                                    ::core::ptr::addr_of_mut!((*value).#name),
+                                    transcoder,
+                                )?;
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    Fields::Unnamed(ref fields) => fields
+                        .unnamed
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| {
+                            let index = Index::from(i + 1);
+                            let ty = &f.ty;
+                            quote! {
+                                <#ty as Transcode>::transcode(
+                                    ::core::ptr::addr_of_mut!((*value).#index),
+                                    transcoder,
+                                )?;
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    Fields::Unit => Vec::new(),
+                };
+                if transcode_fields.is_empty() {
+                    quote! { Discriminant::#variant => (), }
+                } else {
+                    quote! {
+                        Discriminant::#variant => {
+                            let value = value.cast::<#variant_name #ty_generics>();
+                            #(#transcode_fields)*
+                        }
+                    }
+                }
+            });
+
+            let tag_defs = if data.variants.is_empty() {
+                None
+            } else {
+                Some(quote! {
+                    #[allow(dead_code)]
+                    #[repr(#int_repr)]
+                    enum Tag {
+                        #(#tag_variants,)*
+                    }
+
+                    struct Discriminant;
+
+                    #[allow(non_upper_case_globals)]
+                    impl Discriminant {
+                        #(#discriminant_consts)*
+                    }
+                })
+            };
+
+            quote! {
+                #tag_defs
+
+                #(#variant_structs)*
+
+                impl #impl_generics Transcode for #name #ty_generics #transcode_where {
+                    #[inline]
+                    unsafe fn transcode(
+                        value: *mut Self,
+                        transcoder: &mut Transcoder<'_>,
+                    ) -> ::core::result::Result<(), TranscodeError> {
+                        let tag_ptr = value.cast::<#int_repr>();
+                        let tag = transcoder.read(tag_ptr);
+                        <#int_repr as Transcode>::transcode(tag_ptr, transcoder)?;
+                        match tag {
+                            #(#transcode_arms)*
+                            _ => {
+                                return Err(TranscodeError::InvalidTag {
+                                    pos: transcoder.position(tag_ptr.cast()),
+                                })
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "Transcode cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        const _: () = {
+            use #rkyv_path::transcode::{Transcode, TranscodeError, Transcoder};
+            #transcode_impl
+        };
+    })
+}
+
+fn check_transcode_width_fields<'a>(fields: impl Iterator<Item = &'a Field>) -> Result<(), Error> {
+    for field in fields {
+        if let Some(attr) = field.attrs.iter().find(|a| a.path.is_ident("with")) {
+            return Err(Error::new_spanned(
+                attr,
+                "transcode_width does not support fields archived with wrappers",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn transcode_width_where<'a>(
+    where_clause: &WhereClause,
+    fields: impl Iterator<Item = &'a Field>,
+    rkyv_path: &Path,
+) -> WhereClause {
+    let mut width_where = where_clause.clone();
+    for field in fields.filter(|f| !f.attrs.iter().any(|a| a.path.is_ident("omit_bounds"))) {
+        let ty = &field.ty;
+        width_where
+            .predicates
+            .push(parse_quote! { #ty: #rkyv_path::transcode::width::TranscodeWidth });
+    }
+    width_where
+}
+
+fn field_layouts<'a>(
+    tag: Option<&IntRepr>,
+    fields: impl Iterator<Item = &'a Type>,
+    width: TokenStream,
+) -> Punctuated<TokenStream, Comma> {
+    tag.map(|int_repr| quote! { Layout::new::<#int_repr>() })
+        .into_iter()
+        .chain(fields.map(|ty| quote! { <#ty as TranscodeWidth>::archived_layout(#width) }))
+        .collect()
+}
+
+/// Generates the width transcoding impl for a struct with the given fields.
+///
+/// This is called by the `Archive` derive, which has already determined the archived repr.
+pub fn derive_transcode_width_struct(
+    name: &Ident,
+    generics: &Generics,
+    where_clause: &WhereClause,
+    fields: &Fields,
+    repr: &Repr,
+    rkyv_path: &Path,
+) -> Result<TokenStream, Error> {
+    match repr.base_repr {
+        Some((BaseRepr::C, _)) | Some((BaseRepr::Transparent, _)) => (),
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "transcode_width requires archived structs to be repr(C) or repr(transparent)",
+            ))
+        }
+    }
+    if let Some((_, span)) = repr.modifier {
+        return Err(Error::new(
+            span,
+            "transcode_width does not support repr(packed) or repr(align(...))",
+        ));
+    }
+    check_transcode_width_fields(fields.iter())?;
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let width_where = transcode_width_where(where_clause, fields.iter(), rkyv_path);
+
+    let count = fields.len();
+    let field_tys = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let indices = (0..count).collect::<Vec<_>>();
+    let layouts = field_layouts(None, field_tys.iter().copied(), quote! { width });
+    let from_layouts = field_layouts(None, field_tys.iter().copied(), quote! { from });
+    let to_layouts = field_layouts(None, field_tys.iter().copied(), quote! { to });
+
+    Ok(quote! {
+        const _: () = {
+            use ::core::alloc::Layout;
+            use #rkyv_path::transcode::{
+                width::{struct_layout, PointerWidth, TranscodeWidth, WidthResolvers, WidthTranscoder},
+                TranscodeError,
+            };
+
+            impl #impl_generics TranscodeWidth for #name #ty_generics #width_where {
+                #[inline]
+                fn archived_layout(width: PointerWidth) -> Layout {
+                    struct_layout(&[#layouts], &mut [0; #count])
+                }
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn transcode_dependencies(
+                    pos: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    resolvers: &mut WidthResolvers,
+                ) -> ::core::result::Result<(), TranscodeError> {
+                    let from = transcoder.from();
+                    let mut offsets = [0; #count];
+                    struct_layout(&[#from_layouts], &mut offsets);
+                    #(
+                        <#field_tys as TranscodeWidth>::transcode_dependencies(
+                            pos + offsets[#indices],
+                            transcoder,
+                            resolvers,
+                        )?;
+                    )*
+                    Ok(())
+                }
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn transcode_value(
+                    pos: usize,
+                    out: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    resolvers: &mut WidthResolvers,
+                ) -> ::core::result::Result<(), TranscodeError> {
+                    let (from, to) = (transcoder.from(), transcoder.to());
+                    let mut from_offsets = [0; #count];
+                    struct_layout(&[#from_layouts], &mut from_offsets);
+                    let mut to_offsets = [0; #count];
+                    struct_layout(&[#to_layouts], &mut to_offsets);
+                    #(
+                        <#field_tys as TranscodeWidth>::transcode_value(
+                            pos + from_offsets[#indices],
+                            out + to_offsets[#indices],
+                            transcoder,
+                            resolvers,
+                        )?;
+                    )*
+                    Ok(())
+                }
+            }
+        };
+    })
+}
+
+/// Generates the width transcoding impl for an enum with the given variants.
+///
+/// This is called by the `Archive` derive and must be placed in the same scope as the archived
+/// enum's `ArchivedTag`.
+pub fn derive_transcode_width_enum(
+    name: &Ident,
+    generics: &Generics,
+    where_clause: &WhereClause,
+    data: &DataEnum,
+    int_repr: &IntRepr,
+    rkyv_path: &Path,
+) -> Result<TokenStream, Error> {
+    let fields = || data.variants.iter().flat_map(|v| v.fields.iter());
+    check_transcode_width_fields(fields())?;
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let width_where = transcode_width_where(where_clause, fields(), rkyv_path);
+
+    let variant_layouts = data.variants.iter().map(|v| {
+        let layouts = field_layouts(
+            Some(int_repr),
+            v.fields.iter().map(|f| &f.ty),
+            quote! { width },
+        );
+        let count = v.fields.len() + 1;
+        quote! { struct_layout(&[#layouts], &mut [0; #count]) }
+    });
+
+    let tag_consts = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        quote! { const #variant: #int_repr = ArchivedTag::#variant as #int_repr; }
+    });
+
+    let dependencies_arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let count = v.fields.len() + 1;
+        let field_tys = v.fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+        let indices = 1..count;
+        let layouts = field_layouts(Some(int_repr), field_tys.iter().copied(), quote! { from });
+        quote! {
+            TranscodeWidthTag::#variant => {
+                let mut offsets = [0; #count];
+                struct_layout(&[#layouts], &mut offsets);
+                #(
+                    <#field_tys as TranscodeWidth>::transcode_dependencies(
+                        pos + offsets[#indices],
+                        transcoder,
+                        resolvers,
+                    )?;
+                )*
+            }
+        }
+    });
+
+    let value_arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let count = v.fields.len() + 1;
+        let field_tys = v.fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+        let indices = (1..count).collect::<Vec<_>>();
+        let from_layouts =
+            field_layouts(Some(int_repr), field_tys.iter().copied(), quote! { from });
+        let to_layouts = field_layouts(Some(int_repr), field_tys.iter().copied(), quote! { to });
+        quote! {
+            TranscodeWidthTag::#variant => {
+                let mut from_offsets = [0; #count];
+                struct_layout(&[#from_layouts], &mut from_offsets);
+                let mut to_offsets = [0; #count];
+                struct_layout(&[#to_layouts], &mut to_offsets);
+                #(
+                    <#field_tys as TranscodeWidth>::transcode_value(
+                        pos + from_offsets[#indices],
+                        out + to_offsets[#indices],
+                        transcoder,
+                        resolvers,
+                    )?;
+                )*
+            }
+        }
+    });
+
+    Ok(quote! {
+        const _: () = {
+            use ::core::alloc::Layout;
+            use #rkyv_path::transcode::{
+                width::{
+                    struct_layout, union_layout, PointerWidth, TranscodeWidth, WidthResolvers,
+                    WidthTranscoder,
+                },
+                TranscodeError,
+            };
+
+            struct TranscodeWidthTag;
+
+            #[allow(non_upper_case_globals)]
+            impl TranscodeWidthTag {
+                #(#tag_consts)*
+            }
+
+            #[inline]
+            fn read_tag(
+                transcoder: &WidthTranscoder<'_>,
+                pos: usize,
+            ) -> ::core::result::Result<#int_repr, TranscodeError> {
+                let size = ::core::mem::size_of::<#int_repr>();
+                let mut tag = [0u8; ::core::mem::size_of::<#int_repr>()];
+                tag.copy_from_slice(transcoder.read_bytes(pos, size)?);
+                Ok(#int_repr::from_ne_bytes(tag))
+            }
+
+            impl #impl_generics TranscodeWidth for #name #ty_generics #width_where {
+                #[inline]
+                fn archived_layout(width: PointerWidth) -> Layout {
+                    union_layout(&[#(#variant_layouts,)*])
+                }
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn transcode_dependencies(
+                    pos: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    resolvers: &mut WidthResolvers,
+                ) -> ::core::result::Result<(), TranscodeError> {
+                    let from = transcoder.from();
+                    match read_tag(transcoder, pos)? {
+                        #(#dependencies_arms)*
+                        _ => return Err(TranscodeError::InvalidTag { pos }),
+                    }
+                    Ok(())
+                }
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn transcode_value(
+                    pos: usize,
+                    out: usize,
+                    transcoder: &mut WidthTranscoder<'_>,
+                    resolvers: &mut WidthResolvers,
+                ) -> ::core::result::Result<(), TranscodeError> {
+                    let (from, to) = (transcoder.from(), transcoder.to());
+                    let tag = read_tag(transcoder, pos)?;
+                    transcoder.write_bytes(out, &tag.to_ne_bytes());
+                    match tag {
+                        #(#value_arms)*
+                        _ => return Err(TranscodeError::InvalidTag { pos }),
+                    }
+                    Ok(())
+                }
+            }
+        };
+    })
+}
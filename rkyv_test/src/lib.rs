@@ -40,7 +40,12 @@ mod tests {
         test_archive(&1234567890u32);
         test_archive(&12345678901234567890u64);
         test_archive(&123456789012345678901234567890123456789u128);
-        #[cfg(not(any(feature = "strict", feature = "archive_le", feature = "archive_be")))]
+        #[cfg(not(any(
+            feature = "strict",
+            feature = "archive_le",
+            feature = "archive_be",
+            feature = "unaligned"
+        )))]
         test_archive(&(24, true, 16f32));
         test_archive(&[1, 2, 3, 4, 5, 6]);
 
@@ -1147,6 +1147,7 @@ mod tests {
         not(any(feature = "archive_le", feature = "archive_be")),
         feature = "arbitrary_enum_discriminant"
     ))]
+    #[cfg(not(feature = "unaligned"))]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn arbitrary_enum_discriminant() {
         use rkyv::Infallible;
@@ -1225,6 +1226,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "unaligned"))]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn repr_c() {
         #[derive(Archive)]
@@ -1239,6 +1241,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "unaligned"))]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn repr_int() {
         #[derive(Archive)]
@@ -1307,6 +1310,31 @@ mod tests {
         assert_eq!(core::mem::align_of::<ArchivedCAlignRepr>(), 8);
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_align() {
+        #[derive(Archive, Serialize)]
+        #[archive(align = 64)]
+        struct Aligned {
+            a: u8,
+            b: u32,
+        }
+
+        assert_eq!(core::mem::align_of::<ArchivedAligned>(), 64);
+        assert_eq!(AlignedVec::<64>::ALIGNMENT, 64);
+
+        let mut serializer = AlignedSerializer::new(AlignedVec::<64>::new_aligned());
+        serializer.serialize_value(&0u8).unwrap();
+        serializer.serialize_value(&Aligned { a: 1, b: 2 }).unwrap();
+        let buf = serializer.into_inner();
+        assert_eq!(buf.as_ptr() as usize & 63, 0);
+
+        let archived = unsafe { archived_root::<Aligned>(buf.as_ref()) };
+        assert_eq!(archived as *const ArchivedAligned as usize & 63, 0);
+        assert_eq!(archived.a, 1);
+        assert_eq!(archived.b, 2);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_as() {
@@ -1546,6 +1574,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "unaligned"))]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn with_atomic() {
         use core::sync::atomic::{AtomicU32, Ordering};
@@ -1736,6 +1765,456 @@ mod tests {
         assert!(archived.b.iter().find(|&e| e == "fizzbuzz").is_some());
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn with_as_sorted_vec() {
+        use core::ops::Bound::{Excluded, Included, Unbounded};
+        use rkyv::with::AsSortedVec;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(AsSortedVec)]
+            a: BTreeMap<String, String>,
+            #[with(AsSortedVec)]
+            b: BTreeSet<String>,
+            #[with(AsSortedVec)]
+            c: BTreeMap<String, String>,
+        }
+
+        let mut a = BTreeMap::new();
+        a.insert("foo".to_string(), "hello".to_string());
+        a.insert("bar".to_string(), "world".to_string());
+        a.insert("baz".to_string(), "bat".to_string());
+
+        let mut b = BTreeSet::new();
+        b.insert("foo".to_string());
+        b.insert("hello world!".to_string());
+        b.insert("bar".to_string());
+        b.insert("fizzbuzz".to_string());
+
+        let c = BTreeMap::new();
+
+        let value = Test { a, b, c };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let result = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(result.as_slice()) };
+
+        assert_eq!(archived.a.len(), 3);
+        assert_eq!(archived.a["foo"], "hello");
+        assert_eq!(archived.a.get("bar").unwrap(), "world");
+        assert!(archived.a.contains_key("baz"));
+        assert!(archived.a.get("qux").is_none());
+        assert!(archived
+            .a
+            .keys()
+            .map(|k| k.as_str())
+            .eq(["bar", "baz", "foo"].iter().copied()));
+        assert_eq!(archived.a.first_key_value().unwrap().0, "bar");
+        assert_eq!(archived.a.last_key_value().unwrap().0, "foo");
+        assert!(archived
+            .a
+            .range::<str, _>((Included("baz"), Unbounded))
+            .map(|(k, _)| k.as_str())
+            .eq(["baz", "foo"].iter().copied()));
+        assert_eq!(
+            archived
+                .a
+                .range::<str, _>((Included("bb"), Excluded("bz")))
+                .count(),
+            0
+        );
+
+        assert_eq!(archived.b.len(), 4);
+        assert!(archived.b.contains("fizzbuzz"));
+        assert!(!archived.b.contains("buzz"));
+        assert!(archived
+            .b
+            .range::<str, _>((Included("c"), Included("foo")))
+            .map(|k| k.as_str())
+            .eq(["fizzbuzz", "foo"].iter().copied()));
+        assert_eq!(archived.b.first().unwrap(), "bar");
+        assert_eq!(archived.b.last().unwrap(), "hello world!");
+
+        assert!(archived.c.is_empty());
+        assert!(archived.c.get("foo").is_none());
+        assert_eq!(archived.c.range::<str, _>(..).count(), 0);
+
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn with_as_trie() {
+        use rkyv::with::AsTrie;
+
+        fn levenshtein(a: &str, b: &str) -> usize {
+            let b = b.chars().collect::<Vec<_>>();
+            let mut row = (0..=b.len()).collect::<Vec<_>>();
+            for c in a.chars() {
+                let mut diagonal = row[0];
+                row[0] += 1;
+                for i in 0..b.len() {
+                    let above = row[i + 1];
+                    row[i + 1] = (above + 1)
+                        .min(row[i] + 1)
+                        .min(diagonal + (b[i] != c) as usize);
+                    diagonal = above;
+                }
+            }
+            row[b.len()]
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(AsTrie)]
+            words: BTreeMap<String, String>,
+            #[with(AsTrie)]
+            empty: BTreeMap<String, String>,
+        }
+
+        let mut words = BTreeMap::new();
+        for word in [
+            "",
+            "a",
+            "app",
+            "apple",
+            "applet",
+            "application",
+            "apply",
+            "banana",
+            "band",
+            "bandana",
+            "bandit",
+            "can",
+            "cana",
+            "canal",
+            "café",
+            "cafè",
+            "cafétéria",
+            "日本",
+            "日本語",
+        ] {
+            words.insert(word.to_string(), word.to_uppercase());
+        }
+        let value = Test {
+            words,
+            empty: BTreeMap::new(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let result = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(result.as_slice()) };
+
+        assert_eq!(archived.words.len(), value.words.len());
+        for (key, value) in value.words.iter() {
+            assert_eq!(archived.words[key.as_str()], *value);
+        }
+        for key in ["ap", "appl", "b", "bandanas", "caf", "日"] {
+            assert!(!archived.words.contains_key(key));
+        }
+        assert!(archived
+            .words
+            .iter()
+            .map(|(k, v)| (k, v.as_str()))
+            .eq(value.words.iter().map(|(k, v)| (k.clone(), v.as_str()))));
+        assert_eq!(archived.words.keys().len(), value.words.len());
+
+        for prefix in [
+            "",
+            "a",
+            "ap",
+            "appl",
+            "apple",
+            "b",
+            "band",
+            "bandi",
+            "ca",
+            "caf",
+            "café",
+            "日",
+            "x",
+            "applications",
+        ] {
+            let expected = value
+                .words
+                .iter()
+                .filter(|(k, _)| k.starts_with(prefix))
+                .map(|(k, v)| (k.clone(), v.as_str()))
+                .collect::<Vec<_>>();
+            let iter = archived.words.prefix_iter(prefix);
+            assert_eq!(iter.len(), expected.len());
+            assert!(iter.map(|(k, v)| (k, v.as_str())).eq(expected.into_iter()));
+            assert_eq!(
+                archived.words.prefix_values(prefix).len(),
+                value.words.keys().filter(|k| k.starts_with(prefix)).count(),
+            );
+        }
+
+        for (query, max_distance) in [
+            ("aple", 1),
+            ("bandana", 2),
+            ("cafe", 1),
+            ("", 1),
+            ("日本人", 1),
+        ] {
+            let expected = value
+                .words
+                .keys()
+                .filter(|k| levenshtein(k, query) <= max_distance)
+                .cloned()
+                .collect::<Vec<_>>();
+            assert!(archived
+                .words
+                .fuzzy_iter(query, max_distance)
+                .map(|(k, _)| k)
+                .eq(expected.into_iter()));
+        }
+
+        assert!(archived.empty.is_empty());
+        assert!(archived.empty.get("").is_none());
+        assert_eq!(archived.empty.iter().count(), 0);
+        assert_eq!(archived.empty.prefix_iter("").count(), 0);
+        assert_eq!(archived.empty.fuzzy_iter("a", 1).count(), 0);
+
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn with_bit_packed_and_delta_packed() {
+        use rkyv::with::{BitPacked, DeltaPacked};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(BitPacked)]
+            bits: Vec<u64>,
+            #[with(BitPacked)]
+            sorted_bits: Vec<u16>,
+            #[with(DeltaPacked)]
+            deltas: Vec<u64>,
+            #[with(DeltaPacked)]
+            sorted_deltas: Vec<u32>,
+            #[with(BitPacked)]
+            empty_bits: Vec<u32>,
+            #[with(DeltaPacked)]
+            empty_deltas: Vec<u8>,
+        }
+
+        let mut unsorted = (0..1000u64)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 20)
+            .collect::<Vec<_>>();
+        unsorted[300] = 0;
+        unsorted[301] = u64::MAX;
+        unsorted[302] = 0;
+        let value = Test {
+            bits: unsorted.iter().map(|x| x >> 24).collect(),
+            sorted_bits: (0..1000).map(|i| 500 + i * 7).collect(),
+            deltas: unsorted,
+            sorted_deltas: (0..1000).map(|i| 1_000_000 + i * i * 3).collect(),
+            empty_bits: Vec::new(),
+            empty_deltas: Vec::new(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let result = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(result.as_slice()) };
+
+        assert_eq!(archived.bits.len(), value.bits.len());
+        assert_eq!(archived.bits.bit_width(), 40);
+        for (i, x) in value.bits.iter().enumerate() {
+            assert_eq!(archived.bits.get(i), Some(*x));
+        }
+        assert_eq!(archived.bits.get(value.bits.len()), None);
+        assert_eq!(archived.bits.first(), value.bits.first().copied());
+        assert_eq!(archived.bits.last(), value.bits.last().copied());
+        assert!(archived.bits.iter().eq(value.bits.iter().copied()));
+        assert!(archived
+            .bits
+            .iter()
+            .rev()
+            .eq(value.bits.iter().rev().copied()));
+        assert_eq!(archived.bits, *value.bits.as_slice());
+
+        assert_eq!(archived.sorted_bits.bit_width(), 13);
+        assert_eq!(archived.sorted_bits, *value.sorted_bits.as_slice());
+        for x in 0..8000 {
+            assert_eq!(
+                archived.sorted_bits.binary_search(x),
+                value.sorted_bits.binary_search(&x),
+            );
+        }
+
+        assert_eq!(archived.deltas.len(), value.deltas.len());
+        for (i, x) in value.deltas.iter().enumerate() {
+            assert_eq!(archived.deltas.get(i), Some(*x));
+        }
+        assert_eq!(archived.deltas.get(value.deltas.len()), None);
+        assert_eq!(archived.deltas.first(), value.deltas.first().copied());
+        assert_eq!(archived.deltas.last(), value.deltas.last().copied());
+        assert_eq!(archived.deltas.iter().len(), value.deltas.len());
+        assert_eq!(archived.deltas, *value.deltas.as_slice());
+
+        assert_eq!(archived.sorted_deltas, *value.sorted_deltas.as_slice());
+        for x in value.sorted_deltas.iter().flat_map(|&x| [x - 1, x, x + 1]) {
+            assert_eq!(
+                archived.sorted_deltas.binary_search(x),
+                value.sorted_deltas.binary_search(&x),
+            );
+        }
+        assert_eq!(archived.sorted_deltas.binary_search(0), Err(0));
+        assert_eq!(
+            archived.sorted_deltas.binary_search(u32::MAX),
+            Err(value.sorted_deltas.len()),
+        );
+
+        assert!(archived.empty_bits.is_empty());
+        assert_eq!(archived.empty_bits.get(0), None);
+        assert_eq!(archived.empty_bits.iter().count(), 0);
+        assert_eq!(archived.empty_bits.binary_search(1), Err(0));
+        assert!(archived.empty_deltas.is_empty());
+        assert_eq!(archived.empty_deltas.get(0), None);
+        assert_eq!(archived.empty_deltas.iter().count(), 0);
+        assert_eq!(archived.empty_deltas.binary_search(1), Err(0));
+
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn with_columnar() {
+        use rkyv::with::{Columnar, Niche};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(columnar)]
+        struct Record {
+            id: u32,
+            name: String,
+            #[with(Niche)]
+            parent: Option<Box<u32>>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Table {
+            #[with(Columnar)]
+            records: Vec<Record>,
+            #[with(Columnar)]
+            empty: Vec<Record>,
+        }
+
+        let value = Table {
+            records: (0..100)
+                .map(|i| Record {
+                    id: i * 3,
+                    name: i.to_string(),
+                    parent: (i % 7 != 0).then(|| Box::new(i / 7 * 3)),
+                })
+                .collect(),
+            empty: Vec::new(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let result = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Table>(result.as_slice()) };
+
+        assert_eq!(archived.records.len(), value.records.len());
+        assert!(!archived.records.is_empty());
+        assert!(archived
+            .records
+            .id()
+            .iter()
+            .eq(value.records.iter().map(|r| &r.id)));
+        assert!(archived
+            .records
+            .name()
+            .iter()
+            .eq(value.records.iter().map(|r| &r.name)));
+        for (i, record) in value.records.iter().enumerate() {
+            let row = archived.records.get(i).unwrap();
+            assert_eq!(*row.id, record.id);
+            assert_eq!(row.name, &record.name);
+            assert_eq!(*row.parent, record.parent);
+        }
+        assert!(archived.records.get(value.records.len()).is_none());
+        assert_eq!(archived.records.iter().count(), value.records.len());
+        assert!(archived
+            .records
+            .iter()
+            .map(|row| *row.id)
+            .eq(value.records.iter().map(|r| r.id)));
+
+        assert!(archived.empty.is_empty());
+        assert!(archived.empty.get(0).is_none());
+        assert_eq!(archived.empty.iter().count(), 0);
+
+        let deserialized: Table = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn with_wide() {
+        use core::mem::size_of;
+        use rkyv::{
+            wide::{ArchivedWideBox, ArchivedWideString, ArchivedWideVec},
+            with::Wide,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            name: String,
+            #[with(Wide)]
+            blob: Vec<u8>,
+            #[with(Wide)]
+            label: String,
+            #[with(Wide)]
+            boxed: Box<u32>,
+            #[with(Wide)]
+            boxed_slice: Box<[String]>,
+            #[with(Wide)]
+            empty: Vec<u64>,
+        }
+
+        assert_eq!(size_of::<ArchivedWideVec<u8>>(), 16);
+        assert_eq!(size_of::<ArchivedWideString>(), 16);
+        assert_eq!(size_of::<ArchivedWideBox<u32>>(), 16);
+
+        let value = Test {
+            name: "test".to_string(),
+            blob: (0..1000).map(|i| i as u8).collect(),
+            label: "a label that is long enough to be out of line".to_string(),
+            boxed: Box::new(42),
+            boxed_slice: vec!["a".to_string(), "bc".to_string(), "def".to_string()]
+                .into_boxed_slice(),
+            empty: Vec::new(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let result = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(result.as_slice()) };
+
+        assert_eq!(archived.name, "test");
+        assert_eq!(archived.blob.len(), 1000);
+        assert_eq!(archived.blob, value.blob);
+        assert_eq!(archived.label, value.label);
+        assert_eq!(&*archived.label, value.label.as_str());
+        assert_eq!(*archived.boxed, 42);
+        assert_eq!(archived.boxed_slice.len(), 3);
+        assert_eq!(archived.boxed_slice[2], "def");
+        assert!(archived.empty.is_empty());
+
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn with_niche() {
@@ -2210,4 +2689,1309 @@ mod tests {
 
         drop(ManuallyDrop::into_inner(vec));
     }
+
+    #[test]
+    #[cfg(all(feature = "compact", not(feature = "strict")))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_round_trip() {
+        use rkyv::compact::compact;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        enum Shape {
+            Point,
+            Circle(Box<u32>),
+            Polygon { points: Vec<[i16; 2]> },
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test {
+            name: String,
+            short: String,
+            values: Vec<String>,
+            boxed: Box<[u64]>,
+            option: Option<Box<[u8]>>,
+            shapes: Vec<Shape>,
+            map: BTreeMap<u32, String>,
+            tuple: (u8, String),
+        }
+
+        let value = Test {
+            name: "a string that is too long to be inlined".to_string(),
+            short: "inline".to_string(),
+            values: vec!["hello".to_string(), "a longer string value".to_string()],
+            boxed: vec![1, 2, 3].into_boxed_slice(),
+            option: Some(b"boxed option".to_vec().into_boxed_slice()),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(Box::new(10)),
+                Shape::Polygon {
+                    points: vec![[0, 0], [1, 2], [-3, 4]],
+                },
+            ],
+            map: (0..500).map(|i| (i, i.to_string().repeat(4))).collect(),
+            tuple: (1, "a string in a tuple".to_string()),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        // An archive with no unreachable bytes is unchanged
+        let compacted = unsafe { compact::<Test>(&original) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        // Unreachable values are removed
+        let mut serializer = DefaultSerializer::default();
+        serializer
+            .serialize_value(&"some unreachable bytes".to_string())
+            .unwrap();
+        serializer.serialize_value(&value).unwrap();
+        serializer.serialize_value(&vec![1u32, 2, 3]).unwrap();
+        let pos = serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+        assert!(garbage.len() > original.len());
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let compacted = unsafe { rkyv::compact::compact_value::<Test>(&garbage, pos) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_update() {
+        use rkyv::compact::{compact, ArchiveUpdater};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq, Clone)]
+        #[archive(compact)]
+        struct Item {
+            name: String,
+            tags: Vec<u32>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq, Clone)]
+        #[archive(compact)]
+        struct Test {
+            owner: String,
+            items: Vec<Item>,
+            boxed: Box<[Box<Item>]>,
+            map: BTreeMap<u32, Item>,
+        }
+
+        let item = |i: u32| Item {
+            name: "an item name that is not inlined ".to_string() + &i.to_string(),
+            tags: (0..i % 5).collect(),
+        };
+        let mut value = Test {
+            owner: "the owner of the items".to_string(),
+            items: (0..10).map(item).collect(),
+            boxed: (10..20).map(|i| Box::new(item(i))).collect(),
+            map: (0..200).map(|i| (i, item(i))).collect(),
+        };
+
+        let serialize = |value: &Test| {
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(value).unwrap();
+            serializer.into_serializer().into_inner()
+        };
+        fn new_updater(bytes: &AlignedVec) -> ArchiveUpdater<'_, DefaultSerializer> {
+            let mut serializer = DefaultSerializer::default();
+            serializer.write(bytes).unwrap();
+            ArchiveUpdater::new(bytes.as_slice(), serializer)
+        }
+
+        let mut bytes = serialize(&value);
+
+        // Update an inline field of the root
+        let archived = unsafe { archived_root::<Test>(&bytes) };
+        let mut updater = new_updater(&bytes);
+        updater
+            .update::<_, String>(archived, &archived.owner, &"a new owner".to_string())
+            .unwrap();
+        let updated = updater.into_serializer().into_serializer().into_inner();
+        value.owner = "a new owner".to_string();
+        assert!(updated.len() - bytes.len() < bytes.len() / 2);
+        let deserialized: Test = unsafe { archived_root::<Test>(&updated) }
+            .deserialize(&mut Infallible)
+            .unwrap();
+        assert_eq!(deserialized, value);
+        assert_eq!(
+            unsafe { compact::<Test>(&updated) }.as_slice(),
+            serialize(&value).as_slice()
+        );
+        bytes = updated;
+
+        // Update an element of a vec
+        let archived = unsafe { archived_root::<Test>(&bytes) };
+        let mut updater = new_updater(&bytes);
+        updater
+            .update::<_, Item>(archived, &archived.items[3], &item(100))
+            .unwrap();
+        let updated = updater.into_serializer().into_serializer().into_inner();
+        value.items[3] = item(100);
+        let deserialized: Test = unsafe { archived_root::<Test>(&updated) }
+            .deserialize(&mut Infallible)
+            .unwrap();
+        assert_eq!(deserialized, value);
+        assert_eq!(
+            unsafe { compact::<Test>(&updated) }.as_slice(),
+            serialize(&value).as_slice()
+        );
+
+        // The previous version is still intact
+        let previous: Test = unsafe { archived_root::<Test>(&updated[..bytes.len()]) }
+            .deserialize(&mut Infallible)
+            .unwrap();
+        assert_eq!(previous.items[3], item(3));
+        bytes = updated;
+
+        // Update a value in a map
+        let archived = unsafe { archived_root::<Test>(&bytes) };
+        let mut updater = new_updater(&bytes);
+        updater
+            .update::<_, Item>(
+                archived,
+                archived.map.values().nth(150).unwrap(),
+                &item(300),
+            )
+            .unwrap();
+        let updated = updater.into_serializer().into_serializer().into_inner();
+        value.map.insert(150, item(300));
+        let deserialized: Test = unsafe { archived_root::<Test>(&updated) }
+            .deserialize(&mut Infallible)
+            .unwrap();
+        assert_eq!(deserialized, value);
+        assert_eq!(
+            unsafe { compact::<Test>(&updated) }.as_slice(),
+            serialize(&value).as_slice()
+        );
+        bytes = updated;
+
+        // Update a boxed value, which requires the box to be on the path
+        let archived = unsafe { archived_root::<Test>(&bytes) };
+        let mut updater = new_updater(&bytes);
+        updater
+            .through(&archived.boxed[5])
+            .update::<_, Item>(archived, &*archived.boxed[5], &item(400))
+            .unwrap();
+        let updated = updater.into_serializer().into_serializer().into_inner();
+        *value.boxed[5] = item(400);
+        let deserialized: Test = unsafe { archived_root::<Test>(&updated) }
+            .deserialize(&mut Infallible)
+            .unwrap();
+        assert_eq!(deserialized, value);
+        assert_eq!(
+            unsafe { compact::<Test>(&updated) }.as_slice(),
+            serialize(&value).as_slice()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_shared() {
+        use rkyv::compact::compact;
+
+        #[derive(Archive, Serialize, Deserialize)]
+        #[archive(compact)]
+        struct Shared {
+            a: Rc<String>,
+            b: Rc<String>,
+            weak: Weak<String>,
+            c: Rc<[u32]>,
+        }
+
+        let a = Rc::new("a shared string that is not inlined".to_string());
+        let value = Shared {
+            a: a.clone(),
+            b: a.clone(),
+            weak: Rc::downgrade(&a),
+            c: Rc::from(vec![1, 2, 3]),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&vec![4u8; 100]).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Shared>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Shared>(&compacted) };
+        let deserialized: Shared = archived
+            .deserialize(&mut DefaultDeserializer::default())
+            .unwrap();
+        assert_eq!(*deserialized.a, "a shared string that is not inlined");
+        assert!(Rc::ptr_eq(&deserialized.a, &deserialized.b));
+        assert!(Rc::ptr_eq(
+            &deserialized.a,
+            &deserialized.weak.upgrade().unwrap()
+        ));
+        assert_eq!(&*deserialized.c, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "transcode", not(feature = "strict")))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_round_trip() {
+        use rkyv::transcode::{transcode_root, transcode_root_in_place, Endianness};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        enum Shape {
+            Point,
+            Circle(u32),
+            Rect { width: u16, height: u64 },
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        struct Test {
+            id: u32,
+            name: String,
+            short: String,
+            values: Vec<i64>,
+            boxed: Box<f64>,
+            option: Option<u16>,
+            shapes: Vec<Shape>,
+            map: BTreeMap<u32, String>,
+            tuple: (u8, i16, char),
+        }
+
+        #[derive(Archive, Serialize, Deserialize)]
+        #[archive(transcode)]
+        struct Shared {
+            a: Rc<u32>,
+            b: Rc<u32>,
+        }
+
+        let value = Test {
+            id: 0x12345678,
+            name: "a string that is too long to be inlined".to_string(),
+            short: "inline".to_string(),
+            values: vec![-1, 2, -3, 4],
+            boxed: Box::new(1.5),
+            option: Some(0x1234),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(0xabcdef),
+                Shape::Rect {
+                    width: 0x0102,
+                    height: 0x0304050607,
+                },
+            ],
+            map: (0..20).map(|i| (i * 1000, i.to_string())).collect(),
+            tuple: (1, -2, 'x'),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED).unwrap() };
+        assert_ne!(exported.as_slice(), original.as_slice());
+
+        let mut imported = exported.clone();
+        unsafe {
+            transcode_root_in_place::<Test>(&mut imported, Endianness::ARCHIVED.opposite())
+                .unwrap();
+        }
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+
+        let shared = Shared {
+            a: Rc::new(0x01020304),
+            b: Rc::new(0x05060708),
+        };
+        let shared = Shared {
+            a: shared.a.clone(),
+            b: shared.a,
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&shared).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut bytes = original.clone();
+        unsafe {
+            transcode_root_in_place::<Shared>(&mut bytes, Endianness::ARCHIVED).unwrap();
+            transcode_root_in_place::<Shared>(&mut bytes, Endianness::ARCHIVED.opposite()).unwrap();
+        }
+        assert_eq!(bytes.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Shared>(&bytes) };
+        let deserialized: Shared = archived
+            .deserialize(&mut DefaultDeserializer::default())
+            .unwrap();
+        assert_eq!(*deserialized.a, 0x01020304);
+        assert!(Rc::ptr_eq(&deserialized.a, &deserialized.b));
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "compact",
+        feature = "transcode",
+        target_pointer_width = "64"
+    ))]
+    fn compact_and_transcode_wide() {
+        use rkyv::{
+            compact::compact,
+            transcode::{transcode_root, transcode_root_in_place, Endianness},
+            with::Wide,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact, transcode)]
+        struct Test {
+            #[with(Wide)]
+            values: Vec<u32>,
+            #[with(Wide)]
+            strings: Box<[String]>,
+            #[with(Wide)]
+            label: String,
+        }
+
+        let value = Test {
+            values: (0..100).map(|i| i * 0x01020304).collect(),
+            strings: vec!["a string that is too long to be inlined".to_string(); 3]
+                .into_boxed_slice(),
+            label: "label".to_string(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&vec![1u32, 2, 3]).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED).unwrap() };
+        assert_ne!(exported.as_slice(), original.as_slice());
+
+        let mut imported = exported.clone();
+        unsafe {
+            transcode_root_in_place::<Test>(&mut imported, Endianness::ARCHIVED.opposite())
+                .unwrap();
+        }
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_out_of_bounds() {
+        use rkyv::transcode::{transcode_root_in_place, Endianness, TranscodeError};
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&vec![1u32, 2, 3]).unwrap();
+        let mut bytes = serializer.into_serializer().into_inner();
+
+        // Truncating the elements leaves the vec pointing past the start of the archive
+        let root_size = core::mem::size_of::<Archived<Vec<u32>>>();
+        let len = bytes.len();
+        let mut truncated = bytes[len - root_size..].to_vec();
+        assert!(matches!(
+            unsafe { transcode_root_in_place::<Vec<u32>>(&mut truncated, Endianness::ARCHIVED) },
+            Err(TranscodeError::OutOfBounds { .. })
+        ));
+
+        assert!(matches!(
+            unsafe {
+                transcode_root_in_place::<Vec<u32>>(
+                    &mut bytes[..root_size - 1],
+                    Endianness::ARCHIVED,
+                )
+            },
+            Err(TranscodeError::Overrun { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_sized_pointee_out_of_bounds() {
+        use rkyv::{
+            to_archived,
+            transcode::{transcode_root_in_place, Endianness, TranscodeError},
+            FixedIsize,
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&Box::new(1u64)).unwrap();
+        let mut bytes = serializer.into_serializer().into_inner();
+
+        // Point the box at the end of the archive, where there isn't room for its value
+        let root_size = core::mem::size_of::<Archived<Box<u64>>>();
+        let root = bytes.len() - root_size;
+        let offset: Archived<isize> = to_archived!(root_size as FixedIsize);
+        unsafe {
+            core::ptr::write_unaligned(bytes[root..].as_mut_ptr().cast(), offset);
+        }
+        assert!(matches!(
+            unsafe { transcode_root_in_place::<Box<u64>>(&mut bytes, Endianness::ARCHIVED) },
+            Err(TranscodeError::Overrun { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_round_trip() {
+        use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode_width)]
+        enum Shape {
+            Point,
+            Circle(u32),
+            Polygon { name: String, points: Vec<[u16; 2]> },
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode_width)]
+        #[archive_attr(repr(C))]
+        struct Test {
+            id: u8,
+            len: usize,
+            offset: isize,
+            short: String,
+            medium: String,
+            long: String,
+            values: Vec<u64>,
+            boxed: Box<str>,
+            option: Option<Box<u16>>,
+            shapes: Vec<Shape>,
+            array: [usize; 3],
+        }
+
+        let value = Test {
+            id: 42,
+            len: 1234,
+            offset: -1234,
+            short: "abc".to_string(),
+            medium: "twelve bytes".to_string(),
+            long: "a string that is too long to be inlined".to_string(),
+            values: vec![1, 2, 3],
+            boxed: "boxed str".into(),
+            option: Some(Box::new(0x1234)),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(10),
+                Shape::Polygon {
+                    name: "triangle".to_string(),
+                    points: vec![[0, 0], [1, 0], [0, 1]],
+                },
+            ],
+            array: [1, 2, 3],
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        for width in [
+            PointerWidth::Size16,
+            PointerWidth::Size32,
+            PointerWidth::Size64,
+        ] {
+            let transcoded =
+                transcode_width_root::<Test>(&original, PointerWidth::ARCHIVED, width).unwrap();
+            if width == PointerWidth::ARCHIVED {
+                assert_eq!(transcoded.as_slice(), original.as_slice());
+            } else {
+                assert_ne!(transcoded.as_slice(), original.as_slice());
+            }
+
+            let restored =
+                transcode_width_root::<Test>(&transcoded, width, PointerWidth::ARCHIVED).unwrap();
+            let archived = unsafe { archived_root::<Test>(&restored) };
+            let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+            assert_eq!(deserialized, value);
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&1234usize).unwrap();
+        let original = serializer.into_serializer().into_inner();
+        let wide =
+            transcode_width_root::<usize>(&original, PointerWidth::ARCHIVED, PointerWidth::Size64)
+                .unwrap();
+        assert_eq!(wide.len(), 8);
+        let restored =
+            transcode_width_root::<usize>(&wide, PointerWidth::Size64, PointerWidth::ARCHIVED)
+                .unwrap();
+        assert_eq!(restored.as_slice(), original.as_slice());
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_shared() {
+        use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+
+        #[derive(Archive, Serialize, Deserialize)]
+        #[archive(transcode_width)]
+        #[archive_attr(repr(C))]
+        struct Shared {
+            a: Rc<u32>,
+            b: Rc<u32>,
+            c: Rc<[u32]>,
+        }
+
+        let a = Rc::new(10);
+        let value = Shared {
+            a: a.clone(),
+            b: a,
+            c: Rc::from(vec![1, 2, 3]),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let wide =
+            transcode_width_root::<Shared>(&original, PointerWidth::ARCHIVED, PointerWidth::Size64)
+                .unwrap();
+        let restored =
+            transcode_width_root::<Shared>(&wide, PointerWidth::Size64, PointerWidth::ARCHIVED)
+                .unwrap();
+
+        let archived = unsafe { archived_root::<Shared>(&restored) };
+        let deserialized: Shared = archived
+            .deserialize(&mut DefaultDeserializer::default())
+            .unwrap();
+        assert_eq!(*deserialized.a, 10);
+        assert!(Rc::ptr_eq(&deserialized.a, &deserialized.b));
+        assert_eq!(&*deserialized.c, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "transcode", feature = "strict"))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_btree_map() {
+        use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode_width)]
+        #[archive_attr(repr(C))]
+        struct Test {
+            values: BTreeMap<String, Vec<usize>>,
+            ids: BTreeSet<u32>,
+            empty: BTreeMap<u8, usize>,
+        }
+
+        fn round_trip<T>(value: &T, widths: &[PointerWidth])
+        where
+            T: Serialize<DefaultSerializer> + rkyv::transcode::width::TranscodeWidth + PartialEq,
+            T: core::fmt::Debug,
+            T::Archived: Deserialize<T, Infallible>,
+        {
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(value).unwrap();
+            let original = serializer.into_serializer().into_inner();
+
+            for &width in widths {
+                let transcoded =
+                    transcode_width_root::<T>(&original, PointerWidth::ARCHIVED, width).unwrap();
+                let restored =
+                    transcode_width_root::<T>(&transcoded, width, PointerWidth::ARCHIVED).unwrap();
+                assert_eq!(restored.as_slice(), original.as_slice());
+
+                let archived = unsafe { archived_root::<T>(&restored) };
+                let deserialized: T = archived.deserialize(&mut Infallible).unwrap();
+                assert_eq!(&deserialized, value);
+            }
+        }
+
+        // Long keys split the map into several leaf nodes under one inner node
+        let value = Test {
+            values: (0..60)
+                .map(|i| ("0".repeat(200) + &i.to_string(), vec![i; i % 4]))
+                .collect(),
+            ids: (0..2000).collect(),
+            empty: BTreeMap::new(),
+        };
+        round_trip(
+            &value,
+            &[
+                PointerWidth::Size16,
+                PointerWidth::Size32,
+                PointerWidth::Size64,
+            ],
+        );
+
+        // Even longer keys make a tree with several levels of inner nodes, which is too large for
+        // 16-bit pointers
+        #[cfg(not(feature = "size_16"))]
+        {
+            let deep = (0..100)
+                .map(|i| ("0".repeat(1000) + &i.to_string(), i))
+                .collect::<BTreeMap<String, u32>>();
+            round_trip(&deep, &[PointerWidth::Size32, PointerWidth::Size64]);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "transcode", not(feature = "size_16")))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_overflow() {
+        use rkyv::transcode::{
+            width::{transcode_width_root, PointerWidth},
+            TranscodeError,
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&vec![100_000usize]).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+
+        let wide = transcode_width_root::<Vec<usize>>(
+            &bytes,
+            PointerWidth::ARCHIVED,
+            PointerWidth::Size64,
+        )
+        .unwrap();
+        assert!(matches!(
+            transcode_width_root::<Vec<usize>>(&wide, PointerWidth::Size64, PointerWidth::Size16),
+            Err(TranscodeError::Overflow {
+                value: 100_000,
+                width: PointerWidth::Size16,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_malformed() {
+        use rkyv::transcode::{
+            width::{transcode_width_root, transcode_width_root_with_max_depth, PointerWidth},
+            Endianness, TranscodeError,
+        };
+
+        // Writes a 32-bit archived integer
+        fn put(bytes: &mut [u8], pos: usize, value: i64) {
+            let value = match Endianness::ARCHIVED {
+                Endianness::Little => (value as i32).to_le_bytes(),
+                Endianness::Big => (value as i32).to_be_bytes(),
+            };
+            bytes[pos..pos + 4].copy_from_slice(&value);
+        }
+
+        // An inner vec whose elements are the outer vec's elements
+        let mut bytes = [0; 16];
+        put(&mut bytes, 0, 0);
+        put(&mut bytes, 4, 2);
+        put(&mut bytes, 8, -8);
+        put(&mut bytes, 12, 1);
+        assert!(matches!(
+            transcode_width_root::<Vec<Vec<u32>>>(
+                &bytes,
+                PointerWidth::Size32,
+                PointerWidth::Size64
+            ),
+            Err(TranscodeError::Overlap { pos: 0, size: 8 })
+        ));
+
+        // Many zero-sized elements in a few bytes
+        let mut bytes = [0; 8];
+        put(&mut bytes, 4, i32::MAX as i64);
+        assert!(matches!(
+            transcode_width_root::<Vec<()>>(&bytes, PointerWidth::Size32, PointerWidth::Size64),
+            Err(TranscodeError::Overrun { .. })
+        ));
+
+        let mut serializer = DefaultSerializer::default();
+        serializer
+            .serialize_value(&vec![vec![1u32, 2], vec![3]])
+            .unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+        assert!(matches!(
+            transcode_width_root_with_max_depth::<Vec<Vec<u32>>>(
+                &bytes,
+                PointerWidth::ARCHIVED,
+                PointerWidth::Size64,
+                2,
+            ),
+            Err(TranscodeError::ExceededMaximumDepth { max_depth: 2, .. })
+        ));
+        transcode_width_root_with_max_depth::<Vec<Vec<u32>>>(
+            &bytes,
+            PointerWidth::ARCHIVED,
+            PointerWidth::Size64,
+            3,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "unaligned")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn unaligned_reprs() {
+        #[derive(Archive)]
+        #[archive_attr(repr(C))]
+        pub struct TestStruct {
+            a: u8,
+            b: u16,
+            c: u8,
+        }
+
+        // There is no padding between fields because every field has an alignment of 1
+        assert_eq!(core::mem::size_of::<ArchivedTestStruct>(), 4);
+        assert_eq!(core::mem::align_of::<ArchivedTestStruct>(), 1);
+
+        #[derive(Archive)]
+        #[allow(dead_code)]
+        pub enum InferredRepr {
+            V0,
+            V1,
+        }
+
+        assert_eq!(core::mem::size_of::<ArchivedInferredRepr>(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "unaligned")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn unaligned_with_atomic() {
+        use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+        use rkyv::with::Atomic;
+
+        #[derive(Archive, Serialize, Deserialize)]
+        struct Test {
+            #[with(Atomic)]
+            value: AtomicU8,
+            #[with(Atomic)]
+            flag: AtomicBool,
+        }
+
+        let value = Test {
+            value: AtomicU8::new(42),
+            flag: AtomicBool::new(true),
+        };
+        let mut serializer = AlignedSerializer::new(AlignedVec::new());
+        serializer.serialize_value(&value).unwrap();
+        let mut result = serializer.into_inner();
+        // NOTE: with(Atomic) is only sound if the backing memory is mutable, use with caution!
+        let archived = unsafe { archived_root_mut::<Test>(Pin::new(result.as_mut_slice())) };
+
+        assert_eq!(archived.value.load(Ordering::Relaxed), 42);
+        assert!(archived.flag.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    #[cfg(feature = "unaligned")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn unaligned_archives() {
+        use core::{mem::align_of, num::NonZeroU32};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        enum Shape {
+            Point,
+            Circle(f32),
+            Polygon(Vec<[i64; 2]>),
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            id: u64,
+            name: String,
+            scores: Vec<u32>,
+            parent: Option<Box<i128>>,
+            letter: char,
+            shapes: Vec<Shape>,
+            lengths: BTreeMap<String, usize>,
+        }
+
+        assert_eq!(align_of::<Archived<u16>>(), 1);
+        assert_eq!(align_of::<Archived<u32>>(), 1);
+        assert_eq!(align_of::<Archived<u64>>(), 1);
+        assert_eq!(align_of::<Archived<i128>>(), 1);
+        assert_eq!(align_of::<Archived<f64>>(), 1);
+        assert_eq!(align_of::<Archived<char>>(), 1);
+        assert_eq!(align_of::<Archived<NonZeroU32>>(), 1);
+        assert_eq!(align_of::<Archived<usize>>(), 1);
+        assert_eq!(align_of::<Archived<isize>>(), 1);
+        assert_eq!(align_of::<Archived<String>>(), 1);
+        assert_eq!(align_of::<Archived<Vec<u64>>>(), 1);
+        assert_eq!(align_of::<Archived<Box<[i64]>>>(), 1);
+        assert_eq!(align_of::<Archived<Option<u32>>>(), 1);
+        assert_eq!(align_of::<Archived<BTreeMap<u32, u64>>>(), 1);
+        assert_eq!(align_of::<Archived<Shape>>(), 1);
+        assert_eq!(align_of::<Archived<Test>>(), 1);
+
+        let value = Test {
+            id: 0x0102030405060708,
+            name: "a string that is too long to be inlined".to_string(),
+            scores: vec![1, 10, 100, 1000],
+            parent: Some(Box::new(-1)),
+            letter: 'ü',
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(1.5),
+                Shape::Polygon(vec![[0, 0], [1, -1], [-1, 1]]),
+            ],
+            lengths: [("one".to_string(), 1), ("two".to_string(), 2)]
+                .into_iter()
+                .collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+
+        // Shift the archive by one byte so that it is no longer aligned
+        let mut shifted = AlignedVec::new();
+        shifted.push(0);
+        shifted.extend_from_slice(&bytes);
+
+        let archived = unsafe { archived_root::<Test>(&shifted[1..]) };
+        assert_eq!(archived.id, value.id);
+        assert_eq!(archived.name, value.name);
+        assert_eq!(archived.scores.as_slice(), [1, 10, 100, 1000]);
+        assert_eq!(archived.letter, 'ü');
+
+        let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn included_archives() {
+        use rkyv::include::{layout_fingerprint, to_included_bytes, IncludeError, IncludedArchive};
+
+        assert_ne!(layout_fingerprint::<u32>(), layout_fingerprint::<u64>());
+
+        let bytes = to_included_bytes::<_, 0>(&[1u8, 2, 3, 4]).unwrap();
+        let mut included = [0u8; 12];
+        included.copy_from_slice(&bytes);
+
+        let archive: &'static IncludedArchive<[u8; 4], 12> =
+            Box::leak(Box::new(IncludedArchive::new(included)));
+        assert_eq!(archive.archive_bytes(), &[1, 2, 3, 4]);
+        assert_eq!(unsafe { archive.get_unchecked() }, &[1, 2, 3, 4]);
+
+        // The fingerprint is for a different type
+        let mismatched: &'static IncludedArchive<[u8; 3], 12> =
+            Box::leak(Box::new(IncludedArchive::new(included)));
+        assert!(matches!(
+            mismatched.check_fingerprint(),
+            Err(IncludeError::FingerprintMismatch { .. })
+        ));
+        assert!(matches!(
+            unsafe { mismatched.try_get_unchecked() },
+            Err(IncludeError::FingerprintMismatch { .. })
+        ));
+
+        let short: &'static IncludedArchive<[u8; 4], 8> =
+            Box::leak(Box::new(IncludedArchive::new([0; 8])));
+        assert_eq!(
+            short.check_fingerprint().unwrap_err(),
+            IncludeError::TooShort {
+                len: 8,
+                min_len: 12
+            }
+        );
+    }
+
+    // The fixtures hold `[1u8, 2, 3, 4]` written with `write_included` for each format. There are
+    // no fixtures for the native endianness of big-endian targets.
+    #[cfg(all(
+        not(feature = "strict"),
+        any(
+            feature = "archive_le",
+            feature = "archive_be",
+            target_endian = "little"
+        )
+    ))]
+    macro_rules! included_fixture {
+        () => {
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/included_",
+                included_fixture!(@size),
+                "_",
+                included_fixture!(@endian),
+                included_fixture!(@unaligned),
+                ".bin",
+            )
+        };
+        (@size) => {
+            if_size!("16", "32", "64")
+        };
+        (@endian) => {
+            if_endian!("le", "be", "native")
+        };
+        (@unaligned) => {
+            if_unaligned!("_unaligned", "")
+        };
+    }
+
+    // These are only used by `included_fixture!`, which has no fixture for some configurations
+    #[cfg(feature = "size_16")]
+    #[allow(unused_macros)]
+    macro_rules! if_size {
+        ($size_16:literal, $size_32:literal, $size_64:literal) => {
+            $size_16
+        };
+    }
+
+    #[cfg(feature = "size_32")]
+    #[allow(unused_macros)]
+    macro_rules! if_size {
+        ($size_16:literal, $size_32:literal, $size_64:literal) => {
+            $size_32
+        };
+    }
+
+    #[cfg(feature = "size_64")]
+    #[allow(unused_macros)]
+    macro_rules! if_size {
+        ($size_16:literal, $size_32:literal, $size_64:literal) => {
+            $size_64
+        };
+    }
+
+    #[cfg(feature = "archive_le")]
+    #[allow(unused_macros)]
+    macro_rules! if_endian {
+        ($le:literal, $be:literal, $native:literal) => {
+            $le
+        };
+    }
+
+    #[cfg(feature = "archive_be")]
+    #[allow(unused_macros)]
+    macro_rules! if_endian {
+        ($le:literal, $be:literal, $native:literal) => {
+            $be
+        };
+    }
+
+    #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+    #[allow(unused_macros)]
+    macro_rules! if_endian {
+        ($le:literal, $be:literal, $native:literal) => {
+            $native
+        };
+    }
+
+    #[cfg(feature = "unaligned")]
+    #[allow(unused_macros)]
+    macro_rules! if_unaligned {
+        ($unaligned:literal, $aligned:literal) => {
+            $unaligned
+        };
+    }
+
+    #[cfg(not(feature = "unaligned"))]
+    #[allow(unused_macros)]
+    macro_rules! if_unaligned {
+        ($unaligned:literal, $aligned:literal) => {
+            $aligned
+        };
+    }
+
+    #[test]
+    #[cfg(all(
+        not(feature = "strict"),
+        any(
+            feature = "archive_le",
+            feature = "archive_be",
+            target_endian = "little"
+        )
+    ))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn include_archive_macro() {
+        fn unchecked() -> &'static [u8; 4] {
+            rkyv::include_archive!(unsafe [u8; 4], included_fixture!())
+        }
+
+        assert_eq!(unchecked(), &[1, 2, 3, 4]);
+        assert!(core::ptr::eq(unchecked(), unchecked()));
+
+        #[cfg(feature = "validation")]
+        {
+            fn checked() -> &'static [u8; 4] {
+                rkyv::include_archive!([u8; 4], included_fixture!())
+            }
+
+            assert_eq!(checked(), &[1, 2, 3, 4]);
+            assert!(core::ptr::eq(checked(), checked()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "layout fingerprint mismatch")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn include_archive_macro_mismatched() {
+        let archived: &'static [u8; 4] = rkyv::include_archive!(
+            unsafe [u8; 4],
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/included_mismatched.bin"
+            )
+        );
+        assert_eq!(archived, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    #[should_panic(expected = "layout fingerprint mismatch")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn include_archive_macro_mismatched_checked() {
+        let archived: &'static [u8; 4] = rkyv::include_archive!(
+            [u8; 4],
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/included_mismatched.bin"
+            )
+        );
+        assert_eq!(archived, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_values() {
+        use rkyv::value::{Number, Value};
+
+        let mut object = BTreeMap::new();
+        object.insert("name".to_string(), Value::String("ferris".to_string()));
+        object.insert("age".to_string(), Value::Number(Number::PosInt(10)));
+        object.insert("offset".to_string(), Value::Number(Number::NegInt(-3)));
+        object.insert("is_crab".to_string(), Value::Bool(true));
+        object.insert("project".to_string(), Value::Null);
+        object.insert(
+            "scores".to_string(),
+            Value::Array(vec![
+                Value::Number(Number::Float(1.5)),
+                Value::Array(Vec::new()),
+            ]),
+        );
+        let value = Value::Object(object);
+
+        test_archive(&value);
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Value>(&bytes) };
+
+        assert_eq!(archived.as_object().map(|o| o.len()), Some(6));
+        assert_eq!(archived["name"].as_str(), Some("ferris"));
+        assert_eq!(archived["age"].as_u64(), Some(10));
+        assert_eq!(archived["offset"].as_i64(), Some(-3));
+        assert_eq!(archived["offset"].as_u64(), None);
+        assert_eq!(archived["is_crab"].as_bool(), Some(true));
+        assert!(archived["project"].is_null());
+        assert_eq!(archived["scores"][0].as_f64(), Some(1.5));
+        assert_eq!(archived["scores"][1].as_array().map(|a| a.len()), Some(0));
+
+        // Missing entries and indices into the wrong kind of value are null
+        assert!(archived.get("missing").is_none());
+        assert!(archived["scores"][2].is_null());
+        assert!(archived["name"][0].is_null());
+        assert!(archived["scores"]["name"].is_null());
+    }
+
+    #[cfg(feature = "bitvec")]
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_elias_fano() {
+        use rkyv::with::{EliasFano, UnsortedError};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(EliasFano)]
+            values: Vec<u64>,
+            #[with(EliasFano)]
+            set: BTreeSet<u64>,
+        }
+
+        fn check(values: Vec<u64>) {
+            let value = Test {
+                set: values.iter().copied().collect(),
+                values,
+            };
+
+            let mut serializer = SortedSerializer::default();
+            serializer.serialize_value(&value).unwrap();
+            let buffer = serializer.0.into_serializer().into_inner();
+            let archived = unsafe { archived_root::<Test>(&buffer) };
+
+            let values = &value.values;
+            assert_eq!(archived.values.len(), values.len());
+            assert_eq!(archived.values.first(), values.first().copied());
+            assert_eq!(archived.values.last(), values.last().copied());
+            for (i, x) in values.iter().enumerate() {
+                assert_eq!(archived.values.get(i), Some(*x));
+            }
+            assert_eq!(archived.values.get(values.len()), None);
+            assert_eq!(archived.values, *values.as_slice());
+            assert_eq!(archived.values.iter().len(), values.len());
+
+            let mut queries = vec![0, 1, u64::MAX - 1, u64::MAX];
+            queries.extend(
+                values
+                    .iter()
+                    .flat_map(|&x| [x.wrapping_sub(1), x, x.wrapping_add(1)]),
+            );
+            for x in queries {
+                let successor = values.get(values.partition_point(|&v| v < x)).copied();
+                let predecessor = values
+                    .partition_point(|&v| v <= x)
+                    .checked_sub(1)
+                    .map(|i| values[i]);
+                assert_eq!(archived.values.successor(x), successor);
+                assert_eq!(archived.values.predecessor(x), predecessor);
+                assert_eq!(archived.set.successor(x), successor);
+                assert_eq!(archived.set.predecessor(x), predecessor);
+            }
+
+            let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+            assert_eq!(deserialized, value);
+        }
+
+        check(Vec::new());
+        check(vec![0]);
+        check(vec![u64::MAX]);
+        check(vec![0, u64::MAX]);
+        check(vec![5, 5, 5, 9, 9, 100]);
+        check((0..1000).map(|i| i * i * 31 + 7).collect());
+        check((0..2000).map(|i| i / 3).collect());
+        check(
+            (0..600)
+                .map(|i: u64| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 4)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        );
+
+        let unsorted = Test {
+            values: vec![1, 3, 2],
+            set: BTreeSet::new(),
+        };
+        let mut serializer = SortedSerializer::default();
+        assert!(matches!(
+            serializer.serialize_value(&unsorted),
+            Err(SortedSerializerError::Unsorted(UnsortedError::Unsorted))
+        ));
+    }
+
+    #[cfg(feature = "bitvec")]
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_rank_select() {
+        use bitvec::prelude::*;
+        use rkyv::{bitvec::ArchivedRankSelect, with::RankSelect};
+
+        // Only byte stores are native integers when the endianness is fixed
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        type Store = usize;
+        #[cfg(any(feature = "archive_le", feature = "archive_be"))]
+        type Store = u8;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(RankSelect)]
+            lsb: BitVec<Store>,
+            #[with(RankSelect)]
+            msb: BitVec<u8, Msb0>,
+        }
+
+        fn check_rank_select<T: BitStore, O: BitOrder>(
+            rank_select: &ArchivedRankSelect<T, O>,
+            bits: &BitSlice,
+        ) {
+            let ones = bits.iter_ones().collect::<Vec<_>>();
+            let zeros = bits.iter_zeros().collect::<Vec<_>>();
+            assert_eq!(rank_select.count_ones(), ones.len());
+            assert_eq!(rank_select.count_zeros(), zeros.len());
+            let mut rank = 0;
+            for (index, bit) in bits.iter().by_vals().enumerate() {
+                if index % 5 == 0 {
+                    assert_eq!(rank_select.rank1(index), rank);
+                    assert_eq!(rank_select.rank0(index), index - rank);
+                }
+                rank += bit as usize;
+            }
+            assert_eq!(rank_select.rank1(bits.len()), ones.len());
+            assert_eq!(rank_select.rank0(bits.len()), zeros.len());
+            for (rank, index) in ones.iter().enumerate().step_by(5) {
+                assert_eq!(rank_select.select1(rank), Some(*index));
+            }
+            if let Some(last) = ones.last() {
+                assert_eq!(rank_select.select1(ones.len() - 1), Some(*last));
+            }
+            assert_eq!(rank_select.select1(ones.len()), None);
+            for (rank, index) in zeros.iter().enumerate().step_by(5) {
+                assert_eq!(rank_select.select0(rank), Some(*index));
+            }
+            if let Some(last) = zeros.last() {
+                assert_eq!(rank_select.select0(zeros.len() - 1), Some(*last));
+            }
+            assert_eq!(rank_select.select0(zeros.len()), None);
+        }
+
+        fn check(bits: BitVec) {
+            let value = Test {
+                msb: bits.iter().by_vals().collect(),
+                lsb: bits.iter().by_vals().collect(),
+            };
+
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&value).unwrap();
+            let buffer = serializer.into_serializer().into_inner();
+            let archived = unsafe { archived_root::<Test>(&buffer) };
+
+            check_rank_select(&archived.lsb, &bits);
+            check_rank_select(&archived.msb, &bits);
+            assert!(archived.lsb.iter().by_vals().eq(bits.iter().by_vals()));
+            assert!(archived.msb.iter().by_vals().eq(bits.iter().by_vals()));
+
+            let deserialized: Test = archived.deserialize(&mut Infallible).unwrap();
+            assert_eq!(deserialized, value);
+        }
+
+        check(BitVec::new());
+        check(bitvec![1]);
+        check(bitvec![0; 1000]);
+        check(bitvec![1; 5000]);
+        check((0..12000).map(|i| i % 3 == 0 || i % 7 == 0).collect());
+        check(
+            (0..12000)
+                .map(|i: u64| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 62 == 0)
+                .collect(),
+        );
+        check((0..30000).map(|i| i % 5000 == 17).collect());
+        // Select spans longer than `u16::MAX` bits store the position of every bit
+        #[cfg(not(feature = "size_16"))]
+        {
+            check((0..200_000).map(|i| i % 100 == 0).collect());
+            check((0..200_000).map(|i| i % 100 != 0).collect());
+            check((0..200_000).map(|i| i < 40_000 || i % 700 == 0).collect());
+        }
+    }
 }
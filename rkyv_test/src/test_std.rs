@@ -30,7 +30,7 @@ mod tests {
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_hash_map() {
-        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be", feature = "unaligned")))]
         {
             test_archive(&HashMap::<i32, i32>::new());
 
@@ -68,6 +68,7 @@ mod tests {
 
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(not(feature = "strict"))]
     fn archive_hash_map_tuple_retrieved_by_get_with() {
         let mut hash_map = HashMap::new();
         hash_map.insert(("my".to_string(), "key".to_string()), "value".to_string());
@@ -91,6 +92,411 @@ mod tests {
         assert_eq!(get_with.as_str(), "value");
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_hashed() {
+        use rkyv::{
+            collections::hash_index::{FxHasher, HashBuilder, Keyed},
+            with::Hashed,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(Hashed<FxHasher>)]
+            map: HashMap<String, u32>,
+            #[with(Hashed<Keyed<HashBuilder>>)]
+            set: HashSet<String>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            set: HashSet::new(),
+        };
+        for i in 0..100u32 {
+            value.map.insert(i.to_string(), i);
+            value.set.insert(i.to_string());
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(buf.as_ref()) };
+
+        assert_eq!(archived.map.seed(), 0);
+        for (key, value) in value.map.iter() {
+            assert_eq!(archived.map[key.as_str()], *value);
+            assert!(archived.set.contains(key.as_str()));
+        }
+        assert!(!archived.map.contains_key("100"));
+        assert!(!archived.set.contains("100"));
+
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn keyed_hashes_like_inner() {
+        use core::hash::{Hash, Hasher};
+        use rkyv::collections::hash_index::{ArchiveHasher, FxHasher, Keyed};
+
+        // Hashes integers differently than their bytes, so it can tell whether writes are forwarded
+        #[derive(Clone)]
+        struct Integers(u64);
+
+        impl Hasher for Integers {
+            fn write(&mut self, bytes: &[u8]) {
+                for &b in bytes {
+                    self.0 = self.0.wrapping_mul(31) ^ u64::from(b);
+                }
+            }
+
+            fn write_u8(&mut self, i: u8) {
+                self.write_u64(i.into());
+            }
+
+            fn write_u16(&mut self, i: u16) {
+                self.write_u64(i.into());
+            }
+
+            fn write_u32(&mut self, i: u32) {
+                self.write_u64(i.into());
+            }
+
+            fn write_u64(&mut self, i: u64) {
+                self.0 = self.0.wrapping_mul(37).wrapping_add(i);
+            }
+
+            fn write_u128(&mut self, i: u128) {
+                self.write_u64(i as u64);
+                self.write_u64((i >> 64) as u64);
+            }
+
+            fn write_usize(&mut self, i: usize) {
+                self.write_u64(i as u64);
+            }
+
+            fn finish(&self) -> u64 {
+                self.0
+            }
+        }
+
+        impl ArchiveHasher for Integers {
+            fn from_seed(seed: u64) -> Self {
+                Self(seed)
+            }
+        }
+
+        fn check<T: Hash>(value: T) {
+            fn check_hasher<H: ArchiveHasher, T: Hash>(value: &T) {
+                for seed in [0, 1, 0x0123_4567_89ab_cdef] {
+                    let mut keyed = Keyed::<H>::from_seed(seed);
+                    value.hash(&mut keyed);
+                    let mut inner = H::from_seed(seed);
+                    value.hash(&mut inner);
+                    assert_eq!(keyed.finish(), inner.finish());
+                }
+            }
+
+            check_hasher::<FxHasher, T>(&value);
+            check_hasher::<Integers, T>(&value);
+        }
+
+        check(0x12u8);
+        check(0x1234u16);
+        check(0x1234_5678u32);
+        check(0x1234_5678_9abc_def0u64);
+        check(0x1234_5678_9abc_def0_1234_5678_9abc_def0u128);
+        check(usize::MAX);
+        check(-0x12i8);
+        check(-0x1234i16);
+        check(-0x1234_5678i32);
+        check(-0x1234_5678_9abc_def0i64);
+        check(-0x1234_5678_9abc_def0_1234_5678_9abc_def0i128);
+        check(isize::MIN);
+        check("a string key");
+
+        // 128-bit integers are hashed as their little-endian halves on every host
+        let value = 0x1234_5678_9abc_def0_0fed_cba9_8765_4321u128;
+        let mut wide = FxHasher::from_seed(0);
+        wide.write_u128(value);
+        let mut halves = FxHasher::from_seed(0);
+        halves.write_u64(value as u64);
+        halves.write_u64((value >> 64) as u64);
+        assert_eq!(wide.finish(), halves.finish());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_hash_map_seed() {
+        use rkyv::{
+            collections::{hash_index::FxHasher, hash_map::HashMapResolver, ArchivedHashMap},
+            string::ArchivedString,
+        };
+
+        struct Seeded(HashMap<String, String>, u64);
+
+        impl Archive for Seeded {
+            type Archived = ArchivedHashMap<ArchivedString, ArchivedString, FxHasher>;
+            type Resolver = HashMapResolver;
+
+            unsafe fn resolve(
+                &self,
+                pos: usize,
+                resolver: Self::Resolver,
+                out: *mut Self::Archived,
+            ) {
+                ArchivedHashMap::resolve_from_len(self.0.len(), pos, resolver, out);
+            }
+        }
+
+        impl Serialize<DefaultSerializer> for Seeded {
+            fn serialize(
+                &self,
+                serializer: &mut DefaultSerializer,
+            ) -> Result<Self::Resolver, <DefaultSerializer as rkyv::Fallible>::Error> {
+                unsafe {
+                    Self::Archived::serialize_from_iter_with_seed(self.0.iter(), self.1, serializer)
+                }
+            }
+        }
+
+        let mut hash_map = HashMap::new();
+        for i in 0..100 {
+            hash_map.insert(i.to_string(), (i * 2).to_string());
+        }
+
+        let mut bufs = Vec::new();
+        for seed in [0, 1, 0x0123_4567_89ab_cdef] {
+            let mut serializer = DefaultSerializer::default();
+            serializer
+                .serialize_value(&Seeded(hash_map.clone(), seed))
+                .unwrap();
+            let buf = serializer.into_serializer().into_inner();
+            let archived = unsafe { archived_root::<Seeded>(buf.as_ref()) };
+
+            assert_eq!(archived.seed(), seed);
+            for (key, value) in hash_map.iter() {
+                assert_eq!(archived[key.as_str()], *value);
+            }
+            bufs.push(buf);
+        }
+        assert_ne!(bufs[0].as_slice(), bufs[1].as_slice());
+        assert_ne!(bufs[1].as_slice(), bufs[2].as_slice());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_hash_map_default_layout() {
+        use core::mem::size_of;
+        use rkyv::{
+            collections::{hash_index::FxHasher, ArchivedHashMap},
+            Archived, FixedUsize, RawRelPtr,
+        };
+
+        type Unseeded = ArchivedHashMap<Archived<u32>, Archived<u32>>;
+        type Seeded = ArchivedHashMap<Archived<u32>, Archived<u32>, FxHasher>;
+
+        // The default hasher always uses a seed of zero, so its seed isn't stored in the archive.
+        assert_eq!(
+            size_of::<Unseeded>(),
+            size_of::<Archived<FixedUsize>>() + 2 * size_of::<RawRelPtr>(),
+        );
+        assert_eq!(size_of::<Unseeded>(), size_of::<Seeded>(),);
+
+        let mut hash_map = HashMap::new();
+        for i in 0..100u32 {
+            hash_map.insert(i, i * 2);
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        unsafe {
+            Unseeded::serialize_from_iter(hash_map.iter(), &mut serializer).unwrap();
+        }
+        let default_len = serializer.into_serializer().into_inner().len();
+
+        let mut serializer = DefaultSerializer::default();
+        unsafe {
+            Seeded::serialize_from_iter_with_seed(hash_map.iter(), 0, &mut serializer).unwrap();
+        }
+        let seeded_len = serializer.into_serializer().into_inner().len();
+        assert!(seeded_len >= default_len + size_of::<Archived<u64>>());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_swiss_map() {
+        use rkyv::{collections::hash_index::FxHasher, with::AsSwissMap};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(AsSwissMap)]
+            map: HashMap<String, u32>,
+            #[with(AsSwissMap<FxHasher>)]
+            fx: HashMap<String, String>,
+            #[with(AsSwissMap)]
+            empty: HashMap<String, u32>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            fx: HashMap::new(),
+            empty: HashMap::new(),
+        };
+        for i in 0..1000u32 {
+            value.map.insert(i.to_string(), i);
+        }
+        for i in 0..10u32 {
+            value
+                .fx
+                .insert(i.to_string(), "a value that is not inlined".repeat(2));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(buf.as_ref()) };
+
+        assert_eq!(archived.map.len(), 1000);
+        assert!(archived.map.buckets() > 1000);
+        for (key, value) in value.map.iter() {
+            assert_eq!(archived.map[key.as_str()], *value);
+        }
+        for (key, value) in value.fx.iter() {
+            assert_eq!(archived.fx.get(key.as_str()).unwrap(), value);
+        }
+        assert!(archived.map.get("1000").is_none());
+        assert!(archived.fx.get("10").is_none());
+        assert!(archived.empty.is_empty());
+        assert!(archived.empty.get("0").is_none());
+        assert_eq!(archived.map.iter().count(), 1000);
+        assert_eq!(
+            archived
+                .map
+                .get_with("500", |key, other| key.as_str() == other),
+            Some(&archived.map["500"]),
+        );
+
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_sorted_vec() {
+        use core::ops::Bound::{Excluded, Included, Unbounded};
+        use rkyv::with::AsSortedVec;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(AsSortedVec)]
+            map: HashMap<String, u32>,
+            #[with(AsSortedVec)]
+            set: HashSet<String>,
+            #[with(AsSortedVec)]
+            empty: HashMap<String, u32>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            set: HashSet::new(),
+            empty: HashMap::new(),
+        };
+        for i in 0..1000u32 {
+            value.map.insert(format!("{:04}", i), i);
+            value.set.insert(i.to_string());
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(buf.as_ref()) };
+
+        assert_eq!(archived.map.len(), 1000);
+        for (key, value) in value.map.iter() {
+            assert_eq!(archived.map[key.as_str()], *value);
+        }
+        for key in value.set.iter() {
+            assert!(archived.set.contains(key.as_str()));
+        }
+        assert!(archived.map.get("1000").is_none());
+        assert!(!archived.set.contains("1000"));
+        assert!(archived
+            .map
+            .keys()
+            .zip(archived.map.keys().skip(1))
+            .all(|(a, b)| a < b));
+        assert!(archived
+            .set
+            .iter()
+            .zip(archived.set.iter().skip(1))
+            .all(|(a, b)| a < b));
+        assert!(archived
+            .map
+            .range::<str, _>((Included("0100"), Excluded("0110")))
+            .map(|(k, _)| k.as_str())
+            .eq((100..110)
+                .map(|i| format!("{:04}", i))
+                .collect::<Vec<_>>()
+                .iter()
+                .map(|k| k.as_str())));
+        assert_eq!(
+            archived
+                .map
+                .range::<str, _>((Excluded("0989"), Unbounded))
+                .count(),
+            10
+        );
+        assert!(archived.empty.is_empty());
+        assert!(archived.empty.get("0").is_none());
+
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_trie() {
+        use rkyv::with::AsTrie;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(AsTrie)]
+            map: HashMap<String, u32>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+        };
+        for i in 0..1000u32 {
+            value.map.insert(format!("key/{}/{}", i % 10, i), i);
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Test>(buf.as_ref()) };
+
+        assert_eq!(archived.map.len(), 1000);
+        for (key, value) in value.map.iter() {
+            assert_eq!(archived.map[key.as_str()], *value);
+        }
+        assert!(archived.map.get("key/1").is_none());
+        assert!(archived.map.get("key/1/1000").is_none());
+
+        let mut keys = value.map.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        assert!(archived.map.keys().eq(keys.iter().cloned()));
+        assert_eq!(archived.map.prefix_iter("key/").len(), 1000);
+        assert_eq!(archived.map.prefix_iter("key/3/").len(), 100);
+        assert_eq!(archived.map.prefix_iter("key/3/1").len(), 11);
+        assert_eq!(archived.map.prefix_values("key/7").len(), 100);
+        assert_eq!(archived.map.prefix_iter("key/10").len(), 0);
+
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     #[allow(deprecated)]
@@ -134,7 +540,7 @@ mod tests {
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_hash_set() {
-        #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+        #[cfg(not(any(feature = "archive_le", feature = "archive_be", feature = "unaligned")))]
         {
             test_archive(&HashSet::<i32>::new());
 
@@ -204,6 +610,66 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_interned() {
+        use rkyv::with::{Interned, Map};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Node {
+            #[with(Interned)]
+            kind: String,
+            #[with(Map<Interned>)]
+            labels: Vec<String>,
+            #[with(Interned)]
+            properties: HashMap<String, u32>,
+        }
+
+        let kinds = ["person", "place", "thing", ""];
+        let value = (0..100)
+            .map(|i| Node {
+                kind: kinds[i % kinds.len()].to_string(),
+                labels: (0..i % 5)
+                    .map(|j| kinds[(i + j) % kinds.len()].to_string())
+                    .collect(),
+                properties: (0..i % 3)
+                    .map(|j| (kinds[j].to_string(), j as u32))
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        let archived = unsafe { archived_root::<Vec<Node>>(buf.as_ref()) };
+
+        let mut strings = HashMap::new();
+        let mut check = |s: &str| {
+            let ptr = strings.entry(s.to_string()).or_insert(s.as_ptr());
+            assert_eq!(*ptr, s.as_ptr());
+        };
+        for (node, archived_node) in value.iter().zip(archived.iter()) {
+            assert_eq!(archived_node.kind.as_ref(), node.kind);
+            check(&archived_node.kind);
+            assert_eq!(archived_node.labels.len(), node.labels.len());
+            for (label, archived_label) in node.labels.iter().zip(archived_node.labels.iter()) {
+                assert_eq!(archived_label.as_ref(), label);
+                check(archived_label);
+            }
+            assert_eq!(archived_node.properties.len(), node.properties.len());
+            for (key, value) in node.properties.iter() {
+                assert_eq!(archived_node.properties[key.as_str()], *value);
+            }
+            for (key, _) in archived_node.properties.iter() {
+                check(key);
+            }
+        }
+        assert_eq!(strings.len(), kinds.len());
+
+        let deserialized: Vec<Node> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_net() {
@@ -238,6 +704,370 @@ mod tests {
         test_archive(&value);
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_streams() {
+        use core::mem::{size_of, MaybeUninit};
+        use rkyv::{
+            collections::{hash_index::FxHasher, ArchivedBTreeMap, ArchivedHashMap},
+            ser::serializers::{
+                AlignedSerializer, AllocScratch, CompositeSerializer, FileSpill, SpillScratch,
+            },
+            string::ArchivedString,
+            vec::ArchivedVec,
+            AlignedVec, Infallible,
+        };
+        use std::{collections::BTreeMap, io::Cursor};
+
+        type StreamSerializer = CompositeSerializer<
+            AlignedSerializer<AlignedVec>,
+            SpillScratch<AllocScratch, FileSpill<Cursor<Vec<u8>>>>,
+            Infallible,
+        >;
+
+        const SEED: u64 = 0x0123_4567_89AB_CDEF;
+
+        fn serializer() -> StreamSerializer {
+            CompositeSerializer::new(
+                AlignedSerializer::new(AlignedVec::new()),
+                SpillScratch::new(
+                    AllocScratch::new(),
+                    // A small buffer makes sure that the spilled data goes through the file
+                    FileSpill::with_capacity(Cursor::new(Vec::new()), 256),
+                ),
+                Infallible,
+            )
+        }
+
+        fn finish<T>(
+            mut serializer: StreamSerializer,
+            resolve: impl FnOnce(usize, *mut T),
+        ) -> AlignedVec {
+            let pos = serializer.align_for::<T>().unwrap();
+            let mut root = MaybeUninit::<T>::zeroed();
+            resolve(pos, root.as_mut_ptr());
+            let bytes =
+                unsafe { core::slice::from_raw_parts(root.as_ptr().cast::<u8>(), size_of::<T>()) };
+            serializer.write(bytes).unwrap();
+            serializer.into_serializer().into_inner()
+        }
+
+        type Vector = ArchivedVec<ArchivedString>;
+        type Hashed = ArchivedHashMap<ArchivedString, ArchivedString, FxHasher>;
+        type Ordered = ArchivedBTreeMap<ArchivedString, ArchivedString>;
+
+        // Large enough for the B-tree map to have inner nodes
+        #[cfg(not(feature = "size_16"))]
+        const MAX_LEN: usize = 5000;
+        #[cfg(feature = "size_16")]
+        const MAX_LEN: usize = 500;
+
+        for len in [0, 1, 10, MAX_LEN] {
+            let keys = (0..len).map(|i| format!("key {:04}", i));
+            let values = (0..len).map(|i| format!("value {}", i));
+            let items = keys.clone().zip(values.clone()).collect::<Vec<_>>();
+
+            // Vec
+            let mut streamed = serializer();
+            let (streamed_len, resolver) = ArchivedVec::serialize_from_stream::<String, _, _, _>(
+                values.clone(),
+                &mut streamed,
+            )
+            .unwrap();
+            assert_eq!(streamed_len, len);
+            let streamed = finish(streamed, |pos, out| unsafe {
+                Vector::resolve_from_len(streamed_len, pos, resolver, out)
+            });
+
+            let mut expected = serializer();
+            let resolver = ArchivedVec::serialize_from_iter::<String, _, _, _>(
+                items.iter().map(|(_, v)| v),
+                &mut expected,
+            )
+            .unwrap();
+            let expected = finish(expected, |pos, out| unsafe {
+                Vector::resolve_from_len(len, pos, resolver, out)
+            });
+
+            assert_eq!(streamed.as_slice(), expected.as_slice());
+            let archived = unsafe { archived_root::<Vec<String>>(streamed.as_ref()) };
+            assert!(archived
+                .iter()
+                .map(|v| v.as_str())
+                .eq(items.iter().map(|(_, v)| v.as_str())));
+
+            // Hash map
+            let mut streamed = serializer();
+            let (streamed_len, resolver) = unsafe {
+                Hashed::serialize_from_stream_with_seed::<String, String, _, _, _, _>(
+                    keys.clone().zip(values.clone()),
+                    SEED,
+                    &mut streamed,
+                )
+                .unwrap()
+            };
+            assert_eq!(streamed_len, len);
+            let streamed = finish(streamed, |pos, out| unsafe {
+                Hashed::resolve_from_len(streamed_len, pos, resolver, out)
+            });
+
+            let mut expected = serializer();
+            let resolver = unsafe {
+                Hashed::serialize_from_iter_with_seed(
+                    items.iter().map(|(k, v)| (k, v)),
+                    SEED,
+                    &mut expected,
+                )
+                .unwrap()
+            };
+            let expected = finish(expected, |pos, out| unsafe {
+                Hashed::resolve_from_len(len, pos, resolver, out)
+            });
+
+            // Keys and values are written in the order they arrive, so the bytes may differ
+            let root = |bytes: &AlignedVec| unsafe {
+                &*bytes
+                    .as_ptr()
+                    .add(bytes.len() - size_of::<Hashed>())
+                    .cast::<Hashed>()
+            };
+            let archived = root(&streamed);
+            assert_eq!(archived, root(&expected));
+            assert_eq!(archived.seed(), SEED);
+            assert_eq!(archived.len(), len);
+            for (key, value) in items.iter() {
+                assert_eq!(archived.get(key.as_str()).unwrap(), value);
+            }
+            assert!(archived.get("missing").is_none());
+
+            // B-tree map
+            let mut streamed = serializer();
+            let (streamed_len, resolver) = unsafe {
+                Ordered::serialize_from_reverse_stream::<String, String, _, _, _, _>(
+                    items.clone().into_iter().rev(),
+                    &mut streamed,
+                )
+                .unwrap()
+            };
+            assert_eq!(streamed_len, len);
+            let streamed = finish(streamed, |pos, out| unsafe {
+                Ordered::resolve_from_len(streamed_len, pos, resolver, out)
+            });
+
+            let map = items.iter().cloned().collect::<BTreeMap<_, _>>();
+            let mut expected = serializer();
+            let resolver = unsafe {
+                Ordered::serialize_from_reverse_iter(map.iter().rev(), &mut expected).unwrap()
+            };
+            let expected = finish(expected, |pos, out| unsafe {
+                Ordered::resolve_from_len(len, pos, resolver, out)
+            });
+
+            assert_eq!(streamed.as_slice(), expected.as_slice());
+            let archived = unsafe { archived_root::<BTreeMap<String, String>>(streamed.as_ref()) };
+            assert_eq!(archived.len(), len);
+            assert!(archived
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .eq(items.iter().map(|(k, v)| (k.as_str(), v.as_str()))));
+            for (key, value) in items.iter() {
+                assert_eq!(archived.get(key.as_str()).unwrap(), value);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_streams_drop_on_failure() {
+        use rkyv::{
+            collections::ArchivedHashMap,
+            ser::serializers::{
+                AllocScratch, AllocSpill, BufferSerializer, CompositeSerializer, SpillScratch,
+            },
+            string::ArchivedString,
+            vec::ArchivedVec,
+            with::Skip,
+            AlignedBytes, Infallible,
+        };
+        use std::rc::Rc;
+
+        #[derive(Archive, Serialize)]
+        struct Tracked {
+            value: String,
+            #[with(Skip)]
+            _guard: Rc<()>,
+        }
+
+        type SmallSerializer = CompositeSerializer<
+            BufferSerializer<AlignedBytes<256>>,
+            SpillScratch<AllocScratch, AllocSpill>,
+            Infallible,
+        >;
+
+        let guard = Rc::new(());
+        let items = || {
+            (0..100).map(|i| Tracked {
+                value: format!("a value that is too long to be inlined {}", i),
+                _guard: guard.clone(),
+            })
+        };
+
+        let mut serializer = SmallSerializer::default();
+        let result = ArchivedVec::<ArchivedTracked>::serialize_from_stream::<Tracked, _, _, _>(
+            items(),
+            &mut serializer,
+        );
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&guard), 1);
+
+        let mut serializer = SmallSerializer::default();
+        let result = unsafe {
+            ArchivedHashMap::<ArchivedString, ArchivedTracked>::serialize_from_stream::<
+                String,
+                Tracked,
+                _,
+                _,
+                _,
+                _,
+            >(
+                items().enumerate().map(|(i, item)| (i.to_string(), item)),
+                &mut serializer,
+            )
+        };
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&guard), 1);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_streams_truncate_spill_on_failure() {
+        use rkyv::{
+            collections::{ArchivedBTreeMap, ArchivedHashMap},
+            ser::{
+                serializers::{
+                    AlignedSerializer, AllocScratch, AllocSpill, CompositeSerializer, SpillScratch,
+                },
+                SpillSpace,
+            },
+            string::ArchivedString,
+            with::Skip,
+            AlignedVec, Fallible, Infallible,
+        };
+        use std::rc::Rc;
+
+        #[derive(Archive, Serialize)]
+        struct Tracked {
+            value: String,
+            #[with(Skip)]
+            _guard: Rc<()>,
+        }
+
+        // Spill space that fails once it would hold more than a fixed number of bytes
+        struct LimitedSpill {
+            inner: AllocSpill,
+            limit: usize,
+        }
+
+        #[derive(Debug)]
+        struct SpillLimitError;
+
+        impl Fallible for LimitedSpill {
+            type Error = SpillLimitError;
+        }
+
+        impl SpillSpace for LimitedSpill {
+            fn spill_pos(&self) -> usize {
+                self.inner.spill_pos()
+            }
+
+            fn spill(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+                if self.spill_pos() + bytes.len() > self.limit {
+                    return Err(SpillLimitError);
+                }
+                Ok(self.inner.spill(bytes).unwrap())
+            }
+
+            fn respill(&mut self, pos: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.inner.respill(pos, bytes).unwrap();
+                Ok(())
+            }
+
+            fn unspill(&mut self, pos: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+                self.inner.unspill(pos, bytes).unwrap();
+                Ok(())
+            }
+
+            fn truncate_spill(&mut self, pos: usize) -> Result<(), Self::Error> {
+                self.inner.truncate_spill(pos).unwrap();
+                Ok(())
+            }
+        }
+
+        type LimitedSerializer = CompositeSerializer<
+            AlignedSerializer<AlignedVec>,
+            SpillScratch<AllocScratch, LimitedSpill>,
+            Infallible,
+        >;
+
+        fn limited_serializer(limit: usize) -> LimitedSerializer {
+            CompositeSerializer::new(
+                AlignedSerializer::new(AlignedVec::new()),
+                SpillScratch::new(
+                    AllocScratch::new(),
+                    LimitedSpill {
+                        inner: AllocSpill::new(),
+                        limit,
+                    },
+                ),
+                Infallible,
+            )
+        }
+
+        let guard = Rc::new(());
+        let items = || {
+            (0..1000).rev().map(|i| Tracked {
+                value: format!("a value that is too long to be inlined {:04}", i),
+                _guard: guard.clone(),
+            })
+        };
+
+        // Building the hash index runs out of spill space
+        let mut serializer = limited_serializer(64);
+        let result = unsafe {
+            ArchivedHashMap::<ArchivedString, ArchivedTracked>::serialize_from_stream::<
+                String,
+                Tracked,
+                _,
+                _,
+                _,
+                _,
+            >(
+                items().enumerate().map(|(i, item)| (i.to_string(), item)),
+                &mut serializer,
+            )
+        };
+        assert!(result.is_err());
+        assert_eq!(serializer.spill_pos(), 0);
+        assert_eq!(Rc::strong_count(&guard), 1);
+
+        // Only a couple of leaf nodes fit before the B-tree map runs out of spill space, and their
+        // first keys are still waiting for the next level
+        let mut serializer = limited_serializer(16);
+        let result = unsafe {
+            ArchivedBTreeMap::<ArchivedTracked, ArchivedString>::serialize_from_reverse_stream::<
+                Tracked,
+                String,
+                _,
+                _,
+                _,
+                _,
+            >(items().map(|item| (item, String::new())), &mut serializer)
+        };
+        assert!(result.is_err());
+        assert_eq!(serializer.spill_pos(), 0);
+        assert_eq!(Rc::strong_count(&guard), 1);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn c_string() {
@@ -363,7 +1193,7 @@ mod tests {
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     // Don't run these tests with non-native endianness because ArchivedHashMap won't have
     // PartialEq<HashMap>
-    #[cfg(not(any(feature = "archive_le", feature = "archive_be")))]
+    #[cfg(not(any(feature = "archive_le", feature = "archive_be", feature = "unaligned")))]
     fn archive_zst_containers() {
         use std::collections::HashSet;
 
@@ -389,4 +1219,1024 @@ mod tests {
         value.insert(());
         test_archive(&value);
     }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_hash_map() {
+        use rkyv::transcode::{transcode_root, Endianness};
+
+        let mut value = HashMap::new();
+        for i in 0..100u32 {
+            value.insert(i, format!("value {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported =
+            unsafe { transcode_root::<HashMap<u32, String>>(&original, Endianness::ARCHIVED) }
+                .unwrap();
+        assert_ne!(exported.as_slice(), original.as_slice());
+        let imported = unsafe {
+            transcode_root::<HashMap<u32, String>>(&exported, Endianness::ARCHIVED.opposite())
+        }
+        .unwrap();
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<HashMap<u32, String>>(&imported) };
+        let deserialized: HashMap<u32, String> =
+            archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(all(feature = "compact", not(feature = "strict")))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_hash_map() {
+        use rkyv::compact::compact;
+
+        let mut value = HashMap::new();
+        for i in 0..100u32 {
+            value.insert(i, format!("a value that is not inlined {}", i));
+        }
+        let set = value.keys().cloned().collect::<HashSet<u32>>();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer
+            .serialize_value(&(value.clone(), set.clone()))
+            .unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        serializer
+            .serialize_value(&(value.clone(), set.clone()))
+            .unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<(HashMap<u32, String>, HashSet<u32>)>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<(HashMap<u32, String>, HashSet<u32>)>(&compacted) };
+        let deserialized: (HashMap<u32, String>, HashSet<u32>) =
+            archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, (value, set));
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_swiss_map() {
+        use rkyv::{
+            transcode::{transcode_root, Endianness},
+            with::AsSwissMap,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        struct Test(#[with(AsSwissMap)] HashMap<u32, String>);
+
+        let mut value = Test(HashMap::new());
+        for i in 0..100u32 {
+            value.0.insert(i, format!("value {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED) }.unwrap();
+        assert_ne!(exported.as_slice(), original.as_slice());
+        let imported =
+            unsafe { transcode_root::<Test>(&exported, Endianness::ARCHIVED.opposite()) }.unwrap();
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_hash_set() {
+        use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+        use std::time::Duration;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode_width)]
+        #[archive_attr(repr(C))]
+        struct Test {
+            names: HashSet<String>,
+            ids: HashSet<usize>,
+            empty: HashSet<u8>,
+            timeout: Duration,
+        }
+
+        let value = Test {
+            names: (0..50)
+                .map(|i| format!("a name that is not inlined {}", i))
+                .collect(),
+            ids: (0..100).collect(),
+            empty: HashSet::new(),
+            timeout: Duration::new(12, 345),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        for width in [
+            PointerWidth::Size16,
+            PointerWidth::Size32,
+            PointerWidth::Size64,
+        ] {
+            let transcoded =
+                transcode_width_root::<Test>(&original, PointerWidth::ARCHIVED, width).unwrap();
+            let restored =
+                transcode_width_root::<Test>(&transcoded, width, PointerWidth::ARCHIVED).unwrap();
+            assert_eq!(restored.as_slice(), original.as_slice());
+
+            let archived = unsafe { archived_root::<Test>(&restored) };
+            assert!(archived.names.contains("a name that is not inlined 42"));
+            assert_eq!(archived.ids.len(), 100);
+            let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+            assert_eq!(deserialized, value);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "transcode", feature = "strict"))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_width_hash_map() {
+        use rkyv::transcode::width::{transcode_width_root, PointerWidth};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode_width)]
+        #[archive_attr(repr(C))]
+        struct Test {
+            values: HashMap<String, Vec<usize>>,
+            offsets: HashMap<u8, isize>,
+            empty: HashMap<usize, String>,
+        }
+
+        let value = Test {
+            values: (0..50)
+                .map(|i| (format!("a key that is not inlined {}", i), vec![i; i % 4]))
+                .collect(),
+            offsets: (0..100).map(|i| (i, -(i as isize))).collect(),
+            empty: HashMap::new(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        for width in [
+            PointerWidth::Size16,
+            PointerWidth::Size32,
+            PointerWidth::Size64,
+        ] {
+            let transcoded =
+                transcode_width_root::<Test>(&original, PointerWidth::ARCHIVED, width).unwrap();
+            let restored =
+                transcode_width_root::<Test>(&transcoded, width, PointerWidth::ARCHIVED).unwrap();
+            assert_eq!(restored.as_slice(), original.as_slice());
+
+            let archived = unsafe { archived_root::<Test>(&restored) };
+            assert_eq!(
+                archived
+                    .values
+                    .get("a key that is not inlined 7")
+                    .unwrap()
+                    .as_slice(),
+                &[7, 7, 7]
+            );
+            assert_eq!(archived.offsets.get(&42).unwrap(), &-42);
+            let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+            assert_eq!(deserialized, value);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_swiss_map() {
+        use rkyv::{compact::compact, with::AsSwissMap};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test(
+            #[with(AsSwissMap)] HashMap<u32, String>,
+            #[with(AsSwissMap)] HashMap<u32, String>,
+        );
+
+        let mut value = Test(HashMap::new(), HashMap::new());
+        for i in 0..100u32 {
+            value
+                .0
+                .insert(i, format!("a value that is not inlined {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value.0).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_sorted_vec() {
+        use rkyv::{
+            transcode::{transcode_root, Endianness},
+            with::AsSortedVec,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        struct Test(
+            #[with(AsSortedVec)] HashMap<String, u32>,
+            #[with(AsSortedVec)] HashSet<String>,
+        );
+
+        let mut value = Test(HashMap::new(), HashSet::new());
+        for i in 0..100u32 {
+            value.0.insert(format!("key {}", i), i);
+            value.1.insert(format!("a key that is not inlined {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED) }.unwrap();
+        assert_ne!(exported.as_slice(), original.as_slice());
+        let imported =
+            unsafe { transcode_root::<Test>(&exported, Endianness::ARCHIVED.opposite()) }.unwrap();
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_sorted_vec() {
+        use rkyv::{compact::compact, with::AsSortedVec};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test(
+            #[with(AsSortedVec)] HashMap<String, String>,
+            #[with(AsSortedVec)] HashSet<String>,
+        );
+
+        let mut value = Test(HashMap::new(), HashSet::new());
+        for i in 0..100u32 {
+            value
+                .0
+                .insert(i.to_string(), format!("a value that is not inlined {}", i));
+            value.1.insert(format!("a key that is not inlined {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value.0).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_trie() {
+        use rkyv::{
+            transcode::{transcode_root, Endianness},
+            with::AsTrie,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        struct Test(#[with(AsTrie)] HashMap<String, u32>);
+
+        let mut value = Test(HashMap::new());
+        for i in 0..100u32 {
+            value.0.insert(format!("key {}", i), i);
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED) }.unwrap();
+        assert_ne!(exported.as_slice(), original.as_slice());
+        let imported =
+            unsafe { transcode_root::<Test>(&exported, Endianness::ARCHIVED.opposite()) }.unwrap();
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_trie() {
+        use rkyv::{compact::compact, with::AsTrie};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test(#[with(AsTrie)] HashMap<String, String>);
+
+        let mut value = Test(HashMap::new());
+        for i in 0..100u32 {
+            value.0.insert(
+                format!("key {}", i),
+                format!("a value that is not inlined {}", i),
+            );
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value.0).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "transcode")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn transcode_packed() {
+        use rkyv::{
+            transcode::{transcode_root, Endianness},
+            with::{BitPacked, DeltaPacked},
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transcode)]
+        struct Test {
+            #[with(BitPacked)]
+            bits: Vec<u32>,
+            #[with(DeltaPacked)]
+            deltas: Vec<u64>,
+        }
+
+        let value = Test {
+            bits: (0..500).map(|i| i * 12345 % 100_000).collect(),
+            deltas: (0..500).map(|i| (1 << 40) + i * 17).collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let exported = unsafe { transcode_root::<Test>(&original, Endianness::ARCHIVED) }.unwrap();
+        assert_ne!(exported.as_slice(), original.as_slice());
+        let imported =
+            unsafe { transcode_root::<Test>(&exported, Endianness::ARCHIVED.opposite()) }.unwrap();
+        assert_eq!(imported.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&imported) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_packed() {
+        use rkyv::{
+            compact::compact,
+            with::{BitPacked, DeltaPacked},
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test {
+            #[with(BitPacked)]
+            bits: Vec<u32>,
+            #[with(DeltaPacked)]
+            deltas: Vec<u64>,
+        }
+
+        let value = Test {
+            bits: (0..500).map(|i| i * 12345 % 100_000).collect(),
+            deltas: (0..500).map(|i| (1 << 40) + i * 17).collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value.bits).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_interned() {
+        use rkyv::{
+            compact::compact,
+            with::{Interned, Map},
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact)]
+        struct Test {
+            #[with(Interned)]
+            name: String,
+            #[with(Map<Interned>)]
+            labels: Vec<String>,
+        }
+
+        let value = Test {
+            name: "a string that is long enough to not be inlined".to_string(),
+            labels: vec![
+                "a string that is long enough to not be inlined".to_string(),
+                "another string".to_string(),
+                "another string".to_string(),
+            ],
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let original = serializer.into_serializer().into_inner();
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value.labels).unwrap();
+        serializer.serialize_value(&value).unwrap();
+        let garbage = serializer.into_serializer().into_inner();
+
+        let compacted = unsafe { compact::<Test>(&garbage) };
+        assert_eq!(compacted.as_slice(), original.as_slice());
+
+        let archived = unsafe { archived_root::<Test>(&compacted) };
+        assert_eq!(archived.name.as_ptr(), archived.labels[0].as_ptr());
+        assert_eq!(archived.labels[1].as_ptr(), archived.labels[2].as_ptr());
+        let deserialized: Test = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_update_append() {
+        use rkyv::{
+            compact::ArchiveUpdater,
+            ser::serializers::{AllocScratch, CompositeSerializer},
+            AlignedVec,
+        };
+
+        let mut value = HashMap::new();
+        for i in 0..100u32 {
+            value.insert(i, format!("a value that is not inlined {}", i));
+        }
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+
+        // Append the update to a copy of the archive like it was a file
+        let mut file = bytes.to_vec();
+        let archived = unsafe { archived_root::<HashMap<u32, String>>(&bytes) };
+        let serializer = CompositeSerializer::new(
+            WriteSerializer::with_pos(&mut file, bytes.len()),
+            AllocScratch::default(),
+            rkyv::Infallible,
+        );
+        let mut updater = ArchiveUpdater::new(&bytes, serializer);
+        let (key, target) = archived.iter().next().unwrap();
+        let key: u32 = key.deserialize(&mut rkyv::Infallible).unwrap();
+        updater
+            .update::<_, String>(archived, target, &"an updated value".to_string())
+            .unwrap();
+        drop(updater);
+
+        let mut updated = AlignedVec::new();
+        updated.extend_from_slice(&file);
+        let archived = unsafe { archived_root::<HashMap<u32, String>>(&updated) };
+        let deserialized: HashMap<u32, String> =
+            archived.deserialize(&mut rkyv::Infallible).unwrap();
+        value.insert(key, "an updated value".to_string());
+        assert_eq!(deserialized, value);
+    }
+
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        use std::{
+            pin::pin,
+            sync::Arc,
+            task::{Context, Poll, Wake},
+        };
+
+        struct NoopWaker;
+
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "futures-io")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn async_write_serializer_futures() {
+        use core::{
+            pin::Pin,
+            task::{Context, Poll},
+        };
+        use rkyv::{
+            ser::serializers::{AsyncAllocSerializer, AsyncWriteSerializer, FuturesIo},
+            AlignedVec, Archived,
+        };
+        use std::io;
+
+        // Alternates between pending and writing at most 100 bytes at a time
+        #[derive(Default)]
+        struct SlowWriter {
+            bytes: Vec<u8>,
+            max_write: usize,
+            ready: bool,
+        }
+
+        impl futures_io::AsyncWrite for SlowWriter {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.max_write = usize::max(self.max_write, buf.len());
+                self.ready = !self.ready;
+                if self.ready {
+                    let len = usize::min(buf.len(), 100);
+                    self.bytes.extend_from_slice(&buf[..len]);
+                    Poll::Ready(Ok(len))
+                } else {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let values = (0..200)
+            .map(|i| format!("{:048}", i))
+            .collect::<Vec<String>>();
+
+        let mut serializer = AsyncAllocSerializer::<_, 256>::new(
+            AsyncWriteSerializer::with_chunk_size(FuturesIo(SlowWriter::default()), 256),
+            Default::default(),
+            Default::default(),
+        );
+        let root_pos = block_on(serializer.serialize_seq_root(&values)).unwrap();
+        assert!(serializer.async_serializer().buffered().is_empty());
+        let writer = serializer.into_serializer().into_inner().0;
+
+        // Only about one chunk and one element should have been buffered at a time
+        assert!(writer.max_write < 256 + 64);
+        assert_eq!(
+            root_pos + core::mem::size_of::<Archived<Vec<String>>>(),
+            writer.bytes.len()
+        );
+
+        let mut bytes = AlignedVec::new();
+        bytes.extend_from_slice(&writer.bytes);
+        let archived = unsafe { archived_root::<Vec<String>>(&bytes) };
+        assert_eq!(archived.len(), values.len());
+        for (archived, value) in archived.iter().zip(values.iter()) {
+            assert_eq!(archived, value);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn async_write_serializer_tokio() {
+        use rkyv::ser::serializers::{AsyncAllocSerializer, AsyncWriteSerializer, TokioIo};
+
+        #[derive(Archive, Serialize)]
+        struct Example {
+            name: String,
+            values: Vec<u32>,
+        }
+
+        let value = Example {
+            name: "async".to_string(),
+            values: (0..100).collect(),
+        };
+
+        let mut serializer = AsyncAllocSerializer::<_, 256>::new(
+            AsyncWriteSerializer::with_chunk_size(TokioIo(Vec::new()), 16),
+            Default::default(),
+            Default::default(),
+        );
+        block_on(serializer.serialize_root_buffered(&value)).unwrap();
+        let bytes = serializer.into_serializer().into_inner().0;
+
+        // Writing out the archive must produce exactly the same bytes as serializing it in memory
+        let expected = rkyv::to_bytes::<_, 256>(&value).unwrap();
+        assert_eq!(bytes, expected.as_slice());
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "serde",
+        any(
+            feature = "unaligned",
+            not(any(feature = "archive_le", feature = "archive_be"))
+        )
+    ))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serde_serialize_archived() {
+        use std::{
+            collections::BTreeMap,
+            net::{Ipv4Addr, SocketAddr},
+            time::Duration,
+        };
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        struct Wrapper<T> {
+            value: T,
+        }
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        enum Shape {
+            Empty,
+            Circle(u32),
+            Rect { width: u32, height: u32 },
+        }
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        struct Example {
+            name: String,
+            values: Vec<u32>,
+            label: Option<Box<str>>,
+            missing: Option<u64>,
+            counts: BTreeMap<String, u32>,
+            elapsed: Duration,
+            addr: Ipv4Addr,
+            socket: SocketAddr,
+            shapes: Vec<Shape>,
+            wrapped: Wrapper<String>,
+        }
+
+        let value = Example {
+            name: "example".to_string(),
+            values: vec![1, 2, 3],
+            label: Some("label".into()),
+            missing: None,
+            counts: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+            elapsed: Duration::new(5, 250),
+            addr: Ipv4Addr::new(127, 0, 0, 1),
+            socket: "[::1]:8080".parse().unwrap(),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(4),
+                Shape::Rect {
+                    width: 2,
+                    height: 3,
+                },
+            ],
+            wrapped: Wrapper {
+                value: "wrapped".to_string(),
+            },
+        };
+
+        let bytes = rkyv::to_bytes::<_, 256>(&value).unwrap();
+        let archived = unsafe { archived_root::<Example>(&bytes) };
+
+        // Archived values must serialize exactly like the values they were archived from
+        assert_eq!(
+            serde_json::to_string(archived).unwrap(),
+            serde_json::to_string(&value).unwrap(),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serde_serialize_archived_primitives() {
+        use core::num::NonZeroU32;
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        enum Reading {
+            Missing,
+            Scalar(f64),
+            Pair { low: i16, high: i16 },
+        }
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        struct Sample(u64, char);
+
+        #[derive(Archive, Serialize, rkyv::serde::Serialize)]
+        #[archive(serde)]
+        #[serde(crate = "rkyv::serde")]
+        struct Example {
+            id: u32,
+            len: usize,
+            offset: i8,
+            ratio: f32,
+            initial: char,
+            nonzero: NonZeroU32,
+            values: Vec<u32>,
+            missing: Option<u64>,
+            present: Option<i128>,
+            boxed: Box<[i16]>,
+            pair: [f64; 2],
+            readings: Vec<Reading>,
+            sample: Sample,
+            name: String,
+        }
+
+        let value = Example {
+            id: 0x12345678,
+            len: 42,
+            offset: -4,
+            ratio: 0.5,
+            initial: 'f',
+            nonzero: NonZeroU32::new(7).unwrap(),
+            values: vec![1, 2, 0x01020304],
+            missing: None,
+            present: Some(-1),
+            boxed: vec![-2, 300].into_boxed_slice(),
+            pair: [1.5, -2.25],
+            readings: vec![
+                Reading::Missing,
+                Reading::Scalar(3.5),
+                Reading::Pair { low: -1, high: 1 },
+            ],
+            sample: Sample(u64::MAX, 'x'),
+            name: "example".to_string(),
+        };
+
+        let bytes = rkyv::to_bytes::<_, 256>(&value).unwrap();
+        let archived = unsafe { archived_root::<Example>(&bytes) };
+
+        // Archived primitives must serialize as their native values with any endianness
+        assert_eq!(
+            serde_json::to_string(archived).unwrap(),
+            serde_json::to_string(&value).unwrap(),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serde_to_archived_value() {
+        use rkyv::value::{Value, ValueSerializeError};
+        use std::collections::BTreeMap;
+
+        #[derive(rkyv::serde::Serialize)]
+        #[serde(crate = "rkyv::serde")]
+        enum Shape {
+            Empty,
+            Circle(u32),
+            Point(i32, i32),
+            Rect { width: u32, height: u32 },
+        }
+
+        #[derive(rkyv::serde::Serialize)]
+        #[serde(crate = "rkyv::serde")]
+        struct Document {
+            name: String,
+            id: u64,
+            offset: i8,
+            ratio: f32,
+            initial: char,
+            tags: Vec<&'static str>,
+            missing: Option<u32>,
+            counts: HashMap<u32, bool>,
+            shapes: Vec<Shape>,
+            unit: (),
+        }
+
+        let document = Document {
+            name: "ferris".to_string(),
+            id: u64::MAX,
+            offset: -4,
+            ratio: 0.5,
+            initial: 'f',
+            tags: vec!["crab", "a much longer tag that is not stored inline"],
+            missing: None,
+            counts: HashMap::from([(1, true), (2, false)]),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(3),
+                Shape::Point(-1, 2),
+                Shape::Rect {
+                    width: 4,
+                    height: 5,
+                },
+            ],
+            unit: (),
+        };
+
+        let bytes = rkyv::value::to_bytes::<_, 256>(&document).unwrap();
+        let archived = unsafe { archived_root::<Value>(&bytes) };
+
+        assert_eq!(archived["name"].as_str(), Some("ferris"));
+        assert_eq!(archived["id"].as_u64(), Some(u64::MAX));
+        assert_eq!(archived["offset"].as_i64(), Some(-4));
+        assert_eq!(archived["ratio"].as_f64(), Some(0.5));
+        assert_eq!(archived["initial"].as_str(), Some("f"));
+        assert_eq!(
+            archived["tags"][1].as_str(),
+            Some("a much longer tag that is not stored inline")
+        );
+        assert!(archived["missing"].is_null());
+        assert_eq!(archived["counts"]["2"].as_bool(), Some(false));
+        assert_eq!(archived["shapes"][0].as_str(), Some("Empty"));
+        assert_eq!(archived["shapes"][1]["Circle"].as_u64(), Some(3));
+        assert_eq!(archived["shapes"][2]["Point"][0].as_i64(), Some(-1));
+        assert_eq!(archived["shapes"][3]["Rect"]["height"].as_u64(), Some(5));
+        assert!(archived["unit"].is_null());
+        assert_eq!(
+            serde_json::to_value(archived).unwrap(),
+            serde_json::to_value(&document).unwrap(),
+        );
+
+        // Later entries replace earlier entries with the same key
+        struct Duplicates;
+
+        impl rkyv::serde::Serialize for Duplicates {
+            fn serialize<S: rkyv::serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.collect_map([("b", 1), ("a", 2), ("b", 3)])
+            }
+        }
+
+        let bytes = rkyv::value::to_bytes::<_, 256>(&Duplicates).unwrap();
+        let archived = unsafe { archived_root::<Value>(&bytes) };
+        assert_eq!(archived.as_object().map(|o| o.len()), Some(2));
+        assert_eq!(archived["a"].as_u64(), Some(2));
+        assert_eq!(archived["b"].as_u64(), Some(3));
+
+        let mut unsupported = BTreeMap::new();
+        unsupported.insert(vec![1u8], 1u32);
+        assert!(matches!(
+            rkyv::value::to_bytes::<_, 256>(&unsupported),
+            Err(ValueSerializeError::KeyMustBeString)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serde_archive_value_directly() {
+        use rkyv::{
+            serde::ser::SerializeSeq,
+            value::{to_value, Value},
+        };
+
+        // Reports a length that may not match the number of elements serialized
+        struct Elements {
+            len: Option<usize>,
+            elements: Vec<&'static str>,
+        }
+
+        impl rkyv::serde::Serialize for Elements {
+            fn serialize<S: rkyv::serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                let mut seq = s.serialize_seq(self.len)?;
+                for element in self.elements.iter() {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+        }
+
+        struct Bytes(&'static [u8]);
+
+        impl rkyv::serde::Serialize for Bytes {
+            fn serialize<S: rkyv::serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(self.0)
+            }
+        }
+
+        let strings = vec![
+            "short",
+            "a string that is much too long to be stored inline",
+            "",
+        ];
+        let mut document = HashMap::new();
+        document.insert(
+            "exact",
+            vec![Elements {
+                len: Some(3),
+                elements: strings.clone(),
+            }],
+        );
+        document.insert(
+            "unknown",
+            vec![Elements {
+                len: None,
+                elements: strings.clone(),
+            }],
+        );
+        // Arrays outgrow their scratch space when they have more elements than they reported
+        document.insert(
+            "underreported",
+            vec![
+                Elements {
+                    len: Some(1),
+                    elements: strings.clone(),
+                },
+                Elements {
+                    len: Some(0),
+                    elements: strings.clone(),
+                },
+            ],
+        );
+        document.insert(
+            "overreported",
+            vec![Elements {
+                len: Some(8),
+                elements: strings.clone(),
+            }],
+        );
+
+        let bytes = rkyv::value::to_bytes::<_, 256>(&document).unwrap();
+        let archived = unsafe { archived_root::<Value>(&bytes) };
+
+        assert_eq!(*archived, to_value(&document).unwrap());
+        for key in ["exact", "unknown", "overreported"] {
+            assert_eq!(archived[key][0].as_array().map(|a| a.len()), Some(3));
+        }
+        assert_eq!(archived["underreported"][1][1].as_str(), Some(strings[1]));
+
+        let bytes = rkyv::value::to_bytes::<_, 256>(&Bytes(&[0, 1, 255])).unwrap();
+        let archived = unsafe { archived_root::<Value>(&bytes) };
+        assert_eq!(*archived, to_value(&Bytes(&[0, 1, 255])).unwrap());
+        assert_eq!(archived[2].as_u64(), Some(255));
+    }
 }
@@ -84,4 +84,71 @@ pub mod alloc {
     pub type DefaultDeserializer = rkyv::de::deserializers::SharedDeserializeMap;
 
     impl_test_archive!(DefaultSerializer, DefaultDeserializer);
+
+    /// The error type of [`SortedSerializer`].
+    #[cfg(feature = "bitvec")]
+    #[derive(Debug)]
+    pub enum SortedSerializerError {
+        Serializer(<DefaultSerializer as rkyv::Fallible>::Error),
+        Unsorted(rkyv::with::UnsortedError),
+    }
+
+    #[cfg(feature = "bitvec")]
+    impl From<rkyv::with::UnsortedError> for SortedSerializerError {
+        fn from(e: rkyv::with::UnsortedError) -> Self {
+            SortedSerializerError::Unsorted(e)
+        }
+    }
+
+    /// A default serializer that can report unsorted values, which vectors serialized with
+    /// `EliasFano` require.
+    #[cfg(feature = "bitvec")]
+    #[derive(Default)]
+    pub struct SortedSerializer(pub DefaultSerializer);
+
+    #[cfg(feature = "bitvec")]
+    const _: () = {
+        use core::{alloc::Layout, ptr::NonNull};
+        use rkyv::{
+            ser::{ScratchSpace, Serializer},
+            Fallible,
+        };
+
+        impl Fallible for SortedSerializer {
+            type Error = SortedSerializerError;
+        }
+
+        impl Serializer for SortedSerializer {
+            fn pos(&self) -> usize {
+                self.0.pos()
+            }
+
+            fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0
+                    .write(bytes)
+                    .map_err(SortedSerializerError::Serializer)
+            }
+        }
+
+        impl ScratchSpace for SortedSerializer {
+            unsafe fn push_scratch(
+                &mut self,
+                layout: Layout,
+            ) -> Result<NonNull<[u8]>, Self::Error> {
+                self.0
+                    .push_scratch(layout)
+                    .map_err(SortedSerializerError::Serializer)
+            }
+
+            unsafe fn pop_scratch(
+                &mut self,
+                ptr: NonNull<u8>,
+                layout: Layout,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .pop_scratch(ptr, layout)
+                    .map_err(SortedSerializerError::Serializer)
+            }
+        }
+    };
 }
@@ -24,6 +24,7 @@ mod tests {
     use wasm_bindgen_test::*;
 
     #[test]
+    #[cfg(not(feature = "unaligned"))]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn basic_functionality() {
         // Regular archiving
@@ -525,6 +526,103 @@ mod tests {
         assert_eq!(buf.as_ref(), &ARCHIVED_BYTES);
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_columnar() {
+        use rkyv::with::Columnar;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(columnar, check_bytes)]
+        struct Record {
+            id: u32,
+            name: String,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Table {
+            #[with(Columnar)]
+            records: Vec<Record>,
+        }
+
+        let value = Table {
+            records: (0..10)
+                .map(|i| Record {
+                    id: i,
+                    name: i.to_string(),
+                })
+                .collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let mut buf = serializer.into_serializer().into_inner();
+
+        let archived = check_archived_root::<Table>(buf.as_ref()).unwrap();
+        assert_eq!(archived.records.len(), 10);
+        assert_eq!(archived.records.get(4).unwrap().name, "4");
+
+        // Shorten one of the columns by a row
+        type Len = rkyv::Archived<rkyv::FixedUsize>;
+        let root = buf.len() - core::mem::size_of::<rkyv::Archived<Table>>();
+        let rows: Len = rkyv::to_archived!(10 as rkyv::FixedUsize);
+        let len_pos = (root..buf.len())
+            .step_by(core::mem::size_of::<Len>())
+            .find(|&pos| unsafe {
+                core::ptr::read_unaligned(buf[pos..].as_ptr().cast::<Len>()) == rows
+            })
+            .unwrap();
+        let mut shortened_buf = buf.clone();
+        unsafe {
+            core::ptr::write_unaligned(
+                shortened_buf[len_pos..].as_mut_ptr().cast::<Len>(),
+                rkyv::to_archived!(9 as rkyv::FixedUsize),
+            );
+        }
+        match check_archived_root::<Table>(shortened_buf.as_ref()) {
+            Err(error) => assert!(error.to_string().contains("column length mismatch")),
+            Ok(_) => panic!("columns with different lengths passed validation"),
+        }
+
+        // Point the id column far past the end of the buffer
+        let column_size = core::mem::size_of::<rkyv::vec::ArchivedVec<rkyv::Archived<u32>>>();
+        buf[root..root + column_size].fill(0x7f);
+        assert!(check_archived_root::<Table>(buf.as_ref()).is_err());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn check_wide() {
+        use rkyv::with::Wide;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(Wide)]
+            strings: Vec<String>,
+        }
+
+        let value = Test {
+            strings: vec![
+                "hello".to_string(),
+                "a string that is too long to inline".to_string(),
+            ],
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let mut buf = serializer.into_serializer().into_inner();
+
+        let archived = check_archived_root::<Test>(buf.as_ref()).unwrap();
+        assert_eq!(archived.strings.len(), 2);
+        assert_eq!(archived.strings[1], value.strings[1]);
+
+        // Make the wide pointer and length point far past the end of the buffer
+        let root = buf.len() - core::mem::size_of::<rkyv::Archived<Test>>();
+        buf[root..].fill(0x7f);
+        assert!(check_archived_root::<Test>(buf.as_ref()).is_err());
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn check_valid_durations() {
@@ -559,4 +657,757 @@ mod tests {
             ))
         ));
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_archive_align() {
+        use rkyv::{
+            ser::serializers::AlignedSerializer,
+            validation::{
+                check_archived_root_with_context,
+                validators::{ArchiveError, DefaultValidator, DefaultValidatorError},
+                CheckArchiveError,
+            },
+            AlignedVec,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes, align = 64)]
+        #[archive_attr(derive(Debug))]
+        struct Aligned {
+            value: u32,
+        }
+
+        let mut serializer = AlignedSerializer::new(AlignedVec::<64>::new_aligned());
+        serializer.serialize_value(&Aligned { value: 42 }).unwrap();
+        let bytes = serializer.into_inner();
+
+        let archived = check_archived_root::<Aligned>(&bytes).unwrap();
+        assert_eq!(archived.value, 42);
+
+        // A buffer that is only 32-aligned is rejected
+        let mut shifted = AlignedVec::<64>::new_aligned();
+        shifted.extend_from_slice(&[0; 32]);
+        shifted.extend_from_slice(&bytes);
+        match check_archived_root::<Aligned>(&shifted[32..]) {
+            Err(CheckArchiveError::ContextError(DefaultValidatorError::ArchiveError(
+                ArchiveError::Underaligned {
+                    expected_align: 64,
+                    actual_align: 32,
+                },
+            ))) => (),
+            other => panic!("expected underaligned error, got {:?}", other),
+        }
+
+        // Archives serialized with a lower alignment are rejected even if the bytes are aligned
+        let mut validator = DefaultValidator::with_alignment(&bytes, 16);
+        match check_archived_root_with_context::<Aligned, _>(&bytes, &mut validator) {
+            Err(CheckArchiveError::ContextError(DefaultValidatorError::ArchiveError(
+                ArchiveError::Underaligned {
+                    expected_align: 64,
+                    actual_align: 16,
+                },
+            ))) => (),
+            other => panic!("expected underaligned error, got {:?}", other),
+        }
+
+        let mut validator = DefaultValidator::with_alignment(&bytes, 64);
+        let archived =
+            check_archived_root_with_context::<Aligned, _>(&bytes, &mut validator).unwrap();
+        assert_eq!(archived.value, 42);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_included_archives() {
+        use rkyv::include::{to_included_bytes, IncludedArchive};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Flag {
+            value: u8,
+            flag: bool,
+        }
+
+        let bytes = to_included_bytes::<_, 0>(&Flag {
+            value: 42,
+            flag: true,
+        })
+        .unwrap();
+        let mut included = [0u8; 10];
+        included.copy_from_slice(&bytes);
+
+        let archive: &'static IncludedArchive<Flag, 10> =
+            Box::leak(Box::new(IncludedArchive::new(included)));
+        let archived = archive.get();
+        assert_eq!(archived.value, 42);
+        assert!(archived.flag);
+        // Once checked, the archive is not validated again
+        assert!(archive.check_bytes().is_ok());
+
+        // Invalid archives are rejected
+        included[..2].copy_from_slice(&[2, 2]);
+        let invalid: &'static IncludedArchive<Flag, 10> =
+            Box::leak(Box::new(IncludedArchive::new(included)));
+        assert!(invalid.check_fingerprint().is_ok());
+        assert!(invalid.check_bytes().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_included_archive_type_mismatch() {
+        use rkyv::{
+            include::{to_included_bytes, IncludedArchive},
+            FixedIsize,
+        };
+
+        // Both archived types are two isize-sized integers, so their layout fingerprints match
+        #[derive(Archive, Serialize)]
+        struct Numbers {
+            a: [FixedIsize; 2],
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Bytes {
+            a: Vec<u8>,
+        }
+
+        let bytes = to_included_bytes::<_, 0>(&Numbers {
+            a: [FixedIsize::MAX; 2],
+        })
+        .unwrap();
+        const LEN: usize = 2 * core::mem::size_of::<FixedIsize>() + 8;
+        let mut included = [0u8; LEN];
+        included.copy_from_slice(&bytes);
+
+        let archive: &'static IncludedArchive<Bytes, LEN> =
+            Box::leak(Box::new(IncludedArchive::new(included)));
+        assert!(archive.check_fingerprint().is_ok());
+        assert!(archive.check_bytes().is_err());
+        assert!(std::panic::catch_unwind(|| archive.get()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "ipc")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn ipc_ring() {
+        use core::ptr::NonNull;
+        use rkyv::{
+            ipc::{Ring, RingError, SendError},
+            AlignedVec,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Event {
+            id: u32,
+            name: String,
+        }
+
+        let len = Ring::required_len(128, 4).unwrap();
+        let mut region = AlignedVec::<64>::with_capacity_aligned(len);
+        region.resize(len, 0);
+        let ptr = NonNull::new(region.as_mut_ptr()).unwrap();
+
+        // The region doesn't contain a ring yet
+        assert!(matches!(
+            unsafe { Ring::open(ptr, len) },
+            Err(RingError::NotARing { .. })
+        ));
+        assert!(matches!(
+            unsafe { Ring::create(ptr, len, 128, 3) },
+            Err(RingError::InvalidSlotCount { slot_count: 3 })
+        ));
+        assert!(matches!(
+            unsafe { Ring::create(ptr, len - 1, 128, 4) },
+            Err(RingError::RegionTooSmall { .. })
+        ));
+
+        let producer = unsafe { Ring::create(ptr, len, 128, 4).unwrap() };
+        let consumer = unsafe { Ring::open(ptr, len).unwrap() };
+        assert_eq!(consumer.slot_size(), 128);
+        assert_eq!(consumer.slot_count(), 4);
+        assert!(consumer.try_recv::<Event>().is_none());
+
+        for id in 0..4 {
+            let event = Event {
+                id,
+                name: "a string that is too long to be inlined".to_string(),
+            };
+            producer.try_send::<_, 0>(&event).unwrap();
+        }
+        let event = Event {
+            id: 4,
+            name: String::new(),
+        };
+        assert!(matches!(
+            producer.try_send::<_, 0>(&event),
+            Err(SendError::Full)
+        ));
+
+        // Messages are received in order and their slots are reclaimed when they are dropped
+        let first = consumer.try_recv::<Event>().unwrap().unwrap();
+        assert_eq!(first.id, 0);
+        let second = consumer.try_recv::<Event>().unwrap().unwrap();
+        assert_eq!(second.id, 1);
+        assert!(producer.try_reserve().is_none());
+        drop(first);
+        producer.try_send::<_, 0>(&event).unwrap();
+        drop(second);
+
+        // Messages that are too large for a slot fail to serialize and are skipped
+        let large = Event {
+            id: 5,
+            name: "x".repeat(256),
+        };
+        assert!(matches!(
+            producer.try_send::<_, 0>(&large),
+            Err(SendError::Serialize(_))
+        ));
+
+        for id in 2..5 {
+            let message = consumer.try_recv::<Event>().unwrap().unwrap();
+            assert_eq!(message.id, id);
+        }
+        assert!(consumer.try_recv::<Event>().is_none());
+
+        // Invalid messages are rejected and their slots are reclaimed
+        let mut writer = producer.try_reserve().unwrap();
+        writer.bytes_mut()[..4].copy_from_slice(&[0xff; 4]);
+        writer.commit(4);
+        assert!(consumer.try_recv::<char>().unwrap().is_err());
+        assert!(consumer.try_recv::<char>().is_none());
+        for _ in 0..4 {
+            producer.try_send::<_, 0>(&'a').unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "unaligned")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn unaligned_basic_functionality() {
+        use rkyv::validation::{
+            validators::{ArchiveError, DefaultValidatorError},
+            CheckArchiveError,
+        };
+
+        let value = Some("Hello world".to_string());
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+
+        check_archived_root::<Option<String>>(buf.as_ref()).unwrap();
+
+        // Out of bounds
+        match check_archived_value::<u32>(AlignedBytes([0, 1, 2, 3, 4]).as_ref(), 8) {
+            Err(CheckArchiveError::ContextError(DefaultValidatorError::ArchiveError(
+                ArchiveError::OutOfBounds { .. },
+            ))) => (),
+            other => panic!("expected out of bounds error, got {:?}", other),
+        }
+        // Overrun
+        match check_archived_value::<u32>(AlignedBytes([0, 1, 2, 3, 4]).as_ref(), 4) {
+            Err(CheckArchiveError::ContextError(DefaultValidatorError::ArchiveError(
+                ArchiveError::Overrun { .. },
+            ))) => (),
+            other => panic!("expected overrun error, got {:?}", other),
+        }
+        // Unaligned positions and underaligned buffers are allowed
+        check_archived_value::<u32>(AlignedBytes([0, 1, 2, 3, 4]).as_ref(), 1).unwrap();
+        check_archived_value::<u32>(&AlignedBytes([0, 1, 2, 3, 4])[1..], 0).unwrap();
+        // Undersized
+        match check_archived_root::<u32>(&AlignedBytes([]).as_ref()) {
+            Err(CheckArchiveError::ContextError(DefaultValidatorError::ArchiveError(
+                ArchiveError::OutOfBounds { .. },
+            ))) => (),
+            other => panic!("expected out of bounds error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "unaligned")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_unaligned_archives() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Test {
+            values: Vec<u64>,
+            name: String,
+            letter: char,
+        }
+
+        let value = Test {
+            values: vec![1, 2, 3, 4],
+            name: "a string that is too long to be inlined".to_string(),
+            letter: 'ü',
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+
+        for offset in 0..8 {
+            let mut shifted = vec![0u8; offset];
+            shifted.extend_from_slice(&bytes);
+
+            let archived = check_archived_root::<Test>(&shifted[offset..]).unwrap();
+            assert_eq!(archived.values.as_slice(), [1, 2, 3, 4]);
+            assert_eq!(archived.name, value.name);
+            assert_eq!(archived.letter, 'ü');
+        }
+
+        // Invalid primitives are still rejected
+        let mut invalid = bytes.to_vec();
+        let letter = invalid.len() - 4;
+        invalid[letter..].copy_from_slice(&[0xff; 4]);
+        assert!(check_archived_root::<Test>(&invalid).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_values() {
+        use rkyv::value::{ArchivedValue, Number, Value};
+
+        let mut object = BTreeMap::new();
+        object.insert("name".to_string(), Value::String("ferris".to_string()));
+        object.insert("age".to_string(), Value::Number(Number::PosInt(10)));
+        object.insert(
+            "tags".to_string(),
+            Value::Array(vec![Value::String("crab".to_string()), Value::Null]),
+        );
+        let value = Value::Object(object);
+        serialize_and_check(&value);
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let mut bytes = serializer.into_serializer().into_inner();
+        let archived = check_archived_root::<Value>(bytes.as_ref()).unwrap();
+        assert_eq!(archived["tags"][0].as_str(), Some("crab"));
+
+        // Corrupt the tag of the root value
+        let root = bytes.len() - core::mem::size_of::<ArchivedValue>();
+        bytes[root] = 6;
+        check_archived_root::<Value>(bytes.as_ref()).unwrap_err();
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn dynamic_schema() {
+        #[cfg(not(feature = "std"))]
+        use alloc::format;
+        use core::mem::MaybeUninit;
+        use rkyv::{
+            collections::btree_map::ArchivedBTreeMap,
+            dynamic::{
+                self,
+                schema::{EnumSchema, Field, MapSchema, Schema, StructSchema},
+                DynamicError,
+            },
+            string::ArchivedString,
+        };
+
+        #[derive(Archive, Serialize)]
+        enum Mode {
+            Off,
+            Level(i32),
+            Named { label: String },
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Config {
+            name: String,
+            port: u16,
+            ratio: f64,
+            enabled: bool,
+            initial: char,
+            peers: Vec<u32>,
+            backup: Option<Box<u32>>,
+            modes: Vec<Mode>,
+            offsets: Box<[i8]>,
+            labels: BTreeMap<String, String>,
+        }
+
+        let value = Config {
+            name: "a service with a long name".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            enabled: true,
+            initial: 'λ',
+            peers: vec![1, 2, 3],
+            backup: Some(Box::new(7)),
+            modes: vec![
+                Mode::Off,
+                Mode::Level(-4),
+                Mode::Named {
+                    label: "fast".to_string(),
+                },
+            ],
+            offsets: vec![-1, 2].into_boxed_slice(),
+            labels: [("region", "west"), ("tier", "gold")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+
+        let mode = Schema::Enum(EnumSchema::repr_c(
+            "Mode",
+            vec![
+                ("Off".to_string(), vec![]),
+                ("Level".to_string(), vec![("0".to_string(), Schema::I32)]),
+                (
+                    "Named".to_string(),
+                    vec![("label".to_string(), Schema::String)],
+                ),
+            ],
+        ));
+        let (key_offset, value_offset) =
+            ArchivedBTreeMap::<ArchivedString, ArchivedString>::entry_offsets();
+        let mut config = MaybeUninit::<ArchivedConfig>::uninit();
+        let out = config.as_mut_ptr();
+        let schema = Schema::Struct(StructSchema::new(
+            "Config",
+            vec![
+                Field::new("name", rkyv::out_field!(out.name).0, Schema::String),
+                Field::new("port", rkyv::out_field!(out.port).0, Schema::U16),
+                Field::new("ratio", rkyv::out_field!(out.ratio).0, Schema::F64),
+                Field::new("enabled", rkyv::out_field!(out.enabled).0, Schema::Bool),
+                Field::new("initial", rkyv::out_field!(out.initial).0, Schema::Char),
+                Field::new(
+                    "peers",
+                    rkyv::out_field!(out.peers).0,
+                    Schema::vec(Schema::U32),
+                ),
+                Field::new(
+                    "backup",
+                    rkyv::out_field!(out.backup).0,
+                    Schema::option(Schema::boxed(Schema::U32)),
+                ),
+                Field::new("modes", rkyv::out_field!(out.modes).0, Schema::vec(mode)),
+                Field::new(
+                    "offsets",
+                    rkyv::out_field!(out.offsets).0,
+                    Schema::boxed_slice(Schema::I8),
+                ),
+                Field::new(
+                    "labels",
+                    rkyv::out_field!(out.labels).0,
+                    Schema::btree_map(MapSchema::new(
+                        Schema::String,
+                        key_offset,
+                        Schema::String,
+                        value_offset,
+                    )),
+                ),
+            ],
+        ));
+        assert_eq!(
+            schema.layout(),
+            core::alloc::Layout::new::<ArchivedConfig>()
+        );
+
+        let root = dynamic::root(bytes.as_ref(), &schema).unwrap();
+        let field = |name| root.get_field(name).unwrap();
+        assert_eq!(
+            field("name").as_str().unwrap(),
+            "a service with a long name"
+        );
+        assert_eq!(field("port").as_u64().unwrap(), 8080);
+        assert_eq!(field("port").as_i64().unwrap(), 8080);
+        assert_eq!(field("ratio").as_f64().unwrap(), 0.5);
+        assert!(field("enabled").as_bool().unwrap());
+        assert_eq!(field("initial").as_char().unwrap(), 'λ');
+        assert_eq!(field("peers").len().unwrap(), 3);
+        assert_eq!(field("peers").index(2).unwrap().as_u64().unwrap(), 3);
+        let backup = field("backup").as_option().unwrap().unwrap();
+        assert_eq!(backup.unbox().unwrap().as_u64().unwrap(), 7);
+        let modes = field("modes");
+        assert_eq!(modes.index(0).unwrap().variant().unwrap(), "Off");
+        let level = modes.index(1).unwrap();
+        assert_eq!(level.variant().unwrap(), "Level");
+        assert_eq!(level.get_field("0").unwrap().as_i64().unwrap(), -4);
+        let named = modes.index(2).unwrap().get_field("label").unwrap();
+        assert_eq!(named.as_str().unwrap(), "fast");
+        assert_eq!(field("offsets").index(0).unwrap().as_i64().unwrap(), -1);
+        let labels = field("labels");
+        assert_eq!(labels.len().unwrap(), 2);
+        assert_eq!(labels.entries().unwrap()[0].0.as_str().unwrap(), "region");
+        let tier = labels.get_key("tier").unwrap().unwrap();
+        assert_eq!(tier.as_str().unwrap(), "gold");
+        assert!(labels.get_key("zone").unwrap().is_none());
+
+        assert_eq!(
+            root.to_string(),
+            "Config { name: \"a service with a long name\", port: 8080, ratio: 0.5, \
+             enabled: true, initial: 'λ', peers: [1, 2, 3], backup: Some(7), \
+             modes: [Off, Level { 0: -4 }, Named { label: \"fast\" }], offsets: [-1, 2], \
+             labels: {\"region\": \"west\", \"tier\": \"gold\"} }"
+        );
+        assert_eq!(
+            format!("{:#}", field("modes")),
+            "[\n    Off,\n    Level {\n        0: -4,\n    },\n    Named {\n        \
+             label: \"fast\",\n    },\n]"
+        );
+
+        assert!(matches!(
+            root.get_field("host"),
+            Err(DynamicError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            field("peers").index(3),
+            Err(DynamicError::IndexOutOfBounds { index: 3, len: 3 })
+        ));
+        assert!(matches!(
+            field("port").as_str(),
+            Err(DynamicError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            field("ratio").as_u64(),
+            Err(DynamicError::TypeMismatch { .. })
+        ));
+
+        // Reading the same archive without its out-of-line data fails the bounds checks
+        let root_pos = bytes.len() - schema.layout().size();
+        let truncated = &bytes.as_ref()[root_pos..];
+        let root = dynamic::value_at(truncated, 0, &schema).unwrap();
+        assert!(matches!(
+            root.get_field("peers").unwrap().index(0),
+            Err(DynamicError::Archive(_))
+        ));
+        assert!(root.to_string().contains("<invalid: "));
+        assert!(dynamic::root(&truncated[1..], &schema).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn extract() {
+        use rkyv::{compact::extract, de::deserializers::SharedDeserializeMap};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Item {
+            name: String,
+            tags: Vec<u32>,
+            shared: Rc<String>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Test {
+            items: Vec<Item>,
+            map: BTreeMap<u32, Item>,
+        }
+
+        let shared = Rc::new("a shared string that is not inlined".to_string());
+        let item = |i: u32| Item {
+            name: "an item name that is not inlined".to_string() + &i.to_string(),
+            tags: (0..i).collect(),
+            shared: shared.clone(),
+        };
+        let value = Test {
+            items: (0..10).map(item).collect(),
+            map: (10..20).map(|i| (i, item(i))).collect(),
+        };
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let bytes = serializer.into_serializer().into_inner();
+        let archived = check_archived_root::<Test>(&bytes).unwrap();
+
+        // Values are copied in the order they are checked, which is the order they are serialized
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&item(3)).unwrap();
+        let expected = serializer.into_serializer().into_inner();
+        let extracted = extract::<Item>(&bytes, &archived.items[3]).unwrap();
+        assert_eq!(extracted.as_slice(), expected.as_slice());
+
+        // The dependencies of the value must be located inside of the given archive
+        let items_pos = archived.items.as_ptr() as usize - bytes.as_ptr() as usize;
+        assert!(extract::<Item>(&bytes[items_pos..], &archived.items[3]).is_err());
+        let archived_item = check_archived_root::<Item>(&extracted).unwrap();
+        let deserialized: Item = archived_item
+            .deserialize(&mut SharedDeserializeMap::new())
+            .unwrap();
+        assert_eq!(deserialized, item(3));
+
+        let extracted = extract::<Item>(&bytes, archived.map.values().nth(5).unwrap()).unwrap();
+        let archived_item = check_archived_root::<Item>(&extracted).unwrap();
+        let deserialized: Item = archived_item
+            .deserialize(&mut SharedDeserializeMap::new())
+            .unwrap();
+        assert_eq!(deserialized, item(15));
+
+        let extracted = extract::<Vec<Item>>(&bytes, &archived.items).unwrap();
+        let archived_items = check_archived_root::<Vec<Item>>(&extracted).unwrap();
+        let deserialized: Vec<Item> = archived_items
+            .deserialize(&mut SharedDeserializeMap::new())
+            .unwrap();
+        assert_eq!(deserialized, value.items);
+
+        let extracted = extract::<BTreeMap<u32, Item>>(&bytes, &archived.map).unwrap();
+        let archived_map = check_archived_root::<BTreeMap<u32, Item>>(&extracted).unwrap();
+        let deserialized: BTreeMap<u32, Item> = archived_map
+            .deserialize(&mut SharedDeserializeMap::new())
+            .unwrap();
+        assert_eq!(deserialized, value.map);
+    }
+
+    #[cfg(feature = "bitvec")]
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn elias_fano() {
+        use rkyv::{packed::ArchivedEliasFano, with::EliasFano, AlignedVec};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(EliasFano)]
+            values: Vec<u64>,
+            #[with(EliasFano)]
+            set: BTreeSet<u64>,
+        }
+
+        fn check_consistent(elias_fano: &ArchivedEliasFano) {
+            let values = elias_fano.iter().collect::<Vec<_>>();
+            assert_eq!(values.len(), elias_fano.len());
+            assert!(values.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(elias_fano.first(), values.first().copied());
+            assert_eq!(elias_fano.last(), values.last().copied());
+            for (i, &x) in values.iter().enumerate() {
+                assert_eq!(elias_fano.get(i), Some(x));
+                assert_eq!(elias_fano.successor(x), Some(x));
+                assert_eq!(elias_fano.predecessor(x), Some(x));
+            }
+            assert_eq!(elias_fano.get(values.len()), None);
+        }
+
+        fn check(values: Vec<u64>) {
+            let value = Test {
+                set: values.iter().copied().collect(),
+                values,
+            };
+
+            let mut serializer = SortedSerializer::default();
+            serializer.serialize_value(&value).unwrap();
+            let buffer = serializer.0.into_serializer().into_inner();
+            check_archived_root::<Test>(&buffer).unwrap();
+
+            // Every corrupted byte must either be rejected or still decode to a sorted sequence
+            let mut corrupted: AlignedVec = AlignedVec::new();
+            for offset in 0..buffer.len() {
+                for flip in [0x01, 0x80] {
+                    corrupted.clear();
+                    corrupted.extend_from_slice(&buffer);
+                    corrupted[offset] ^= flip;
+                    if let Ok(archived) = check_archived_root::<Test>(&corrupted) {
+                        check_consistent(&archived.values);
+                        check_consistent(&archived.set);
+                    }
+                }
+            }
+        }
+
+        check(Vec::new());
+        check(vec![0, u64::MAX]);
+        check(vec![5, 5, 5, 9, 9, 100]);
+        check((0..200).map(|i| i * i * 31 + 7).collect());
+        check((0..300).map(|i| i / 3).collect());
+    }
+
+    #[cfg(feature = "bitvec")]
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn rank_select() {
+        use bitvec::prelude::*;
+        use rkyv::{bitvec::ArchivedRankSelect, with::RankSelect, AlignedVec};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(RankSelect)]
+            lsb: BitVec<u8>,
+            #[with(RankSelect)]
+            msb: BitVec<u8, Msb0>,
+        }
+
+        fn check_consistent<O: BitOrder>(rank_select: &ArchivedRankSelect<u8, O>) {
+            let bits = rank_select.as_bitslice();
+            assert_eq!(rank_select.count_ones(), bits.count_ones());
+            assert_eq!(rank_select.count_zeros(), bits.count_zeros());
+            let (mut ones, mut zeros) = (0, 0);
+            for (index, bit) in bits.iter().by_vals().enumerate() {
+                assert_eq!(rank_select.rank1(index), ones);
+                if bit {
+                    if ones % 7 == 0 {
+                        assert_eq!(rank_select.select1(ones), Some(index));
+                    }
+                    ones += 1;
+                } else {
+                    if zeros % 7 == 0 {
+                        assert_eq!(rank_select.select0(zeros), Some(index));
+                    }
+                    zeros += 1;
+                }
+            }
+            assert_eq!(rank_select.rank1(bits.len()), ones);
+            assert_eq!(rank_select.select1(ones), None);
+            assert_eq!(rank_select.select0(zeros), None);
+        }
+
+        fn check(bits: BitVec) {
+            let value = Test {
+                lsb: bits.iter().by_vals().collect(),
+                msb: bits.iter().by_vals().collect(),
+            };
+
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&value).unwrap();
+            let buffer = serializer.into_serializer().into_inner();
+            check_archived_root::<Test>(&buffer).unwrap();
+
+            // Every corrupted byte must be rejected or leave the index consistent with the bits
+            let mut corrupted: AlignedVec = AlignedVec::new();
+            for offset in 0..buffer.len() {
+                for flip in [0x01, 0x80] {
+                    corrupted.clear();
+                    corrupted.extend_from_slice(&buffer);
+                    corrupted[offset] ^= flip;
+                    if let Ok(archived) = check_archived_root::<Test>(&corrupted) {
+                        check_consistent(&archived.lsb);
+                        check_consistent(&archived.msb);
+                    }
+                }
+            }
+        }
+
+        check(BitVec::new());
+        check(bitvec![1]);
+        check(bitvec![0; 700]);
+        check((0..2100).map(|i| i % 3 == 0 || i % 7 == 0).collect());
+        check((0..4200).map(|i| i % 1000 == 17).collect());
+
+        // Spilled select spans are too long to corrupt every byte of
+        #[cfg(not(feature = "size_16"))]
+        {
+            let bits = (0..100_000).map(|i| i % 90 == 0).collect::<BitVec>();
+            let value = Test {
+                lsb: bits.iter().by_vals().collect(),
+                msb: bits.iter().by_vals().collect(),
+            };
+
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&value).unwrap();
+            let buffer = serializer.into_serializer().into_inner();
+            let archived = check_archived_root::<Test>(&buffer).unwrap();
+            check_consistent(&archived.lsb);
+            check_consistent(&archived.msb);
+        }
+    }
 }
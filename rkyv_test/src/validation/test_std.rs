@@ -25,4 +25,658 @@ mod tests {
         set.insert("baz".to_string());
         serialize_and_check(&set);
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn hashed() {
+        use rkyv::{
+            collections::hash_index::{FxHasher, HashBuilder, Keyed},
+            with::Hashed,
+            Archive, Serialize,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(Hashed<FxHasher>)]
+            map: HashMap<String, u32>,
+            #[with(Hashed<Keyed<HashBuilder>>)]
+            set: HashSet<String>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            set: HashSet::new(),
+        };
+        for i in 0..100u32 {
+            value.map.insert(i.to_string(), i);
+            value.set.insert(i.to_string());
+        }
+        serialize_and_check(&value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn swiss_map() {
+        use rkyv::{with::AsSwissMap, Archive, Serialize};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(AsSwissMap)]
+            map: HashMap<String, String>,
+            #[with(AsSwissMap)]
+            empty: HashMap<String, String>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            empty: HashMap::new(),
+        };
+        for i in 0..100u32 {
+            value
+                .map
+                .insert(i.to_string(), "a value that is not inlined".repeat(2));
+        }
+        serialize_and_check(&value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn sorted_vec() {
+        use crate::util::alloc::DefaultSerializer;
+        use rkyv::{
+            check_archived_root,
+            ser::Serializer,
+            with::{AsSortedVec, AsVec},
+            Archive, Serialize,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(AsSortedVec)]
+            map: HashMap<String, String>,
+            #[with(AsSortedVec)]
+            set: HashSet<String>,
+            #[with(AsSortedVec)]
+            empty: HashMap<String, String>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            set: HashSet::new(),
+            empty: HashMap::new(),
+        };
+        for i in 0..100u32 {
+            value
+                .map
+                .insert(i.to_string(), "a value that is not inlined".repeat(2));
+            value.set.insert(i.to_string());
+        }
+        serialize_and_check(&value);
+
+        // A vec of entries has the same layout as a sorted map, but its keys are not sorted
+        #[derive(Archive, Serialize)]
+        struct Unsorted {
+            #[with(AsVec)]
+            map: HashMap<String, String>,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Sorted {
+            #[with(AsSortedVec)]
+            map: HashMap<String, String>,
+        }
+
+        let unsorted = Unsorted { map: value.map };
+        let keys = unsorted.map.keys().collect::<Vec<_>>();
+        assert!(keys.windows(2).any(|pair| pair[0] > pair[1]));
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&unsorted).unwrap();
+        let buf = serializer.into_serializer().into_inner();
+        assert!(check_archived_root::<Sorted>(buf.as_ref()).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn trie() {
+        use crate::util::alloc::DefaultSerializer;
+        use rkyv::{check_archived_root, ser::Serializer, with::AsTrie, Archive, Serialize};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(AsTrie)]
+            map: HashMap<String, String>,
+            #[with(AsTrie)]
+            empty: HashMap<String, String>,
+        }
+
+        let mut value = Test {
+            map: HashMap::new(),
+            empty: HashMap::new(),
+        };
+        for i in 0..100u32 {
+            value.map.insert(
+                format!("key {}", i),
+                "a value that is not inlined".repeat(2),
+            );
+        }
+        value.map.insert(String::new(), String::new());
+        value.map.insert("café".to_string(), String::new());
+        value.map.insert("cafè".to_string(), String::new());
+        serialize_and_check(&value);
+
+        // Tries built by hand with the same layout as an archived trie map
+        #[derive(Archive, Serialize)]
+        struct Node {
+            label_end: usize,
+            children_end: usize,
+            value_start: usize,
+            value_end: usize,
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Trie {
+            labels: Vec<u8>,
+            nodes: Vec<Node>,
+            values: Vec<u32>,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Checked {
+            #[with(AsTrie)]
+            map: HashMap<String, u32>,
+        }
+
+        fn check(labels: &[u8], nodes: [(usize, usize, usize, usize); 3]) -> bool {
+            let trie = Trie {
+                labels: labels.to_vec(),
+                nodes: nodes
+                    .iter()
+                    .map(|&(label_end, children_end, value_start, value_end)| Node {
+                        label_end,
+                        children_end,
+                        value_start,
+                        value_end,
+                    })
+                    .collect(),
+                values: vec![1, 2],
+            };
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&trie).unwrap();
+            let buf = serializer.into_serializer().into_inner();
+            check_archived_root::<Checked>(buf.as_ref()).is_ok()
+        }
+
+        // A trie with the keys "a" and "b"
+        assert!(check(b"ab", [(0, 3, 0, 2), (1, 3, 0, 1), (2, 3, 1, 2)]));
+        // Invalid UTF-8
+        assert!(!check(b"\xffb", [(0, 3, 0, 2), (1, 3, 0, 1), (2, 3, 1, 2)]));
+        // Unsorted children
+        assert!(!check(b"ba", [(0, 3, 0, 2), (1, 3, 0, 1), (2, 3, 1, 2)]));
+        // Children out of bounds
+        assert!(!check(b"ab", [(0, 4, 0, 2), (1, 4, 0, 1), (2, 4, 1, 2)]));
+        // A node that is its own child
+        assert!(!check(b"ab", [(0, 1, 0, 2), (1, 3, 0, 1), (2, 3, 1, 2)]));
+        // Empty label
+        assert!(!check(b"ab", [(0, 3, 0, 2), (0, 3, 0, 1), (2, 3, 1, 2)]));
+        // Overlapping values
+        assert!(!check(b"ab", [(0, 3, 0, 2), (1, 3, 0, 1), (2, 3, 0, 1)]));
+        // Values out of bounds
+        assert!(!check(b"ab", [(0, 3, 0, 3), (1, 3, 0, 1), (2, 3, 1, 3)]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn packed() {
+        use crate::util::alloc::DefaultSerializer;
+        use rkyv::{
+            check_archived_root,
+            ser::Serializer,
+            with::{BitPacked, DeltaPacked},
+            Archive, Serialize,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(BitPacked)]
+            bits: Vec<u32>,
+            #[with(DeltaPacked)]
+            deltas: Vec<u64>,
+            #[with(BitPacked)]
+            empty_bits: Vec<u8>,
+            #[with(DeltaPacked)]
+            empty_deltas: Vec<u16>,
+        }
+
+        serialize_and_check(&Test {
+            bits: (0..300).map(|i| i * 12345 % 100_000).collect(),
+            deltas: (0..300).map(|i| (i * 7) ^ 0xff).collect(),
+            empty_bits: Vec::new(),
+            empty_deltas: Vec::new(),
+        });
+
+        // Packed arrays built by hand with the same layouts as archived packed arrays
+        #[derive(Archive, Serialize)]
+        struct Bits {
+            words: Vec<u64>,
+            len: usize,
+            base: u64,
+            bit_width: u8,
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Block {
+            anchor: u64,
+            min_delta: u64,
+            word_start: usize,
+            bit_width: u8,
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Deltas {
+            blocks: Vec<Block>,
+            words: Vec<u64>,
+            len: usize,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct CheckedBits(#[with(BitPacked)] Vec<u16>);
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct CheckedDeltas(#[with(DeltaPacked)] Vec<u16>);
+
+        fn check_bits(words: usize, len: usize, base: u64, bit_width: u8) -> bool {
+            let bits = Bits {
+                words: vec![0; words],
+                len,
+                base,
+                bit_width,
+            };
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&bits).unwrap();
+            let buf = serializer.into_serializer().into_inner();
+            check_archived_root::<CheckedBits>(buf.as_ref()).is_ok()
+        }
+
+        fn check_deltas(words: usize, len: usize, blocks: &[(u64, usize, u8)]) -> bool {
+            let deltas = Deltas {
+                blocks: blocks
+                    .iter()
+                    .map(|&(anchor, word_start, bit_width)| Block {
+                        anchor,
+                        min_delta: 1,
+                        word_start,
+                        bit_width,
+                    })
+                    .collect(),
+                words: vec![0; words],
+                len,
+            };
+            let mut serializer = DefaultSerializer::default();
+            serializer.serialize_value(&deltas).unwrap();
+            let buf = serializer.into_serializer().into_inner();
+            check_archived_root::<CheckedDeltas>(buf.as_ref()).is_ok()
+        }
+
+        // Five 16-bit values in two words
+        assert!(check_bits(2, 5, 0, 16));
+        assert!(check_bits(0, 5, 7, 0));
+        // Bit width wider than the integer type
+        assert!(!check_bits(2, 5, 0, 17));
+        // Base larger than the integer type
+        assert!(!check_bits(2, 5, 1 << 16, 16));
+        // Too few or too many words
+        assert!(!check_bits(1, 5, 0, 16));
+        assert!(!check_bits(3, 5, 0, 16));
+
+        // Two blocks of 128 and 2 values, with 127 and 1 deltas of 1 bit
+        assert!(check_deltas(3, 130, &[(0, 0, 1), (100, 2, 1)]));
+        assert!(check_deltas(0, 130, &[(0, 0, 0), (100, 0, 0)]));
+        // Too few or too many blocks
+        assert!(!check_deltas(2, 130, &[(0, 0, 1)]));
+        assert!(!check_deltas(3, 2, &[(0, 0, 1), (100, 2, 1)]));
+        // Bit width wider than 64 bits
+        assert!(!check_deltas(3, 130, &[(0, 0, 1), (100, 2, 65)]));
+        // Anchor larger than the integer type
+        assert!(!check_deltas(3, 130, &[(0, 0, 1), (1 << 16, 2, 1)]));
+        // Overlapping or out of order blocks
+        assert!(!check_deltas(3, 130, &[(0, 0, 1), (100, 1, 1)]));
+        assert!(!check_deltas(3, 130, &[(0, 2, 1), (100, 0, 1)]));
+        // Too few or too many words
+        assert!(!check_deltas(2, 130, &[(0, 0, 1), (100, 2, 1)]));
+        assert!(!check_deltas(4, 130, &[(0, 0, 1), (100, 2, 1)]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn interned() {
+        use crate::util::alloc::DefaultSerializer;
+        use rkyv::{
+            check_archived_root,
+            ser::Serializer,
+            with::{Interned, Map},
+            Archive, Serialize,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Test {
+            #[with(Interned)]
+            name: String,
+            #[with(Map<Interned>)]
+            labels: Vec<String>,
+            #[with(Interned)]
+            properties: HashMap<String, u32>,
+        }
+
+        let value = Test {
+            name: "interned".to_string(),
+            labels: vec!["interned".to_string(), String::new(), String::new()],
+            properties: [("interned".to_string(), 1), (String::new(), 2)]
+                .into_iter()
+                .collect(),
+        };
+        serialize_and_check(&value);
+
+        let mut serializer = DefaultSerializer::default();
+        serializer.serialize_value(&value).unwrap();
+        let mut buf = serializer.into_serializer().into_inner();
+
+        // The string is only serialized once, so corrupting it affects every field
+        let start = buf
+            .windows(8)
+            .position(|window| window == b"interned")
+            .unwrap();
+        assert!(!buf[start + 8..]
+            .windows(8)
+            .any(|window| window == b"interned"));
+        buf[start] = 0xff;
+        assert!(check_archived_root::<Test>(buf.as_ref()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "ipc")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn ipc_ring_threads() {
+        use core::ptr::NonNull;
+        use rkyv::{
+            from_archived,
+            ipc::{Ring, SendError},
+            AlignedVec, Archive, Serialize,
+        };
+        use std::thread;
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Sent {
+            producer: u32,
+            index: u32,
+        }
+
+        const PRODUCERS: u32 = 4;
+        const MESSAGES: u32 = 1000;
+
+        let len = Ring::required_len(64, 8).unwrap();
+        let mut region = AlignedVec::<64>::with_capacity_aligned(len);
+        region.resize(len, 0);
+        let ptr = NonNull::new(region.as_mut_ptr()).unwrap();
+        let ring = unsafe { Ring::create(ptr, len, 64, 8).unwrap() };
+
+        let mut received = vec![Vec::new(); PRODUCERS as usize];
+        thread::scope(|scope| {
+            for producer in 0..PRODUCERS {
+                let ring = &ring;
+                scope.spawn(move || {
+                    for index in 0..MESSAGES {
+                        loop {
+                            match ring.try_send::<_, 0>(&Sent { producer, index }) {
+                                Ok(()) => break,
+                                Err(SendError::Full) => thread::yield_now(),
+                                Err(e) => panic!("failed to send message: {}", e),
+                            }
+                        }
+                    }
+                });
+            }
+
+            let consumer = unsafe { Ring::open(ptr, len).unwrap() };
+            let mut remaining = PRODUCERS * MESSAGES;
+            while remaining > 0 {
+                match consumer.try_recv::<Sent>() {
+                    Some(message) => {
+                        let message = message.unwrap();
+                        let producer = from_archived!(message.producer) as usize;
+                        received[producer].push(from_archived!(message.index));
+                        remaining -= 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+        });
+
+        // Messages from each producer arrive in the order they were sent
+        for messages in received {
+            assert_eq!(messages, (0..MESSAGES).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn framed_streams() {
+        use rkyv::{
+            io::{FrameReadError, FrameReader, FrameWriteError, FrameWriter},
+            Archive, Serialize,
+        };
+        use std::io::ErrorKind;
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Event {
+            id: u32,
+            name: String,
+        }
+
+        let mut writer = FrameWriter::new(Vec::new());
+        for id in 0..3 {
+            let event = Event {
+                id,
+                name: "a string that is too long to be inlined".repeat(id as usize),
+            };
+            writer.write_value::<_, 0>(&event).unwrap();
+        }
+        // Invalid archive bytes can still be framed
+        writer.write_frame(&[0xff; 4]).unwrap();
+        let stream = writer.into_inner();
+
+        let mut reader = FrameReader::new(stream.as_slice());
+        for id in 0..3 {
+            let event = reader.read_root::<Event>().unwrap().unwrap();
+            assert_eq!(event.id, id);
+            assert_eq!(event.name.len(), 39 * id as usize);
+        }
+        assert!(matches!(
+            reader.read_root::<char>(),
+            Err(FrameReadError::Check(_))
+        ));
+        assert!(reader.read_root::<Event>().unwrap().is_none());
+
+        // Frames that are too large are rejected before they are written or read
+        let event = Event {
+            id: 3,
+            name: "x".repeat(64),
+        };
+        let mut small_writer = FrameWriter::with_max_frame_len(Vec::new(), 32);
+        assert!(matches!(
+            small_writer.write_value::<_, 0>(&event),
+            Err(FrameWriteError::TooLarge { max_len: 32, .. })
+        ));
+        assert!(small_writer.into_inner().is_empty());
+        let mut large_writer = FrameWriter::new(Vec::new());
+        large_writer.write_value::<_, 0>(&event).unwrap();
+        let large_stream = large_writer.into_inner();
+        let mut small_reader = FrameReader::with_max_frame_len(large_stream.as_slice(), 32);
+        assert!(matches!(
+            small_reader.read_frame(),
+            Err(FrameReadError::TooLarge { max_len: 32, .. })
+        ));
+
+        // Streams that end in the middle of a frame are errors
+        for len in [2, 6] {
+            let mut reader = FrameReader::new(&stream[..len]);
+            match reader.read_frame() {
+                Err(FrameReadError::Io(e)) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+                other => panic!("expected unexpected EOF, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_serde_values() {
+        use rkyv::{check_archived_root, value::Value};
+
+        let mut document = HashMap::new();
+        document.insert("name", vec!["ferris".to_string()]);
+        document.insert("tags", vec!["crab".to_string(), "rust".to_string()]);
+
+        let bytes = rkyv::value::to_bytes::<_, 256>(&document).unwrap();
+        let archived = check_archived_root::<Value>(&bytes).unwrap();
+        assert_eq!(archived["tags"][1].as_str(), Some("rust"));
+
+        #[derive(rkyv::serde::Serialize)]
+        #[serde(crate = "rkyv::serde")]
+        enum Shape {
+            Point(i32, i32),
+            Rect { width: u32, height: u32 },
+            Named(String),
+        }
+
+        let shapes = vec![
+            Shape::Point(-1, 2),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+            Shape::Named("a name that is much too long to be stored inline".to_string()),
+        ];
+        let bytes = rkyv::value::to_bytes::<_, 256>(&shapes).unwrap();
+        let archived = check_archived_root::<Value>(&bytes).unwrap();
+        assert_eq!(*archived, rkyv::value::to_value(&shapes).unwrap());
+
+        let mut layers = std::collections::HashMap::new();
+        layers.insert("background".to_string(), (0.5f64, shapes));
+        layers.insert("empty".to_string(), (1.0, Vec::new()));
+        let bytes = rkyv::value::to_bytes::<_, 256>(&layers).unwrap();
+        let archived = check_archived_root::<Value>(&bytes).unwrap();
+        assert_eq!(*archived, rkyv::value::to_value(&layers).unwrap());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn dynamic_hash_map() {
+        use core::mem::MaybeUninit;
+        use rkyv::{
+            collections::util::Entry,
+            dynamic::{
+                self,
+                schema::{MapSchema, Schema},
+            },
+            string::ArchivedString,
+            Archived,
+        };
+
+        let mut map = HashMap::new();
+        map.insert("Hello".to_string(), 12u32);
+        map.insert("world".to_string(), 34);
+        map.insert("a longer key that is stored out of line".to_string(), 56);
+
+        let bytes = rkyv::to_bytes::<_, 256>(&map).unwrap();
+        let mut entry = MaybeUninit::<Entry<ArchivedString, Archived<u32>>>::uninit();
+        let out = entry.as_mut_ptr();
+        let schema = Schema::hash_map(MapSchema::new(
+            Schema::String,
+            rkyv::out_field!(out.key).0,
+            Schema::U32,
+            rkyv::out_field!(out.value).0,
+        ));
+
+        let root = dynamic::root(&bytes, &schema).unwrap();
+        assert_eq!(root.len().unwrap(), 3);
+        let mut entries = root
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k.as_str().unwrap().to_string(), v.as_u64().unwrap() as u32))
+            .collect::<Vec<_>>();
+        entries.sort();
+        let mut expected = map.into_iter().collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(entries, expected);
+
+        let world = root.get_key("world").unwrap().unwrap();
+        assert_eq!(world.as_u64().unwrap(), 34);
+        assert!(root.to_string().contains("\"Hello\": 12"));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn dynamic_corrupt_map_len() {
+        use core::mem::{size_of, MaybeUninit};
+        use rkyv::{
+            collections::{btree_map::ArchivedBTreeMap, hash_map::ArchivedHashMap, util::Entry},
+            dynamic::{
+                self,
+                schema::{MapSchema, Schema},
+                DynamicError,
+            },
+            Archived,
+        };
+        use std::collections::BTreeMap;
+
+        // Both maps start with their length, so this sets it to the largest archived length
+        fn corrupt_len(bytes: &mut [u8], root_size: usize) {
+            let root = bytes.len() - root_size;
+            for b in bytes[root..root + size_of::<Archived<usize>>()].iter_mut() {
+                *b = 0xff;
+            }
+        }
+
+        let map = (0..3u32).map(|i| (i, i * 2)).collect::<BTreeMap<_, _>>();
+        let mut bytes = rkyv::to_bytes::<_, 256>(&map).unwrap();
+        corrupt_len(&mut bytes, size_of::<ArchivedBTreeMap<u32, u32>>());
+        let (key_offset, value_offset) = ArchivedBTreeMap::<u32, u32>::entry_offsets();
+        let schema = Schema::btree_map(MapSchema::new(
+            Schema::U32,
+            key_offset,
+            Schema::U32,
+            value_offset,
+        ));
+        let root = dynamic::root(&bytes, &schema).unwrap();
+        assert!(matches!(root.entries(), Err(DynamicError::MalformedMap)));
+
+        let mut map = HashMap::new();
+        map.insert((), ());
+        let mut bytes = rkyv::to_bytes::<_, 256>(&map).unwrap();
+        corrupt_len(&mut bytes, size_of::<ArchivedHashMap<(), ()>>());
+        let mut entry = MaybeUninit::<Entry<(), ()>>::uninit();
+        let out = entry.as_mut_ptr();
+        let schema = Schema::hash_map(MapSchema::new(
+            Schema::Unit,
+            rkyv::out_field!(out.key).0,
+            Schema::Unit,
+            rkyv::out_field!(out.value).0,
+        ));
+        let root = dynamic::root(&bytes, &schema).unwrap();
+        assert!(matches!(root.entries(), Err(DynamicError::MalformedMap)));
+    }
 }